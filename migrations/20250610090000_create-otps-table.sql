@@ -0,0 +1,12 @@
+-- One-time codes, at most one live code per user and purpose
+CREATE TABLE otps (
+    identifier UUID PRIMARY KEY,
+    user_identifier UUID NOT NULL REFERENCES users (identifier) ON DELETE CASCADE,
+    kind VARCHAR(64) NOT NULL,
+    code_hash VARCHAR(255) NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_identifier, kind)
+);
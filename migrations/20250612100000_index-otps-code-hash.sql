@@ -0,0 +1,2 @@
+-- Opaque tokens are looked up by their hash since the caller does not know the owner
+CREATE INDEX otps_kind_code_hash_idx ON otps (kind, code_hash);
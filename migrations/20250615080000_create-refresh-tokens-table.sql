@@ -0,0 +1,14 @@
+-- Opaque refresh tokens, every rotation stays in the family of the login that started it
+CREATE TABLE refresh_tokens (
+    identifier UUID PRIMARY KEY,
+    user_identifier UUID NOT NULL REFERENCES users (identifier) ON DELETE CASCADE,
+    family_identifier UUID NOT NULL,
+    token_hash VARCHAR(255) NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    rotated_at TIMESTAMPTZ DEFAULT NULL,
+    revoked_at TIMESTAMPTZ DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX refresh_tokens_family_identifier_idx ON refresh_tokens (family_identifier);
+CREATE INDEX refresh_tokens_user_identifier_idx ON refresh_tokens (user_identifier);
@@ -0,0 +1,9 @@
+-- Access tokens revoked before they expire, rows are only useful until expires_at
+CREATE TABLE revoked_tokens (
+    jti UUID PRIMARY KEY,
+    user_identifier UUID NOT NULL REFERENCES users (identifier) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX revoked_tokens_expires_at_idx ON revoked_tokens (expires_at);
@@ -0,0 +1,2 @@
+-- Embedded in every issued token, bumping it invalidates all of the user's outstanding tokens
+ALTER TABLE users ADD COLUMN token_version INTEGER NOT NULL DEFAULT 0;
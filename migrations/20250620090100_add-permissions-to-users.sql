@@ -0,0 +1,2 @@
+-- Administrative permissions, see adapters::dto::permission for the accepted values
+ALTER TABLE users ADD COLUMN permissions TEXT[] NOT NULL DEFAULT '{}';
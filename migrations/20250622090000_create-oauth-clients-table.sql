@@ -0,0 +1,8 @@
+-- Relying parties allowed to start an authorization code flow
+CREATE TABLE oauth_clients (
+    client_id VARCHAR(255) PRIMARY KEY,
+    name VARCHAR(255) NOT NULL,
+    redirect_uris TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NULL
+);
@@ -0,0 +1,15 @@
+-- Single-use authorization codes, only the hash of the code is stored
+CREATE TABLE authorization_codes (
+    identifier UUID PRIMARY KEY,
+    code_hash VARCHAR(255) NOT NULL UNIQUE,
+    client_id VARCHAR(255) NOT NULL REFERENCES oauth_clients (client_id) ON DELETE CASCADE,
+    user_identifier UUID NOT NULL REFERENCES users (identifier) ON DELETE CASCADE,
+    redirect_uri TEXT NOT NULL,
+    scope TEXT NOT NULL,
+    nonce TEXT DEFAULT NULL,
+    code_challenge VARCHAR(255) NOT NULL,
+    auth_time TIMESTAMPTZ NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,4 @@
+-- Refresh tokens issued through the oauth endpoints keep the client and scope they were granted
+ALTER TABLE refresh_tokens
+    ADD COLUMN client_id VARCHAR(255) DEFAULT NULL REFERENCES oauth_clients (client_id) ON DELETE CASCADE,
+    ADD COLUMN scope TEXT DEFAULT NULL;
@@ -0,0 +1,8 @@
+-- Public clients have no secret, first-party clients may also use the direct login endpoints
+ALTER TABLE oauth_clients
+    ADD COLUMN client_secret_hash VARCHAR(255) DEFAULT NULL,
+    ADD COLUMN grant_types TEXT[] NOT NULL DEFAULT '{authorization_code,refresh_token}',
+    ADD COLUMN scopes TEXT[] NOT NULL DEFAULT '{openid,profile,email,offline_access}',
+    ADD COLUMN access_token_ttl_seconds INTEGER NOT NULL DEFAULT 600,
+    ADD COLUMN refresh_token_ttl_seconds INTEGER NOT NULL DEFAULT 2592000,
+    ADD COLUMN first_party BOOLEAN NOT NULL DEFAULT FALSE;
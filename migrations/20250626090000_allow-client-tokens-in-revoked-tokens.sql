@@ -0,0 +1,2 @@
+-- Tokens issued through the client credentials grant have no user behind them
+ALTER TABLE revoked_tokens ALTER COLUMN user_identifier DROP NOT NULL;
@@ -0,0 +1,17 @@
+-- Device authorization requests (RFC 8628), only the hash of the device code is stored while the
+-- short user code is typed in by hand and kept as is
+CREATE TABLE device_codes (
+    identifier UUID PRIMARY KEY,
+    device_code_hash VARCHAR(255) NOT NULL UNIQUE,
+    user_code VARCHAR(16) NOT NULL UNIQUE,
+    client_id VARCHAR(255) NOT NULL REFERENCES oauth_clients (client_id) ON DELETE CASCADE,
+    scope TEXT NOT NULL,
+    user_identifier UUID DEFAULT NULL REFERENCES users (identifier) ON DELETE CASCADE,
+    interval_seconds INTEGER NOT NULL,
+    last_polled_at TIMESTAMPTZ DEFAULT NULL,
+    approved_at TIMESTAMPTZ DEFAULT NULL,
+    denied_at TIMESTAMPTZ DEFAULT NULL,
+    consumed_at TIMESTAMPTZ DEFAULT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
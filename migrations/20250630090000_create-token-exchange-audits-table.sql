@@ -0,0 +1,18 @@
+-- One row per token exchange, granted or refused, kept without foreign keys so the trail
+-- outlives the users and clients it mentions
+CREATE TABLE token_exchange_audits (
+    identifier UUID PRIMARY KEY,
+    client_id VARCHAR(255) NOT NULL,
+    subject_identifier UUID NOT NULL,
+    subject_token_jti UUID NOT NULL,
+    actor VARCHAR(255) NOT NULL,
+    actor_type VARCHAR(16) NOT NULL,
+    impersonation BOOLEAN NOT NULL,
+    scope TEXT NOT NULL,
+    audience TEXT NOT NULL,
+    granted BOOLEAN NOT NULL,
+    reason TEXT DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX token_exchange_audits_subject_identifier_idx ON token_exchange_audits (subject_identifier);
@@ -0,0 +1,9 @@
+-- TOTP authenticators (RFC 6238), at most one per user. The secret is encrypted by the application
+-- and the last accepted time step is kept so that a code cannot be replayed
+CREATE TABLE totp_authenticators (
+    user_identifier UUID PRIMARY KEY REFERENCES users (identifier) ON DELETE CASCADE,
+    secret_ciphertext BYTEA NOT NULL,
+    last_used_step BIGINT DEFAULT NULL,
+    confirmed_at TIMESTAMPTZ DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,9 @@
+-- Single-use recovery codes that stand in for a TOTP code, only their hashes are stored
+CREATE TABLE recovery_codes (
+    identifier UUID PRIMARY KEY,
+    user_identifier UUID NOT NULL REFERENCES users (identifier) ON DELETE CASCADE,
+    code_hash VARCHAR(255) NOT NULL,
+    used_at TIMESTAMPTZ DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (user_identifier, code_hash)
+);
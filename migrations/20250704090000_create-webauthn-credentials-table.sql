@@ -0,0 +1,15 @@
+-- WebAuthn credentials (passkeys), the public key is kept in its COSE encoding as the authenticator
+-- returned it and the sign counter is used to detect cloned authenticators
+CREATE TABLE webauthn_credentials (
+    identifier UUID PRIMARY KEY,
+    user_identifier UUID NOT NULL REFERENCES users (identifier) ON DELETE CASCADE,
+    credential_id VARCHAR(1024) NOT NULL UNIQUE,
+    public_key BYTEA NOT NULL,
+    sign_count BIGINT NOT NULL DEFAULT 0,
+    transports TEXT[] NOT NULL DEFAULT '{}',
+    name VARCHAR(255) NOT NULL,
+    last_used_at TIMESTAMPTZ DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX webauthn_credentials_user_identifier_idx ON webauthn_credentials (user_identifier);
@@ -0,0 +1,9 @@
+-- Challenges of WebAuthn ceremonies in progress, each is consumed by the response to it. The user is
+-- unknown until the response arrives for a passwordless login
+CREATE TABLE webauthn_challenges (
+    challenge VARCHAR(255) PRIMARY KEY,
+    ceremony VARCHAR(32) NOT NULL,
+    user_identifier UUID DEFAULT NULL REFERENCES users (identifier) ON DELETE CASCADE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,10 @@
+-- Failed sign-in attempts, counted per account email and per source address so unknown
+-- accounts are throttled exactly like real ones
+CREATE TABLE login_failures (
+    scope VARCHAR(16) NOT NULL,
+    subject VARCHAR(320) NOT NULL,
+    failures INTEGER NOT NULL DEFAULT 0,
+    blocked_until TIMESTAMPTZ DEFAULT NULL,
+    last_failure_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (scope, subject)
+);
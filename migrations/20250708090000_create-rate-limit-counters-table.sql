@@ -0,0 +1,8 @@
+-- Fixed-window request counters shared by every replica when RATE_LIMIT_STORE=postgres
+CREATE TABLE rate_limit_counters (
+    key VARCHAR(512) PRIMARY KEY,
+    hits INTEGER NOT NULL,
+    window_ends_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX rate_limit_counters_window_ends_at_idx ON rate_limit_counters (window_ends_at);
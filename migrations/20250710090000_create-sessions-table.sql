@@ -0,0 +1,13 @@
+-- One row per sign-in, a session lives exactly as long as its refresh token family has a token
+-- that has not been revoked
+CREATE TABLE sessions (
+    identifier UUID PRIMARY KEY,
+    user_identifier UUID NOT NULL REFERENCES users (identifier) ON DELETE CASCADE,
+    family_identifier UUID NOT NULL UNIQUE,
+    user_agent TEXT DEFAULT NULL,
+    ip_address VARCHAR(45) DEFAULT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX sessions_user_identifier_idx ON sessions (user_identifier);
@@ -0,0 +1,19 @@
+-- Upstream OpenID Connect providers users can sign in through. The client secret has to be sent to
+-- the provider, so it is encrypted rather than hashed, and the claim columns name the claims the
+-- profile of a new user is filled from
+CREATE TABLE identity_providers (
+    identifier UUID PRIMARY KEY,
+    slug VARCHAR(64) NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    issuer VARCHAR(2048) NOT NULL,
+    client_id VARCHAR(255) NOT NULL,
+    client_secret BYTEA NOT NULL,
+    scopes TEXT[] NOT NULL,
+    email_claim VARCHAR(255) NOT NULL,
+    first_name_claim VARCHAR(255) NOT NULL,
+    last_name_claim VARCHAR(255) NOT NULL,
+    trust_email BOOLEAN NOT NULL DEFAULT FALSE,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NULL
+);
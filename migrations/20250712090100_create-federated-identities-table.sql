@@ -0,0 +1,12 @@
+-- Accounts at an identity provider linked to local users, the subject is the provider's stable
+-- identifier for the account and survives changes to its email address
+CREATE TABLE federated_identities (
+    provider_identifier UUID NOT NULL REFERENCES identity_providers (identifier) ON DELETE CASCADE,
+    subject VARCHAR(255) NOT NULL,
+    user_identifier UUID NOT NULL REFERENCES users (identifier) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_login_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (provider_identifier, subject)
+);
+
+CREATE INDEX federated_identities_user_identifier_idx ON federated_identities (user_identifier);
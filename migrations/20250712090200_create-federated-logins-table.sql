@@ -0,0 +1,11 @@
+-- Sign-ins waiting on the identity provider to redirect back, each is consumed by the callback
+-- carrying its state
+CREATE TABLE federated_logins (
+    state VARCHAR(255) PRIMARY KEY,
+    provider_identifier UUID NOT NULL REFERENCES identity_providers (identifier) ON DELETE CASCADE,
+    nonce VARCHAR(255) NOT NULL,
+    code_verifier VARCHAR(255) NOT NULL,
+    client_id VARCHAR(255) DEFAULT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,18 @@
+-- Applications that sign users in with SAML 2.0, registered from their metadata. Assertions are only
+-- ever posted to one of the assertion consumer services listed, the default first, and the attribute
+-- columns name the attributes the user's profile is released as, NULL leaves one out
+CREATE TABLE saml_service_providers (
+    identifier UUID PRIMARY KEY,
+    entity_id VARCHAR(1024) NOT NULL UNIQUE,
+    name VARCHAR(255) NOT NULL,
+    assertion_consumer_services TEXT[] NOT NULL,
+    name_id_format VARCHAR(255) NOT NULL,
+    email_attribute VARCHAR(255),
+    first_name_attribute VARCHAR(255),
+    last_name_attribute VARCHAR(255),
+    display_name_attribute VARCHAR(255),
+    metadata TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NULL
+);
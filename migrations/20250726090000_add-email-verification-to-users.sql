@@ -0,0 +1,8 @@
+-- A new address waits in pending_email until the code sent to it is confirmed, federated logins
+-- only join an account whose address was verified and has not just been changed
+ALTER TABLE users ADD COLUMN pending_email VARCHAR(255) DEFAULT NULL;
+ALTER TABLE users ADD COLUMN email_verified_at TIMESTAMPTZ DEFAULT NULL;
+ALTER TABLE users ADD COLUMN email_changed_at TIMESTAMPTZ DEFAULT NULL;
+
+-- accounts are only activated by proving the address, so the active ones have done it already
+UPDATE users SET email_verified_at = COALESCE(updated_at, created_at) WHERE is_active;
@@ -0,0 +1,2 @@
+-- When the approving user signed in, handed to the device as the auth_time of its id token
+ALTER TABLE device_codes ADD COLUMN auth_time TIMESTAMPTZ DEFAULT NULL;
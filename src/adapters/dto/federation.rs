@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// the parts of an upstream provider's OpenID Connect discovery document the login flow uses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+    pub userinfo_endpoint: Option<String>,
+}
+
+/// the claims a user's profile is filled from, named as the provider names them
+#[derive(Debug, Clone)]
+pub struct ClaimMapping {
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+/// an identity provider as the OpenID Connect client sees it, with the secret in the clear
+#[derive(Debug, Clone)]
+pub struct UpstreamProvider {
+    pub issuer: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: Vec<String>,
+    pub claim_mapping: ClaimMapping,
+    pub trust_email: bool,
+}
+
+/// what the authorization request is bound to, kept until the provider redirects back
+#[derive(Debug, Clone)]
+pub struct UpstreamAuthorization {
+    pub redirect_uri: String,
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+/// RFC 6749 token response, the id token is what the sign-in rests on
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpstreamTokenResponse {
+    pub access_token: String,
+    pub id_token: Option<String>,
+}
+
+/// RFC 6749 error response
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpstreamErrorResponse {
+    pub error: String,
+    pub error_description: Option<String>,
+}
+
+/// the account at the provider, read from a verified id token and the userinfo endpoint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FederatedProfile {
+    /// the provider's stable identifier for the account
+    pub subject: String,
+    pub email: String,
+    pub email_verified: bool,
+    pub first_name: String,
+    pub last_name: String,
+}
+
+/// a completed sign-in at a provider, not yet tied to a local user
+#[derive(Debug, Clone)]
+pub struct FederatedAccount {
+    pub provider_identifier: Uuid,
+    pub profile: FederatedProfile,
+    pub client_id: Option<String>,
+}
@@ -1,70 +1,221 @@
 use std::time::Duration;
 
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, encode};
+use jsonwebtoken::Validation;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::errors::auth_service_error::AuthenticationServiceError;
-use crate::shared::extract_env::extract_env;
+use crate::{
+    adapters::dto::oauth::OAuthGrant, config::jwt::JwtConfig, entities::session::SessionEntity,
+};
 
-pub const _FIVE_MINUTES: Duration = Duration::from_secs(5 * 60 * 60);
-pub const TWENTY_FIVE_MINUTES: Duration = Duration::from_secs(26 * 60 * 60);
-pub const TEN_MINUTES: Duration = Duration::from_secs(10 * 60 * 60);
+pub const TEN_MINUTES: Duration = Duration::from_secs(10 * 60);
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct JwtCredentials {
-    pub email: String,
-    pub identifier: Uuid,
+/// what a token may be used for, every extractor accepts exactly one of these
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenUse {
+    Access,
+    Verification,
+    /// proves the password was accepted, traded for an access token once the second factor is
+    MfaChallenge,
 }
 
-pub type Claims = JwtCredentials;
-
-pub struct Keys {
-    encoding: EncodingKey,
-    pub(crate) decoding: DecodingKey,
+/// who a token speaks for, lets resource servers tell user tokens from service tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubjectType {
+    #[default]
+    User,
+    Client,
 }
 
-impl Keys {
-    pub(crate) fn new(secret: &[u8]) -> Self {
-        Self {
-            encoding: EncodingKey::from_secret(secret),
-            decoding: DecodingKey::from_secret(secret),
+impl SubjectType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubjectType::User => "user",
+            SubjectType::Client => "client",
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
-struct Claim {
+/// the party acting on behalf of the subject of an exchanged token (RFC 8693), a chain of
+/// exchanges nests the earlier actors
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    pub sub: String,
+    pub sub_type: SubjectType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Box<Actor>>,
+}
+
+#[derive(Debug)]
+pub struct JwtCredentials {
     pub email: String,
-    pub identifier: String,
+    pub identifier: Uuid,
+    pub token_version: i32,
+    pub token_use: TokenUse,
+    pub grant: Option<OAuthGrant>,
+    /// defaults to the configured audience
+    pub audience: Option<String>,
+    pub actor: Option<Actor>,
+    pub session: Option<Uuid>,
+    pub auth_time: Option<i64>,
+    pub first_party: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub jti: Uuid,
+    pub iss: String,
+    pub aud: String,
+    pub sub: Uuid,
+    #[serde(default)]
+    pub sub_type: SubjectType,
+    pub email: String,
+    /// the user's token version at issue time, tokens with an older version are rejected
+    pub ver: i32,
+    pub token_use: TokenUse,
+    /// set on tokens issued to an oauth client, absent on first-party logins
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// set on tokens from a token exchange
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+    /// the session a first-party sign-in started, the token stops working once it is revoked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<Uuid>,
+    /// when the user signed in to the session, unlike `iat` it stays put across refreshes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_time: Option<i64>,
+    /// issued to a first-party client or to no client at all, the only tokens the first-party
+    /// api accepts, decided at issue time so requests do not have to look the client up
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub first_party: bool,
     pub iat: i64,
     pub exp: i64,
 }
 
 impl JwtCredentials {
-    pub fn new(email: &str, identifier: &Uuid) -> Self {
+    pub fn new(email: &str, identifier: &Uuid, token_version: i32, token_use: TokenUse) -> Self {
         Self {
             email: email.to_string(),
             identifier: identifier.to_owned(),
+            token_version,
+            token_use,
+            grant: None,
+            audience: None,
+            actor: None,
+            session: None,
+            auth_time: None,
+            first_party: false,
         }
     }
 
-    pub fn generate_token(&self, validity: Duration) -> Result<String, AuthenticationServiceError> {
+    pub fn with_grant(mut self, grant: OAuthGrant) -> Self {
+        self.grant = Some(grant);
+        self
+    }
+
+    pub fn with_audience(mut self, audience: &str) -> Self {
+        self.audience = Some(audience.to_string());
+        self
+    }
+
+    pub fn with_actor(mut self, actor: Actor) -> Self {
+        self.actor = Some(actor);
+        self
+    }
+
+    pub fn with_first_party(mut self, first_party: bool) -> Self {
+        self.first_party = first_party;
+        self
+    }
+
+    /// the token belongs to the session and carries the time the user signed in to it
+    pub fn with_session(mut self, session: &SessionEntity) -> Self {
+        self.session = Some(session.identifier);
+        self.auth_time = Some(session.created_at.timestamp());
+        self
+    }
+
+    pub fn to_claims(&self, config: &JwtConfig, validity: Duration) -> Claims {
         let now = chrono::Utc::now().timestamp();
-        let claim = Claim {
+        Claims {
+            jti: Uuid::new_v4(),
+            iss: config.issuer.to_string(),
+            aud: self
+                .audience
+                .as_deref()
+                .unwrap_or(&config.audience)
+                .to_string(),
+            sub: self.identifier,
+            sub_type: SubjectType::User,
             email: self.email.to_string(),
-            identifier: self.identifier.to_string(),
+            ver: self.token_version,
+            token_use: self.token_use,
+            client_id: self.grant.as_ref().map(|grant| grant.client_id.to_string()),
+            scope: self.grant.as_ref().map(|grant| grant.scope.to_string()),
+            act: self.actor.clone(),
+            sid: self.session,
+            auth_time: self.auth_time,
+            first_party: self.first_party,
             iat: now,
             exp: now + validity.as_secs() as i64,
-        };
+        }
+    }
+}
+
+/// claims of an access token issued through the client credentials grant, the subject is the
+/// client itself and there is no user behind it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientClaims {
+    pub jti: Uuid,
+    pub iss: String,
+    pub aud: String,
+    pub sub: String,
+    pub sub_type: SubjectType,
+    pub token_use: TokenUse,
+    pub client_id: String,
+    pub scope: String,
+    pub iat: i64,
+    pub exp: i64,
+}
 
-        let secret =
-            extract_env::<String>("JWT_SIGNING_KEY").map_err(AuthenticationServiceError::from)?;
+impl ClientClaims {
+    pub fn new(config: &JwtConfig, client_id: &str, scope: &str, validity: Duration) -> Self {
+        let now = chrono::Utc::now().timestamp();
+        Self {
+            jti: Uuid::new_v4(),
+            iss: config.issuer.to_string(),
+            aud: config.audience.to_string(),
+            sub: client_id.to_string(),
+            sub_type: SubjectType::Client,
+            token_use: TokenUse::Access,
+            client_id: client_id.to_string(),
+            scope: scope.to_string(),
+            iat: now,
+            exp: now + validity.as_secs() as i64,
+        }
+    }
+}
 
-        let encoding_key = Keys::new(secret.as_bytes()).encoding;
-        let token = encode(&Header::default(), &claim, &encoding_key)
-            .map_err(AuthenticationServiceError::from)?;
+impl Claims {
+    /// the signing algorithm is left to the key store, it depends on the key that signed the token
+    pub fn validation(config: &JwtConfig) -> Validation {
+        let mut validation = Validation::default();
+        validation.set_issuer(&[&config.issuer]);
+        validation.set_audience(&[&config.audience]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation
+    }
 
-        Ok(token)
+    /// tokens from a token exchange can be meant for another service, these are only verified by
+    /// the endpoints that answer for any audience
+    pub fn validation_for_any_audience(config: &JwtConfig) -> Validation {
+        let mut validation = Self::validation(config);
+        validation.validate_aud = false;
+        validation
     }
 }
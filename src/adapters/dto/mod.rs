@@ -1,3 +1,8 @@
+pub mod federation;
 pub mod jwt;
+pub mod oauth;
 pub mod otp;
+pub mod permission;
+pub mod saml;
+pub mod session;
 pub mod user;
@@ -2,19 +2,23 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OtpKind {
     AccountVerification,
+    EmailLogin,
     PasswordReset,
     PasswordUpdate,
+    EmailChange,
 }
 
 impl Display for OtpKind {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             OtpKind::AccountVerification => write!(f, "account_verification"),
+            OtpKind::EmailLogin => write!(f, "email_login"),
             OtpKind::PasswordReset => write!(f, "password_reset"),
             OtpKind::PasswordUpdate => write!(f, "password_update"),
+            OtpKind::EmailChange => write!(f, "email_change"),
         }
     }
 }
@@ -0,0 +1,53 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// administrative permissions, stored on the user as their string form
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Permission {
+    #[serde(rename = "clients:manage")]
+    ManageClients,
+    #[serde(rename = "identity-providers:manage")]
+    ManageIdentityProviders,
+    #[serde(rename = "service-providers:manage")]
+    ManageServiceProviders,
+    #[serde(rename = "users:manage")]
+    ManageUsers,
+    /// act as another user through a token exchange, for support staff
+    #[serde(rename = "users:impersonate")]
+    ImpersonateUsers,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::ManageClients => "clients:manage",
+            Permission::ManageIdentityProviders => "identity-providers:manage",
+            Permission::ManageServiceProviders => "service-providers:manage",
+            Permission::ManageUsers => "users:manage",
+            Permission::ImpersonateUsers => "users:impersonate",
+        }
+    }
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Permission {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "clients:manage" => Ok(Permission::ManageClients),
+            "identity-providers:manage" => Ok(Permission::ManageIdentityProviders),
+            "service-providers:manage" => Ok(Permission::ManageServiceProviders),
+            "users:manage" => Ok(Permission::ManageUsers),
+            "users:impersonate" => Ok(Permission::ImpersonateUsers),
+            other => Err(format!("unknown permission {other}")),
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// SAML 2.0 Core section 8.3, the forms the subject of an assertion can take
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NameIdFormat {
+    /// the user's email address
+    #[serde(rename = "urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress")]
+    EmailAddress,
+    /// an opaque identifier that stays the same for the user at one service provider and differs
+    /// between service providers
+    #[serde(rename = "urn:oasis:names:tc:SAML:2.0:nameid-format:persistent")]
+    Persistent,
+    /// a new opaque identifier on every sign-in
+    #[serde(rename = "urn:oasis:names:tc:SAML:2.0:nameid-format:transient")]
+    Transient,
+    /// the user's identifier
+    #[serde(rename = "urn:oasis:names:tc:SAML:1.1:nameid-format:unspecified")]
+    Unspecified,
+}
+
+impl NameIdFormat {
+    pub const ALL: [NameIdFormat; 4] = [
+        NameIdFormat::EmailAddress,
+        NameIdFormat::Persistent,
+        NameIdFormat::Transient,
+        NameIdFormat::Unspecified,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NameIdFormat::EmailAddress => "urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress",
+            NameIdFormat::Persistent => "urn:oasis:names:tc:SAML:2.0:nameid-format:persistent",
+            NameIdFormat::Transient => "urn:oasis:names:tc:SAML:2.0:nameid-format:transient",
+            NameIdFormat::Unspecified => "urn:oasis:names:tc:SAML:1.1:nameid-format:unspecified",
+        }
+    }
+}
+
+impl Display for NameIdFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for NameIdFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        NameIdFormat::ALL
+            .into_iter()
+            .find(|format| format.as_str() == value)
+            .ok_or_else(|| format!("unsupported name id format {value}"))
+    }
+}
+
+/// the ways a service provider can send an authentication request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SamlBinding {
+    /// deflated and base64 encoded in the query string
+    Redirect,
+    /// base64 encoded in a form field
+    Post,
+}
+
+/// SAML 2.0 Core section 3.4.1, the parts of an authentication request the identity provider acts on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthnRequest {
+    pub id: String,
+    /// the service provider's entity id
+    pub issuer: String,
+    pub destination: Option<String>,
+    pub assertion_consumer_service_url: Option<String>,
+    pub protocol_binding: Option<String>,
+    pub name_id_format: Option<String>,
+}
+
+/// the parts of a service provider's metadata the registry keeps
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceProviderMetadata {
+    pub entity_id: String,
+    /// locations that take the HTTP-POST binding, the default one first
+    pub assertion_consumer_services: Vec<String>,
+    /// in the service provider's order of preference
+    pub name_id_formats: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamlAttribute {
+    pub name: String,
+    pub value: String,
+}
+
+/// what a response tells a service provider about the signed-in user
+#[derive(Debug, Clone)]
+pub struct SamlAssertion {
+    /// the identity provider's entity id
+    pub issuer: String,
+    /// the service provider's entity id
+    pub audience: String,
+    /// the assertion consumer service the response is posted to
+    pub destination: String,
+    /// the id of the authentication request, absent when the identity provider started the sign-in
+    pub in_response_to: Option<String>,
+    pub name_id: String,
+    pub name_id_format: NameIdFormat,
+    pub session_index: Option<String>,
+    pub authn_instant: DateTime<Utc>,
+    pub attributes: Vec<SamlAttribute>,
+    /// how long the service provider may accept the assertion for
+    pub lifetime: Duration,
+}
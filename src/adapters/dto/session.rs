@@ -0,0 +1,8 @@
+use std::net::IpAddr;
+
+/// what a sign-in records about the device it came from
+#[derive(Debug, Clone, Default)]
+pub struct DeviceInfo {
+    pub ip_address: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
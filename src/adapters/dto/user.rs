@@ -8,4 +8,8 @@ pub struct UserDto {
     pub email: String,
     pub first_name: String,
     pub last_name: String,
+    /// the session of the token the profile was requested with
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_identifier: Option<Uuid>,
 }
@@ -0,0 +1,38 @@
+use std::path::{Path, PathBuf};
+
+use uuid::Uuid;
+
+use crate::{
+    adapters::mailer::{Email, Mailer},
+    errors::mailer_error::MailerError,
+};
+
+/// writes every email as a json document to a directory, meant for local development and tests
+pub struct FileMailer {
+    output_dir: PathBuf,
+}
+
+impl FileMailer {
+    pub fn new(output_dir: &Path) -> Self {
+        Self {
+            output_dir: output_dir.to_path_buf(),
+        }
+    }
+}
+
+impl Mailer for FileMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailerError> {
+        tokio::fs::create_dir_all(&self.output_dir).await?;
+
+        let file_name = format!(
+            "{}-{}.json",
+            chrono::Utc::now().format("%Y%m%d%H%M%S%3f"),
+            Uuid::new_v4()
+        );
+        let content = serde_json::to_vec_pretty(email)
+            .map_err(|err| MailerError::MessageError(err.to_string()))?;
+        tokio::fs::write(self.output_dir.join(file_name), content).await?;
+
+        Ok(())
+    }
+}
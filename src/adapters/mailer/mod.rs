@@ -0,0 +1,72 @@
+pub mod file;
+pub mod smtp;
+pub mod templates;
+
+use serde::Serialize;
+
+use crate::{
+    adapters::mailer::{file::FileMailer, smtp::SmtpMailer},
+    config::mailer::{MailTransportConfig, MailerConfig},
+    errors::mailer_error::MailerError,
+};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Email {
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: String,
+}
+
+pub trait Mailer {
+    fn send(
+        &self,
+        email: &Email,
+    ) -> impl std::future::Future<Output = Result<(), MailerError>> + Send;
+}
+
+/// the transport selected through `MAIL_TRANSPORT`
+pub enum MailTransport {
+    Smtp(SmtpMailer),
+    File(FileMailer),
+    Log,
+}
+
+impl MailTransport {
+    pub fn from_config(config: &MailerConfig) -> Result<Self, MailerError> {
+        let transport = match &config.transport {
+            MailTransportConfig::Smtp {
+                host,
+                port,
+                username,
+                password,
+            } => MailTransport::Smtp(SmtpMailer::new(
+                host,
+                *port,
+                username.as_deref(),
+                password.as_deref(),
+            )?),
+            MailTransportConfig::File { output_dir } => {
+                MailTransport::File(FileMailer::new(output_dir))
+            }
+            MailTransportConfig::Log => MailTransport::Log,
+        };
+
+        Ok(transport)
+    }
+}
+
+impl Mailer for MailTransport {
+    async fn send(&self, email: &Email) -> Result<(), MailerError> {
+        match self {
+            MailTransport::Smtp(mailer) => mailer.send(email).await,
+            MailTransport::File(mailer) => mailer.send(email).await,
+            // the body carries codes and links that let anyone reading the logs into the account
+            MailTransport::Log => {
+                log::info!("email to {}: {} (body withheld)", email.to, email.subject);
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor, message::MultiPart,
+    transport::smtp::authentication::Credentials,
+};
+
+use crate::{
+    adapters::mailer::{Email, Mailer},
+    errors::mailer_error::MailerError,
+};
+
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpMailer {
+    pub fn new(
+        host: &str,
+        port: u16,
+        username: Option<&str>,
+        password: Option<&str>,
+    ) -> Result<Self, MailerError> {
+        let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .map_err(|err| MailerError::TransportError(err.to_string()))?
+            .port(port);
+
+        if let (Some(username), Some(password)) = (username, password) {
+            builder = builder.credentials(Credentials::new(username.into(), password.into()));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+        })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailerError> {
+        let message = Message::builder()
+            .from(
+                email
+                    .from
+                    .parse()
+                    .map_err(|_| MailerError::InvalidAddress(email.from.clone()))?,
+            )
+            .to(email
+                .to
+                .parse()
+                .map_err(|_| MailerError::InvalidAddress(email.to.clone()))?)
+            .subject(&email.subject)
+            .multipart(MultiPart::alternative_plain_html(
+                email.text_body.clone(),
+                email.html_body.clone(),
+            ))
+            .map_err(|err| MailerError::MessageError(err.to_string()))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|err| MailerError::TransportError(err.to_string()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+const LAYOUT_HTML: &str = include_str!("../../../templates/emails/layout.html");
+const ACCOUNT_VERIFICATION_HTML: &str =
+    include_str!("../../../templates/emails/account_verification.html");
+const ACCOUNT_VERIFICATION_TEXT: &str =
+    include_str!("../../../templates/emails/account_verification.txt");
+const EMAIL_CHANGE_HTML: &str = include_str!("../../../templates/emails/email_change.html");
+const EMAIL_CHANGE_TEXT: &str = include_str!("../../../templates/emails/email_change.txt");
+const LOGIN_CODE_HTML: &str = include_str!("../../../templates/emails/login_code.html");
+const LOGIN_CODE_TEXT: &str = include_str!("../../../templates/emails/login_code.txt");
+const LOGIN_LINK_HTML: &str = include_str!("../../../templates/emails/login_link.html");
+const LOGIN_LINK_TEXT: &str = include_str!("../../../templates/emails/login_link.txt");
+const PASSWORD_RESET_HTML: &str = include_str!("../../../templates/emails/password_reset.html");
+const PASSWORD_RESET_TEXT: &str = include_str!("../../../templates/emails/password_reset.txt");
+const SECURITY_NOTIFICATION_HTML: &str =
+    include_str!("../../../templates/emails/security_notification.html");
+const SECURITY_NOTIFICATION_TEXT: &str =
+    include_str!("../../../templates/emails/security_notification.txt");
+
+#[derive(Debug, Clone)]
+pub enum EmailTemplate {
+    AccountVerification {
+        first_name: String,
+        otp: String,
+        expires_in_minutes: u64,
+    },
+    EmailChange {
+        first_name: String,
+        otp: String,
+        expires_in_minutes: u64,
+    },
+    LoginCode {
+        first_name: String,
+        code: String,
+        expires_in_minutes: u64,
+    },
+    LoginLink {
+        first_name: String,
+        login_link: String,
+        expires_in_minutes: u64,
+    },
+    PasswordReset {
+        first_name: String,
+        reset_link: String,
+        expires_in_minutes: u64,
+    },
+    SecurityNotification {
+        first_name: String,
+        event: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderedTemplate {
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: String,
+}
+
+impl EmailTemplate {
+    pub fn render(&self) -> RenderedTemplate {
+        let (subject, html, text, variables) = match self {
+            EmailTemplate::AccountVerification {
+                first_name,
+                otp,
+                expires_in_minutes,
+            } => (
+                "Verify your account",
+                ACCOUNT_VERIFICATION_HTML,
+                ACCOUNT_VERIFICATION_TEXT,
+                vec![
+                    ("first_name", first_name.to_string()),
+                    ("otp", otp.to_string()),
+                    ("expires_in", expires_in_minutes.to_string()),
+                ],
+            ),
+            EmailTemplate::EmailChange {
+                first_name,
+                otp,
+                expires_in_minutes,
+            } => (
+                "Confirm your new email address",
+                EMAIL_CHANGE_HTML,
+                EMAIL_CHANGE_TEXT,
+                vec![
+                    ("first_name", first_name.to_string()),
+                    ("otp", otp.to_string()),
+                    ("expires_in", expires_in_minutes.to_string()),
+                ],
+            ),
+            EmailTemplate::LoginCode {
+                first_name,
+                code,
+                expires_in_minutes,
+            } => (
+                "Your sign-in code",
+                LOGIN_CODE_HTML,
+                LOGIN_CODE_TEXT,
+                vec![
+                    ("first_name", first_name.to_string()),
+                    ("code", code.to_string()),
+                    ("expires_in", expires_in_minutes.to_string()),
+                ],
+            ),
+            EmailTemplate::LoginLink {
+                first_name,
+                login_link,
+                expires_in_minutes,
+            } => (
+                "Sign in to your account",
+                LOGIN_LINK_HTML,
+                LOGIN_LINK_TEXT,
+                vec![
+                    ("first_name", first_name.to_string()),
+                    ("login_link", login_link.to_string()),
+                    ("expires_in", expires_in_minutes.to_string()),
+                ],
+            ),
+            EmailTemplate::PasswordReset {
+                first_name,
+                reset_link,
+                expires_in_minutes,
+            } => (
+                "Reset your password",
+                PASSWORD_RESET_HTML,
+                PASSWORD_RESET_TEXT,
+                vec![
+                    ("first_name", first_name.to_string()),
+                    ("reset_link", reset_link.to_string()),
+                    ("expires_in", expires_in_minutes.to_string()),
+                ],
+            ),
+            EmailTemplate::SecurityNotification { first_name, event } => (
+                "Security alert for your account",
+                SECURITY_NOTIFICATION_HTML,
+                SECURITY_NOTIFICATION_TEXT,
+                vec![
+                    ("first_name", first_name.to_string()),
+                    ("event", event.to_string()),
+                ],
+            ),
+        };
+
+        let content = substitute(html, &variables, escape_html);
+        let html_body = substitute(
+            LAYOUT_HTML,
+            &[("subject", subject.to_string())],
+            escape_html,
+        )
+        .replace("{{content}}", &content);
+
+        RenderedTemplate {
+            subject: subject.to_string(),
+            text_body: substitute(text, &variables, |value| value.to_string()),
+            html_body,
+        }
+    }
+}
+
+fn substitute(template: &str, variables: &[(&str, String)], escape: fn(&str) -> String) -> String {
+    variables
+        .iter()
+        .fold(template.to_string(), |rendered, (key, value)| {
+            rendered.replace(&format!("{{{{{key}}}}}"), &escape(value))
+        })
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
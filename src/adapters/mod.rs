@@ -1,3 +1,5 @@
 pub mod dto;
+pub mod mailer;
+pub mod rate_limit;
 pub mod requests;
 pub mod response;
@@ -0,0 +1,58 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+use crate::{
+    adapters::rate_limit::{RateLimitHit, RateLimitStore},
+    errors::common_service_error::ServiceError,
+};
+
+/// counters kept in the process, each replica would count on its own
+#[derive(Clone, Default)]
+pub struct MemoryStore {
+    counters: Arc<Mutex<HashMap<String, (u32, Instant)>>>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn delete_expired(&self) {
+        let now = Instant::now();
+        self.counters
+            .lock()
+            .expect("the rate limit counters lock is not poisoned")
+            .retain(|_, (_, window_ends_at)| *window_ends_at > now);
+    }
+}
+
+impl RateLimitStore for MemoryStore {
+    async fn hit(&self, key: &str, window: Duration) -> Result<RateLimitHit, ServiceError> {
+        let now = Instant::now();
+        let mut counters = self
+            .counters
+            .lock()
+            .expect("the rate limit counters lock is not poisoned");
+
+        let (hits, window_ends_at) = counters
+            .entry(key.to_string())
+            .and_modify(|(hits, window_ends_at)| {
+                if *window_ends_at <= now {
+                    *hits = 0;
+                    *window_ends_at = now + window;
+                }
+                *hits += 1;
+            })
+            .or_insert((1, now + window));
+
+        Ok(RateLimitHit {
+            hits: *hits,
+            reset_after: window_ends_at.saturating_duration_since(now),
+        })
+    }
+}
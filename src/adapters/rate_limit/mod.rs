@@ -0,0 +1,76 @@
+pub mod memory;
+pub mod postgres;
+
+use std::time::Duration;
+
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    adapters::rate_limit::{memory::MemoryStore, postgres::PostgresStore},
+    config::rate_limit::{RateLimitConfig, RateLimitStoreConfig},
+    errors::common_service_error::ServiceError,
+};
+
+/// the state of a key's window after a request was counted against it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitHit {
+    pub hits: u32,
+    /// time left until the window ends and the count starts over
+    pub reset_after: Duration,
+}
+
+pub trait RateLimitStore {
+    fn hit(
+        &self,
+        key: &str,
+        window: Duration,
+    ) -> impl std::future::Future<Output = Result<RateLimitHit, ServiceError>> + Send;
+}
+
+/// the store selected through `RATE_LIMIT_STORE`
+#[derive(Clone)]
+pub enum RateLimitBackend {
+    Memory(MemoryStore),
+    Postgres(PostgresStore),
+}
+
+impl RateLimitBackend {
+    pub fn from_config(config: &RateLimitConfig, pool: &Pool<Postgres>) -> Self {
+        match config.store {
+            RateLimitStoreConfig::Memory => RateLimitBackend::Memory(MemoryStore::new()),
+            RateLimitStoreConfig::Postgres => RateLimitBackend::Postgres(PostgresStore::new(pool)),
+        }
+    }
+
+    /// removes counters whose window has ended, in the background
+    pub fn spawn_cleanup(&self, period: Duration) {
+        let backend = self.clone();
+
+        tokio::task::spawn(async move {
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                interval.tick().await;
+                match &backend {
+                    RateLimitBackend::Memory(store) => store.delete_expired(),
+                    RateLimitBackend::Postgres(store) => match store.delete_expired().await {
+                        Ok(0) => {}
+                        Ok(deleted) => log::info!("removed {deleted} expired rate limit counters"),
+                        Err(err) => {
+                            log::error!("error removing expired rate limit counters: {err}")
+                        }
+                    },
+                }
+            }
+        });
+    }
+}
+
+impl RateLimitStore for RateLimitBackend {
+    async fn hit(&self, key: &str, window: Duration) -> Result<RateLimitHit, ServiceError> {
+        match self {
+            RateLimitBackend::Memory(store) => store.hit(key, window).await,
+            RateLimitBackend::Postgres(store) => store.hit(key, window).await,
+        }
+    }
+}
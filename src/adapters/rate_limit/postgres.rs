@@ -0,0 +1,43 @@
+use std::time::Duration;
+
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    adapters::rate_limit::{RateLimitHit, RateLimitStore},
+    errors::common_service_error::ServiceError,
+    repositories::rate_limit_repository::{RateLimitRepository, RateLimitRepositoryTrait},
+};
+
+/// counters in the `rate_limit_counters` table, shared by every replica
+#[derive(Clone)]
+pub struct PostgresStore {
+    rate_limit_repository: RateLimitRepository,
+}
+
+impl PostgresStore {
+    pub fn new(pool: &Pool<Postgres>) -> Self {
+        Self {
+            rate_limit_repository: RateLimitRepository::init(pool),
+        }
+    }
+
+    pub async fn delete_expired(&self) -> Result<u64, ServiceError> {
+        self.rate_limit_repository.delete_expired().await
+    }
+}
+
+impl RateLimitStore for PostgresStore {
+    async fn hit(&self, key: &str, window: Duration) -> Result<RateLimitHit, ServiceError> {
+        let (hits, window_ends_at) = self
+            .rate_limit_repository
+            .increment(key, window.as_secs_f64())
+            .await?;
+
+        Ok(RateLimitHit {
+            hits: hits.max(0) as u32,
+            reset_after: (window_ends_at - chrono::Utc::now())
+                .to_std()
+                .unwrap_or_default(),
+        })
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use validator::Validate;
 
-use crate::adapters::dto::jwt::Claims;
+use crate::adapters::requests::webauthn::AssertionCredential;
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
@@ -23,6 +23,41 @@ pub struct LoginRequest {
     pub email: String,
     #[validate(length(min = 1, message = "password cannot be empty"))]
     pub password: String,
+    /// first-party client the tokens are issued to, defaults to the configured first-party client
+    pub client_id: Option<String>,
+}
+
+/// what the passwordless sign-in email carries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailLoginMethod {
+    /// a single-use link to the client application
+    #[default]
+    Link,
+    /// a six digit code the user types in
+    Code,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct EmailLoginRequest {
+    #[validate(email)]
+    pub email: String,
+    #[serde(default)]
+    pub method: EmailLoginMethod,
+}
+
+/// redeems what `/login/email` sent, either the token from the link or the email address with the
+/// code
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyEmailLoginRequest {
+    pub token: Option<String>,
+    #[validate(email)]
+    pub email: Option<String>,
+    pub code: Option<String>,
+    /// first-party client the tokens are issued to, defaults to the configured first-party client
+    pub client_id: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate)]
@@ -35,6 +70,8 @@ pub struct ForgottenPasswordRequest {
 #[derive(Debug, Serialize, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct SetNewPasswordRequest {
+    #[validate(length(min = 1, message = "reset token is required"))]
+    pub token: String,
     #[validate(length(min = 1, message = "password cannot be empty"))]
     pub password: String,
     #[validate(must_match(other = "password", message = "password does  not match"))]
@@ -44,7 +81,73 @@ pub struct SetNewPasswordRequest {
 #[derive(Debug, Serialize, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct VerifyAccountRequest {
+    #[validate(length(equal = 6, message = "otp must be six digits"))]
     pub otp: String,
 }
 
-pub type RefreshTokenRequest = Claims;
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshTokenRequest {
+    #[validate(length(min = 1, message = "refresh token is required"))]
+    pub refresh_token: String,
+    pub client_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoutRequest {
+    /// also ends the refresh token family when given, otherwise only the access token is revoked
+    pub refresh_token: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeTokenRequest {
+    #[validate(length(min = 1, message = "token is required"))]
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeEmailRequest {
+    #[validate(email)]
+    pub email: String,
+    #[validate(length(min = 1, message = "password cannot be empty"))]
+    pub password: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmEmailChangeRequest {
+    #[validate(length(equal = 6, message = "otp must be six digits"))]
+    pub otp: String,
+}
+
+/// second step of a login for users with a second factor, any one of them will do
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct MfaLoginRequest {
+    #[validate(length(min = 1, message = "mfa token is required"))]
+    pub mfa_token: String,
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+    /// a passkey assertion against the options from `/login/mfa/webauthn/options`
+    pub credential: Option<AssertionCredential>,
+}
+
+/// the query a sign-in through an identity provider is started with
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FederatedLoginRequest {
+    /// first-party client the tokens are issued to, defaults to the configured first-party client
+    pub client_id: Option<String>,
+}
+
+/// the query the identity provider redirects back with, an error in place of the code when the
+/// user did not sign in
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FederatedCallbackRequest {
+    pub state: Option<String>,
+    pub code: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+use validator::{Validate, ValidationError};
+
+use crate::adapters::dto::oauth::{
+    GRANT_AUTHORIZATION_CODE, SUPPORTED_GRANT_TYPES, is_valid_scope_token,
+};
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateClientRequest {
+    #[validate(length(min = 1, message = "name cannot be empty"))]
+    pub name: String,
+    #[serde(default)]
+    #[validate(custom(function = "validate_redirect_uris"))]
+    pub redirect_uris: Vec<String>,
+    #[validate(custom(function = "validate_grant_types"))]
+    pub grant_types: Vec<String>,
+    #[validate(custom(function = "validate_scopes"))]
+    pub scopes: Vec<String>,
+    /// confidential clients are issued a secret, public clients rely on PKCE alone
+    pub confidential: bool,
+    #[serde(default)]
+    pub first_party: bool,
+    #[validate(range(
+        min = 60,
+        max = 86400,
+        message = "access token lifetime must be between a minute and a day"
+    ))]
+    pub access_token_ttl_seconds: Option<i32>,
+    #[validate(range(min = 60, message = "refresh token lifetime must be at least a minute"))]
+    pub refresh_token_ttl_seconds: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateClientRequest {
+    #[validate(length(min = 1, message = "name cannot be empty"))]
+    pub name: String,
+    #[serde(default)]
+    #[validate(custom(function = "validate_redirect_uris"))]
+    pub redirect_uris: Vec<String>,
+    #[validate(custom(function = "validate_grant_types"))]
+    pub grant_types: Vec<String>,
+    #[validate(custom(function = "validate_scopes"))]
+    pub scopes: Vec<String>,
+    pub first_party: bool,
+    #[validate(range(
+        min = 60,
+        max = 86400,
+        message = "access token lifetime must be between a minute and a day"
+    ))]
+    pub access_token_ttl_seconds: i32,
+    #[validate(range(min = 60, message = "refresh token lifetime must be at least a minute"))]
+    pub refresh_token_ttl_seconds: i32,
+}
+
+/// absolute uris without a fragment, as RFC 6749 requires of redirection endpoints
+fn validate_redirect_uris(redirect_uris: &[String]) -> Result<(), ValidationError> {
+    let valid = redirect_uris.iter().all(|redirect_uri| {
+        Url::parse(redirect_uri).is_ok_and(|url| url.fragment().is_none() && url.has_host())
+    });
+    if !valid {
+        return Err(ValidationError::new("redirect_uris")
+            .with_message("redirect uris must be absolute and have no fragment".into()));
+    }
+
+    Ok(())
+}
+
+fn validate_grant_types(grant_types: &[String]) -> Result<(), ValidationError> {
+    if grant_types.is_empty()
+        || grant_types
+            .iter()
+            .any(|grant_type| !SUPPORTED_GRANT_TYPES.contains(&grant_type.as_str()))
+    {
+        return Err(ValidationError::new("grant_types").with_message(
+            format!(
+                "grant types must be one of {}",
+                SUPPORTED_GRANT_TYPES.join(", ")
+            )
+            .into(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_scopes(scopes: &[String]) -> Result<(), ValidationError> {
+    if scopes.iter().any(|scope| !is_valid_scope_token(scope)) {
+        return Err(ValidationError::new("scopes")
+            .with_message("scopes must be non-empty and contain no spaces or quotes".into()));
+    }
+
+    Ok(())
+}
+
+/// the authorization code flow is useless without somewhere to send the code
+pub fn requires_redirect_uris(grant_types: &[String], redirect_uris: &[String]) -> bool {
+    grant_types
+        .iter()
+        .any(|grant_type| grant_type == GRANT_AUTHORIZATION_CODE)
+        && redirect_uris.is_empty()
+}
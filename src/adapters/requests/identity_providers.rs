@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use url::Url;
+use validator::{Validate, ValidationError};
+
+use crate::adapters::dto::oauth::{SCOPE_OPENID, is_valid_scope_token};
+
+/// taken by the other sign-in routes under `/login`
+const RESERVED_SLUGS: [&str; 3] = ["email", "mfa", "webauthn"];
+
+const DEFAULT_SCOPES: [&str; 3] = ["openid", "email", "profile"];
+const DEFAULT_EMAIL_CLAIM: &str = "email";
+const DEFAULT_FIRST_NAME_CLAIM: &str = "given_name";
+const DEFAULT_LAST_NAME_CLAIM: &str = "family_name";
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateIdentityProviderRequest {
+    #[validate(custom(function = "validate_slug"))]
+    pub slug: String,
+    #[validate(length(min = 1, message = "name cannot be empty"))]
+    pub name: String,
+    #[validate(custom(function = "validate_issuer"))]
+    pub issuer: String,
+    #[validate(length(min = 1, message = "client id cannot be empty"))]
+    pub client_id: String,
+    #[validate(length(min = 1, message = "client secret cannot be empty"))]
+    pub client_secret: String,
+    #[serde(default = "default_scopes")]
+    #[validate(custom(function = "validate_scopes"))]
+    pub scopes: Vec<String>,
+    #[serde(default = "default_email_claim")]
+    #[validate(length(min = 1, message = "email claim cannot be empty"))]
+    pub email_claim: String,
+    #[serde(default = "default_first_name_claim")]
+    #[validate(length(min = 1, message = "first name claim cannot be empty"))]
+    pub first_name_claim: String,
+    #[serde(default = "default_last_name_claim")]
+    #[validate(length(min = 1, message = "last name claim cannot be empty"))]
+    pub last_name_claim: String,
+    #[serde(default)]
+    pub trust_email: bool,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateIdentityProviderRequest {
+    #[validate(length(min = 1, message = "name cannot be empty"))]
+    pub name: String,
+    #[validate(custom(function = "validate_issuer"))]
+    pub issuer: String,
+    #[validate(length(min = 1, message = "client id cannot be empty"))]
+    pub client_id: String,
+    /// the current secret is kept when left out
+    #[validate(length(min = 1, message = "client secret cannot be empty"))]
+    pub client_secret: Option<String>,
+    #[validate(custom(function = "validate_scopes"))]
+    pub scopes: Vec<String>,
+    #[validate(length(min = 1, message = "email claim cannot be empty"))]
+    pub email_claim: String,
+    #[validate(length(min = 1, message = "first name claim cannot be empty"))]
+    pub first_name_claim: String,
+    #[validate(length(min = 1, message = "last name claim cannot be empty"))]
+    pub last_name_claim: String,
+    pub trust_email: bool,
+    pub enabled: bool,
+}
+
+fn default_scopes() -> Vec<String> {
+    DEFAULT_SCOPES.map(String::from).to_vec()
+}
+
+fn default_email_claim() -> String {
+    DEFAULT_EMAIL_CLAIM.into()
+}
+
+fn default_first_name_claim() -> String {
+    DEFAULT_FIRST_NAME_CLAIM.into()
+}
+
+fn default_last_name_claim() -> String {
+    DEFAULT_LAST_NAME_CLAIM.into()
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// the slug is a path segment of the login routes
+fn validate_slug(slug: &str) -> Result<(), ValidationError> {
+    let valid = (1..=64).contains(&slug.len())
+        && slug
+            .bytes()
+            .all(|byte| byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-')
+        && !RESERVED_SLUGS.contains(&slug);
+    if !valid {
+        return Err(ValidationError::new("slug").with_message(
+            "slugs are up to 64 lowercase letters, digits and hyphens, and cannot be email, mfa or webauthn"
+                .into(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// discovery and the keys are fetched from the issuer, so it must be https unless it is local
+fn validate_issuer(issuer: &str) -> Result<(), ValidationError> {
+    let valid = Url::parse(issuer).is_ok_and(|url| {
+        let local = matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+        (url.scheme() == "https" || (url.scheme() == "http" && local))
+            && url.query().is_none()
+            && url.fragment().is_none()
+    });
+    if !valid {
+        return Err(ValidationError::new("issuer")
+            .with_message("the issuer must be an https url without a query or fragment".into()));
+    }
+
+    Ok(())
+}
+
+fn validate_scopes(scopes: &[String]) -> Result<(), ValidationError> {
+    if !scopes.iter().any(|scope| scope == SCOPE_OPENID)
+        || scopes.iter().any(|scope| !is_valid_scope_token(scope))
+    {
+        return Err(ValidationError::new("scopes")
+            .with_message("scopes must include openid and contain no spaces or quotes".into()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmTotpRequest {
+    #[validate(length(equal = 6, message = "code must be six digits"))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct DisableTotpRequest {
+    #[validate(length(min = 1, message = "password cannot be empty"))]
+    pub password: String,
+    pub code: Option<String>,
+    /// for users who lost the authenticator
+    pub recovery_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RegenerateRecoveryCodesRequest {
+    #[validate(length(equal = 6, message = "code must be six digits"))]
+    pub code: String,
+}
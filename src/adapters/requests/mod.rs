@@ -1 +1,8 @@
 pub mod auth;
+pub mod clients;
+pub mod identity_providers;
+pub mod mfa;
+pub mod oauth;
+pub mod saml;
+pub mod service_providers;
+pub mod webauthn;
@@ -0,0 +1,103 @@
+use axum_extra::headers::authorization::Basic;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::errors::oauth_error::OAuthError;
+
+/// parameters of an authorization request, every field is optional so that missing ones are
+/// reported in the oauth error format instead of being rejected by the extractor
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct AuthorizationRequest {
+    pub response_type: Option<String>,
+    pub client_id: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub scope: Option<String>,
+    pub state: Option<String>,
+    pub nonce: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenRequest {
+    pub grant_type: Option<String>,
+    pub code: Option<String>,
+    pub redirect_uri: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub code_verifier: Option<String>,
+    pub refresh_token: Option<String>,
+    pub scope: Option<String>,
+    pub device_code: Option<String>,
+    pub subject_token: Option<String>,
+    pub subject_token_type: Option<String>,
+    pub actor_token: Option<String>,
+    pub actor_token_type: Option<String>,
+    pub requested_token_type: Option<String>,
+    pub audience: Option<String>,
+    /// identifier of the user to impersonate, the subject token must then belong to an admin
+    /// allowed to do so
+    pub requested_subject: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeviceAuthorizationRequest {
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    pub scope: Option<String>,
+}
+
+/// the signed in user's answer to a device authorization request
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceVerificationRequest {
+    #[validate(length(min = 1, message = "user code is required"))]
+    pub user_code: String,
+    pub approve: bool,
+}
+
+/// body of an introspection request, `token_type_hint` is accepted but the kind of token is
+/// worked out from the token itself
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IntrospectionRequest {
+    pub token: Option<String>,
+    pub token_type_hint: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+/// the revocation endpoint takes the same parameters as the introspection endpoint
+pub type RevocationRequest = IntrospectionRequest;
+
+/// how a client identified itself at the token, introspection or revocation endpoint, either with HTTP basic authentication or
+/// with `client_id` and `client_secret` in the request body, never both
+#[derive(Debug, Clone)]
+pub struct ClientCredentials {
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+impl ClientCredentials {
+    pub fn from_request(
+        basic: Option<Basic>,
+        client_id: Option<&str>,
+        client_secret: Option<&str>,
+    ) -> Result<Self, OAuthError> {
+        match basic {
+            Some(_) if client_secret.is_some() => Err(OAuthError::InvalidRequest(
+                "only one client authentication method may be used".into(),
+            )),
+            Some(basic) if client_id.is_some_and(|client_id| client_id != basic.username()) => {
+                Err(OAuthError::InvalidClient)
+            }
+            Some(basic) => Ok(Self {
+                client_id: Some(basic.username().to_string()),
+                client_secret: Some(basic.password().to_string()),
+            }),
+            None => Ok(Self {
+                client_id: client_id.map(String::from),
+                client_secret: client_secret.map(String::from),
+            }),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// parameters of the HTTP-Redirect and HTTP-POST bindings, optional so that a missing request is
+/// reported in the api format instead of being rejected by the extractor
+///
+/// a signature on a redirect is not checked, assertions only ever go to the locations the service
+/// provider registered
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SamlSsoRequest {
+    #[serde(rename = "SAMLRequest")]
+    pub saml_request: Option<String>,
+    #[serde(rename = "RelayState")]
+    pub relay_state: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CompleteSamlSsoRequest {
+    /// the `SAMLRequest` the login page was sent to with, encoded as the redirect binding does
+    #[validate(length(min = 1, message = "saml request cannot be empty"))]
+    pub saml_request: String,
+    pub relay_state: Option<String>,
+}
+
+/// signs the user in at a service provider that did not ask for it
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct InitiateSamlSsoRequest {
+    #[validate(length(min = 1, message = "entity id cannot be empty"))]
+    pub entity_id: String,
+    /// passed back to the service provider untouched, often the page to land on
+    pub relay_state: Option<String>,
+}
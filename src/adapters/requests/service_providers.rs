@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use crate::adapters::dto::saml::NameIdFormat;
+
+const DEFAULT_EMAIL_ATTRIBUTE: &str = "email";
+const DEFAULT_FIRST_NAME_ATTRIBUTE: &str = "firstName";
+const DEFAULT_LAST_NAME_ATTRIBUTE: &str = "lastName";
+
+/// the attribute fields name the attribute each part of the profile is released as, null leaves it
+/// out of the assertion
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateServiceProviderRequest {
+    #[validate(length(min = 1, message = "name cannot be empty"))]
+    pub name: String,
+    /// the service provider's SAML metadata, its entity id and assertion consumer services are
+    /// read from it
+    #[validate(length(min = 1, message = "metadata cannot be empty"))]
+    pub metadata: String,
+    /// the first format the metadata lists that is supported when left out, else the email address
+    pub name_id_format: Option<NameIdFormat>,
+    #[serde(default = "default_email_attribute")]
+    #[validate(length(min = 1, message = "attribute names cannot be empty"))]
+    pub email_attribute: Option<String>,
+    #[serde(default = "default_first_name_attribute")]
+    #[validate(length(min = 1, message = "attribute names cannot be empty"))]
+    pub first_name_attribute: Option<String>,
+    #[serde(default = "default_last_name_attribute")]
+    #[validate(length(min = 1, message = "attribute names cannot be empty"))]
+    pub last_name_attribute: Option<String>,
+    #[validate(length(min = 1, message = "attribute names cannot be empty"))]
+    pub display_name_attribute: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateServiceProviderRequest {
+    #[validate(length(min = 1, message = "name cannot be empty"))]
+    pub name: String,
+    /// the current metadata is kept when left out
+    #[validate(length(min = 1, message = "metadata cannot be empty"))]
+    pub metadata: Option<String>,
+    pub name_id_format: NameIdFormat,
+    #[validate(length(min = 1, message = "attribute names cannot be empty"))]
+    pub email_attribute: Option<String>,
+    #[validate(length(min = 1, message = "attribute names cannot be empty"))]
+    pub first_name_attribute: Option<String>,
+    #[validate(length(min = 1, message = "attribute names cannot be empty"))]
+    pub last_name_attribute: Option<String>,
+    #[validate(length(min = 1, message = "attribute names cannot be empty"))]
+    pub display_name_attribute: Option<String>,
+    pub enabled: bool,
+}
+
+fn default_email_attribute() -> Option<String> {
+    Some(DEFAULT_EMAIL_ATTRIBUTE.into())
+}
+
+fn default_first_name_attribute() -> Option<String> {
+    Some(DEFAULT_FIRST_NAME_ATTRIBUTE.into())
+}
+
+fn default_last_name_attribute() -> Option<String> {
+    Some(DEFAULT_LAST_NAME_ATTRIBUTE.into())
+}
+
+fn default_enabled() -> bool {
+    true
+}
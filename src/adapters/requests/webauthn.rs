@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// `AuthenticatorAttestationResponse` as serialised by `PublicKeyCredential.toJSON()`, binary
+/// fields are base64url
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttestationResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub attestation_object: String,
+    #[serde(default)]
+    pub transports: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationCredential {
+    /// base64url credential id
+    pub id: String,
+    pub response: AttestationResponse,
+}
+
+/// `AuthenticatorAssertionResponse` as serialised by `PublicKeyCredential.toJSON()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionResponse {
+    #[serde(rename = "clientDataJSON")]
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+    /// set by passkeys, the user id given at registration
+    pub user_handle: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionCredential {
+    pub id: String,
+    pub response: AssertionResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationOptionsRequest {
+    /// the current password, needed unless the user signed in recently
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterCredentialRequest {
+    /// shown in the list of passkeys, defaults to "Passkey"
+    #[validate(length(max = 255, message = "name cannot be longer than 255 characters"))]
+    pub name: Option<String>,
+    pub credential: RegistrationCredential,
+    /// the current password, needed unless the user signed in recently
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyLoginRequest {
+    pub credential: AssertionCredential,
+    /// first-party client the tokens are issued to, defaults to the configured first-party client
+    pub client_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct MfaWebAuthnOptionsRequest {
+    #[validate(length(min = 1, message = "mfa token is required"))]
+    pub mfa_token: String,
+}
@@ -4,22 +4,36 @@ use serde::{Deserialize, Serialize};
 #[serde(rename_all = "camelCase")]
 pub struct CreateUserResponse {
     pub email: String,
-    pub password: String,
     pub first_name: String,
     pub last_name: String,
+    pub verification_token: String,
 }
 
+/// the tokens, or a challenge to complete at `/login/mfa` when the user has an authenticator
 #[derive(Debug, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct LoginResponse {
-    pub token: String,
+#[serde(untagged, rename_all_fields = "camelCase")]
+pub enum LoginResponse {
+    Authenticated {
+        token: String,
+        refresh_token: String,
+    },
+    MfaRequired {
+        mfa_token: String,
+        /// seconds
+        expires_in: u64,
+        /// the second factors the user can complete the login with
+        methods: Vec<String>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct ForgottenPasswordResponse {
-    pub token: String,
-}
+pub struct EmailLoginResponse {}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForgottenPasswordResponse {}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SetNewPasswordResponse {}
@@ -28,8 +42,39 @@ pub struct SetNewPasswordResponse {}
 #[serde(rename_all = "camelCase")]
 pub struct VerifyAccountResponse {}
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResendVerificationResponse {
+    pub verification_token: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RefreshTokenResponse {
     pub token: String,
+    pub refresh_token: String,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoutResponse {}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevokeTokenResponse {}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoutEverywhereResponse {}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnlockUserResponse {}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeEmailResponse {}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmEmailChangeResponse {}
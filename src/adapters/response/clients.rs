@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::oauth_client::OAuthClientEntity;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientResponse {
+    pub client_id: String,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub scopes: Vec<String>,
+    pub confidential: bool,
+    pub first_party: bool,
+    pub access_token_ttl_seconds: i32,
+    pub refresh_token_ttl_seconds: i32,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<OAuthClientEntity> for ClientResponse {
+    fn from(client: OAuthClientEntity) -> Self {
+        Self {
+            confidential: client.is_confidential(),
+            client_id: client.client_id,
+            name: client.name,
+            redirect_uris: client.redirect_uris,
+            grant_types: client.grant_types,
+            scopes: client.scopes,
+            first_party: client.first_party,
+            access_token_ttl_seconds: client.access_token_ttl_seconds,
+            refresh_token_ttl_seconds: client.refresh_token_ttl_seconds,
+            created_at: client.created_at,
+            updated_at: client.updated_at,
+        }
+    }
+}
+
+/// the secret is only ever shown here, it is stored hashed
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateClientResponse {
+    #[serde(flatten)]
+    pub client: ClientResponse,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientSecretResponse {
+    pub client_id: String,
+    pub client_secret: String,
+}
@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::entities::identity_provider::IdentityProviderEntity;
+
+/// the client secret is never shown again once it has been registered
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityProviderResponse {
+    pub slug: String,
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    pub scopes: Vec<String>,
+    pub email_claim: String,
+    pub first_name_claim: String,
+    pub last_name_claim: String,
+    pub trust_email: bool,
+    pub enabled: bool,
+    /// to be registered with the provider as the client's redirect uri
+    pub redirect_uri: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl IdentityProviderResponse {
+    pub fn new(provider: IdentityProviderEntity, redirect_uri: String) -> Self {
+        Self {
+            slug: provider.slug,
+            name: provider.name,
+            issuer: provider.issuer,
+            client_id: provider.client_id,
+            scopes: provider.scopes,
+            email_claim: provider.email_claim,
+            first_name_claim: provider.first_name_claim,
+            last_name_claim: provider.last_name_claim,
+            trust_email: provider.trust_email,
+            enabled: provider.enabled,
+            redirect_uri,
+            created_at: provider.created_at,
+            updated_at: provider.updated_at,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MfaStatusResponse {
+    pub totp_enabled: bool,
+    pub recovery_codes_remaining: i64,
+    pub passkeys: usize,
+}
+
+/// the secret is shown once, for authenticator apps that cannot scan the provisioning uri
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TotpEnrollmentResponse {
+    pub secret: String,
+    /// `otpauth://` uri to render as a QR code
+    pub provisioning_uri: String,
+}
+
+/// the codes are only ever shown here, they are stored hashed
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DisableTotpResponse {}
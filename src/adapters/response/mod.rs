@@ -1,2 +1,11 @@
 pub mod api_response;
 pub mod auth;
+pub mod clients;
+pub mod identity_providers;
+pub mod mfa;
+pub mod oauth;
+pub mod root;
+pub mod saml;
+pub mod service_providers;
+pub mod sessions;
+pub mod webauthn;
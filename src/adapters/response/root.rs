@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+use crate::shared::hashing_pool::HashingPoolMetrics;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheckResponse {
+    pub password_hashing: HashingPoolMetrics,
+}
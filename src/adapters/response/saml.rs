@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// the HTTP-POST binding is the only one a response can take, the client application posts a form
+/// with `SAMLResponse` and `RelayState` to `destination` on the user's behalf
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SamlSsoResponse {
+    /// the service provider's assertion consumer service
+    pub destination: String,
+    /// the signed response, base64 encoded
+    pub saml_response: String,
+    pub relay_state: Option<String>,
+}
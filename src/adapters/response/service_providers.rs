@@ -0,0 +1,41 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::saml_service_provider::SamlServiceProviderEntity;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceProviderResponse {
+    pub identifier: Uuid,
+    pub entity_id: String,
+    pub name: String,
+    pub assertion_consumer_services: Vec<String>,
+    pub name_id_format: String,
+    pub email_attribute: Option<String>,
+    pub first_name_attribute: Option<String>,
+    pub last_name_attribute: Option<String>,
+    pub display_name_attribute: Option<String>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl From<SamlServiceProviderEntity> for ServiceProviderResponse {
+    fn from(provider: SamlServiceProviderEntity) -> Self {
+        Self {
+            identifier: provider.identifier,
+            entity_id: provider.entity_id,
+            name: provider.name,
+            assertion_consumer_services: provider.assertion_consumer_services,
+            name_id_format: provider.name_id_format,
+            email_attribute: provider.email_attribute,
+            first_name_attribute: provider.first_name_attribute,
+            last_name_attribute: provider.last_name_attribute,
+            display_name_attribute: provider.display_name_attribute,
+            enabled: provider.enabled,
+            created_at: provider.created_at,
+            updated_at: provider.updated_at,
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::entities::webauthn_credential::WebAuthnCredentialEntity;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelyingParty {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCredentialUser {
+    /// base64url of the user identifier, returned as the user handle when the passkey is used
+    pub id: String,
+    pub name: String,
+    pub display_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCredentialParameters {
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub alg: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublicKeyCredentialDescriptor {
+    #[serde(rename = "type")]
+    pub credential_type: String,
+    pub id: String,
+    pub transports: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthenticatorSelection {
+    pub resident_key: String,
+    pub user_verification: String,
+}
+
+/// the `publicKey` member of `navigator.credentials.create()`, binary fields are base64url
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialCreationOptions {
+    pub rp: RelyingParty,
+    pub user: PublicKeyCredentialUser,
+    pub challenge: String,
+    pub pub_key_cred_params: Vec<PublicKeyCredentialParameters>,
+    /// milliseconds
+    pub timeout: u64,
+    pub exclude_credentials: Vec<PublicKeyCredentialDescriptor>,
+    pub authenticator_selection: AuthenticatorSelection,
+    pub attestation: String,
+}
+
+/// the `publicKey` member of `navigator.credentials.get()`, `allowCredentials` is empty for a
+/// passwordless login so that the authenticator offers its passkeys
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialRequestOptions {
+    pub challenge: String,
+    /// milliseconds
+    pub timeout: u64,
+    pub rp_id: String,
+    pub allow_credentials: Vec<PublicKeyCredentialDescriptor>,
+    pub user_verification: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialCreationOptionsResponse {
+    pub public_key: CredentialCreationOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialRequestOptionsResponse {
+    pub public_key: CredentialRequestOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebAuthnCredentialResponse {
+    pub identifier: Uuid,
+    pub name: String,
+    pub transports: Vec<String>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<WebAuthnCredentialEntity> for WebAuthnCredentialResponse {
+    fn from(credential: WebAuthnCredentialEntity) -> Self {
+        Self {
+            identifier: credential.identifier,
+            name: credential.name,
+            transports: credential.transports,
+            last_used_at: credential.last_used_at,
+            created_at: credential.created_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveWebAuthnCredentialResponse {}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use crate::shared::extract_env::extract_env_or;
+
+const DEFAULT_EMAIL_LOGIN_URL: &str = "http://localhost:3000/login/email";
+const DEFAULT_EMAIL_LOGIN_EXPIRY_SECONDS: u64 = 10 * 60;
+
+#[derive(Debug, Clone)]
+pub struct EmailLoginConfig {
+    /// page of the client application the sign-in link points to, the token is appended as a
+    /// query parameter
+    pub url: String,
+    /// how long a sign-in link or code can be redeemed for
+    pub expiry: Duration,
+}
+
+impl EmailLoginConfig {
+    pub fn from_env() -> Self {
+        Self {
+            url: extract_env_or("EMAIL_LOGIN_URL", DEFAULT_EMAIL_LOGIN_URL.into()),
+            expiry: Duration::from_secs(extract_env_or(
+                "EMAIL_LOGIN_EXPIRY_SECONDS",
+                DEFAULT_EMAIL_LOGIN_EXPIRY_SECONDS,
+            )),
+        }
+    }
+
+    pub fn login_link(&self, token: &str) -> String {
+        let separator = if self.url.contains('?') { '&' } else { '?' };
+        format!("{}{}token={}", self.url, separator, token)
+    }
+}
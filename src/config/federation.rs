@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use crate::{
+    config::jwt::JwtConfig,
+    shared::extract_env::{extract_env_or, extract_optional_env},
+};
+
+/// stands for the provider's slug in the redirect url
+pub const PROVIDER_PLACEHOLDER: &str = "{provider}";
+
+const DEFAULT_FEDERATION_LOGIN_TTL_SECONDS: u64 = 10 * 60;
+const DEFAULT_FEDERATION_HTTP_TIMEOUT_SECONDS: u64 = 10;
+const DEFAULT_FEDERATION_METADATA_TTL_SECONDS: u64 = 60 * 60;
+const DEFAULT_FEDERATION_EMAIL_CHANGE_LINK_DELAY_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Clone)]
+pub struct FederationConfig {
+    /// where identity providers send the browser back to, `{provider}` is replaced with the
+    /// provider's slug and the result has to be registered with the provider
+    pub redirect_url: String,
+    /// how long a user has to finish signing in at the provider
+    pub login_ttl: Duration,
+    pub http_timeout: Duration,
+    /// discovery documents and signing keys are fetched again after this long, or sooner when an
+    /// id token is signed with a key that is not known yet
+    pub metadata_ttl: Duration,
+    /// a provider account is not joined by email to a user whose address changed more recently
+    /// than this, the old owner of the address gets that long to notice and take it back
+    pub email_change_link_delay: Duration,
+}
+
+impl FederationConfig {
+    pub fn from_env() -> Self {
+        let redirect_url = extract_optional_env::<String>("FEDERATION_REDIRECT_URL")
+            .unwrap_or_else(|| {
+                format!(
+                    "{}/login/{PROVIDER_PLACEHOLDER}/callback",
+                    JwtConfig::from_env().issuer.trim_end_matches('/')
+                )
+            });
+
+        Self {
+            redirect_url,
+            login_ttl: Duration::from_secs(extract_env_or(
+                "FEDERATION_LOGIN_TTL_SECONDS",
+                DEFAULT_FEDERATION_LOGIN_TTL_SECONDS,
+            )),
+            http_timeout: Duration::from_secs(extract_env_or(
+                "FEDERATION_HTTP_TIMEOUT_SECONDS",
+                DEFAULT_FEDERATION_HTTP_TIMEOUT_SECONDS,
+            )),
+            metadata_ttl: Duration::from_secs(extract_env_or(
+                "FEDERATION_METADATA_TTL_SECONDS",
+                DEFAULT_FEDERATION_METADATA_TTL_SECONDS,
+            )),
+            email_change_link_delay: Duration::from_secs(extract_env_or(
+                "FEDERATION_EMAIL_CHANGE_LINK_DELAY_SECONDS",
+                DEFAULT_FEDERATION_EMAIL_CHANGE_LINK_DELAY_SECONDS,
+            )),
+        }
+    }
+
+    pub fn redirect_uri(&self, slug: &str) -> String {
+        self.redirect_url.replace(PROVIDER_PLACEHOLDER, slug)
+    }
+}
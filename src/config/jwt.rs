@@ -0,0 +1,19 @@
+use crate::shared::extract_env::extract_env_or;
+
+const DEFAULT_JWT_ISSUER: &str = "uranium";
+const DEFAULT_JWT_AUDIENCE: &str = "uranium";
+
+#[derive(Debug, Clone)]
+pub struct JwtConfig {
+    pub issuer: String,
+    pub audience: String,
+}
+
+impl JwtConfig {
+    pub fn from_env() -> Self {
+        Self {
+            issuer: extract_env_or("JWT_ISSUER", DEFAULT_JWT_ISSUER.into()),
+            audience: extract_env_or("JWT_AUDIENCE", DEFAULT_JWT_AUDIENCE.into()),
+        }
+    }
+}
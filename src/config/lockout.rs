@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use crate::shared::extract_env::extract_env_or;
+
+const DEFAULT_LOCKOUT_ACCOUNT_THRESHOLD: u32 = 10;
+const DEFAULT_LOCKOUT_IP_THRESHOLD: u32 = 50;
+const DEFAULT_LOCKOUT_DURATION_SECONDS: u64 = 15 * 60;
+const DEFAULT_LOCKOUT_FAILURE_WINDOW_SECONDS: u64 = 15 * 60;
+const DEFAULT_LOCKOUT_BACKOFF_AFTER: u32 = 3;
+const DEFAULT_LOCKOUT_BACKOFF_BASE_SECONDS: u64 = 1;
+const DEFAULT_LOCKOUT_BACKOFF_MAX_SECONDS: u64 = 60;
+
+#[derive(Debug, Clone)]
+pub struct LockoutConfig {
+    /// failures against one account before it is locked
+    pub account_threshold: u32,
+    /// failures from one address, across every account, before the address is locked out
+    pub ip_threshold: u32,
+    pub lockout_duration: Duration,
+    /// failures older than this are no longer counted
+    pub failure_window: Duration,
+    /// failures allowed before the back-off starts
+    pub backoff_after: u32,
+    pub backoff_base: Duration,
+    pub backoff_max: Duration,
+}
+
+impl LockoutConfig {
+    pub fn from_env() -> Self {
+        Self {
+            account_threshold: extract_env_or(
+                "LOCKOUT_ACCOUNT_THRESHOLD",
+                DEFAULT_LOCKOUT_ACCOUNT_THRESHOLD,
+            ),
+            ip_threshold: extract_env_or("LOCKOUT_IP_THRESHOLD", DEFAULT_LOCKOUT_IP_THRESHOLD),
+            lockout_duration: Duration::from_secs(extract_env_or(
+                "LOCKOUT_DURATION_SECONDS",
+                DEFAULT_LOCKOUT_DURATION_SECONDS,
+            )),
+            failure_window: Duration::from_secs(extract_env_or(
+                "LOCKOUT_FAILURE_WINDOW_SECONDS",
+                DEFAULT_LOCKOUT_FAILURE_WINDOW_SECONDS,
+            )),
+            backoff_after: extract_env_or("LOCKOUT_BACKOFF_AFTER", DEFAULT_LOCKOUT_BACKOFF_AFTER),
+            backoff_base: Duration::from_secs(extract_env_or(
+                "LOCKOUT_BACKOFF_BASE_SECONDS",
+                DEFAULT_LOCKOUT_BACKOFF_BASE_SECONDS,
+            )),
+            backoff_max: Duration::from_secs(extract_env_or(
+                "LOCKOUT_BACKOFF_MAX_SECONDS",
+                DEFAULT_LOCKOUT_BACKOFF_MAX_SECONDS,
+            )),
+        }
+    }
+
+    /// how long the next attempt has to wait after `failures` consecutive failures, doubling
+    /// with every failure past `backoff_after` up to `backoff_max`
+    pub fn backoff(&self, failures: u32) -> Duration {
+        if failures < self.backoff_after {
+            return Duration::ZERO;
+        }
+
+        let exponent = (failures - self.backoff_after).min(31);
+        self.backoff_base
+            .saturating_mul(1 << exponent)
+            .min(self.backoff_max)
+    }
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use crate::{
+    errors::app_error::AppError,
+    shared::extract_env::{extract_env_or, extract_optional_env},
+};
+
+const DEFAULT_MAIL_FROM: &str = "Uranium <no-reply@uranium.local>";
+const DEFAULT_MAIL_OUTPUT_DIR: &str = "mails";
+const DEFAULT_SMTP_PORT: u16 = 587;
+const DEFAULT_MAIL_MAX_RETRIES: u32 = 3;
+
+#[derive(Debug, Clone)]
+pub enum MailTransportConfig {
+    Smtp {
+        host: String,
+        port: u16,
+        username: Option<String>,
+        password: Option<String>,
+    },
+    File {
+        output_dir: PathBuf,
+    },
+    Log,
+}
+
+#[derive(Debug, Clone)]
+pub struct MailerConfig {
+    pub from: String,
+    pub transport: MailTransportConfig,
+    pub max_retries: u32,
+}
+
+impl MailerConfig {
+    /// the transport has to be named, falling back to one that does not deliver would go unnoticed
+    pub fn from_env() -> Result<Self, AppError> {
+        let transport = extract_optional_env::<String>("MAIL_TRANSPORT").ok_or_else(|| {
+            AppError::StartupError("MAIL_TRANSPORT must be set to smtp, file or log".into())
+        })?;
+        let transport = match transport.to_ascii_lowercase().as_str() {
+            "smtp" => MailTransportConfig::Smtp {
+                host: extract_env_or("SMTP_HOST", "localhost".into()),
+                port: extract_env_or("SMTP_PORT", DEFAULT_SMTP_PORT),
+                username: extract_optional_env("SMTP_USERNAME"),
+                password: extract_optional_env("SMTP_PASSWORD"),
+            },
+            "file" => MailTransportConfig::File {
+                output_dir: extract_env_or(
+                    "MAIL_OUTPUT_DIR",
+                    PathBuf::from(DEFAULT_MAIL_OUTPUT_DIR),
+                ),
+            },
+            "log" => MailTransportConfig::Log,
+            _ => {
+                return Err(AppError::StartupError(format!(
+                    "unknown MAIL_TRANSPORT {transport}, expected smtp, file or log"
+                )));
+            }
+        };
+
+        Ok(Self {
+            from: extract_env_or("MAIL_FROM", DEFAULT_MAIL_FROM.into()),
+            transport,
+            max_retries: extract_env_or("MAIL_MAX_RETRIES", DEFAULT_MAIL_MAX_RETRIES),
+        })
+    }
+}
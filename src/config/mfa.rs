@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use crate::shared::extract_env::extract_env_or;
+
+const DEFAULT_TOTP_ISSUER: &str = "Uranium";
+const DEFAULT_MFA_CHALLENGE_EXPIRY_SECONDS: u64 = 5 * 60;
+const DEFAULT_RECOVERY_CODE_COUNT: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct MfaConfig {
+    /// the name authenticator apps list the account under
+    pub totp_issuer: String,
+    /// how long a user has to enter the code once the password was accepted
+    pub challenge_expiry: Duration,
+    pub recovery_code_count: usize,
+}
+
+impl MfaConfig {
+    pub fn from_env() -> Self {
+        Self {
+            totp_issuer: extract_env_or("MFA_TOTP_ISSUER", DEFAULT_TOTP_ISSUER.into()),
+            challenge_expiry: Duration::from_secs(extract_env_or(
+                "MFA_CHALLENGE_EXPIRY_SECONDS",
+                DEFAULT_MFA_CHALLENGE_EXPIRY_SECONDS,
+            )),
+            recovery_code_count: extract_env_or(
+                "MFA_RECOVERY_CODE_COUNT",
+                DEFAULT_RECOVERY_CODE_COUNT,
+            ),
+        }
+    }
+}
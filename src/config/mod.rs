@@ -1 +1,17 @@
 pub mod database;
+pub mod email_login;
+pub mod federation;
+pub mod jwt;
+pub mod lockout;
+pub mod mailer;
+pub mod mfa;
+pub mod network;
+pub mod oauth;
+pub mod otp;
+pub mod password_hashing;
+pub mod password_policy;
+pub mod password_reset;
+pub mod rate_limit;
+pub mod saml;
+pub mod token;
+pub mod webauthn;
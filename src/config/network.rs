@@ -0,0 +1,16 @@
+use crate::shared::extract_env::extract_env_or;
+
+#[derive(Debug, Clone)]
+pub struct NetworkConfig {
+    /// take the client address from the first `X-Forwarded-For` hop, only safe behind a proxy
+    /// that overwrites the header
+    pub trust_forwarded_for: bool,
+}
+
+impl NetworkConfig {
+    pub fn from_env() -> Self {
+        Self {
+            trust_forwarded_for: extract_env_or("TRUST_FORWARDED_FOR", false),
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use crate::shared::extract_env::{extract_env_or, extract_optional_env};
+
+const DEFAULT_OAUTH_LOGIN_URL: &str = "http://localhost:3000/login";
+const DEFAULT_AUTHORIZATION_CODE_TTL_SECONDS: u64 = 60;
+const DEFAULT_DEVICE_VERIFICATION_URL: &str = "http://localhost:3000/device";
+const DEFAULT_DEVICE_CODE_TTL_SECONDS: u64 = 600;
+const DEFAULT_DEVICE_POLL_INTERVAL_SECONDS: u64 = 5;
+const DEFAULT_DEVICE_CODE_CLEANUP_INTERVAL_SECONDS: u64 = 60 * 60;
+
+/// client registered at startup so the first-party apps can use the authorization code flow
+#[derive(Debug, Clone)]
+pub struct FirstPartyClient {
+    pub client_id: String,
+    pub redirect_uris: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OAuthConfig {
+    /// page of the client application that signs the user in and completes the authorization
+    /// request, the original query is passed along untouched
+    pub login_url: String,
+    pub authorization_code_ttl: Duration,
+    /// page of the client application where a signed in user enters the code shown by a device
+    pub device_verification_url: String,
+    pub device_code_ttl: Duration,
+    /// how long devices wait between polls of the token endpoint
+    pub device_poll_interval: Duration,
+    /// how often expired device requests are removed, their user codes become available again
+    pub device_code_cleanup_interval: Duration,
+    pub first_party_client: Option<FirstPartyClient>,
+}
+
+impl OAuthConfig {
+    pub fn from_env() -> Self {
+        let first_party_client =
+            extract_optional_env::<String>("OAUTH_FIRST_PARTY_CLIENT_ID").map(|client_id| {
+                FirstPartyClient {
+                    client_id,
+                    redirect_uris: extract_env_or::<String>(
+                        "OAUTH_FIRST_PARTY_REDIRECT_URIS",
+                        String::new(),
+                    )
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|uri| !uri.is_empty())
+                    .map(String::from)
+                    .collect(),
+                }
+            });
+
+        Self {
+            login_url: extract_env_or("OAUTH_LOGIN_URL", DEFAULT_OAUTH_LOGIN_URL.into()),
+            authorization_code_ttl: Duration::from_secs(extract_env_or(
+                "OAUTH_AUTHORIZATION_CODE_TTL_SECONDS",
+                DEFAULT_AUTHORIZATION_CODE_TTL_SECONDS,
+            )),
+            device_verification_url: extract_env_or(
+                "OAUTH_DEVICE_VERIFICATION_URL",
+                DEFAULT_DEVICE_VERIFICATION_URL.into(),
+            ),
+            device_code_ttl: Duration::from_secs(extract_env_or(
+                "OAUTH_DEVICE_CODE_TTL_SECONDS",
+                DEFAULT_DEVICE_CODE_TTL_SECONDS,
+            )),
+            device_poll_interval: Duration::from_secs(extract_env_or(
+                "OAUTH_DEVICE_POLL_INTERVAL_SECONDS",
+                DEFAULT_DEVICE_POLL_INTERVAL_SECONDS,
+            )),
+            device_code_cleanup_interval: Duration::from_secs(extract_env_or(
+                "OAUTH_DEVICE_CODE_CLEANUP_INTERVAL_SECONDS",
+                DEFAULT_DEVICE_CODE_CLEANUP_INTERVAL_SECONDS,
+            )),
+            first_party_client,
+        }
+    }
+
+    pub fn login_redirect(&self, query: &str) -> String {
+        let separator = if self.login_url.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        format!("{}{}{}", self.login_url, separator, query)
+    }
+
+    /// the verification page with the user code filled in, for devices that can show a QR code
+    pub fn device_verification_redirect(&self, user_code: &str) -> String {
+        let url = &self.device_verification_url;
+        let separator = if url.contains('?') { '&' } else { '?' };
+        format!("{url}{separator}user_code={user_code}")
+    }
+}
@@ -0,0 +1,25 @@
+use std::time::Duration;
+
+use crate::shared::extract_env::extract_env_or;
+
+const DEFAULT_OTP_EXPIRY_SECONDS: u64 = 10 * 60;
+const DEFAULT_OTP_MAX_ATTEMPTS: i32 = 5;
+pub const OTP_LENGTH: usize = 6;
+
+#[derive(Debug, Clone)]
+pub struct OtpConfig {
+    pub expiry: Duration,
+    pub max_attempts: i32,
+}
+
+impl OtpConfig {
+    pub fn from_env() -> Self {
+        Self {
+            expiry: Duration::from_secs(extract_env_or(
+                "OTP_EXPIRY_SECONDS",
+                DEFAULT_OTP_EXPIRY_SECONDS,
+            )),
+            max_attempts: extract_env_or("OTP_MAX_ATTEMPTS", DEFAULT_OTP_MAX_ATTEMPTS),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use crate::shared::extract_env::extract_env_or;
+
+/// the OWASP recommendation for Argon2id
+const DEFAULT_PASSWORD_HASH_MEMORY_KIB: u32 = 19 * 1024;
+const DEFAULT_PASSWORD_HASH_ITERATIONS: u32 = 2;
+const DEFAULT_PASSWORD_HASH_PARALLELISM: u32 = 1;
+const DEFAULT_PASSWORD_HASH_MAX_QUEUE: usize = 64;
+
+/// Argon2id parameters for new hashes, stored hashes made with other parameters are upgraded the
+/// next time the password is checked
+#[derive(Debug, Clone)]
+pub struct PasswordHashingConfig {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+    /// threads set aside for hashing, one per core unless configured
+    pub workers: usize,
+    /// hashes allowed to wait for a free thread, requests beyond that are turned away
+    pub max_queue: usize,
+}
+
+impl PasswordHashingConfig {
+    pub fn from_env() -> Self {
+        Self {
+            memory_kib: extract_env_or(
+                "PASSWORD_HASH_MEMORY_KIB",
+                DEFAULT_PASSWORD_HASH_MEMORY_KIB,
+            ),
+            iterations: extract_env_or(
+                "PASSWORD_HASH_ITERATIONS",
+                DEFAULT_PASSWORD_HASH_ITERATIONS,
+            ),
+            parallelism: extract_env_or(
+                "PASSWORD_HASH_PARALLELISM",
+                DEFAULT_PASSWORD_HASH_PARALLELISM,
+            ),
+            workers: extract_env_or(
+                "PASSWORD_HASH_WORKERS",
+                std::thread::available_parallelism().map_or(1, usize::from),
+            ),
+            max_queue: extract_env_or("PASSWORD_HASH_MAX_QUEUE", DEFAULT_PASSWORD_HASH_MAX_QUEUE),
+        }
+    }
+}
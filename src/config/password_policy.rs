@@ -0,0 +1,42 @@
+use std::path::PathBuf;
+
+use crate::shared::extract_env::{extract_env_or, extract_optional_env};
+
+const DEFAULT_PASSWORD_MIN_LENGTH: usize = 10;
+const DEFAULT_PASSWORD_MAX_LENGTH: usize = 128;
+const DEFAULT_PASSWORD_MIN_CHARACTER_CLASSES: usize = 3;
+const DEFAULT_PASSWORD_MIN_STRENGTH: u8 = 3;
+
+#[derive(Debug, Clone)]
+pub struct PasswordPolicyConfig {
+    pub min_length: usize,
+    /// bounds the work a password check does
+    pub max_length: usize,
+    /// how many of lowercase letters, uppercase letters, digits and symbols must appear
+    pub min_character_classes: usize,
+    /// the lowest acceptable strength estimate, from 0 (trivial) to 4 (very strong)
+    pub min_strength: u8,
+    /// refuse passwords that contain the account's email address or name
+    pub forbid_personal_information: bool,
+    /// a file of SHA-1 hash prefixes of breached passwords, one hex prefix per line
+    pub breached_corpus_path: Option<PathBuf>,
+}
+
+impl PasswordPolicyConfig {
+    pub fn from_env() -> Self {
+        Self {
+            min_length: extract_env_or("PASSWORD_MIN_LENGTH", DEFAULT_PASSWORD_MIN_LENGTH),
+            max_length: extract_env_or("PASSWORD_MAX_LENGTH", DEFAULT_PASSWORD_MAX_LENGTH),
+            min_character_classes: extract_env_or(
+                "PASSWORD_MIN_CHARACTER_CLASSES",
+                DEFAULT_PASSWORD_MIN_CHARACTER_CLASSES,
+            ),
+            min_strength: extract_env_or("PASSWORD_MIN_STRENGTH", DEFAULT_PASSWORD_MIN_STRENGTH),
+            forbid_personal_information: extract_env_or(
+                "PASSWORD_FORBID_PERSONAL_INFORMATION",
+                true,
+            ),
+            breached_corpus_path: extract_optional_env("PASSWORD_BREACHED_CORPUS_PATH"),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use crate::shared::extract_env::extract_env_or;
+
+const DEFAULT_PASSWORD_RESET_URL: &str = "http://localhost:3000/reset-password";
+const DEFAULT_PASSWORD_RESET_EXPIRY_SECONDS: u64 = 30 * 60;
+
+#[derive(Debug, Clone)]
+pub struct PasswordResetConfig {
+    /// page of the client application the reset link points to, the token is appended as a query parameter
+    pub url: String,
+    pub expiry: Duration,
+}
+
+impl PasswordResetConfig {
+    pub fn from_env() -> Self {
+        Self {
+            url: extract_env_or("PASSWORD_RESET_URL", DEFAULT_PASSWORD_RESET_URL.into()),
+            expiry: Duration::from_secs(extract_env_or(
+                "PASSWORD_RESET_EXPIRY_SECONDS",
+                DEFAULT_PASSWORD_RESET_EXPIRY_SECONDS,
+            )),
+        }
+    }
+
+    pub fn reset_link(&self, token: &str) -> String {
+        let separator = if self.url.contains('?') { '&' } else { '?' };
+        format!("{}{}token={}", self.url, separator, token)
+    }
+}
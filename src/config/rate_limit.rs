@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use crate::shared::extract_env::extract_env_or;
+
+const DEFAULT_RATE_LIMIT_CLEANUP_INTERVAL_SECONDS: u64 = 10 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitStoreConfig {
+    /// counters live in the process, enough for a single instance
+    Memory,
+    /// counters are shared through the database by every replica
+    Postgres,
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    pub store: RateLimitStoreConfig,
+    /// how often counters whose window has passed are removed
+    pub cleanup_interval: Duration,
+}
+
+impl RateLimitConfig {
+    pub fn from_env() -> Self {
+        let store = match extract_env_or("RATE_LIMIT_STORE", String::new()).as_str() {
+            "postgres" => RateLimitStoreConfig::Postgres,
+            _ => RateLimitStoreConfig::Memory,
+        };
+
+        Self {
+            store,
+            cleanup_interval: Duration::from_secs(extract_env_or(
+                "RATE_LIMIT_CLEANUP_INTERVAL_SECONDS",
+                DEFAULT_RATE_LIMIT_CLEANUP_INTERVAL_SECONDS,
+            )),
+        }
+    }
+}
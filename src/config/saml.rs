@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use crate::{
+    config::jwt::JwtConfig,
+    shared::extract_env::{extract_env_or, extract_optional_env},
+};
+
+const DEFAULT_SAML_LOGIN_URL: &str = "http://localhost:3000/saml";
+const DEFAULT_SAML_ASSERTION_TTL_SECONDS: u64 = 5 * 60;
+
+#[derive(Debug, Clone)]
+pub struct SamlConfig {
+    /// the identity provider's entity id, the url its metadata is published at unless set
+    pub entity_id: String,
+    /// where service providers send authentication requests
+    pub sso_url: String,
+    /// page of the client application that signs the user in and completes the authentication
+    /// request, it is passed the request and relay state as `SAMLRequest` and `RelayState`
+    pub login_url: String,
+    /// how long a service provider may accept an assertion after it was issued
+    pub assertion_ttl: Duration,
+    /// PEM certificate published in the metadata, set together with `private_key_path`
+    pub certificate_path: Option<String>,
+    /// PEM RSA or P-256 private key assertions are signed with
+    pub private_key_path: Option<String>,
+    /// development only, signs with a key generated at startup when no key is configured, every
+    /// restart and every replica then publishes a different certificate
+    pub ephemeral_key: bool,
+}
+
+impl SamlConfig {
+    pub fn from_env() -> Self {
+        let issuer = JwtConfig::from_env().issuer;
+        let issuer = issuer.trim_end_matches('/');
+
+        Self {
+            entity_id: extract_optional_env::<String>("SAML_ENTITY_ID")
+                .unwrap_or_else(|| format!("{issuer}/saml/metadata")),
+            sso_url: format!("{issuer}/saml/sso"),
+            login_url: extract_env_or("SAML_LOGIN_URL", DEFAULT_SAML_LOGIN_URL.into()),
+            assertion_ttl: Duration::from_secs(extract_env_or(
+                "SAML_ASSERTION_TTL_SECONDS",
+                DEFAULT_SAML_ASSERTION_TTL_SECONDS,
+            )),
+            certificate_path: extract_optional_env::<String>("SAML_CERTIFICATE_PATH"),
+            private_key_path: extract_optional_env::<String>("SAML_PRIVATE_KEY_PATH"),
+            ephemeral_key: extract_env_or("SAML_EPHEMERAL_KEY", false),
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use crate::shared::extract_env::extract_env_or;
+
+const DEFAULT_REFRESH_TOKEN_TTL_SECONDS: u64 = 30 * 24 * 60 * 60;
+const DEFAULT_REVOCATION_CLEANUP_INTERVAL_SECONDS: u64 = 60 * 60;
+
+#[derive(Debug, Clone)]
+pub struct TokenConfig {
+    pub refresh_token_ttl: Duration,
+    pub revocation_cleanup_interval: Duration,
+}
+
+impl TokenConfig {
+    pub fn from_env() -> Self {
+        Self {
+            refresh_token_ttl: Duration::from_secs(extract_env_or(
+                "REFRESH_TOKEN_TTL_SECONDS",
+                DEFAULT_REFRESH_TOKEN_TTL_SECONDS,
+            )),
+            revocation_cleanup_interval: Duration::from_secs(extract_env_or(
+                "REVOCATION_CLEANUP_INTERVAL_SECONDS",
+                DEFAULT_REVOCATION_CLEANUP_INTERVAL_SECONDS,
+            )),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use crate::shared::extract_env::extract_env_or;
+
+const DEFAULT_WEBAUTHN_RP_ID: &str = "localhost";
+const DEFAULT_WEBAUTHN_RP_NAME: &str = "Uranium";
+const DEFAULT_WEBAUTHN_ORIGINS: &str = "http://localhost:3000";
+const DEFAULT_WEBAUTHN_CHALLENGE_TTL_SECONDS: u64 = 5 * 60;
+const DEFAULT_WEBAUTHN_REAUTHENTICATION_WINDOW_SECONDS: u64 = 5 * 60;
+
+#[derive(Debug, Clone)]
+pub struct WebAuthnConfig {
+    /// the domain credentials are scoped to, the pages running the ceremonies must be on it or one
+    /// of its subdomains
+    pub rp_id: String,
+    pub rp_name: String,
+    /// origins of the pages allowed to run the ceremonies, compared exactly
+    pub origins: Vec<String>,
+    pub challenge_ttl: Duration,
+    /// how long after signing in a passkey can be added without entering the password again
+    pub reauthentication_window: Duration,
+}
+
+impl WebAuthnConfig {
+    pub fn from_env() -> Self {
+        Self {
+            rp_id: extract_env_or("WEBAUTHN_RP_ID", DEFAULT_WEBAUTHN_RP_ID.into()),
+            rp_name: extract_env_or("WEBAUTHN_RP_NAME", DEFAULT_WEBAUTHN_RP_NAME.into()),
+            origins: extract_env_or::<String>("WEBAUTHN_ORIGINS", DEFAULT_WEBAUTHN_ORIGINS.into())
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(String::from)
+                .collect(),
+            challenge_ttl: Duration::from_secs(extract_env_or(
+                "WEBAUTHN_CHALLENGE_TTL_SECONDS",
+                DEFAULT_WEBAUTHN_CHALLENGE_TTL_SECONDS,
+            )),
+            reauthentication_window: Duration::from_secs(extract_env_or(
+                "WEBAUTHN_REAUTHENTICATION_WINDOW_SECONDS",
+                DEFAULT_WEBAUTHN_REAUTHENTICATION_WINDOW_SECONDS,
+            )),
+        }
+    }
+}
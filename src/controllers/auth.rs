@@ -1,7 +1,16 @@
-use crate::adapters::dto::jwt::Claims;
-use crate::adapters::requests::auth::VerifyAccountRequest;
+use crate::adapters::dto::session::DeviceInfo;
+use crate::adapters::requests::auth::{
+    ChangeEmailRequest, ConfirmEmailChangeRequest, EmailLoginRequest, FederatedCallbackRequest,
+    FederatedLoginRequest, LogoutRequest, MfaLoginRequest, RefreshTokenRequest, RevokeTokenRequest,
+    VerifyAccountRequest, VerifyEmailLoginRequest,
+};
 use crate::adapters::response::api_response::ApiResponseBuilder;
-use crate::adapters::response::auth::{ForgottenPasswordResponse, RefreshTokenResponse};
+use crate::adapters::response::auth::{
+    ChangeEmailResponse, ConfirmEmailChangeResponse, EmailLoginResponse, ForgottenPasswordResponse,
+    LogoutEverywhereResponse, LogoutResponse, RefreshTokenResponse, ResendVerificationResponse,
+    RevokeTokenResponse,
+};
+use crate::middlewares::auth::{AccessClaims, VerificationClaims};
 use crate::middlewares::validator::ValidatedRequest;
 use crate::{
     adapters::{
@@ -16,34 +25,117 @@ use crate::{
     errors::auth_service_error::AuthenticationServiceError,
     services::auth_service::{AuthenticationService, AuthenticationServiceTrait},
 };
-use axum::extract::State;
+use axum::extract::{Path, Query, State};
 use axum::http::StatusCode;
+use axum::response::Redirect;
 
 pub async fn create_account(
     State(auth_service): State<AuthenticationService>,
     ValidatedRequest(request): ValidatedRequest<CreateUserRequest>,
 ) -> Result<ApiResponse<CreateUserResponse>, AuthenticationServiceError> {
-    auth_service.create_account(&request).await?;
+    let create_account_response = auth_service.create_account(&request).await?;
 
     Ok(ApiResponseBuilder::new()
         .status_code(StatusCode::CREATED)
+        .data(create_account_response)
         .message("Account created successfully")
         .build())
 }
 pub async fn login(
     State(auth_service): State<AuthenticationService>,
+    device: DeviceInfo,
     ValidatedRequest(request): ValidatedRequest<LoginRequest>,
 ) -> Result<ApiResponse<LoginResponse>, AuthenticationServiceError> {
-    let login_response = auth_service.login(&request).await?;
+    let login_response = auth_service.login(&request, &device).await?;
+    let message = match login_response {
+        LoginResponse::Authenticated { .. } => "logged in successfully",
+        LoginResponse::MfaRequired { .. } => "enter the code from your authenticator to continue",
+    };
+    Ok(ApiResponseBuilder::new()
+        .status_code(StatusCode::OK)
+        .data(login_response)
+        .message(message)
+        .build())
+}
+
+pub async fn complete_mfa_login(
+    State(auth_service): State<AuthenticationService>,
+    device: DeviceInfo,
+    ValidatedRequest(request): ValidatedRequest<MfaLoginRequest>,
+) -> Result<ApiResponse<LoginResponse>, AuthenticationServiceError> {
+    let login_response = auth_service.complete_mfa_login(&request, &device).await?;
     Ok(ApiResponseBuilder::new()
         .status_code(StatusCode::OK)
         .data(login_response)
         .message("logged in successfully")
         .build())
 }
+
+pub async fn email_login(
+    State(auth_service): State<AuthenticationService>,
+    ValidatedRequest(request): ValidatedRequest<EmailLoginRequest>,
+) -> Result<ApiResponse<EmailLoginResponse>, AuthenticationServiceError> {
+    let email_login_response = auth_service.email_login(&request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(email_login_response)
+        .message(
+            "if an account exists for the email address, sign-in instructions have been sent to it",
+        )
+        .build())
+}
+
+pub async fn verify_email_login(
+    State(auth_service): State<AuthenticationService>,
+    device: DeviceInfo,
+    ValidatedRequest(request): ValidatedRequest<VerifyEmailLoginRequest>,
+) -> Result<ApiResponse<LoginResponse>, AuthenticationServiceError> {
+    let login_response = auth_service.verify_email_login(&request, &device).await?;
+    let message = match login_response {
+        LoginResponse::Authenticated { .. } => "logged in successfully",
+        LoginResponse::MfaRequired { .. } => "enter the code from your authenticator to continue",
+    };
+    Ok(ApiResponseBuilder::new()
+        .status_code(StatusCode::OK)
+        .data(login_response)
+        .message(message)
+        .build())
+}
+
+/// sends the browser to the identity provider, which redirects back to the callback below
+pub async fn start_federated_login(
+    State(auth_service): State<AuthenticationService>,
+    Path(provider): Path<String>,
+    Query(request): Query<FederatedLoginRequest>,
+) -> Result<Redirect, AuthenticationServiceError> {
+    let authorization_url = auth_service
+        .start_federated_login(&provider, &request)
+        .await?;
+    Ok(Redirect::to(&authorization_url))
+}
+
+pub async fn complete_federated_login(
+    State(auth_service): State<AuthenticationService>,
+    Path(provider): Path<String>,
+    device: DeviceInfo,
+    Query(request): Query<FederatedCallbackRequest>,
+) -> Result<ApiResponse<LoginResponse>, AuthenticationServiceError> {
+    let login_response = auth_service
+        .complete_federated_login(&provider, &request, &device)
+        .await?;
+    let message = match login_response {
+        LoginResponse::Authenticated { .. } => "logged in successfully",
+        LoginResponse::MfaRequired { .. } => "enter the code from your authenticator to continue",
+    };
+    Ok(ApiResponseBuilder::new()
+        .status_code(StatusCode::OK)
+        .data(login_response)
+        .message(message)
+        .build())
+}
 pub async fn verify_account(
     State(auth_service): State<AuthenticationService>,
-    claims: Claims,
+    VerificationClaims(claims): VerificationClaims,
     ValidatedRequest(request): ValidatedRequest<VerifyAccountRequest>,
 ) -> Result<ApiResponse<VerifyAccountResponse>, AuthenticationServiceError> {
     let verify_account_response = auth_service.verify_account(&claims, &request).await?;
@@ -52,6 +144,17 @@ pub async fn verify_account(
         .data(verify_account_response)
         .build())
 }
+
+pub async fn resend_verification(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+) -> Result<ApiResponse<ResendVerificationResponse>, AuthenticationServiceError> {
+    let resend_verification_response = auth_service.resend_verification(&claims).await?;
+    Ok(ApiResponseBuilder::new()
+        .data(resend_verification_response)
+        .message("a new verification code has been sent to the registered email address")
+        .build())
+}
 pub async fn forgotten_password(
     State(auth_service): State<AuthenticationService>,
     ValidatedRequest(request): ValidatedRequest<ForgottenPasswordRequest>,
@@ -60,16 +163,15 @@ pub async fn forgotten_password(
 
     Ok(ApiResponseBuilder::new()
         .data(forgotten_password_response)
-        .message("account retrival instructions has been sent to the registered email address")
+        .message("if an account exists for the email address, account retrival instructions have been sent to it")
         .build())
 }
 
 pub async fn set_new_password(
     State(auth_service): State<AuthenticationService>,
-    claims: Claims,
     ValidatedRequest(request): ValidatedRequest<SetNewPasswordRequest>,
 ) -> Result<ApiResponse<()>, AuthenticationServiceError> {
-    let _ = auth_service.set_new_password(&request, &claims).await?;
+    let _ = auth_service.set_new_password(&request).await?;
 
     Ok(ApiResponseBuilder::new()
         .data(())
@@ -79,12 +181,80 @@ pub async fn set_new_password(
 
 pub async fn request_refresh_token(
     State(auth_service): State<AuthenticationService>,
-    claims: Claims,
+    device: DeviceInfo,
+    ValidatedRequest(request): ValidatedRequest<RefreshTokenRequest>,
 ) -> Result<ApiResponse<RefreshTokenResponse>, AuthenticationServiceError> {
-    let refresh_token_response = auth_service.request_refresh_token(&claims).await?;
+    let refresh_token_response = auth_service
+        .request_refresh_token(&request, &device)
+        .await?;
 
     Ok(ApiResponseBuilder::new()
         .data(refresh_token_response)
         .message("token updated successfully")
         .build())
 }
+
+pub async fn logout(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<LogoutRequest>,
+) -> Result<ApiResponse<LogoutResponse>, AuthenticationServiceError> {
+    let logout_response = auth_service.logout(&claims, &request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(logout_response)
+        .message("logged out successfully")
+        .build())
+}
+
+pub async fn revoke_token(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<RevokeTokenRequest>,
+) -> Result<ApiResponse<RevokeTokenResponse>, AuthenticationServiceError> {
+    let revoke_token_response = auth_service.revoke_token(&claims, &request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(revoke_token_response)
+        .message("token revoked successfully")
+        .build())
+}
+
+pub async fn logout_everywhere(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+) -> Result<ApiResponse<LogoutEverywhereResponse>, AuthenticationServiceError> {
+    let logout_everywhere_response = auth_service.logout_everywhere(&claims).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(logout_everywhere_response)
+        .message("logged out of all sessions successfully")
+        .build())
+}
+
+pub async fn change_email(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<ChangeEmailRequest>,
+) -> Result<ApiResponse<ChangeEmailResponse>, AuthenticationServiceError> {
+    let change_email_response = auth_service.change_email(&claims, &request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(change_email_response)
+        .message("a code was sent to the new address, confirm it to finish the change")
+        .build())
+}
+
+pub async fn confirm_email_change(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<ConfirmEmailChangeRequest>,
+) -> Result<ApiResponse<ConfirmEmailChangeResponse>, AuthenticationServiceError> {
+    let confirm_email_change_response =
+        auth_service.confirm_email_change(&claims, &request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(confirm_email_change_response)
+        .message("email updated successfully, log in again to continue")
+        .build())
+}
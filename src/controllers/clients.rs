@@ -0,0 +1,97 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+
+use crate::{
+    adapters::{
+        requests::clients::{CreateClientRequest, UpdateClientRequest},
+        response::{
+            api_response::{ApiResponse, ApiResponseBuilder},
+            clients::{ClientResponse, ClientSecretResponse, CreateClientResponse},
+        },
+    },
+    errors::client_service_error::ClientServiceError,
+    middlewares::{
+        auth::{ManageClients, PermittedClaims},
+        validator::ValidatedRequest,
+    },
+    services::client_service::{ClientService, ClientServiceTrait},
+};
+
+pub async fn list_clients(
+    State(client_service): State<ClientService>,
+    _: PermittedClaims<ManageClients>,
+) -> Result<ApiResponse<Vec<ClientResponse>>, ClientServiceError> {
+    let clients = client_service.list_clients().await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(clients)
+        .message("clients fetched successfully")
+        .build())
+}
+
+pub async fn create_client(
+    State(client_service): State<ClientService>,
+    _: PermittedClaims<ManageClients>,
+    ValidatedRequest(request): ValidatedRequest<CreateClientRequest>,
+) -> Result<ApiResponse<CreateClientResponse>, ClientServiceError> {
+    let create_client_response = client_service.create_client(&request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .status_code(StatusCode::CREATED)
+        .data(create_client_response)
+        .message("client created successfully, the secret will not be shown again")
+        .build())
+}
+
+pub async fn retrieve_client(
+    State(client_service): State<ClientService>,
+    _: PermittedClaims<ManageClients>,
+    Path(client_id): Path<String>,
+) -> Result<ApiResponse<ClientResponse>, ClientServiceError> {
+    let client = client_service.retrieve_client(&client_id).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(client)
+        .message("client fetched successfully")
+        .build())
+}
+
+pub async fn update_client(
+    State(client_service): State<ClientService>,
+    _: PermittedClaims<ManageClients>,
+    Path(client_id): Path<String>,
+    ValidatedRequest(request): ValidatedRequest<UpdateClientRequest>,
+) -> Result<ApiResponse<ClientResponse>, ClientServiceError> {
+    let client = client_service.update_client(&client_id, &request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(client)
+        .message("client updated successfully")
+        .build())
+}
+
+pub async fn delete_client(
+    State(client_service): State<ClientService>,
+    _: PermittedClaims<ManageClients>,
+    Path(client_id): Path<String>,
+) -> Result<ApiResponse<()>, ClientServiceError> {
+    client_service.delete_client(&client_id).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(())
+        .message("client deleted successfully")
+        .build())
+}
+
+pub async fn rotate_client_secret(
+    State(client_service): State<ClientService>,
+    _: PermittedClaims<ManageClients>,
+    Path(client_id): Path<String>,
+) -> Result<ApiResponse<ClientSecretResponse>, ClientServiceError> {
+    let client_secret_response = client_service.rotate_client_secret(&client_id).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(client_secret_response)
+        .message("client secret rotated successfully, the secret will not be shown again")
+        .build())
+}
@@ -0,0 +1,72 @@
+use axum::{
+    Form, Json,
+    extract::{Path, State, rejection::FormRejection},
+    http::{HeaderMap, HeaderValue, header},
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Basic},
+};
+
+use crate::{
+    adapters::{
+        requests::oauth::{
+            ClientCredentials, DeviceAuthorizationRequest, DeviceVerificationRequest,
+        },
+        response::{
+            api_response::{ApiResponse, ApiResponseBuilder},
+            oauth::{DeviceAuthorizationResponse, DeviceVerificationResponse},
+        },
+    },
+    errors::oauth_error::OAuthError,
+    middlewares::{auth::AccessClaims, validator::ValidatedRequest},
+    services::oauth_service::{OAuthService, OAuthServiceTrait},
+};
+
+pub async fn authorize_device(
+    State(oauth_service): State<OAuthService>,
+    basic_credentials: Option<TypedHeader<Authorization<Basic>>>,
+    request: Result<Form<DeviceAuthorizationRequest>, FormRejection>,
+) -> Result<(HeaderMap, Json<DeviceAuthorizationResponse>), OAuthError> {
+    let Form(request) = request.map_err(|err| OAuthError::InvalidRequest(err.body_text()))?;
+    let credentials = ClientCredentials::from_request(
+        basic_credentials.map(|TypedHeader(Authorization(basic))| basic),
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+    )?;
+    let device_authorization = oauth_service
+        .authorize_device(&credentials, &request)
+        .await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    Ok((headers, Json(device_authorization)))
+}
+
+pub async fn find_device_authorization(
+    State(oauth_service): State<OAuthService>,
+    AccessClaims(_): AccessClaims,
+    Path(user_code): Path<String>,
+) -> Result<ApiResponse<DeviceVerificationResponse>, OAuthError> {
+    let device_authorization = oauth_service.find_device_authorization(&user_code).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(device_authorization)
+        .message("device authorization retrieved")
+        .build())
+}
+
+pub async fn verify_device(
+    State(oauth_service): State<OAuthService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<DeviceVerificationRequest>,
+) -> Result<ApiResponse<()>, OAuthError> {
+    oauth_service.verify_device(&claims, &request).await?;
+
+    let message = if request.approve {
+        "device approved"
+    } else {
+        "device denied"
+    };
+    Ok(ApiResponseBuilder::new().message(message).build())
+}
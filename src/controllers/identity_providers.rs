@@ -0,0 +1,88 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+
+use crate::{
+    adapters::{
+        requests::identity_providers::{
+            CreateIdentityProviderRequest, UpdateIdentityProviderRequest,
+        },
+        response::{
+            api_response::{ApiResponse, ApiResponseBuilder},
+            identity_providers::IdentityProviderResponse,
+        },
+    },
+    errors::identity_provider_service_error::IdentityProviderServiceError,
+    middlewares::{
+        auth::{ManageIdentityProviders, PermittedClaims},
+        validator::ValidatedRequest,
+    },
+    services::identity_provider_service::{IdentityProviderService, IdentityProviderServiceTrait},
+};
+
+pub async fn list_identity_providers(
+    State(identity_provider_service): State<IdentityProviderService>,
+    _: PermittedClaims<ManageIdentityProviders>,
+) -> Result<ApiResponse<Vec<IdentityProviderResponse>>, IdentityProviderServiceError> {
+    let providers = identity_provider_service.list_providers().await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(providers)
+        .message("identity providers fetched successfully")
+        .build())
+}
+
+pub async fn create_identity_provider(
+    State(identity_provider_service): State<IdentityProviderService>,
+    _: PermittedClaims<ManageIdentityProviders>,
+    ValidatedRequest(request): ValidatedRequest<CreateIdentityProviderRequest>,
+) -> Result<ApiResponse<IdentityProviderResponse>, IdentityProviderServiceError> {
+    let provider = identity_provider_service.create_provider(&request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .status_code(StatusCode::CREATED)
+        .data(provider)
+        .message("identity provider created successfully")
+        .build())
+}
+
+pub async fn retrieve_identity_provider(
+    State(identity_provider_service): State<IdentityProviderService>,
+    _: PermittedClaims<ManageIdentityProviders>,
+    Path(slug): Path<String>,
+) -> Result<ApiResponse<IdentityProviderResponse>, IdentityProviderServiceError> {
+    let provider = identity_provider_service.retrieve_provider(&slug).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(provider)
+        .message("identity provider fetched successfully")
+        .build())
+}
+
+pub async fn update_identity_provider(
+    State(identity_provider_service): State<IdentityProviderService>,
+    _: PermittedClaims<ManageIdentityProviders>,
+    Path(slug): Path<String>,
+    ValidatedRequest(request): ValidatedRequest<UpdateIdentityProviderRequest>,
+) -> Result<ApiResponse<IdentityProviderResponse>, IdentityProviderServiceError> {
+    let provider = identity_provider_service
+        .update_provider(&slug, &request)
+        .await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(provider)
+        .message("identity provider updated successfully")
+        .build())
+}
+
+pub async fn delete_identity_provider(
+    State(identity_provider_service): State<IdentityProviderService>,
+    _: PermittedClaims<ManageIdentityProviders>,
+    Path(slug): Path<String>,
+) -> Result<ApiResponse<()>, IdentityProviderServiceError> {
+    identity_provider_service.delete_provider(&slug).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(())
+        .message("identity provider deleted successfully")
+        .build())
+}
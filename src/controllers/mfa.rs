@@ -0,0 +1,82 @@
+use axum::extract::State;
+
+use crate::{
+    adapters::{
+        requests::mfa::{ConfirmTotpRequest, DisableTotpRequest, RegenerateRecoveryCodesRequest},
+        response::{
+            api_response::{ApiResponse, ApiResponseBuilder},
+            mfa::{
+                DisableTotpResponse, MfaStatusResponse, RecoveryCodesResponse,
+                TotpEnrollmentResponse,
+            },
+        },
+    },
+    errors::auth_service_error::AuthenticationServiceError,
+    middlewares::{auth::AccessClaims, validator::ValidatedRequest},
+    services::auth_service::{AuthenticationService, AuthenticationServiceTrait},
+};
+
+pub async fn mfa_status(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+) -> Result<ApiResponse<MfaStatusResponse>, AuthenticationServiceError> {
+    let mfa_status_response = auth_service.mfa_status(&claims).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(mfa_status_response)
+        .message("multi-factor authentication status fetched successfully")
+        .build())
+}
+
+pub async fn enroll_totp(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+) -> Result<ApiResponse<TotpEnrollmentResponse>, AuthenticationServiceError> {
+    let enrollment_response = auth_service.enroll_totp(&claims).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(enrollment_response)
+        .message("add the account to your authenticator and confirm it with a code")
+        .build())
+}
+
+pub async fn confirm_totp(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<ConfirmTotpRequest>,
+) -> Result<ApiResponse<RecoveryCodesResponse>, AuthenticationServiceError> {
+    let recovery_codes_response = auth_service.confirm_totp(&claims, &request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(recovery_codes_response)
+        .message("two-factor authentication enabled, store the recovery codes and log in again to continue")
+        .build())
+}
+
+pub async fn disable_totp(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<DisableTotpRequest>,
+) -> Result<ApiResponse<DisableTotpResponse>, AuthenticationServiceError> {
+    let disable_totp_response = auth_service.disable_totp(&claims, &request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(disable_totp_response)
+        .message("two-factor authentication disabled, log in again to continue")
+        .build())
+}
+
+pub async fn regenerate_recovery_codes(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<RegenerateRecoveryCodesRequest>,
+) -> Result<ApiResponse<RecoveryCodesResponse>, AuthenticationServiceError> {
+    let recovery_codes_response = auth_service
+        .regenerate_recovery_codes(&claims, &request)
+        .await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(recovery_codes_response)
+        .message("new recovery codes generated, the previous ones no longer work")
+        .build())
+}
@@ -1,3 +1,12 @@
 pub mod auth;
+pub mod clients;
+pub mod device;
+pub mod identity_providers;
+pub mod mfa;
+pub mod oauth;
 pub mod root;
+pub mod saml;
+pub mod service_providers;
 pub mod user;
+pub mod webauthn;
+pub mod well_known;
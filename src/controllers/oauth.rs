@@ -0,0 +1,120 @@
+use axum::{
+    Form, Json,
+    extract::{Query, RawQuery, State, rejection::FormRejection},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::Redirect,
+};
+use axum_extra::{
+    TypedHeader,
+    headers::{Authorization, authorization::Basic},
+};
+
+use crate::{
+    adapters::{
+        requests::oauth::{
+            AuthorizationRequest, ClientCredentials, IntrospectionRequest, RevocationRequest,
+            TokenRequest,
+        },
+        response::{
+            api_response::{ApiResponse, ApiResponseBuilder},
+            oauth::{
+                AuthorizationResponse, IntrospectionResponse, TokenResponse, UserInfoResponse,
+            },
+        },
+    },
+    errors::oauth_error::{AuthorizationError, OAuthError},
+    middlewares::{
+        auth::{AccessClaims, OAuthAccessClaims},
+        validator::ValidatedRequest,
+    },
+    services::oauth_service::{OAuthService, OAuthServiceTrait},
+};
+
+pub async fn start_authorization(
+    State(oauth_service): State<OAuthService>,
+    RawQuery(query): RawQuery,
+    Query(request): Query<AuthorizationRequest>,
+) -> Result<Redirect, AuthorizationError> {
+    let login_redirect = oauth_service
+        .start_authorization(&request, &query.unwrap_or_default())
+        .await?;
+
+    Ok(Redirect::to(&login_redirect))
+}
+
+pub async fn authorize(
+    State(oauth_service): State<OAuthService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<AuthorizationRequest>,
+) -> Result<ApiResponse<AuthorizationResponse>, OAuthError> {
+    let authorization_response = oauth_service.authorize(&claims, &request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(authorization_response)
+        .message("authorization granted")
+        .build())
+}
+
+/// answered in the RFC 6749 shape, token responses must never be cached
+pub async fn exchange_token(
+    State(oauth_service): State<OAuthService>,
+    basic_credentials: Option<TypedHeader<Authorization<Basic>>>,
+    request: Result<Form<TokenRequest>, FormRejection>,
+) -> Result<(HeaderMap, Json<TokenResponse>), OAuthError> {
+    let Form(request) = request.map_err(|err| OAuthError::InvalidRequest(err.body_text()))?;
+    let credentials = ClientCredentials::from_request(
+        basic_credentials.map(|TypedHeader(Authorization(basic))| basic),
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+    )?;
+    let token_response = oauth_service.exchange_token(&credentials, &request).await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    headers.insert(header::PRAGMA, HeaderValue::from_static("no-cache"));
+    Ok((headers, Json(token_response)))
+}
+
+pub async fn userinfo(
+    State(oauth_service): State<OAuthService>,
+    OAuthAccessClaims(claims): OAuthAccessClaims,
+) -> Result<Json<UserInfoResponse>, OAuthError> {
+    let userinfo_response = oauth_service.userinfo(&claims).await?;
+
+    Ok(Json(userinfo_response))
+}
+
+pub async fn introspect(
+    State(oauth_service): State<OAuthService>,
+    basic_credentials: Option<TypedHeader<Authorization<Basic>>>,
+    request: Result<Form<IntrospectionRequest>, FormRejection>,
+) -> Result<(HeaderMap, Json<IntrospectionResponse>), OAuthError> {
+    let Form(request) = request.map_err(|err| OAuthError::InvalidRequest(err.body_text()))?;
+    let credentials = ClientCredentials::from_request(
+        basic_credentials.map(|TypedHeader(Authorization(basic))| basic),
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+    )?;
+    let introspection_response = oauth_service.introspect(&credentials, &request).await?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    Ok((headers, Json(introspection_response)))
+}
+
+/// answers with an empty 200 whether or not the token was known
+pub async fn revoke(
+    State(oauth_service): State<OAuthService>,
+    basic_credentials: Option<TypedHeader<Authorization<Basic>>>,
+    request: Result<Form<RevocationRequest>, FormRejection>,
+) -> Result<StatusCode, OAuthError> {
+    let Form(request) = request.map_err(|err| OAuthError::InvalidRequest(err.body_text()))?;
+    let credentials = ClientCredentials::from_request(
+        basic_credentials.map(|TypedHeader(Authorization(basic))| basic),
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+    )?;
+    oauth_service.revoke(&credentials, &request).await?;
+
+    Ok(StatusCode::OK)
+}
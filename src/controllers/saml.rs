@@ -0,0 +1,77 @@
+use axum::{
+    Form,
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Redirect},
+};
+
+use crate::{
+    adapters::{
+        dto::saml::SamlBinding,
+        requests::saml::{CompleteSamlSsoRequest, InitiateSamlSsoRequest, SamlSsoRequest},
+        response::{
+            api_response::{ApiResponse, ApiResponseBuilder},
+            saml::SamlSsoResponse,
+        },
+    },
+    errors::saml_error::SamlError,
+    middlewares::{auth::AccessClaims, validator::ValidatedRequest},
+    services::saml_service::{SamlService, SamlServiceTrait},
+};
+
+pub async fn identity_provider_metadata(
+    State(saml_service): State<SamlService>,
+) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "application/samlmetadata+xml")],
+        saml_service.metadata(),
+    )
+}
+
+/// the HTTP-Redirect binding, the request is checked and the browser sent on to the login page
+pub async fn start_sso_redirect(
+    State(saml_service): State<SamlService>,
+    Query(request): Query<SamlSsoRequest>,
+) -> Result<Redirect, SamlError> {
+    let login_url = saml_service
+        .start_sso(&request, SamlBinding::Redirect)
+        .await?;
+    Ok(Redirect::to(&login_url))
+}
+
+/// the HTTP-POST binding, answered the same way as a redirect
+pub async fn start_sso_post(
+    State(saml_service): State<SamlService>,
+    Form(request): Form<SamlSsoRequest>,
+) -> Result<Redirect, SamlError> {
+    let login_url = saml_service.start_sso(&request, SamlBinding::Post).await?;
+    Ok(Redirect::to(&login_url))
+}
+
+/// called by the login page once the user is signed in, the front end posts the response to the
+/// destination
+pub async fn complete_sso(
+    State(saml_service): State<SamlService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<CompleteSamlSsoRequest>,
+) -> Result<ApiResponse<SamlSsoResponse>, SamlError> {
+    let response = saml_service.complete_sso(&claims, &request).await?;
+    Ok(ApiResponseBuilder::new()
+        .status_code(StatusCode::OK)
+        .data(response)
+        .message("post the saml response to the destination to sign in")
+        .build())
+}
+
+pub async fn initiate_sso(
+    State(saml_service): State<SamlService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<InitiateSamlSsoRequest>,
+) -> Result<ApiResponse<SamlSsoResponse>, SamlError> {
+    let response = saml_service.initiate_sso(&claims, &request).await?;
+    Ok(ApiResponseBuilder::new()
+        .status_code(StatusCode::OK)
+        .data(response)
+        .message("post the saml response to the destination to sign in")
+        .build())
+}
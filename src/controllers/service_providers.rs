@@ -0,0 +1,91 @@
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use uuid::Uuid;
+
+use crate::{
+    adapters::{
+        requests::service_providers::{CreateServiceProviderRequest, UpdateServiceProviderRequest},
+        response::{
+            api_response::{ApiResponse, ApiResponseBuilder},
+            service_providers::ServiceProviderResponse,
+        },
+    },
+    errors::service_provider_service_error::ServiceProviderServiceError,
+    middlewares::{
+        auth::{ManageServiceProviders, PermittedClaims},
+        validator::ValidatedRequest,
+    },
+    services::service_provider_service::{ServiceProviderService, ServiceProviderServiceTrait},
+};
+
+pub async fn list_service_providers(
+    State(service_provider_service): State<ServiceProviderService>,
+    _: PermittedClaims<ManageServiceProviders>,
+) -> Result<ApiResponse<Vec<ServiceProviderResponse>>, ServiceProviderServiceError> {
+    let providers = service_provider_service.list_providers().await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(providers)
+        .message("service providers fetched successfully")
+        .build())
+}
+
+pub async fn create_service_provider(
+    State(service_provider_service): State<ServiceProviderService>,
+    _: PermittedClaims<ManageServiceProviders>,
+    ValidatedRequest(request): ValidatedRequest<CreateServiceProviderRequest>,
+) -> Result<ApiResponse<ServiceProviderResponse>, ServiceProviderServiceError> {
+    let provider = service_provider_service.create_provider(&request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .status_code(StatusCode::CREATED)
+        .data(provider)
+        .message("service provider created successfully")
+        .build())
+}
+
+pub async fn retrieve_service_provider(
+    State(service_provider_service): State<ServiceProviderService>,
+    _: PermittedClaims<ManageServiceProviders>,
+    Path(identifier): Path<Uuid>,
+) -> Result<ApiResponse<ServiceProviderResponse>, ServiceProviderServiceError> {
+    let provider = service_provider_service
+        .retrieve_provider(&identifier)
+        .await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(provider)
+        .message("service provider fetched successfully")
+        .build())
+}
+
+pub async fn update_service_provider(
+    State(service_provider_service): State<ServiceProviderService>,
+    _: PermittedClaims<ManageServiceProviders>,
+    Path(identifier): Path<Uuid>,
+    ValidatedRequest(request): ValidatedRequest<UpdateServiceProviderRequest>,
+) -> Result<ApiResponse<ServiceProviderResponse>, ServiceProviderServiceError> {
+    let provider = service_provider_service
+        .update_provider(&identifier, &request)
+        .await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(provider)
+        .message("service provider updated successfully")
+        .build())
+}
+
+pub async fn delete_service_provider(
+    State(service_provider_service): State<ServiceProviderService>,
+    _: PermittedClaims<ManageServiceProviders>,
+    Path(identifier): Path<Uuid>,
+) -> Result<ApiResponse<()>, ServiceProviderServiceError> {
+    service_provider_service
+        .delete_provider(&identifier)
+        .await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(())
+        .message("service provider deleted successfully")
+        .build())
+}
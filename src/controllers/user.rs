@@ -1,23 +1,98 @@
-use axum::extract::State;
+use axum::extract::{Path, State};
+use uuid::Uuid;
 
 use crate::{
     adapters::{
-        dto::{jwt::Claims, user::UserDto},
-        response::api_response::{ApiResponse, ApiResponseBuilder},
+        dto::user::UserDto,
+        response::{
+            api_response::{ApiResponse, ApiResponseBuilder},
+            auth::{LogoutEverywhereResponse, UnlockUserResponse},
+            sessions::{RevokeOtherSessionsResponse, RevokeSessionResponse, SessionResponse},
+        },
+    },
+    errors::{
+        auth_service_error::AuthenticationServiceError, user_service_error::UserServiceError,
+    },
+    middlewares::auth::{AccessClaims, ManageUsers, PermittedClaims},
+    services::{
+        auth_service::{AuthenticationService, AuthenticationServiceTrait},
+        user_service::UserService,
     },
-    errors::user_service_error::UserServiceError,
-    services::user_service::UserService,
 };
 
 use crate::services::user_service::UserServiceTrait;
 pub async fn retrieve_information(
     State(user_service): State<UserService>,
-    claim: Claims,
+    claims: AccessClaims,
 ) -> Result<ApiResponse<UserDto>, UserServiceError> {
-    let user_data = user_service.retrieve_information(claim.identifier).await?;
+    let mut user_data = user_service.retrieve_information(claims.sub).await?;
+    user_data.session_identifier = claims.sid;
 
     Ok(ApiResponseBuilder::new()
         .data(user_data)
         .message("User's profile fetched successfully")
         .build())
 }
+
+pub async fn sign_out_user(
+    State(auth_service): State<AuthenticationService>,
+    _: PermittedClaims<ManageUsers>,
+    Path(user_identifier): Path<Uuid>,
+) -> Result<ApiResponse<LogoutEverywhereResponse>, AuthenticationServiceError> {
+    let logout_response = auth_service.logout_user(&user_identifier).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(logout_response)
+        .message("user signed out of all sessions")
+        .build())
+}
+
+pub async fn unlock_user(
+    State(auth_service): State<AuthenticationService>,
+    _: PermittedClaims<ManageUsers>,
+    Path(user_identifier): Path<Uuid>,
+) -> Result<ApiResponse<UnlockUserResponse>, AuthenticationServiceError> {
+    let unlock_response = auth_service.unlock_user(&user_identifier).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(unlock_response)
+        .message("failed sign-in attempts cleared, the user can sign in again")
+        .build())
+}
+
+pub async fn list_sessions(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+) -> Result<ApiResponse<Vec<SessionResponse>>, AuthenticationServiceError> {
+    let sessions = auth_service.list_sessions(&claims).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(sessions)
+        .message("sessions fetched successfully")
+        .build())
+}
+
+pub async fn revoke_session(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+    Path(identifier): Path<Uuid>,
+) -> Result<ApiResponse<RevokeSessionResponse>, AuthenticationServiceError> {
+    let revoke_response = auth_service.revoke_session(&claims, &identifier).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(revoke_response)
+        .message("session signed out")
+        .build())
+}
+
+pub async fn revoke_other_sessions(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+) -> Result<ApiResponse<RevokeOtherSessionsResponse>, AuthenticationServiceError> {
+    let revoke_response = auth_service.revoke_other_sessions(&claims).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(revoke_response)
+        .message("every other session signed out")
+        .build())
+}
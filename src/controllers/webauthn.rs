@@ -0,0 +1,116 @@
+use axum::extract::{Path, State};
+use uuid::Uuid;
+
+use crate::{
+    adapters::{
+        dto::session::DeviceInfo,
+        requests::webauthn::{
+            MfaWebAuthnOptionsRequest, PasskeyLoginRequest, RegisterCredentialRequest,
+            RegistrationOptionsRequest,
+        },
+        response::{
+            api_response::{ApiResponse, ApiResponseBuilder},
+            auth::LoginResponse,
+            webauthn::{
+                CredentialCreationOptionsResponse, CredentialRequestOptionsResponse,
+                RemoveWebAuthnCredentialResponse, WebAuthnCredentialResponse,
+            },
+        },
+    },
+    errors::auth_service_error::AuthenticationServiceError,
+    middlewares::{auth::AccessClaims, validator::ValidatedRequest},
+    services::auth_service::{AuthenticationService, AuthenticationServiceTrait},
+};
+
+pub async fn registration_options(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<RegistrationOptionsRequest>,
+) -> Result<ApiResponse<CredentialCreationOptionsResponse>, AuthenticationServiceError> {
+    let options = auth_service
+        .webauthn_registration_options(&claims, &request)
+        .await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(options)
+        .message("pass the options to navigator.credentials.create()")
+        .build())
+}
+
+pub async fn register_credential(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+    ValidatedRequest(request): ValidatedRequest<RegisterCredentialRequest>,
+) -> Result<ApiResponse<WebAuthnCredentialResponse>, AuthenticationServiceError> {
+    let credential = auth_service
+        .register_webauthn_credential(&claims, &request)
+        .await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(credential)
+        .message("passkey registered successfully, log in again to continue")
+        .build())
+}
+
+pub async fn list_credentials(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+) -> Result<ApiResponse<Vec<WebAuthnCredentialResponse>>, AuthenticationServiceError> {
+    let credentials = auth_service.list_webauthn_credentials(&claims).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(credentials)
+        .message("passkeys fetched successfully")
+        .build())
+}
+
+pub async fn remove_credential(
+    State(auth_service): State<AuthenticationService>,
+    AccessClaims(claims): AccessClaims,
+    Path(identifier): Path<Uuid>,
+) -> Result<ApiResponse<RemoveWebAuthnCredentialResponse>, AuthenticationServiceError> {
+    let remove_response = auth_service
+        .remove_webauthn_credential(&claims, &identifier)
+        .await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(remove_response)
+        .message("passkey removed successfully, log in again to continue")
+        .build())
+}
+
+pub async fn passkey_login_options(
+    State(auth_service): State<AuthenticationService>,
+) -> Result<ApiResponse<CredentialRequestOptionsResponse>, AuthenticationServiceError> {
+    let options = auth_service.passkey_login_options().await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(options)
+        .message("pass the options to navigator.credentials.get()")
+        .build())
+}
+
+pub async fn passkey_login(
+    State(auth_service): State<AuthenticationService>,
+    device: DeviceInfo,
+    ValidatedRequest(request): ValidatedRequest<PasskeyLoginRequest>,
+) -> Result<ApiResponse<LoginResponse>, AuthenticationServiceError> {
+    let login_response = auth_service.passkey_login(&request, &device).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(login_response)
+        .message("logged in successfully")
+        .build())
+}
+
+pub async fn mfa_webauthn_options(
+    State(auth_service): State<AuthenticationService>,
+    ValidatedRequest(request): ValidatedRequest<MfaWebAuthnOptionsRequest>,
+) -> Result<ApiResponse<CredentialRequestOptionsResponse>, AuthenticationServiceError> {
+    let options = auth_service.mfa_webauthn_options(&request).await?;
+
+    Ok(ApiResponseBuilder::new()
+        .data(options)
+        .message("pass the options to navigator.credentials.get()")
+        .build())
+}
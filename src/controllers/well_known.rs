@@ -0,0 +1,21 @@
+use axum::{Json, extract::State};
+use jsonwebtoken::jwk::JwkSet;
+
+use crate::{
+    adapters::response::oauth::OpenIdConfiguration,
+    services::{
+        oauth_service::{OAuthService, OAuthServiceTrait},
+        token_service::{TokenService, TokenServiceTrait},
+    },
+};
+
+/// published as a bare JWK set rather than in the api envelope, verifiers expect the standard shape
+pub async fn jwks(State(token_service): State<TokenService>) -> Json<JwkSet> {
+    Json(token_service.jwks())
+}
+
+pub async fn openid_configuration(
+    State(oauth_service): State<OAuthService>,
+) -> Json<OpenIdConfiguration> {
+    Json(oauth_service.openid_configuration())
+}
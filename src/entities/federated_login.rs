@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct FederatedLoginEntity {
+    pub state: String,
+    pub provider_identifier: Uuid,
+    /// must come back in the id token, ties the token to this sign-in
+    pub nonce: String,
+    pub code_verifier: String,
+    /// the first-party client the tokens are issued to once the sign-in completes
+    pub client_id: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct IdentityProviderEntity {
+    pub identifier: Uuid,
+    /// names the provider in the login routes
+    pub slug: String,
+    pub name: String,
+    pub issuer: String,
+    pub client_id: String,
+    /// sealed with the [`SecretBox`](crate::shared::secret_box::SecretBox)
+    #[serde(skip)]
+    pub client_secret: Vec<u8>,
+    pub scopes: Vec<String>,
+    pub email_claim: String,
+    pub first_name_claim: String,
+    pub last_name_claim: String,
+    /// the provider owns the domains of its accounts, so their email addresses count as verified
+    /// without an `email_verified` claim
+    pub trust_email: bool,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct LoginFailureEntity {
+    /// "account" or "ip"
+    pub scope: String,
+    /// the normalised email address or the source address
+    pub subject: String,
+    pub failures: i32,
+    /// attempts are refused until then, set by the back-off or a lockout
+    pub blocked_until: Option<DateTime<Utc>>,
+    pub last_failure_at: DateTime<Utc>,
+}
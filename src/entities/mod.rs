@@ -1,2 +1,15 @@
-
+pub mod authorization_code;
+pub mod device_code;
+pub mod federated_login;
+pub mod identity_provider;
+pub mod login_failure;
+pub mod oauth_client;
+pub mod otp;
+pub mod refresh_token;
+pub mod saml_service_provider;
+pub mod session;
+pub mod token_exchange_audit;
+pub mod totp_authenticator;
 pub mod user;
+pub mod webauthn_challenge;
+pub mod webauthn_credential;
@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct RefreshTokenEntity {
+    pub identifier: Uuid,
+    pub user_identifier: Uuid,
+    pub family_identifier: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub rotated_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub client_id: Option<String>,
+    pub scope: Option<String>,
+}
@@ -0,0 +1,24 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SamlServiceProviderEntity {
+    pub identifier: Uuid,
+    pub entity_id: String,
+    pub name: String,
+    /// HTTP-POST locations from the metadata, the default one first
+    pub assertion_consumer_services: Vec<String>,
+    /// used unless the authentication request asks for another format
+    pub name_id_format: String,
+    pub email_attribute: Option<String>,
+    pub first_name_attribute: Option<String>,
+    pub last_name_attribute: Option<String>,
+    pub display_name_attribute: Option<String>,
+    /// the metadata as it was registered
+    pub metadata: String,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,18 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct SessionEntity {
+    pub identifier: Uuid,
+    pub user_identifier: Uuid,
+    /// the refresh token family the sign-in started, revoking the family ends the session
+    pub family_identifier: Uuid,
+    pub user_agent: Option<String>,
+    /// where the session was last seen from
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// when the session last refreshed its tokens
+    pub last_seen_at: DateTime<Utc>,
+}
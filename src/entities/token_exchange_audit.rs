@@ -0,0 +1,22 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TokenExchangeAuditEntity {
+    pub identifier: Uuid,
+    pub client_id: String,
+    /// the user the exchanged token speaks for
+    pub subject_identifier: Uuid,
+    pub subject_token_jti: Uuid,
+    pub actor: String,
+    pub actor_type: String,
+    pub impersonation: bool,
+    pub scope: String,
+    pub audience: String,
+    pub granted: bool,
+    /// why the exchange was refused
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct TotpAuthenticatorEntity {
+    pub user_identifier: Uuid,
+    pub secret_ciphertext: Vec<u8>,
+    pub last_used_step: Option<i64>,
+    /// None until the user has proved the authenticator works, an unconfirmed authenticator is
+    /// not asked for at login
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
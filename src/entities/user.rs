@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
@@ -10,4 +11,10 @@ pub struct UserEntity {
     pub last_name: String,
     pub password: String,
     pub is_active: bool,
+    pub token_version: i32,
+    pub permissions: Vec<String>,
+    /// the address an email change is waiting to be confirmed for
+    pub pending_email: Option<String>,
+    pub email_verified_at: Option<DateTime<Utc>>,
+    pub email_changed_at: Option<DateTime<Utc>>,
 }
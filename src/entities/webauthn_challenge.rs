@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct WebAuthnChallengeEntity {
+    pub challenge: String,
+    /// `webauthn.create` or `webauthn.get`, the client data type the response must carry
+    pub ceremony: String,
+    pub user_identifier: Option<Uuid>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
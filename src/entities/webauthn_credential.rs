@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct WebAuthnCredentialEntity {
+    pub identifier: Uuid,
+    pub user_identifier: Uuid,
+    /// base64url, as the browser reports it
+    pub credential_id: String,
+    /// COSE encoded
+    pub public_key: Vec<u8>,
+    pub sign_count: i64,
+    pub transports: Vec<String>,
+    pub name: String,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
@@ -1,8 +1,14 @@
 use crate::adapters::response::api_response::ApiResponseBuilder;
 use crate::errors::{
-    app_error::AppError, common_service_error::ServiceError, user_service_error::UserServiceError,
+    app_error::AppError, common_service_error::ServiceError, federation_error::FederationError,
+    lockout_service_error::LockoutServiceError, mfa_service_error::MfaServiceError,
+    otp_service_error::OtpServiceError, session_service_error::SessionServiceError,
+    user_service_error::UserServiceError, webauthn_service_error::WebAuthnServiceError,
+};
+use axum::{
+    http::{HeaderValue, StatusCode, header},
+    response::IntoResponse,
 };
-use axum::{http::StatusCode, response::IntoResponse};
 
 #[derive(Debug, thiserror::Error)]
 pub enum AuthenticationServiceError {
@@ -12,11 +18,35 @@ pub enum AuthenticationServiceError {
     MissingCredentials,
     #[error("Invalid token")]
     InvalidToken,
+    #[error("account has already been verified")]
+    AccountAlreadyVerified,
+    #[error("you do not have permission to perform this action")]
+    Forbidden,
+    #[error("the client is not registered for first-party login")]
+    InvalidClient,
+    #[error("a sign-in token, or an email address and code, is required")]
+    MissingEmailLoginCode,
+    #[error("there is no email change waiting to be confirmed")]
+    NoPendingEmailChange,
+    #[error("enter your password again to continue")]
+    ReauthenticationRequired,
     #[error(transparent)]
     ServiceError(#[from] ServiceError),
     #[error(transparent)]
     UserServiceError(#[from] UserServiceError),
     #[error(transparent)]
+    OtpServiceError(#[from] OtpServiceError),
+    #[error(transparent)]
+    MfaServiceError(#[from] MfaServiceError),
+    #[error(transparent)]
+    WebAuthnServiceError(#[from] WebAuthnServiceError),
+    #[error(transparent)]
+    LockoutServiceError(#[from] LockoutServiceError),
+    #[error(transparent)]
+    SessionServiceError(#[from] SessionServiceError),
+    #[error(transparent)]
+    FederationError(#[from] FederationError),
+    #[error(transparent)]
     AppError(#[from] AppError),
     #[error("error processing authorization token")]
     JwtError(#[from] jsonwebtoken::errors::Error),
@@ -29,8 +59,20 @@ impl AuthenticationServiceError {
             AuthenticationServiceError::MissingCredentials => StatusCode::BAD_REQUEST,
 
             AuthenticationServiceError::InvalidToken => StatusCode::UNAUTHORIZED,
+            AuthenticationServiceError::AccountAlreadyVerified => StatusCode::CONFLICT,
+            AuthenticationServiceError::Forbidden => StatusCode::FORBIDDEN,
+            AuthenticationServiceError::InvalidClient => StatusCode::BAD_REQUEST,
+            AuthenticationServiceError::MissingEmailLoginCode => StatusCode::BAD_REQUEST,
+            AuthenticationServiceError::NoPendingEmailChange => StatusCode::BAD_REQUEST,
+            AuthenticationServiceError::ReauthenticationRequired => StatusCode::FORBIDDEN,
             AuthenticationServiceError::ServiceError(err) => err.status_code(),
             AuthenticationServiceError::UserServiceError(err) => err.status_code(),
+            AuthenticationServiceError::OtpServiceError(err) => err.status_code(),
+            AuthenticationServiceError::MfaServiceError(err) => err.status_code(),
+            AuthenticationServiceError::WebAuthnServiceError(err) => err.status_code(),
+            AuthenticationServiceError::LockoutServiceError(err) => err.status_code(),
+            AuthenticationServiceError::SessionServiceError(err) => err.status_code(),
+            AuthenticationServiceError::FederationError(err) => err.status_code(),
             AuthenticationServiceError::AppError(err) => err.status_code(),
             AuthenticationServiceError::JwtError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
@@ -38,10 +80,25 @@ impl AuthenticationServiceError {
 }
 impl IntoResponse for AuthenticationServiceError {
     fn into_response(self) -> axum::response::Response {
-        ApiResponseBuilder::<()>::new()
+        // validation failures carry their field errors
+        if let AuthenticationServiceError::ServiceError(err) = self {
+            return err.into_response();
+        }
+        let retry_after = match &self {
+            AuthenticationServiceError::LockoutServiceError(err) => err.retry_after(),
+            _ => None,
+        };
+        let mut response = ApiResponseBuilder::<()>::new()
             .status_code(self.status_code())
             .message(&self.to_string())
             .build()
-            .into_response()
+            .into_response();
+        if let Some(retry_after) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+
+        response
     }
 }
@@ -0,0 +1,34 @@
+use axum::{http::StatusCode, response::IntoResponse};
+
+use crate::adapters::response::api_response::ApiResponseBuilder;
+use crate::errors::common_service_error::ServiceError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientServiceError {
+    #[error("client not found")]
+    NotFound,
+    #[error("{0}")]
+    InvalidRequest(String),
+    #[error(transparent)]
+    ServiceError(#[from] ServiceError),
+}
+
+impl ClientServiceError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ClientServiceError::NotFound => StatusCode::NOT_FOUND,
+            ClientServiceError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            ClientServiceError::ServiceError(err) => err.status_code(),
+        }
+    }
+}
+
+impl IntoResponse for ClientServiceError {
+    fn into_response(self) -> axum::response::Response {
+        ApiResponseBuilder::<()>::new()
+            .status_code(self.status_code())
+            .message(&self.to_string())
+            .build()
+            .into_response()
+    }
+}
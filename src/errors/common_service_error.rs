@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use axum::extract::rejection::{FormRejection, JsonRejection};
 use axum::response::Response;
 use axum::{http::StatusCode, response::IntoResponse};
@@ -28,6 +30,15 @@ impl ServiceError {
 }
 impl IntoResponse for ServiceError {
     fn into_response(self) -> Response {
+        if let ServiceError::ValidationError(errors) = &self {
+            return ApiResponseBuilder::new()
+                .status_code(self.status_code())
+                .message(&self.to_string())
+                .data(field_errors(errors))
+                .build()
+                .into_response();
+        }
+
         ApiResponseBuilder::<()>::new()
             .status_code(self.status_code())
             .message(&self.to_string())
@@ -35,3 +46,24 @@ impl IntoResponse for ServiceError {
             .into_response()
     }
 }
+
+/// the messages for each field that failed validation, keyed by the field name
+fn field_errors(errors: &validator::ValidationErrors) -> BTreeMap<String, Vec<String>> {
+    errors
+        .field_errors()
+        .into_iter()
+        .map(|(field, errors)| {
+            let messages = errors
+                .iter()
+                .map(|error| {
+                    error
+                        .message
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_else(|| error.code.to_string())
+                })
+                .collect();
+            (field.to_string(), messages)
+        })
+        .collect()
+}
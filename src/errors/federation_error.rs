@@ -0,0 +1,55 @@
+use axum::{http::StatusCode, response::IntoResponse};
+
+use crate::adapters::response::api_response::ApiResponseBuilder;
+use crate::errors::{app_error::AppError, common_service_error::ServiceError};
+
+#[derive(thiserror::Error, Debug)]
+pub enum FederationError {
+    #[error("identity provider not found")]
+    ProviderNotFound,
+    #[error("the sign-in has expired or was already completed, start over")]
+    LoginExpired,
+    #[error("the identity provider refused the sign-in: {0}")]
+    Denied(String),
+    #[error("the identity provider could not be reached: {0}")]
+    Unavailable(String),
+    #[error("the identity provider sent an invalid response: {0}")]
+    InvalidResponse(String),
+    /// deliberately vague, the caller is not told which check failed
+    #[error("the identity provider's id token could not be verified")]
+    InvalidIdToken,
+    /// the provider does not vouch for the email address, so it cannot be trusted to take over the
+    /// account that already has it
+    #[error("an account with this email address already exists, sign in to it another way")]
+    AccountExists,
+    #[error(transparent)]
+    ServiceError(#[from] ServiceError),
+    #[error(transparent)]
+    AppError(#[from] AppError),
+}
+
+impl FederationError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::ProviderNotFound => StatusCode::NOT_FOUND,
+            Self::LoginExpired => StatusCode::BAD_REQUEST,
+            Self::Denied(_) => StatusCode::UNAUTHORIZED,
+            Self::Unavailable(_) => StatusCode::BAD_GATEWAY,
+            Self::InvalidResponse(_) => StatusCode::BAD_GATEWAY,
+            Self::InvalidIdToken => StatusCode::UNAUTHORIZED,
+            Self::AccountExists => StatusCode::CONFLICT,
+            Self::ServiceError(err) => err.status_code(),
+            Self::AppError(err) => err.status_code(),
+        }
+    }
+}
+
+impl IntoResponse for FederationError {
+    fn into_response(self) -> axum::response::Response {
+        ApiResponseBuilder::<()>::new()
+            .status_code(self.status_code())
+            .message(&self.to_string())
+            .build()
+            .into_response()
+    }
+}
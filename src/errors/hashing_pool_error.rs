@@ -0,0 +1,30 @@
+use axum::{http::StatusCode, response::IntoResponse};
+
+use crate::adapters::response::api_response::ApiResponseBuilder;
+
+#[derive(thiserror::Error, Debug)]
+pub enum HashingPoolError {
+    #[error("the server is busy, try again shortly")]
+    Saturated,
+    #[error("the password could not be processed")]
+    Failed,
+}
+
+impl HashingPoolError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::Saturated => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Failed => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for HashingPoolError {
+    fn into_response(self) -> axum::response::Response {
+        ApiResponseBuilder::<()>::new()
+            .status_code(self.status_code())
+            .message(&self.to_string())
+            .build()
+            .into_response()
+    }
+}
@@ -0,0 +1,37 @@
+use axum::{http::StatusCode, response::IntoResponse};
+
+use crate::adapters::response::api_response::ApiResponseBuilder;
+use crate::errors::{app_error::AppError, common_service_error::ServiceError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum IdentityProviderServiceError {
+    #[error("identity provider not found")]
+    NotFound,
+    #[error("an identity provider with the slug already exists")]
+    SlugTaken,
+    #[error(transparent)]
+    ServiceError(#[from] ServiceError),
+    #[error(transparent)]
+    AppError(#[from] AppError),
+}
+
+impl IdentityProviderServiceError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::SlugTaken => StatusCode::CONFLICT,
+            Self::ServiceError(err) => err.status_code(),
+            Self::AppError(err) => err.status_code(),
+        }
+    }
+}
+
+impl IntoResponse for IdentityProviderServiceError {
+    fn into_response(self) -> axum::response::Response {
+        ApiResponseBuilder::<()>::new()
+            .status_code(self.status_code())
+            .message(&self.to_string())
+            .build()
+            .into_response()
+    }
+}
@@ -0,0 +1,50 @@
+use axum::{
+    http::{HeaderValue, StatusCode, header},
+    response::IntoResponse,
+};
+
+use crate::adapters::response::api_response::ApiResponseBuilder;
+use crate::errors::common_service_error::ServiceError;
+
+#[derive(thiserror::Error, Debug)]
+pub enum LockoutServiceError {
+    #[error("too many failed sign-in attempts, try again in {retry_after} seconds")]
+    TooManyAttempts { retry_after: u64 },
+    #[error(transparent)]
+    ServiceError(#[from] ServiceError),
+}
+
+impl LockoutServiceError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::TooManyAttempts { .. } => StatusCode::TOO_MANY_REQUESTS,
+            Self::ServiceError(err) => err.status_code(),
+        }
+    }
+
+    /// seconds for the `Retry-After` header
+    pub fn retry_after(&self) -> Option<u64> {
+        match self {
+            Self::TooManyAttempts { retry_after } => Some(*retry_after),
+            Self::ServiceError(_) => None,
+        }
+    }
+}
+
+impl IntoResponse for LockoutServiceError {
+    fn into_response(self) -> axum::response::Response {
+        let retry_after = self.retry_after();
+        let mut response = ApiResponseBuilder::<()>::new()
+            .status_code(self.status_code())
+            .message(&self.to_string())
+            .build()
+            .into_response();
+        if let Some(retry_after) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+
+        response
+    }
+}
@@ -0,0 +1,11 @@
+#[derive(thiserror::Error, Debug)]
+pub enum MailerError {
+    #[error("invalid email address: {0}")]
+    InvalidAddress(String),
+    #[error("error building email: {0}")]
+    MessageError(String),
+    #[error("error delivering email: {0}")]
+    TransportError(String),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+}
@@ -0,0 +1,43 @@
+use axum::{http::StatusCode, response::IntoResponse};
+
+use crate::adapters::response::api_response::ApiResponseBuilder;
+use crate::errors::{app_error::AppError, common_service_error::ServiceError};
+
+#[derive(thiserror::Error, Debug)]
+pub enum MfaServiceError {
+    #[error("the authentication code is invalid")]
+    InvalidCode,
+    #[error("an authentication code or a recovery code is required")]
+    MissingCode,
+    #[error("an authenticator is already enrolled")]
+    AlreadyEnrolled,
+    #[error("no authenticator is enrolled")]
+    NotEnrolled,
+    #[error(transparent)]
+    ServiceError(#[from] ServiceError),
+    #[error(transparent)]
+    AppError(#[from] AppError),
+}
+
+impl MfaServiceError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::InvalidCode => StatusCode::UNAUTHORIZED,
+            Self::MissingCode => StatusCode::BAD_REQUEST,
+            Self::AlreadyEnrolled => StatusCode::CONFLICT,
+            Self::NotEnrolled => StatusCode::NOT_FOUND,
+            Self::ServiceError(err) => err.status_code(),
+            Self::AppError(err) => err.status_code(),
+        }
+    }
+}
+
+impl IntoResponse for MfaServiceError {
+    fn into_response(self) -> axum::response::Response {
+        ApiResponseBuilder::<()>::new()
+            .status_code(self.status_code())
+            .message(&self.to_string())
+            .build()
+            .into_response()
+    }
+}
@@ -1,4 +1,17 @@
 pub mod app_error;
 pub mod auth_service_error;
+pub mod client_service_error;
 pub mod common_service_error;
+pub mod federation_error;
+pub mod hashing_pool_error;
+pub mod identity_provider_service_error;
+pub mod lockout_service_error;
+pub mod mailer_error;
+pub mod mfa_service_error;
+pub mod oauth_error;
+pub mod otp_service_error;
+pub mod saml_error;
+pub mod service_provider_service_error;
+pub mod session_service_error;
 pub mod user_service_error;
+pub mod webauthn_service_error;
@@ -0,0 +1,161 @@
+use axum::{
+    Json,
+    http::{HeaderValue, StatusCode, header},
+    response::{IntoResponse, Redirect},
+};
+use serde::Serialize;
+
+use crate::errors::{
+    auth_service_error::AuthenticationServiceError, common_service_error::ServiceError,
+};
+
+/// errors of the oauth endpoints, reported in the RFC 6749 format rather than the api envelope
+#[derive(Debug, thiserror::Error)]
+pub enum OAuthError {
+    #[error("{0}")]
+    InvalidRequest(String),
+    #[error("client authentication failed")]
+    InvalidClient,
+    #[error("{0}")]
+    InvalidGrant(String),
+    #[error("the client is not allowed to use this grant type")]
+    UnauthorizedClient,
+    #[error("the grant type is not supported")]
+    UnsupportedGrantType,
+    #[error("only the code response type is supported")]
+    UnsupportedResponseType,
+    #[error("{0}")]
+    InvalidScope(String),
+    #[error("{0}")]
+    InvalidTarget(String),
+    #[error("{0}")]
+    AccessDenied(String),
+    #[error("the server encountered an error processing the request")]
+    ServerError(String),
+    #[error("the user has not yet approved the device")]
+    AuthorizationPending,
+    #[error("polling too fast, wait longer between requests")]
+    SlowDown,
+    #[error("the device code has expired")]
+    ExpiredToken,
+    /// `access_denied` as reported to a polling device, a 400 like every token endpoint error
+    #[error("the user denied the authorization request")]
+    AuthorizationDenied,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OAuthErrorResponse {
+    pub error: &'static str,
+    pub error_description: String,
+}
+
+impl OAuthError {
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            OAuthError::InvalidRequest(_) => "invalid_request",
+            OAuthError::InvalidClient => "invalid_client",
+            OAuthError::InvalidGrant(_) => "invalid_grant",
+            OAuthError::UnauthorizedClient => "unauthorized_client",
+            OAuthError::UnsupportedGrantType => "unsupported_grant_type",
+            OAuthError::UnsupportedResponseType => "unsupported_response_type",
+            OAuthError::InvalidScope(_) => "invalid_scope",
+            OAuthError::InvalidTarget(_) => "invalid_target",
+            OAuthError::AccessDenied(_) => "access_denied",
+            OAuthError::ServerError(_) => "server_error",
+            OAuthError::AuthorizationPending => "authorization_pending",
+            OAuthError::SlowDown => "slow_down",
+            OAuthError::ExpiredToken => "expired_token",
+            OAuthError::AuthorizationDenied => "access_denied",
+        }
+    }
+
+    fn status_code(&self) -> StatusCode {
+        match self {
+            OAuthError::InvalidClient => StatusCode::UNAUTHORIZED,
+            OAuthError::AccessDenied(_) => StatusCode::FORBIDDEN,
+            OAuthError::ServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl From<ServiceError> for OAuthError {
+    fn from(err: ServiceError) -> Self {
+        OAuthError::ServerError(err.to_string())
+    }
+}
+
+impl From<AuthenticationServiceError> for OAuthError {
+    fn from(err: AuthenticationServiceError) -> Self {
+        match err {
+            AuthenticationServiceError::InvalidToken => {
+                OAuthError::InvalidGrant("the grant is invalid, expired or revoked".into())
+            }
+            err => OAuthError::ServerError(err.to_string()),
+        }
+    }
+}
+
+impl IntoResponse for OAuthError {
+    fn into_response(self) -> axum::response::Response {
+        if let OAuthError::ServerError(err) = &self {
+            log::error!("oauth request failed: {err}");
+        }
+
+        let mut response = (
+            self.status_code(),
+            Json(OAuthErrorResponse {
+                error: self.error_code(),
+                error_description: self.to_string(),
+            }),
+        )
+            .into_response();
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        if let OAuthError::InvalidClient = self {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Basic"));
+        }
+        response
+    }
+}
+
+/// an authorization request error, sent back to the client once its redirect uri is trusted and
+/// shown to the user agent otherwise
+#[derive(Debug)]
+pub struct AuthorizationError {
+    pub error: OAuthError,
+    pub redirect_uri: Option<url::Url>,
+    pub state: Option<String>,
+}
+
+impl From<OAuthError> for AuthorizationError {
+    fn from(error: OAuthError) -> Self {
+        Self {
+            error,
+            redirect_uri: None,
+            state: None,
+        }
+    }
+}
+
+impl IntoResponse for AuthorizationError {
+    fn into_response(self) -> axum::response::Response {
+        let Some(mut redirect_uri) = self.redirect_uri else {
+            return self.error.into_response();
+        };
+
+        {
+            let mut query = redirect_uri.query_pairs_mut();
+            query
+                .append_pair("error", self.error.error_code())
+                .append_pair("error_description", &self.error.to_string());
+            if let Some(state) = &self.state {
+                query.append_pair("state", state);
+            }
+        }
+        Redirect::to(redirect_uri.as_str()).into_response()
+    }
+}
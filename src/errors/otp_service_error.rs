@@ -0,0 +1,37 @@
+use axum::{http::StatusCode, response::IntoResponse};
+
+use crate::adapters::response::api_response::ApiResponseBuilder;
+use crate::errors::common_service_error::ServiceError;
+
+#[derive(thiserror::Error, Debug)]
+pub enum OtpServiceError {
+    #[error("the one-time code is invalid")]
+    InvalidCode,
+    #[error("the one-time code has expired, request a new one")]
+    ExpiredCode,
+    #[error("too many attempts, request a new one-time code")]
+    TooManyAttempts,
+    #[error(transparent)]
+    ServiceError(#[from] ServiceError),
+}
+
+impl OtpServiceError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::InvalidCode => StatusCode::BAD_REQUEST,
+            Self::ExpiredCode => StatusCode::BAD_REQUEST,
+            Self::TooManyAttempts => StatusCode::TOO_MANY_REQUESTS,
+            Self::ServiceError(err) => err.status_code(),
+        }
+    }
+}
+
+impl IntoResponse for OtpServiceError {
+    fn into_response(self) -> axum::response::Response {
+        ApiResponseBuilder::<()>::new()
+            .status_code(self.status_code())
+            .message(&self.to_string())
+            .build()
+            .into_response()
+    }
+}
@@ -0,0 +1,57 @@
+use axum::{http::StatusCode, response::IntoResponse};
+
+use crate::adapters::response::api_response::ApiResponseBuilder;
+use crate::errors::{app_error::AppError, common_service_error::ServiceError};
+
+#[derive(thiserror::Error, Debug)]
+pub enum SamlError {
+    #[error("the authentication request is invalid: {0}")]
+    InvalidRequest(String),
+    #[error("the service provider metadata is invalid: {0}")]
+    InvalidMetadata(String),
+    #[error("the service provider is not registered")]
+    UnknownServiceProvider,
+    /// assertions are only ever posted to a location the service provider registered
+    #[error("the assertion consumer service is not registered for the service provider")]
+    UnregisteredAssertionConsumerService,
+    #[error("{0} is not a supported name id format")]
+    UnsupportedNameIdFormat(String),
+    #[error("the user no longer exists")]
+    UserNotFound,
+    /// the access token does not say when the user signed in
+    #[error("sign in again to continue")]
+    SignInRequired,
+    #[error("error signing the assertion: {0}")]
+    SigningFailed(String),
+    #[error(transparent)]
+    ServiceError(#[from] ServiceError),
+    #[error(transparent)]
+    AppError(#[from] AppError),
+}
+
+impl SamlError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            Self::InvalidMetadata(_) => StatusCode::BAD_REQUEST,
+            Self::UnknownServiceProvider => StatusCode::BAD_REQUEST,
+            Self::UnregisteredAssertionConsumerService => StatusCode::BAD_REQUEST,
+            Self::UnsupportedNameIdFormat(_) => StatusCode::BAD_REQUEST,
+            Self::UserNotFound => StatusCode::NOT_FOUND,
+            Self::SignInRequired => StatusCode::FORBIDDEN,
+            Self::SigningFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::ServiceError(err) => err.status_code(),
+            Self::AppError(err) => err.status_code(),
+        }
+    }
+}
+
+impl IntoResponse for SamlError {
+    fn into_response(self) -> axum::response::Response {
+        ApiResponseBuilder::<()>::new()
+            .status_code(self.status_code())
+            .message(&self.to_string())
+            .build()
+            .into_response()
+    }
+}
@@ -0,0 +1,42 @@
+use axum::{http::StatusCode, response::IntoResponse};
+
+use crate::adapters::response::api_response::ApiResponseBuilder;
+use crate::errors::{
+    app_error::AppError, common_service_error::ServiceError, saml_error::SamlError,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ServiceProviderServiceError {
+    #[error("service provider not found")]
+    NotFound,
+    #[error("a service provider with the entity id already exists")]
+    EntityIdTaken,
+    #[error(transparent)]
+    SamlError(#[from] SamlError),
+    #[error(transparent)]
+    ServiceError(#[from] ServiceError),
+    #[error(transparent)]
+    AppError(#[from] AppError),
+}
+
+impl ServiceProviderServiceError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::NotFound => StatusCode::NOT_FOUND,
+            Self::EntityIdTaken => StatusCode::CONFLICT,
+            Self::SamlError(err) => err.status_code(),
+            Self::ServiceError(err) => err.status_code(),
+            Self::AppError(err) => err.status_code(),
+        }
+    }
+}
+
+impl IntoResponse for ServiceProviderServiceError {
+    fn into_response(self) -> axum::response::Response {
+        ApiResponseBuilder::<()>::new()
+            .status_code(self.status_code())
+            .message(&self.to_string())
+            .build()
+            .into_response()
+    }
+}
@@ -0,0 +1,31 @@
+use axum::{http::StatusCode, response::IntoResponse};
+
+use crate::adapters::response::api_response::ApiResponseBuilder;
+use crate::errors::common_service_error::ServiceError;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SessionServiceError {
+    #[error("session not found")]
+    SessionNotFound,
+    #[error(transparent)]
+    ServiceError(#[from] ServiceError),
+}
+
+impl SessionServiceError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::SessionNotFound => StatusCode::NOT_FOUND,
+            Self::ServiceError(err) => err.status_code(),
+        }
+    }
+}
+
+impl IntoResponse for SessionServiceError {
+    fn into_response(self) -> axum::response::Response {
+        ApiResponseBuilder::<()>::new()
+            .status_code(self.status_code())
+            .message(&self.to_string())
+            .build()
+            .into_response()
+    }
+}
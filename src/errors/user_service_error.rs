@@ -2,6 +2,7 @@ use axum::{http::StatusCode, response::IntoResponse};
 
 use crate::adapters::response::api_response::ApiResponseBuilder;
 use crate::errors::common_service_error::ServiceError;
+use crate::errors::hashing_pool_error::HashingPoolError;
 
 #[derive(thiserror::Error, Debug)]
 pub enum UserServiceError {
@@ -9,9 +10,13 @@ pub enum UserServiceError {
     OperationFailed(String),
     #[error("duplicate record: {0}")]
     ConflictError(String),
+    #[error("{0}")]
+    NotFound(String),
     #[error(transparent)]
     ServiceError(#[from] ServiceError),
     #[error(transparent)]
+    HashingPoolError(#[from] HashingPoolError),
+    #[error(transparent)]
     SqlxError(#[from] sqlx::error::Error),
 }
 
@@ -20,7 +25,9 @@ impl UserServiceError {
         match self {
             Self::OperationFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
             Self::ConflictError(_) => StatusCode::CONFLICT,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
             Self::ServiceError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            Self::HashingPoolError(err) => err.status_code(),
             Self::SqlxError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
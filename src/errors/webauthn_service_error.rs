@@ -0,0 +1,47 @@
+use axum::{http::StatusCode, response::IntoResponse};
+
+use crate::adapters::response::api_response::ApiResponseBuilder;
+use crate::errors::{app_error::AppError, common_service_error::ServiceError};
+
+#[derive(thiserror::Error, Debug)]
+pub enum WebAuthnServiceError {
+    #[error("the authenticator response is invalid: {0}")]
+    InvalidResponse(String),
+    #[error("the challenge has expired or was already used, start over")]
+    ChallengeNotFound,
+    /// deliberately vague, the caller is not told which check failed
+    #[error("the passkey could not be verified")]
+    VerificationFailed,
+    #[error("the passkey is already registered")]
+    AlreadyRegistered,
+    #[error("passkey not found")]
+    CredentialNotFound,
+    #[error(transparent)]
+    ServiceError(#[from] ServiceError),
+    #[error(transparent)]
+    AppError(#[from] AppError),
+}
+
+impl WebAuthnServiceError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            Self::InvalidResponse(_) => StatusCode::BAD_REQUEST,
+            Self::ChallengeNotFound => StatusCode::BAD_REQUEST,
+            Self::VerificationFailed => StatusCode::UNAUTHORIZED,
+            Self::AlreadyRegistered => StatusCode::CONFLICT,
+            Self::CredentialNotFound => StatusCode::NOT_FOUND,
+            Self::ServiceError(err) => err.status_code(),
+            Self::AppError(err) => err.status_code(),
+        }
+    }
+}
+
+impl IntoResponse for WebAuthnServiceError {
+    fn into_response(self) -> axum::response::Response {
+        ApiResponseBuilder::<()>::new()
+            .status_code(self.status_code())
+            .message(&self.to_string())
+            .build()
+            .into_response()
+    }
+}
@@ -1,9 +1,10 @@
 #![warn(unused_extern_crates)]
 
-use uralium_lib::{errors, routes, shared};
+use uralium_lib::{errors, routes, services, shared};
 
 use errors::app_error::AppError;
 use routes::router::load_routes;
+use services::oauth_service::OAuthService;
 use shared::extract_env::extract_env;
 use sqlx::migrate::Migrator;
 use sqlx::postgres::PgPoolOptions;
@@ -34,7 +35,9 @@ async fn main() -> Result<(), AppError> {
         .await
         .map_err(|err| AppError::StartupError(err.to_string()))?;
 
-    let app = load_routes(pool);
+    OAuthService::register_first_party_client(&pool).await?;
+
+    let app = load_routes(pool)?;
     let port = extract_env::<u16>("PORT")?;
     let ip_address = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port));
     log::info!("Application listening on http://{}", ip_address);
@@ -42,10 +45,13 @@ async fn main() -> Result<(), AppError> {
     let listener = tokio::net::TcpListener::bind(ip_address)
         .await
         .map_err(|err| AppError::OperationFailed(err.to_string()))?;
-    axum::serve(listener, app)
-        .await
-        .map_err(|err| AppError::OperationFailed(err.to_string()))?;
+    // the peer address is what failed sign-ins are counted against when there is no trusted proxy
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .map_err(|err| AppError::OperationFailed(err.to_string()))?;
 
     Ok(())
 }
-
@@ -1,36 +1,181 @@
-use axum::{RequestPartsExt, extract::FromRequestParts, http::request::Parts};
+use std::{marker::PhantomData, ops::Deref};
+
+use axum::{
+    RequestPartsExt,
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
 use axum_extra::{
     TypedHeader,
     headers::{Authorization, authorization::Bearer},
 };
-use jsonwebtoken::{Validation, decode};
 
-use crate::adapters::dto::jwt::Keys;
-use crate::{
-    adapters::dto::jwt::Claims, errors::auth_service_error::AuthenticationServiceError,
-    shared::extract_env::extract_env,
-};
+use crate::adapters::dto::{jwt::TokenUse, permission::Permission};
+use crate::services::token_service::{TokenService, TokenServiceTrait};
+use crate::services::user_service::{UserService, UserServiceTrait};
+use crate::{adapters::dto::jwt::Claims, errors::auth_service_error::AuthenticationServiceError};
+
+/// claims of a login token from a first-party client, the only kind accepted by resource routes
+#[derive(Debug, Clone)]
+pub struct AccessClaims(pub Claims);
+
+/// claims of any access token, including those issued to third-party oauth clients
+#[derive(Debug, Clone)]
+pub struct OAuthAccessClaims(pub Claims);
+
+/// claims of the token handed out at signup to complete account verification
+#[derive(Debug, Clone)]
+pub struct VerificationClaims(pub Claims);
+
+/// a permission an administrative route requires, see [`PermittedClaims`]
+pub trait RequiredPermission {
+    const PERMISSION: Permission;
+}
+
+pub struct ManageClients;
+
+impl RequiredPermission for ManageClients {
+    const PERMISSION: Permission = Permission::ManageClients;
+}
+
+pub struct ManageIdentityProviders;
+
+impl RequiredPermission for ManageIdentityProviders {
+    const PERMISSION: Permission = Permission::ManageIdentityProviders;
+}
+
+pub struct ManageServiceProviders;
+
+impl RequiredPermission for ManageServiceProviders {
+    const PERMISSION: Permission = Permission::ManageServiceProviders;
+}
+
+pub struct ManageUsers;
+
+impl RequiredPermission for ManageUsers {
+    const PERMISSION: Permission = Permission::ManageUsers;
+}
 
-impl<S> FromRequestParts<S> for Claims
+/// claims of a first-party login whose user holds `P::PERMISSION`, checked on every request so
+/// revoking a permission takes effect immediately
+#[derive(Debug, Clone)]
+pub struct PermittedClaims<P>(pub Claims, PhantomData<P>);
+
+/// revoked tokens are rejected here, so every route behind these extractors honours logout
+async fn extract_claims(
+    parts: &mut Parts,
+    token_service: &TokenService,
+    token_use: TokenUse,
+) -> Result<Claims, AuthenticationServiceError> {
+    // Extract the token from the authorization header
+    let TypedHeader(Authorization(bearer)) = parts
+        .extract::<TypedHeader<Authorization<Bearer>>>()
+        .await
+        .map_err(|_| AuthenticationServiceError::MissingCredentials)?;
+    // Decode the user data
+    token_service.decode_token(bearer.token(), token_use).await
+}
+
+impl<S> FromRequestParts<S> for AccessClaims
 where
     S: Send + Sync,
+    TokenService: FromRef<S>,
 {
     type Rejection = AuthenticationServiceError;
 
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let secret =
-            extract_env::<String>("JWT_SIGNING_KEY").map_err(AuthenticationServiceError::from)?;
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let token_service = TokenService::from_ref(state);
+        let claims = extract_claims(parts, &token_service, TokenUse::Access).await?;
+        if !claims.first_party {
+            return Err(AuthenticationServiceError::Forbidden);
+        }
+
+        Ok(Self(claims))
+    }
+}
+
+impl<S> FromRequestParts<S> for OAuthAccessClaims
+where
+    S: Send + Sync,
+    TokenService: FromRef<S>,
+{
+    type Rejection = AuthenticationServiceError;
 
-        let decoding_key = Keys::new(secret.as_bytes()).decoding;
-        // Extract the token from the authorization header
-        let TypedHeader(Authorization(bearer)) = parts
-            .extract::<TypedHeader<Authorization<Bearer>>>()
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        extract_claims(parts, &TokenService::from_ref(state), TokenUse::Access)
             .await
-            .map_err(|_| AuthenticationServiceError::MissingCredentials)?;
-        // Decode the user data
-        let token_data = decode::<Claims>(bearer.token(), &decoding_key, &Validation::default())
-            .map_err(|_| AuthenticationServiceError::InvalidToken)?;
+            .map(Self)
+    }
+}
+
+impl<S> FromRequestParts<S> for VerificationClaims
+where
+    S: Send + Sync,
+    TokenService: FromRef<S>,
+{
+    type Rejection = AuthenticationServiceError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        extract_claims(
+            parts,
+            &TokenService::from_ref(state),
+            TokenUse::Verification,
+        )
+        .await
+        .map(Self)
+    }
+}
+
+impl<S, P> FromRequestParts<S> for PermittedClaims<P>
+where
+    S: Send + Sync,
+    P: RequiredPermission,
+    TokenService: FromRef<S>,
+    UserService: FromRef<S>,
+{
+    type Rejection = AuthenticationServiceError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let AccessClaims(claims) = AccessClaims::from_request_parts(parts, state).await?;
+        let permitted = UserService::from_ref(state)
+            .has_permission(&claims.sub, P::PERMISSION)
+            .await?;
+        if !permitted {
+            return Err(AuthenticationServiceError::Forbidden);
+        }
+
+        Ok(Self(claims, PhantomData))
+    }
+}
+
+impl Deref for AccessClaims {
+    type Target = Claims;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Deref for OAuthAccessClaims {
+    type Target = Claims;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Deref for VerificationClaims {
+    type Target = Claims;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<P> Deref for PermittedClaims<P> {
+    type Target = Claims;
 
-        Ok(token_data.claims)
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
 }
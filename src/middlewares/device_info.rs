@@ -0,0 +1,38 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{header, request::Parts},
+};
+
+use crate::{
+    adapters::dto::session::DeviceInfo, config::network::NetworkConfig,
+    middlewares::client_ip::ClientIp,
+};
+
+/// user agents are free text from the client, anything longer is cut off
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+impl<S> FromRequestParts<S> for DeviceInfo
+where
+    S: Send + Sync,
+    NetworkConfig: FromRef<S>,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ClientIp(ip_address) = ClientIp::from_parts(parts, &NetworkConfig::from_ref(state));
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.chars().take(MAX_USER_AGENT_LENGTH).collect());
+
+        Ok(Self {
+            ip_address,
+            user_agent,
+        })
+    }
+}
@@ -1,2 +1,5 @@
 pub mod auth;
+pub mod client_ip;
+pub mod device_info;
+pub mod rate_limit;
 pub mod validator;
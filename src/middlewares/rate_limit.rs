@@ -0,0 +1,287 @@
+use std::{
+    convert::Infallible,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::Request,
+    http::{HeaderMap, HeaderValue, StatusCode, header, request::Parts},
+    response::{IntoResponse, Response},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use tower::{Layer, Service};
+
+use crate::{
+    adapters::{
+        rate_limit::{RateLimitBackend, RateLimitHit, RateLimitStore},
+        response::api_response::ApiResponseBuilder,
+    },
+    config::network::NetworkConfig,
+    middlewares::client_ip::ClientIp,
+};
+
+/// the routes that are limited by a body field only take small bodies
+const MAX_KEYED_BODY_BYTES: usize = 64 * 1024;
+
+/// what a policy counts requests against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitKey {
+    /// the client address, see [`ClientIp`]
+    Ip,
+    /// the `email` field of a json or form body
+    Email,
+    /// the `clientId` or `client_id` field of the body, or the user of basic authentication
+    ClientId,
+}
+
+/// at most `limit` requests per `window` for each value of `key`
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitPolicy {
+    /// distinguishes the policy's counters from those of every other policy
+    pub name: &'static str,
+    pub key: RateLimitKey,
+    pub limit: u32,
+    pub window: Duration,
+}
+
+impl RateLimitPolicy {
+    pub const fn new(name: &'static str, key: RateLimitKey, limit: u32, window: Duration) -> Self {
+        Self {
+            name,
+            key,
+            limit,
+            window,
+        }
+    }
+}
+
+/// throttles the routes it wraps, every policy is applied and the request is refused when any of
+/// them is exhausted. A policy whose key is absent from the request does not apply to it
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    store: RateLimitBackend,
+    network_config: NetworkConfig,
+    policies: Arc<[RateLimitPolicy]>,
+}
+
+impl RateLimitLayer {
+    pub fn new(
+        store: RateLimitBackend,
+        network_config: NetworkConfig,
+        policies: &[RateLimitPolicy],
+    ) -> Self {
+        Self {
+            store,
+            network_config,
+            policies: policies.into(),
+        }
+    }
+
+    fn needs_body(&self) -> bool {
+        self.policies
+            .iter()
+            .any(|policy| policy.key != RateLimitKey::Ip)
+    }
+
+    /// counts the request against every policy and returns the hit closest to its limit
+    async fn check(
+        &self,
+        parts: &Parts,
+        body: Option<&Bytes>,
+    ) -> Option<(RateLimitPolicy, RateLimitHit)> {
+        let mut tightest: Option<(RateLimitPolicy, RateLimitHit)> = None;
+
+        for policy in self.policies.iter() {
+            let Some(value) = self.key_value(policy.key, parts, body) else {
+                continue;
+            };
+            let hit = match self
+                .store
+                .hit(&format!("{}:{value}", policy.name), policy.window)
+                .await
+            {
+                Ok(hit) => hit,
+                Err(err) => {
+                    // an unavailable store should not take sign-in down with it
+                    log::error!(
+                        "error counting request for rate limit {}: {err}",
+                        policy.name
+                    );
+                    continue;
+                }
+            };
+
+            let remaining = |(policy, hit): &(RateLimitPolicy, RateLimitHit)| {
+                i64::from(policy.limit) - i64::from(hit.hits)
+            };
+            if tightest
+                .as_ref()
+                .is_none_or(|current| remaining(&(*policy, hit)) < remaining(current))
+            {
+                tightest = Some((*policy, hit));
+            }
+        }
+
+        tightest
+    }
+
+    fn key_value(&self, key: RateLimitKey, parts: &Parts, body: Option<&Bytes>) -> Option<String> {
+        match key {
+            RateLimitKey::Ip => ClientIp::from_parts(parts, &self.network_config)
+                .0
+                .map(|ip_address| ip_address.to_string()),
+            RateLimitKey::Email => body_field(&parts.headers, body?, &["email"])
+                .map(|email| email.trim().to_lowercase()),
+            RateLimitKey::ClientId => body
+                .and_then(|body| body_field(&parts.headers, body, &["clientId", "client_id"]))
+                .or_else(|| basic_auth_user(&parts.headers)),
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimit<S> {
+    inner: S,
+    layer: RateLimitLayer,
+}
+
+impl<S> Service<Request> for RateLimit<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        // the clone that was polled ready handles this request
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let (body, buffered) = if layer.needs_body() {
+                match axum::body::to_bytes(body, MAX_KEYED_BODY_BYTES).await {
+                    Ok(bytes) => (Body::from(bytes.clone()), Some(bytes)),
+                    Err(_) => {
+                        return Ok(ApiResponseBuilder::<()>::new()
+                            .status_code(StatusCode::PAYLOAD_TOO_LARGE)
+                            .message("the request body is too large")
+                            .build()
+                            .into_response());
+                    }
+                }
+            } else {
+                (body, None)
+            };
+
+            let tightest = layer.check(&parts, buffered.as_ref()).await;
+            if let Some((policy, hit)) = tightest
+                && hit.hits > policy.limit
+            {
+                return Ok(too_many_requests(&policy, &hit));
+            }
+
+            let mut response = inner.call(Request::from_parts(parts, body)).await?;
+            if let Some((policy, hit)) = tightest {
+                insert_rate_limit_headers(response.headers_mut(), &policy, &hit);
+            }
+
+            Ok(response)
+        })
+    }
+}
+
+fn too_many_requests(policy: &RateLimitPolicy, hit: &RateLimitHit) -> Response {
+    let retry_after = reset_seconds(hit);
+    let mut response = ApiResponseBuilder::<()>::new()
+        .status_code(StatusCode::TOO_MANY_REQUESTS)
+        .message(&format!(
+            "too many requests, try again in {retry_after} seconds"
+        ))
+        .build()
+        .into_response();
+
+    let headers = response.headers_mut();
+    headers.insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    insert_rate_limit_headers(headers, policy, hit);
+
+    response
+}
+
+/// the `RateLimit-*` fields of the IETF draft, for the policy closest to its limit
+fn insert_rate_limit_headers(
+    headers: &mut HeaderMap,
+    policy: &RateLimitPolicy,
+    hit: &RateLimitHit,
+) {
+    headers.insert("ratelimit-limit", HeaderValue::from(policy.limit));
+    headers.insert(
+        "ratelimit-remaining",
+        HeaderValue::from(policy.limit.saturating_sub(hit.hits)),
+    );
+    headers.insert("ratelimit-reset", HeaderValue::from(reset_seconds(hit)));
+    if let Ok(value) =
+        HeaderValue::from_str(&format!("{};w={}", policy.limit, policy.window.as_secs()))
+    {
+        headers.insert("ratelimit-policy", value);
+    }
+}
+
+fn reset_seconds(hit: &RateLimitHit) -> u64 {
+    hit.reset_after.as_millis().div_ceil(1000).max(1) as u64
+}
+
+/// a top-level string field of a json or url-encoded form body
+fn body_field(headers: &HeaderMap, body: &Bytes, names: &[&str]) -> Option<String> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    let value = if content_type.starts_with("application/x-www-form-urlencoded") {
+        url::form_urlencoded::parse(body)
+            .find(|(name, _)| names.contains(&name.as_ref()))
+            .map(|(_, value)| value.into_owned())
+    } else {
+        let json = serde_json::from_slice::<serde_json::Value>(body).ok()?;
+        names
+            .iter()
+            .find_map(|name| json.get(name)?.as_str().map(str::to_string))
+    };
+
+    value.filter(|value| !value.trim().is_empty())
+}
+
+fn basic_auth_user(headers: &HeaderMap) -> Option<String> {
+    let credentials = headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(STANDARD.decode(credentials.trim()).ok()?).ok()?;
+    let (user, _) = decoded.split_once(':')?;
+
+    Some(user.to_string())
+}
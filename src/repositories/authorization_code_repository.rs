@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    entities::authorization_code::AuthorizationCodeEntity,
+    errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct AuthorizationCodeRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl AuthorizationCodeRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait AuthorizationCodeRepositoryTrait {
+    fn create(
+        &self,
+        authorization_code: &AuthorizationCodeEntity,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// marks the code as used and returns it, None when it does not exist or was already used
+    fn consume(
+        &self,
+        code_hash: &str,
+    ) -> impl std::future::Future<Output = Result<Option<AuthorizationCodeEntity>, ServiceError>> + Send;
+}
+
+impl AuthorizationCodeRepositoryTrait for AuthorizationCodeRepository {
+    async fn create(
+        &self,
+        authorization_code: &AuthorizationCodeEntity,
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"INSERT INTO authorization_codes (identifier, code_hash, client_id, user_identifier, redirect_uri, scope, nonce, code_challenge, auth_time, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"#,
+        )
+        .bind(authorization_code.identifier)
+        .bind(&authorization_code.code_hash)
+        .bind(&authorization_code.client_id)
+        .bind(authorization_code.user_identifier)
+        .bind(&authorization_code.redirect_uri)
+        .bind(&authorization_code.scope)
+        .bind(&authorization_code.nonce)
+        .bind(&authorization_code.code_challenge)
+        .bind(authorization_code.auth_time)
+        .bind(authorization_code.expires_at)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn consume(
+        &self,
+        code_hash: &str,
+    ) -> Result<Option<AuthorizationCodeEntity>, ServiceError> {
+        let authorization_code = sqlx::query_as::<_, AuthorizationCodeEntity>(
+            "UPDATE authorization_codes SET used_at = NOW() WHERE code_hash = $1 AND used_at IS NULL RETURNING *",
+        )
+        .bind(code_hash)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(authorization_code)
+    }
+}
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{entities::device_code::DeviceCodeEntity, errors::common_service_error::ServiceError};
+
+#[derive(Clone)]
+pub struct DeviceCodeRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl DeviceCodeRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait DeviceCodeRepositoryTrait {
+    /// false when the user code is already taken by another request
+    fn create(
+        &self,
+        device_code: &DeviceCodeEntity,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    fn find_by_device_code_hash(
+        &self,
+        device_code_hash: &str,
+    ) -> impl std::future::Future<Output = Result<Option<DeviceCodeEntity>, ServiceError>> + Send;
+
+    /// a request the user can still approve or deny, None once it was decided or has expired
+    fn find_pending_by_user_code(
+        &self,
+        user_code: &str,
+    ) -> impl std::future::Future<Output = Result<Option<DeviceCodeEntity>, ServiceError>> + Send;
+
+    /// false when the request was decided in the meantime or has expired
+    fn approve(
+        &self,
+        identifier: &Uuid,
+        user_identifier: &Uuid,
+        auth_time: DateTime<Utc>,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    fn deny(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    fn record_poll(
+        &self,
+        identifier: &Uuid,
+        polled_at: DateTime<Utc>,
+        interval_seconds: i32,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// marks an approved request as used and returns it, None when it is not approved or was
+    /// already used
+    fn consume(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<Option<DeviceCodeEntity>, ServiceError>> + Send;
+
+    fn delete_expired(&self)
+    -> impl std::future::Future<Output = Result<u64, ServiceError>> + Send;
+}
+
+impl DeviceCodeRepositoryTrait for DeviceCodeRepository {
+    async fn create(&self, device_code: &DeviceCodeEntity) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            r#"INSERT INTO device_codes (identifier, device_code_hash, user_code, client_id, scope, interval_seconds, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7) ON CONFLICT (user_code) DO NOTHING"#,
+        )
+        .bind(device_code.identifier)
+        .bind(&device_code.device_code_hash)
+        .bind(&device_code.user_code)
+        .bind(&device_code.client_id)
+        .bind(&device_code.scope)
+        .bind(device_code.interval_seconds)
+        .bind(device_code.expires_at)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn find_by_device_code_hash(
+        &self,
+        device_code_hash: &str,
+    ) -> Result<Option<DeviceCodeEntity>, ServiceError> {
+        let device_code = sqlx::query_as::<_, DeviceCodeEntity>(
+            "SELECT * FROM device_codes WHERE device_code_hash = $1",
+        )
+        .bind(device_code_hash)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(device_code)
+    }
+
+    async fn find_pending_by_user_code(
+        &self,
+        user_code: &str,
+    ) -> Result<Option<DeviceCodeEntity>, ServiceError> {
+        let device_code = sqlx::query_as::<_, DeviceCodeEntity>(
+            "SELECT * FROM device_codes WHERE user_code = $1 AND approved_at IS NULL AND denied_at IS NULL AND expires_at > NOW()",
+        )
+        .bind(user_code)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(device_code)
+    }
+
+    async fn approve(
+        &self,
+        identifier: &Uuid,
+        user_identifier: &Uuid,
+        auth_time: DateTime<Utc>,
+    ) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            "UPDATE device_codes SET approved_at = NOW(), user_identifier = $2, auth_time = $3 WHERE identifier = $1 AND approved_at IS NULL AND denied_at IS NULL AND expires_at > NOW()",
+        )
+        .bind(identifier)
+        .bind(user_identifier)
+        .bind(auth_time)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn deny(&self, identifier: &Uuid) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            "UPDATE device_codes SET denied_at = NOW() WHERE identifier = $1 AND approved_at IS NULL AND denied_at IS NULL AND expires_at > NOW()",
+        )
+        .bind(identifier)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn record_poll(
+        &self,
+        identifier: &Uuid,
+        polled_at: DateTime<Utc>,
+        interval_seconds: i32,
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            "UPDATE device_codes SET last_polled_at = $2, interval_seconds = $3 WHERE identifier = $1",
+        )
+        .bind(identifier)
+        .bind(polled_at)
+        .bind(interval_seconds)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn consume(&self, identifier: &Uuid) -> Result<Option<DeviceCodeEntity>, ServiceError> {
+        let device_code = sqlx::query_as::<_, DeviceCodeEntity>(
+            "UPDATE device_codes SET consumed_at = NOW() WHERE identifier = $1 AND approved_at IS NOT NULL AND consumed_at IS NULL RETURNING *",
+        )
+        .bind(identifier)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(device_code)
+    }
+
+    async fn delete_expired(&self) -> Result<u64, ServiceError> {
+        let result = sqlx::query("DELETE FROM device_codes WHERE expires_at < NOW()")
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
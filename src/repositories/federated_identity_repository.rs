@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::errors::common_service_error::ServiceError;
+
+#[derive(Clone)]
+pub struct FederatedIdentityRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl FederatedIdentityRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait FederatedIdentityRepositoryTrait {
+    /// the user the provider account is linked to, the sign-in is recorded against the link
+    fn record_login(
+        &self,
+        provider_identifier: &Uuid,
+        subject: &str,
+    ) -> impl std::future::Future<Output = Result<Option<Uuid>, ServiceError>> + Send;
+
+    fn link(
+        &self,
+        provider_identifier: &Uuid,
+        subject: &str,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+}
+
+impl FederatedIdentityRepositoryTrait for FederatedIdentityRepository {
+    async fn record_login(
+        &self,
+        provider_identifier: &Uuid,
+        subject: &str,
+    ) -> Result<Option<Uuid>, ServiceError> {
+        let user_identifier = sqlx::query_scalar::<_, Uuid>(
+            "UPDATE federated_identities SET last_login_at = NOW() WHERE provider_identifier = $1 AND subject = $2 RETURNING user_identifier",
+        )
+        .bind(provider_identifier)
+        .bind(subject)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(user_identifier)
+    }
+
+    async fn link(
+        &self,
+        provider_identifier: &Uuid,
+        subject: &str,
+        user_identifier: &Uuid,
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            "INSERT INTO federated_identities (provider_identifier, subject, user_identifier) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+        )
+        .bind(provider_identifier)
+        .bind(subject)
+        .bind(user_identifier)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+}
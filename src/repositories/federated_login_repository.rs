@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entities::federated_login::FederatedLoginEntity, errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct FederatedLoginRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl FederatedLoginRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait FederatedLoginRepositoryTrait {
+    /// also clears out sign-ins the provider never redirected back from
+    fn create(
+        &self,
+        state: &str,
+        provider_identifier: &Uuid,
+        nonce: &str,
+        code_verifier: &str,
+        client_id: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// removes the sign-in and returns it, provided it was started with the provider and has not
+    /// expired
+    fn consume(
+        &self,
+        state: &str,
+        provider_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<Option<FederatedLoginEntity>, ServiceError>> + Send;
+}
+
+impl FederatedLoginRepositoryTrait for FederatedLoginRepository {
+    async fn create(
+        &self,
+        state: &str,
+        provider_identifier: &Uuid,
+        nonce: &str,
+        code_verifier: &str,
+        client_id: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), ServiceError> {
+        sqlx::query("DELETE FROM federated_logins WHERE expires_at < NOW()")
+            .execute(self.pool.as_ref())
+            .await?;
+        sqlx::query(
+            "INSERT INTO federated_logins (state, provider_identifier, nonce, code_verifier, client_id, expires_at) VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(state)
+        .bind(provider_identifier)
+        .bind(nonce)
+        .bind(code_verifier)
+        .bind(client_id)
+        .bind(expires_at)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn consume(
+        &self,
+        state: &str,
+        provider_identifier: &Uuid,
+    ) -> Result<Option<FederatedLoginEntity>, ServiceError> {
+        let login = sqlx::query_as::<_, FederatedLoginEntity>(
+            "DELETE FROM federated_logins WHERE state = $1 AND provider_identifier = $2 AND expires_at > NOW() RETURNING *",
+        )
+        .bind(state)
+        .bind(provider_identifier)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(login)
+    }
+}
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    entities::identity_provider::IdentityProviderEntity, errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct IdentityProviderRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl IdentityProviderRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait IdentityProviderRepositoryTrait {
+    fn find_by_slug(
+        &self,
+        slug: &str,
+    ) -> impl std::future::Future<Output = Result<Option<IdentityProviderEntity>, ServiceError>> + Send;
+
+    fn find_all(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<IdentityProviderEntity>, ServiceError>> + Send;
+
+    fn create(
+        &self,
+        provider: &IdentityProviderEntity,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// updates everything but the identifier and slug, the sealed secret included
+    fn update(
+        &self,
+        provider: &IdentityProviderEntity,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    fn delete(
+        &self,
+        slug: &str,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+}
+
+impl IdentityProviderRepositoryTrait for IdentityProviderRepository {
+    async fn find_by_slug(
+        &self,
+        slug: &str,
+    ) -> Result<Option<IdentityProviderEntity>, ServiceError> {
+        let provider = sqlx::query_as::<_, IdentityProviderEntity>(
+            "SELECT * FROM identity_providers WHERE slug = $1",
+        )
+        .bind(slug)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(provider)
+    }
+
+    async fn find_all(&self) -> Result<Vec<IdentityProviderEntity>, ServiceError> {
+        let providers = sqlx::query_as::<_, IdentityProviderEntity>(
+            "SELECT * FROM identity_providers ORDER BY created_at",
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        Ok(providers)
+    }
+
+    async fn create(&self, provider: &IdentityProviderEntity) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"INSERT INTO identity_providers (identifier, slug, name, issuer, client_id, client_secret, scopes, email_claim, first_name_claim, last_name_claim, trust_email, enabled)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)"#,
+        )
+        .bind(provider.identifier)
+        .bind(&provider.slug)
+        .bind(&provider.name)
+        .bind(&provider.issuer)
+        .bind(&provider.client_id)
+        .bind(&provider.client_secret)
+        .bind(&provider.scopes)
+        .bind(&provider.email_claim)
+        .bind(&provider.first_name_claim)
+        .bind(&provider.last_name_claim)
+        .bind(provider.trust_email)
+        .bind(provider.enabled)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update(&self, provider: &IdentityProviderEntity) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"UPDATE identity_providers SET name = $2, issuer = $3, client_id = $4, client_secret = $5, scopes = $6, email_claim = $7,
+            first_name_claim = $8, last_name_claim = $9, trust_email = $10, enabled = $11, updated_at = NOW() WHERE identifier = $1"#,
+        )
+        .bind(provider.identifier)
+        .bind(&provider.name)
+        .bind(&provider.issuer)
+        .bind(&provider.client_id)
+        .bind(&provider.client_secret)
+        .bind(&provider.scopes)
+        .bind(&provider.email_claim)
+        .bind(&provider.first_name_claim)
+        .bind(&provider.last_name_claim)
+        .bind(provider.trust_email)
+        .bind(provider.enabled)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, slug: &str) -> Result<bool, ServiceError> {
+        let result = sqlx::query("DELETE FROM identity_providers WHERE slug = $1")
+            .bind(slug)
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    entities::login_failure::LoginFailureEntity, errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct LoginFailureRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl LoginFailureRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait LoginFailureRepositoryTrait {
+    fn find(
+        &self,
+        scope: &str,
+        subject: &str,
+    ) -> impl std::future::Future<Output = Result<Option<LoginFailureEntity>, ServiceError>> + Send;
+
+    /// counts a failure and returns the new total, failures from before `window_start` are
+    /// forgotten
+    fn record_failure(
+        &self,
+        scope: &str,
+        subject: &str,
+        window_start: DateTime<Utc>,
+    ) -> impl std::future::Future<Output = Result<i32, ServiceError>> + Send;
+
+    fn block_until(
+        &self,
+        scope: &str,
+        subject: &str,
+        blocked_until: DateTime<Utc>,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// returns false when nothing was being counted
+    fn clear(
+        &self,
+        scope: &str,
+        subject: &str,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+}
+
+impl LoginFailureRepositoryTrait for LoginFailureRepository {
+    async fn find(
+        &self,
+        scope: &str,
+        subject: &str,
+    ) -> Result<Option<LoginFailureEntity>, ServiceError> {
+        let failure = sqlx::query_as::<_, LoginFailureEntity>(
+            "SELECT * FROM login_failures WHERE scope = $1 AND subject = $2",
+        )
+        .bind(scope)
+        .bind(subject)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(failure)
+    }
+
+    async fn record_failure(
+        &self,
+        scope: &str,
+        subject: &str,
+        window_start: DateTime<Utc>,
+    ) -> Result<i32, ServiceError> {
+        let failures = sqlx::query_scalar::<_, i32>(
+            r#"INSERT INTO login_failures (scope, subject, failures, last_failure_at)
+            VALUES ($1, $2, 1, NOW())
+            ON CONFLICT (scope, subject) DO UPDATE
+            SET failures = CASE
+                    WHEN login_failures.last_failure_at < $3 THEN 1
+                    ELSE login_failures.failures + 1
+                END,
+                last_failure_at = NOW()
+            RETURNING failures"#,
+        )
+        .bind(scope)
+        .bind(subject)
+        .bind(window_start)
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        Ok(failures)
+    }
+
+    async fn block_until(
+        &self,
+        scope: &str,
+        subject: &str,
+        blocked_until: DateTime<Utc>,
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            "UPDATE login_failures SET blocked_until = $3 WHERE scope = $1 AND subject = $2",
+        )
+        .bind(scope)
+        .bind(subject)
+        .bind(blocked_until)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn clear(&self, scope: &str, subject: &str) -> Result<bool, ServiceError> {
+        let result = sqlx::query("DELETE FROM login_failures WHERE scope = $1 AND subject = $2")
+            .bind(scope)
+            .bind(subject)
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
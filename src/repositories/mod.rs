@@ -1 +1,19 @@
+pub mod authorization_code_repository;
+pub mod device_code_repository;
+pub mod federated_identity_repository;
+pub mod federated_login_repository;
+pub mod identity_provider_repository;
+pub mod login_failure_repository;
+pub mod oauth_client_repository;
+pub mod otp_repository;
+pub mod rate_limit_repository;
+pub mod recovery_code_repository;
+pub mod refresh_token_repository;
+pub mod revoked_token_repository;
+pub mod saml_service_provider_repository;
+pub mod session_repository;
+pub mod token_exchange_audit_repository;
+pub mod totp_authenticator_repository;
 pub mod user_repository;
+pub mod webauthn_challenge_repository;
+pub mod webauthn_credential_repository;
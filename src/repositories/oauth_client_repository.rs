@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    entities::oauth_client::OAuthClientEntity, errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct OAuthClientRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl OAuthClientRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait OAuthClientRepositoryTrait {
+    fn find_by_client_id(
+        &self,
+        client_id: &str,
+    ) -> impl std::future::Future<Output = Result<Option<OAuthClientEntity>, ServiceError>> + Send;
+
+    fn find_all(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<OAuthClientEntity>, ServiceError>> + Send;
+
+    fn create(
+        &self,
+        client: &OAuthClientEntity,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// updates everything but the client id and secret
+    fn update(
+        &self,
+        client: &OAuthClientEntity,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    fn update_secret(
+        &self,
+        client_id: &str,
+        client_secret_hash: Option<&str>,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    fn delete(
+        &self,
+        client_id: &str,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    /// registers or refreshes a public first-party client
+    fn upsert_first_party(
+        &self,
+        client_id: &str,
+        name: &str,
+        redirect_uris: &[String],
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+}
+
+impl OAuthClientRepositoryTrait for OAuthClientRepository {
+    async fn find_by_client_id(
+        &self,
+        client_id: &str,
+    ) -> Result<Option<OAuthClientEntity>, ServiceError> {
+        let client = sqlx::query_as::<_, OAuthClientEntity>(
+            "SELECT * FROM oauth_clients WHERE client_id = $1",
+        )
+        .bind(client_id)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(client)
+    }
+
+    async fn find_all(&self) -> Result<Vec<OAuthClientEntity>, ServiceError> {
+        let clients = sqlx::query_as::<_, OAuthClientEntity>(
+            "SELECT * FROM oauth_clients ORDER BY created_at",
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        Ok(clients)
+    }
+
+    async fn create(&self, client: &OAuthClientEntity) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"INSERT INTO oauth_clients (client_id, name, redirect_uris, client_secret_hash, grant_types, scopes, access_token_ttl_seconds, refresh_token_ttl_seconds, first_party)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#,
+        )
+        .bind(&client.client_id)
+        .bind(&client.name)
+        .bind(&client.redirect_uris)
+        .bind(&client.client_secret_hash)
+        .bind(&client.grant_types)
+        .bind(&client.scopes)
+        .bind(client.access_token_ttl_seconds)
+        .bind(client.refresh_token_ttl_seconds)
+        .bind(client.first_party)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update(&self, client: &OAuthClientEntity) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"UPDATE oauth_clients SET name = $2, redirect_uris = $3, grant_types = $4, scopes = $5, access_token_ttl_seconds = $6,
+            refresh_token_ttl_seconds = $7, first_party = $8, updated_at = NOW() WHERE client_id = $1"#,
+        )
+        .bind(&client.client_id)
+        .bind(&client.name)
+        .bind(&client.redirect_uris)
+        .bind(&client.grant_types)
+        .bind(&client.scopes)
+        .bind(client.access_token_ttl_seconds)
+        .bind(client.refresh_token_ttl_seconds)
+        .bind(client.first_party)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update_secret(
+        &self,
+        client_id: &str,
+        client_secret_hash: Option<&str>,
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            "UPDATE oauth_clients SET client_secret_hash = $2, updated_at = NOW() WHERE client_id = $1",
+        )
+        .bind(client_id)
+        .bind(client_secret_hash)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, client_id: &str) -> Result<bool, ServiceError> {
+        let result = sqlx::query("DELETE FROM oauth_clients WHERE client_id = $1")
+            .bind(client_id)
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn upsert_first_party(
+        &self,
+        client_id: &str,
+        name: &str,
+        redirect_uris: &[String],
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"INSERT INTO oauth_clients (client_id, name, redirect_uris, first_party) VALUES ($1, $2, $3, TRUE)
+            ON CONFLICT (client_id) DO UPDATE SET name = EXCLUDED.name, redirect_uris = EXCLUDED.redirect_uris, first_party = TRUE, updated_at = NOW()"#,
+        )
+        .bind(client_id)
+        .bind(name)
+        .bind(redirect_uris)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    adapters::dto::otp::OtpKind, entities::otp::OtpEntity,
+    errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct OtpRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl OtpRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait OtpRepositoryTrait {
+    fn upsert(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+        code_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    fn find_by_user(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+    ) -> impl std::future::Future<Output = Result<Option<OtpEntity>, ServiceError>> + Send;
+
+    fn find_by_code_hash(
+        &self,
+        kind: OtpKind,
+        code_hash: &str,
+    ) -> impl std::future::Future<Output = Result<Option<OtpEntity>, ServiceError>> + Send;
+
+    fn delete_by_user(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// counts an attempt against the user's live code, None when there is nothing left to try
+    /// because the code is used, expired or out of attempts
+    fn record_attempt(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+        max_attempts: i32,
+    ) -> impl std::future::Future<Output = Result<Option<OtpEntity>, ServiceError>> + Send;
+
+    fn mark_as_used(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+}
+
+impl OtpRepositoryTrait for OtpRepository {
+    async fn upsert(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+        code_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"INSERT INTO otps (identifier, user_identifier, kind, code_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (user_identifier, kind) DO UPDATE
+            SET identifier = EXCLUDED.identifier,
+                code_hash = EXCLUDED.code_hash,
+                expires_at = EXCLUDED.expires_at,
+                attempts = 0,
+                used_at = NULL,
+                created_at = NOW()"#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_identifier)
+        .bind(kind.to_string())
+        .bind(code_hash)
+        .bind(expires_at)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_by_user(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+    ) -> Result<Option<OtpEntity>, ServiceError> {
+        let otp = sqlx::query_as::<_, OtpEntity>(
+            "SELECT * FROM otps WHERE user_identifier = $1 AND kind = $2",
+        )
+        .bind(user_identifier)
+        .bind(kind.to_string())
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(otp)
+    }
+
+    async fn find_by_code_hash(
+        &self,
+        kind: OtpKind,
+        code_hash: &str,
+    ) -> Result<Option<OtpEntity>, ServiceError> {
+        let otp =
+            sqlx::query_as::<_, OtpEntity>("SELECT * FROM otps WHERE kind = $1 AND code_hash = $2")
+                .bind(kind.to_string())
+                .bind(code_hash)
+                .fetch_optional(self.pool.as_ref())
+                .await?;
+
+        Ok(otp)
+    }
+
+    async fn delete_by_user(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+    ) -> Result<(), ServiceError> {
+        sqlx::query("DELETE FROM otps WHERE user_identifier = $1 AND kind = $2")
+            .bind(user_identifier)
+            .bind(kind.to_string())
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(())
+    }
+
+    /// a single statement, so that concurrent guesses cannot share an attempt
+    async fn record_attempt(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+        max_attempts: i32,
+    ) -> Result<Option<OtpEntity>, ServiceError> {
+        let otp = sqlx::query_as::<_, OtpEntity>(
+            r#"UPDATE otps SET attempts = attempts + 1
+            WHERE user_identifier = $1 AND kind = $2
+                AND used_at IS NULL AND expires_at > NOW() AND attempts < $3
+            RETURNING *"#,
+        )
+        .bind(user_identifier)
+        .bind(kind.to_string())
+        .bind(max_attempts)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(otp)
+    }
+
+    /// returns false when the code had already been consumed by a concurrent request
+    async fn mark_as_used(&self, identifier: &Uuid) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            "UPDATE otps SET used_at = NOW() WHERE identifier = $1 AND used_at IS NULL",
+        )
+        .bind(identifier)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
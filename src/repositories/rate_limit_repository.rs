@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+
+use crate::errors::common_service_error::ServiceError;
+
+#[derive(Clone)]
+pub struct RateLimitRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl RateLimitRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait RateLimitRepositoryTrait {
+    /// counts a request against the key, starting a new window when the last one has ended, and
+    /// returns the hits so far with the end of the window
+    fn increment(
+        &self,
+        key: &str,
+        window_seconds: f64,
+    ) -> impl std::future::Future<Output = Result<(i32, DateTime<Utc>), ServiceError>> + Send;
+
+    fn delete_expired(&self)
+    -> impl std::future::Future<Output = Result<u64, ServiceError>> + Send;
+}
+
+impl RateLimitRepositoryTrait for RateLimitRepository {
+    async fn increment(
+        &self,
+        key: &str,
+        window_seconds: f64,
+    ) -> Result<(i32, DateTime<Utc>), ServiceError> {
+        let counter = sqlx::query_as::<_, (i32, DateTime<Utc>)>(
+            r#"INSERT INTO rate_limit_counters (key, hits, window_ends_at)
+            VALUES ($1, 1, NOW() + make_interval(secs => $2))
+            ON CONFLICT (key) DO UPDATE
+            SET hits = CASE
+                    WHEN rate_limit_counters.window_ends_at <= NOW() THEN 1
+                    ELSE rate_limit_counters.hits + 1
+                END,
+                window_ends_at = CASE
+                    WHEN rate_limit_counters.window_ends_at <= NOW() THEN EXCLUDED.window_ends_at
+                    ELSE rate_limit_counters.window_ends_at
+                END
+            RETURNING hits, window_ends_at"#,
+        )
+        .bind(key)
+        .bind(window_seconds)
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        Ok(counter)
+    }
+
+    async fn delete_expired(&self) -> Result<u64, ServiceError> {
+        let result = sqlx::query("DELETE FROM rate_limit_counters WHERE window_ends_at <= NOW()")
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::errors::common_service_error::ServiceError;
+
+#[derive(Clone)]
+pub struct RecoveryCodeRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl RecoveryCodeRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait RecoveryCodeRepositoryTrait {
+    /// the user's previous codes, used or not, stop working
+    fn replace(
+        &self,
+        user_identifier: &Uuid,
+        code_hashes: &[String],
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// false when there is no such unused code
+    fn mark_as_used(
+        &self,
+        user_identifier: &Uuid,
+        code_hash: &str,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    fn count_unused(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<i64, ServiceError>> + Send;
+
+    fn delete_by_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+}
+
+impl RecoveryCodeRepositoryTrait for RecoveryCodeRepository {
+    async fn replace(
+        &self,
+        user_identifier: &Uuid,
+        code_hashes: &[String],
+    ) -> Result<(), ServiceError> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM recovery_codes WHERE user_identifier = $1")
+            .bind(user_identifier)
+            .execute(&mut *transaction)
+            .await?;
+        for code_hash in code_hashes {
+            sqlx::query(
+                "INSERT INTO recovery_codes (identifier, user_identifier, code_hash) VALUES ($1, $2, $3)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(user_identifier)
+            .bind(code_hash)
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn mark_as_used(
+        &self,
+        user_identifier: &Uuid,
+        code_hash: &str,
+    ) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            "UPDATE recovery_codes SET used_at = NOW() WHERE user_identifier = $1 AND code_hash = $2 AND used_at IS NULL",
+        )
+        .bind(user_identifier)
+        .bind(code_hash)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn count_unused(&self, user_identifier: &Uuid) -> Result<i64, ServiceError> {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM recovery_codes WHERE user_identifier = $1 AND used_at IS NULL",
+        )
+        .bind(user_identifier)
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        Ok(count)
+    }
+
+    async fn delete_by_user(&self, user_identifier: &Uuid) -> Result<(), ServiceError> {
+        sqlx::query("DELETE FROM recovery_codes WHERE user_identifier = $1")
+            .bind(user_identifier)
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    adapters::dto::oauth::OAuthGrant, entities::refresh_token::RefreshTokenEntity,
+    errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct RefreshTokenRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl RefreshTokenRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait RefreshTokenRepositoryTrait {
+    fn create(
+        &self,
+        user_identifier: &Uuid,
+        family_identifier: &Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+        grant: Option<&OAuthGrant>,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    fn find_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> impl std::future::Future<Output = Result<Option<RefreshTokenEntity>, ServiceError>> + Send;
+
+    fn mark_as_rotated(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    fn revoke_family(
+        &self,
+        family_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    fn revoke_all_for_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+}
+
+impl RefreshTokenRepositoryTrait for RefreshTokenRepository {
+    async fn create(
+        &self,
+        user_identifier: &Uuid,
+        family_identifier: &Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+        grant: Option<&OAuthGrant>,
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"INSERT INTO refresh_tokens (identifier, user_identifier, family_identifier, token_hash, expires_at, client_id, scope)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(user_identifier)
+        .bind(family_identifier)
+        .bind(token_hash)
+        .bind(expires_at)
+        .bind(grant.map(|grant| &grant.client_id))
+        .bind(grant.map(|grant| &grant.scope))
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_by_hash(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshTokenEntity>, ServiceError> {
+        let refresh_token = sqlx::query_as::<_, RefreshTokenEntity>(
+            "SELECT * FROM refresh_tokens WHERE token_hash = $1",
+        )
+        .bind(token_hash)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(refresh_token)
+    }
+
+    /// returns false when another request rotated or revoked the token first
+    async fn mark_as_rotated(&self, identifier: &Uuid) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            "UPDATE refresh_tokens SET rotated_at = NOW() WHERE identifier = $1 AND rotated_at IS NULL AND revoked_at IS NULL",
+        )
+        .bind(identifier)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn revoke_family(&self, family_identifier: &Uuid) -> Result<(), ServiceError> {
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE family_identifier = $1 AND revoked_at IS NULL",
+        )
+        .bind(family_identifier)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_all_for_user(&self, user_identifier: &Uuid) -> Result<(), ServiceError> {
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = NOW() WHERE user_identifier = $1 AND revoked_at IS NULL",
+        )
+        .bind(user_identifier)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::errors::common_service_error::ServiceError;
+
+#[derive(Clone)]
+pub struct RevokedTokenRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl RevokedTokenRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait RevokedTokenRepositoryTrait {
+    fn revoke(
+        &self,
+        jti: &Uuid,
+        user_identifier: Option<&Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    fn is_revoked(
+        &self,
+        jti: &Uuid,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    fn delete_expired(&self)
+    -> impl std::future::Future<Output = Result<u64, ServiceError>> + Send;
+}
+
+impl RevokedTokenRepositoryTrait for RevokedTokenRepository {
+    async fn revoke(
+        &self,
+        jti: &Uuid,
+        user_identifier: Option<&Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            "INSERT INTO revoked_tokens (jti, user_identifier, expires_at) VALUES ($1, $2, $3) ON CONFLICT (jti) DO NOTHING",
+        )
+        .bind(jti)
+        .bind(user_identifier)
+        .bind(expires_at)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn is_revoked(&self, jti: &Uuid) -> Result<bool, ServiceError> {
+        let revoked = sqlx::query_scalar::<_, bool>(
+            "SELECT EXISTS (SELECT 1 FROM revoked_tokens WHERE jti = $1)",
+        )
+        .bind(jti)
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        Ok(revoked)
+    }
+
+    async fn delete_expired(&self) -> Result<u64, ServiceError> {
+        let result = sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < NOW()")
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
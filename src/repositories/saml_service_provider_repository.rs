@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entities::saml_service_provider::SamlServiceProviderEntity,
+    errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct SamlServiceProviderRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl SamlServiceProviderRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait SamlServiceProviderRepositoryTrait {
+    fn find_by_identifier(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<Option<SamlServiceProviderEntity>, ServiceError>> + Send;
+
+    fn find_by_entity_id(
+        &self,
+        entity_id: &str,
+    ) -> impl std::future::Future<Output = Result<Option<SamlServiceProviderEntity>, ServiceError>> + Send;
+
+    fn find_all(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<SamlServiceProviderEntity>, ServiceError>> + Send;
+
+    fn create(
+        &self,
+        provider: &SamlServiceProviderEntity,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// updates everything but the identifier
+    fn update(
+        &self,
+        provider: &SamlServiceProviderEntity,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    fn delete(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+}
+
+impl SamlServiceProviderRepositoryTrait for SamlServiceProviderRepository {
+    async fn find_by_identifier(
+        &self,
+        identifier: &Uuid,
+    ) -> Result<Option<SamlServiceProviderEntity>, ServiceError> {
+        let provider = sqlx::query_as::<_, SamlServiceProviderEntity>(
+            "SELECT * FROM saml_service_providers WHERE identifier = $1",
+        )
+        .bind(identifier)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(provider)
+    }
+
+    async fn find_by_entity_id(
+        &self,
+        entity_id: &str,
+    ) -> Result<Option<SamlServiceProviderEntity>, ServiceError> {
+        let provider = sqlx::query_as::<_, SamlServiceProviderEntity>(
+            "SELECT * FROM saml_service_providers WHERE entity_id = $1",
+        )
+        .bind(entity_id)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(provider)
+    }
+
+    async fn find_all(&self) -> Result<Vec<SamlServiceProviderEntity>, ServiceError> {
+        let providers = sqlx::query_as::<_, SamlServiceProviderEntity>(
+            "SELECT * FROM saml_service_providers ORDER BY created_at",
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        Ok(providers)
+    }
+
+    async fn create(&self, provider: &SamlServiceProviderEntity) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"INSERT INTO saml_service_providers (identifier, entity_id, name, assertion_consumer_services, name_id_format, email_attribute, first_name_attribute, last_name_attribute, display_name_attribute, metadata, enabled)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"#,
+        )
+        .bind(provider.identifier)
+        .bind(&provider.entity_id)
+        .bind(&provider.name)
+        .bind(&provider.assertion_consumer_services)
+        .bind(&provider.name_id_format)
+        .bind(&provider.email_attribute)
+        .bind(&provider.first_name_attribute)
+        .bind(&provider.last_name_attribute)
+        .bind(&provider.display_name_attribute)
+        .bind(&provider.metadata)
+        .bind(provider.enabled)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn update(&self, provider: &SamlServiceProviderEntity) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"UPDATE saml_service_providers SET entity_id = $2, name = $3, assertion_consumer_services = $4, name_id_format = $5, email_attribute = $6,
+            first_name_attribute = $7, last_name_attribute = $8, display_name_attribute = $9, metadata = $10, enabled = $11, updated_at = NOW() WHERE identifier = $1"#,
+        )
+        .bind(provider.identifier)
+        .bind(&provider.entity_id)
+        .bind(&provider.name)
+        .bind(&provider.assertion_consumer_services)
+        .bind(&provider.name_id_format)
+        .bind(&provider.email_attribute)
+        .bind(&provider.first_name_attribute)
+        .bind(&provider.last_name_attribute)
+        .bind(&provider.display_name_attribute)
+        .bind(&provider.metadata)
+        .bind(provider.enabled)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, identifier: &Uuid) -> Result<bool, ServiceError> {
+        let result = sqlx::query("DELETE FROM saml_service_providers WHERE identifier = $1")
+            .bind(identifier)
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
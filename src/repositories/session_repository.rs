@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{entities::session::SessionEntity, errors::common_service_error::ServiceError};
+
+/// a session is active while its family holds a refresh token that is neither revoked nor
+/// expired, rotated tokens count so a refresh in flight does not end the session
+const ACTIVE: &str = r#"EXISTS (SELECT 1 FROM refresh_tokens
+    WHERE refresh_tokens.family_identifier = sessions.family_identifier
+    AND refresh_tokens.revoked_at IS NULL AND refresh_tokens.expires_at > NOW())"#;
+
+#[derive(Clone)]
+pub struct SessionRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl SessionRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait SessionRepositoryTrait {
+    fn create(
+        &self,
+        session: &SessionEntity,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// records activity on the session the family belongs to, None when it has none
+    fn touch(
+        &self,
+        family_identifier: &Uuid,
+        ip_address: Option<&str>,
+    ) -> impl std::future::Future<Output = Result<Option<SessionEntity>, ServiceError>> + Send;
+
+    fn find_active(
+        &self,
+        identifier: &Uuid,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<Option<SessionEntity>, ServiceError>> + Send;
+
+    /// most recently seen first
+    fn find_active_by_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<Vec<SessionEntity>, ServiceError>> + Send;
+
+    fn is_active(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+}
+
+impl SessionRepositoryTrait for SessionRepository {
+    async fn create(&self, session: &SessionEntity) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"INSERT INTO sessions (identifier, user_identifier, family_identifier, user_agent, ip_address, created_at, last_seen_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)"#,
+        )
+        .bind(session.identifier)
+        .bind(session.user_identifier)
+        .bind(session.family_identifier)
+        .bind(&session.user_agent)
+        .bind(&session.ip_address)
+        .bind(session.created_at)
+        .bind(session.last_seen_at)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn touch(
+        &self,
+        family_identifier: &Uuid,
+        ip_address: Option<&str>,
+    ) -> Result<Option<SessionEntity>, ServiceError> {
+        let session = sqlx::query_as::<_, SessionEntity>(
+            r#"UPDATE sessions SET last_seen_at = NOW(), ip_address = COALESCE($2, ip_address)
+            WHERE family_identifier = $1
+            RETURNING *"#,
+        )
+        .bind(family_identifier)
+        .bind(ip_address)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(session)
+    }
+
+    async fn find_active(
+        &self,
+        identifier: &Uuid,
+        user_identifier: &Uuid,
+    ) -> Result<Option<SessionEntity>, ServiceError> {
+        let session = sqlx::query_as::<_, SessionEntity>(&format!(
+            "SELECT * FROM sessions WHERE identifier = $1 AND user_identifier = $2 AND {ACTIVE}"
+        ))
+        .bind(identifier)
+        .bind(user_identifier)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(session)
+    }
+
+    async fn find_active_by_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> Result<Vec<SessionEntity>, ServiceError> {
+        let sessions = sqlx::query_as::<_, SessionEntity>(&format!(
+            "SELECT * FROM sessions WHERE user_identifier = $1 AND {ACTIVE} ORDER BY last_seen_at DESC"
+        ))
+        .bind(user_identifier)
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        Ok(sessions)
+    }
+
+    async fn is_active(&self, identifier: &Uuid) -> Result<bool, ServiceError> {
+        let active = sqlx::query_scalar::<_, bool>(&format!(
+            "SELECT EXISTS (SELECT 1 FROM sessions WHERE identifier = $1 AND {ACTIVE})"
+        ))
+        .bind(identifier)
+        .fetch_one(self.pool.as_ref())
+        .await?;
+
+        Ok(active)
+    }
+}
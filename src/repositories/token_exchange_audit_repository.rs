@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    entities::token_exchange_audit::TokenExchangeAuditEntity,
+    errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct TokenExchangeAuditRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl TokenExchangeAuditRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait TokenExchangeAuditRepositoryTrait {
+    fn record(
+        &self,
+        audit: &TokenExchangeAuditEntity,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+}
+
+impl TokenExchangeAuditRepositoryTrait for TokenExchangeAuditRepository {
+    async fn record(&self, audit: &TokenExchangeAuditEntity) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"INSERT INTO token_exchange_audits (identifier, client_id, subject_identifier, subject_token_jti, actor, actor_type, impersonation, scope, audience, granted, reason)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"#,
+        )
+        .bind(audit.identifier)
+        .bind(&audit.client_id)
+        .bind(audit.subject_identifier)
+        .bind(audit.subject_token_jti)
+        .bind(&audit.actor)
+        .bind(&audit.actor_type)
+        .bind(audit.impersonation)
+        .bind(&audit.scope)
+        .bind(&audit.audience)
+        .bind(audit.granted)
+        .bind(&audit.reason)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entities::totp_authenticator::TotpAuthenticatorEntity,
+    errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct TotpAuthenticatorRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl TotpAuthenticatorRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait TotpAuthenticatorRepositoryTrait {
+    /// stores a new unconfirmed authenticator, replacing an earlier one that was never confirmed
+    fn upsert_pending(
+        &self,
+        user_identifier: &Uuid,
+        secret_ciphertext: &[u8],
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    fn find_by_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<Option<TotpAuthenticatorEntity>, ServiceError>> + Send;
+
+    /// false when the authenticator was confirmed in the meantime
+    fn confirm(
+        &self,
+        user_identifier: &Uuid,
+        step: i64,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    /// records the time step of an accepted code, false when a code from the same or a later
+    /// step was accepted before
+    fn record_used_step(
+        &self,
+        user_identifier: &Uuid,
+        step: i64,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    fn delete(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+}
+
+impl TotpAuthenticatorRepositoryTrait for TotpAuthenticatorRepository {
+    async fn upsert_pending(
+        &self,
+        user_identifier: &Uuid,
+        secret_ciphertext: &[u8],
+    ) -> Result<(), ServiceError> {
+        sqlx::query(
+            r#"INSERT INTO totp_authenticators (user_identifier, secret_ciphertext)
+            VALUES ($1, $2)
+            ON CONFLICT (user_identifier) DO UPDATE
+            SET secret_ciphertext = EXCLUDED.secret_ciphertext, last_used_step = NULL, created_at = NOW()
+            WHERE totp_authenticators.confirmed_at IS NULL"#,
+        )
+        .bind(user_identifier)
+        .bind(secret_ciphertext)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_by_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> Result<Option<TotpAuthenticatorEntity>, ServiceError> {
+        let authenticator = sqlx::query_as::<_, TotpAuthenticatorEntity>(
+            "SELECT * FROM totp_authenticators WHERE user_identifier = $1",
+        )
+        .bind(user_identifier)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(authenticator)
+    }
+
+    async fn confirm(&self, user_identifier: &Uuid, step: i64) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            "UPDATE totp_authenticators SET confirmed_at = NOW(), last_used_step = $2 WHERE user_identifier = $1 AND confirmed_at IS NULL",
+        )
+        .bind(user_identifier)
+        .bind(step)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn record_used_step(
+        &self,
+        user_identifier: &Uuid,
+        step: i64,
+    ) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            "UPDATE totp_authenticators SET last_used_step = $2 WHERE user_identifier = $1 AND (last_used_step IS NULL OR last_used_step < $2)",
+        )
+        .bind(user_identifier)
+        .bind(step)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn delete(&self, user_identifier: &Uuid) -> Result<(), ServiceError> {
+        sqlx::query("DELETE FROM totp_authenticators WHERE user_identifier = $1")
+            .bind(user_identifier)
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(())
+    }
+}
@@ -44,10 +44,42 @@ pub trait UserRepositoryTrait {
         new_password: &str,
     ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
 
+    /// swaps the hash only while it is still `current_password`, so a rehash never undoes a
+    /// password change that happened in the meantime
+    fn upgrade_password_hash(
+        &self,
+        identifier: &Uuid,
+        current_password: &str,
+        new_password: &str,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    /// parks the address until the code sent to it is confirmed, the current one stays in use
+    fn set_pending_email(
+        &self,
+        identifier: &Uuid,
+        email: &str,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// moves the pending address into place and returns it, None when no change was waiting
+    fn confirm_pending_email(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<Option<String>, ServiceError>> + Send;
+
+    fn find_token_version(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<Option<i32>, ServiceError>> + Send;
+
+    fn increment_token_version(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
     fn create_user(
         &self,
         user: CreateUserRequest,
-    ) -> impl std::future::Future<Output = Result<(), UserServiceError>> + Send;
+    ) -> impl std::future::Future<Output = Result<Uuid, UserServiceError>> + Send;
 
     fn retrieve_information(
         &self,
@@ -56,11 +88,12 @@ pub trait UserRepositoryTrait {
 }
 
 impl UserRepositoryTrait for UserRepository {
-    async fn create_user(&self, user: CreateUserRequest) -> Result<(), UserServiceError> {
+    async fn create_user(&self, user: CreateUserRequest) -> Result<Uuid, UserServiceError> {
+        let identifier = Uuid::new_v4();
         sqlx::query(
     "INSERT INTO users (identifier, first_name, last_name, email, password) VALUES ($1, $2, $3, $4, $5)"
 )
-.bind(uuid::Uuid::new_v4())
+.bind(identifier)
 .bind(user.first_name)
 .bind(user.last_name)
 .bind(user.email)
@@ -69,7 +102,7 @@ impl UserRepositoryTrait for UserRepository {
 .await
 .map_err(|err| UserServiceError::OperationFailed(err.to_string()))?;
 
-        Ok(())
+        Ok(identifier)
     }
     async fn find_by_identifier(&self, identifier: &Uuid) -> Option<UserEntity> {
         sqlx::query_as::<_, UserEntity>("SELECT * FROM users WHERE identifier = $1")
@@ -88,14 +121,13 @@ impl UserRepositoryTrait for UserRepository {
     }
 
     async fn update_account_status(&self, identifier: &Uuid) -> Result<(), ServiceError> {
-        let _ = sqlx::query_as::<_, UserEntity>(
-            "UPDATE users SET is_active = $1  WHERE identifier = $2",
+        sqlx::query(
+            "UPDATE users SET is_active = $1, email_verified_at = COALESCE(email_verified_at, NOW()), updated_at = NOW() WHERE identifier = $2",
         )
-        .bind(true)
-        .bind(identifier.to_string())
-        .fetch_one(self.pool.as_ref())
-        .await
-        .map_err(|err| UserServiceError::OperationFailed(err.to_string()));
+            .bind(true)
+            .bind(identifier)
+            .execute(self.pool.as_ref())
+            .await?;
 
         Ok(())
     }
@@ -105,17 +137,80 @@ impl UserRepositoryTrait for UserRepository {
         identifier: &Uuid,
         new_password: &str,
     ) -> Result<(), ServiceError> {
-        let _ = sqlx::query_as::<_, UserEntity>(
-            "UPDATE users SET password = $1  WHERE identifier  = $2",
+        sqlx::query("UPDATE users SET password = $1, updated_at = NOW() WHERE identifier = $2")
+            .bind(new_password)
+            .bind(identifier)
+            .execute(self.pool.as_ref())
+            .await?;
+
+        Ok(())
+    }
+
+    async fn upgrade_password_hash(
+        &self,
+        identifier: &Uuid,
+        current_password: &str,
+        new_password: &str,
+    ) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            "UPDATE users SET password = $1, updated_at = NOW() WHERE identifier = $2 AND password = $3",
         )
         .bind(new_password)
         .bind(identifier)
-        .fetch_one(self.pool.as_ref())
-        .await
-        .map_err(|err| UserServiceError::OperationFailed(err.to_string()));
+        .bind(current_password)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn set_pending_email(&self, identifier: &Uuid, email: &str) -> Result<(), ServiceError> {
+        sqlx::query(
+            "UPDATE users SET pending_email = $1, updated_at = NOW() WHERE identifier = $2",
+        )
+        .bind(email)
+        .bind(identifier)
+        .execute(self.pool.as_ref())
+        .await?;
 
         Ok(())
     }
+
+    async fn confirm_pending_email(
+        &self,
+        identifier: &Uuid,
+    ) -> Result<Option<String>, ServiceError> {
+        let email = sqlx::query_scalar::<_, String>(
+            "UPDATE users SET email = pending_email, pending_email = NULL, email_verified_at = NOW(), email_changed_at = NOW(), updated_at = NOW() WHERE identifier = $1 AND pending_email IS NOT NULL RETURNING email",
+        )
+        .bind(identifier)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(email)
+    }
+
+    async fn find_token_version(&self, identifier: &Uuid) -> Result<Option<i32>, ServiceError> {
+        let token_version =
+            sqlx::query_scalar::<_, i32>("SELECT token_version FROM users WHERE identifier = $1")
+                .bind(identifier)
+                .fetch_optional(self.pool.as_ref())
+                .await?;
+
+        Ok(token_version)
+    }
+
+    async fn increment_token_version(&self, identifier: &Uuid) -> Result<(), ServiceError> {
+        sqlx::query(
+            "UPDATE users SET token_version = token_version + 1, updated_at = NOW() WHERE identifier = $1",
+        )
+        .bind(identifier)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
     async fn retrieve_information(&self, identifier: &Uuid) -> Result<UserDto, UserServiceError> {
         sqlx::query_as::<_, UserDto>(r#"SELECT * FROM users  WHERE identifier = $1"#)
             .bind(identifier)
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entities::webauthn_challenge::WebAuthnChallengeEntity,
+    errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct WebAuthnChallengeRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl WebAuthnChallengeRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait WebAuthnChallengeRepositoryTrait {
+    /// also clears out challenges that expired without a response
+    fn create(
+        &self,
+        challenge: &str,
+        ceremony: &str,
+        user_identifier: Option<&Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> impl std::future::Future<Output = Result<(), ServiceError>> + Send;
+
+    /// removes the challenge and returns it, provided it was issued for the ceremony and has not
+    /// expired
+    fn consume(
+        &self,
+        challenge: &str,
+        ceremony: &str,
+    ) -> impl std::future::Future<Output = Result<Option<WebAuthnChallengeEntity>, ServiceError>> + Send;
+}
+
+impl WebAuthnChallengeRepositoryTrait for WebAuthnChallengeRepository {
+    async fn create(
+        &self,
+        challenge: &str,
+        ceremony: &str,
+        user_identifier: Option<&Uuid>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<(), ServiceError> {
+        sqlx::query("DELETE FROM webauthn_challenges WHERE expires_at < NOW()")
+            .execute(self.pool.as_ref())
+            .await?;
+        sqlx::query(
+            "INSERT INTO webauthn_challenges (challenge, ceremony, user_identifier, expires_at) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(challenge)
+        .bind(ceremony)
+        .bind(user_identifier)
+        .bind(expires_at)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(())
+    }
+
+    async fn consume(
+        &self,
+        challenge: &str,
+        ceremony: &str,
+    ) -> Result<Option<WebAuthnChallengeEntity>, ServiceError> {
+        let challenge = sqlx::query_as::<_, WebAuthnChallengeEntity>(
+            "DELETE FROM webauthn_challenges WHERE challenge = $1 AND ceremony = $2 AND expires_at > NOW() RETURNING *",
+        )
+        .bind(challenge)
+        .bind(ceremony)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(challenge)
+    }
+}
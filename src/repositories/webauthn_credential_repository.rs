@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    entities::webauthn_credential::WebAuthnCredentialEntity,
+    errors::common_service_error::ServiceError,
+};
+
+#[derive(Clone)]
+pub struct WebAuthnCredentialRepository {
+    pool: Arc<Pool<Postgres>>,
+}
+
+impl WebAuthnCredentialRepository {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            pool: Arc::new(pool.clone()),
+        }
+    }
+}
+
+pub trait WebAuthnCredentialRepositoryTrait {
+    /// false when the credential id is already registered
+    fn create(
+        &self,
+        credential: &WebAuthnCredentialEntity,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    fn find_by_credential_id(
+        &self,
+        credential_id: &str,
+    ) -> impl std::future::Future<Output = Result<Option<WebAuthnCredentialEntity>, ServiceError>> + Send;
+
+    fn find_by_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<Vec<WebAuthnCredentialEntity>, ServiceError>> + Send;
+
+    /// false when the counter moved on in the meantime, the same assertion was used twice
+    fn record_use(
+        &self,
+        identifier: &Uuid,
+        previous_sign_count: i64,
+        sign_count: i64,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+
+    fn delete(
+        &self,
+        identifier: &Uuid,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<bool, ServiceError>> + Send;
+}
+
+impl WebAuthnCredentialRepositoryTrait for WebAuthnCredentialRepository {
+    async fn create(&self, credential: &WebAuthnCredentialEntity) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            r#"INSERT INTO webauthn_credentials (identifier, user_identifier, credential_id, public_key, sign_count, transports, name)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (credential_id) DO NOTHING"#,
+        )
+        .bind(credential.identifier)
+        .bind(credential.user_identifier)
+        .bind(&credential.credential_id)
+        .bind(&credential.public_key)
+        .bind(credential.sign_count)
+        .bind(&credential.transports)
+        .bind(&credential.name)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn find_by_credential_id(
+        &self,
+        credential_id: &str,
+    ) -> Result<Option<WebAuthnCredentialEntity>, ServiceError> {
+        let credential = sqlx::query_as::<_, WebAuthnCredentialEntity>(
+            "SELECT * FROM webauthn_credentials WHERE credential_id = $1",
+        )
+        .bind(credential_id)
+        .fetch_optional(self.pool.as_ref())
+        .await?;
+
+        Ok(credential)
+    }
+
+    async fn find_by_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> Result<Vec<WebAuthnCredentialEntity>, ServiceError> {
+        let credentials = sqlx::query_as::<_, WebAuthnCredentialEntity>(
+            "SELECT * FROM webauthn_credentials WHERE user_identifier = $1 ORDER BY created_at",
+        )
+        .bind(user_identifier)
+        .fetch_all(self.pool.as_ref())
+        .await?;
+
+        Ok(credentials)
+    }
+
+    async fn record_use(
+        &self,
+        identifier: &Uuid,
+        previous_sign_count: i64,
+        sign_count: i64,
+    ) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            "UPDATE webauthn_credentials SET sign_count = $3, last_used_at = NOW() WHERE identifier = $1 AND sign_count = $2",
+        )
+        .bind(identifier)
+        .bind(previous_sign_count)
+        .bind(sign_count)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    async fn delete(
+        &self,
+        identifier: &Uuid,
+        user_identifier: &Uuid,
+    ) -> Result<bool, ServiceError> {
+        let result = sqlx::query(
+            "DELETE FROM webauthn_credentials WHERE identifier = $1 AND user_identifier = $2",
+        )
+        .bind(identifier)
+        .bind(user_identifier)
+        .execute(self.pool.as_ref())
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
@@ -1,23 +1,95 @@
+use std::time::Duration;
+
 use axum::{
     Router,
     routing::{get, post},
 };
 
 use crate::{
-    controllers::auth::{
-        create_account, forgotten_password, login, request_refresh_token, set_new_password,
-        verify_account,
+    controllers::{
+        auth::{
+            change_email, complete_federated_login, complete_mfa_login, confirm_email_change,
+            create_account, email_login, forgotten_password, login, logout, logout_everywhere,
+            request_refresh_token, resend_verification, revoke_token, set_new_password,
+            start_federated_login, verify_account, verify_email_login,
+        },
+        webauthn::{mfa_webauthn_options, passkey_login, passkey_login_options},
     },
+    middlewares::rate_limit::{RateLimitKey, RateLimitLayer, RateLimitPolicy},
     states::services_state::ServicesState,
 };
 
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(60 * 60);
+
+const SIGNUP_POLICIES: &[RateLimitPolicy] = &[RateLimitPolicy::new(
+    "signup:ip",
+    RateLimitKey::Ip,
+    10,
+    HOUR,
+)];
+
+/// every way of signing in shares these, so switching between them does not buy more attempts
+const LOGIN_POLICIES: &[RateLimitPolicy] = &[
+    RateLimitPolicy::new("login:ip", RateLimitKey::Ip, 30, MINUTE),
+    RateLimitPolicy::new("login:email", RateLimitKey::Email, 10, MINUTE),
+];
+
+const FORGOTTEN_PASSWORD_POLICIES: &[RateLimitPolicy] = &[
+    RateLimitPolicy::new("forgotten-password:ip", RateLimitKey::Ip, 10, HOUR),
+    RateLimitPolicy::new("forgotten-password:email", RateLimitKey::Email, 5, HOUR),
+];
+
 pub(super) fn authentication_routes(state: ServicesState) -> Router {
+    let rate_limit = |policies| {
+        RateLimitLayer::new(
+            state.rate_limit_store.clone(),
+            state.network_config.clone(),
+            policies,
+        )
+    };
+
     Router::new()
-        .route("/signup", post(create_account))
-        .route("/login", post(login))
-        .route("/forgotten-password", post(forgotten_password))
+        .route(
+            "/signup",
+            post(create_account).layer(rate_limit(SIGNUP_POLICIES)),
+        )
+        .route("/login", post(login).layer(rate_limit(LOGIN_POLICIES)))
+        .route(
+            "/login/email",
+            post(email_login).layer(rate_limit(LOGIN_POLICIES)),
+        )
+        .route(
+            "/login/email/verify",
+            post(verify_email_login).layer(rate_limit(LOGIN_POLICIES)),
+        )
+        .route(
+            "/login/mfa",
+            post(complete_mfa_login).layer(rate_limit(LOGIN_POLICIES)),
+        )
+        .route("/login/mfa/webauthn/options", post(mfa_webauthn_options))
+        .route(
+            "/login/webauthn",
+            post(passkey_login).layer(rate_limit(LOGIN_POLICIES)),
+        )
+        .route("/login/webauthn/options", post(passkey_login_options))
+        .route("/login/{provider}", get(start_federated_login))
+        .route(
+            "/login/{provider}/callback",
+            get(complete_federated_login).layer(rate_limit(LOGIN_POLICIES)),
+        )
+        .route(
+            "/forgotten-password",
+            post(forgotten_password).layer(rate_limit(FORGOTTEN_PASSWORD_POLICIES)),
+        )
         .route("/reset-password", post(set_new_password))
         .route("/verify-account", post(verify_account))
-        .route("/refresh-token", get(request_refresh_token))
+        .route("/verify-account/resend", post(resend_verification))
+        .route("/refresh-token", post(request_refresh_token))
+        .route("/logout", post(logout))
+        .route("/revoke-token", post(revoke_token))
+        .route("/logout/all", post(logout_everywhere))
+        .route("/change-email", post(change_email))
+        .route("/change-email/confirm", post(confirm_email_change))
         .with_state(state)
 }
@@ -0,0 +1,25 @@
+use axum::{
+    Router,
+    routing::{get, post},
+};
+
+use crate::{
+    controllers::clients::{
+        create_client, delete_client, list_clients, retrieve_client, rotate_client_secret,
+        update_client,
+    },
+    states::services_state::ServicesState,
+};
+
+pub(super) fn client_routes(state: ServicesState) -> Router {
+    Router::new()
+        .route("/", get(list_clients).post(create_client))
+        .route(
+            "/{client_id}",
+            get(retrieve_client)
+                .put(update_client)
+                .delete(delete_client),
+        )
+        .route("/{client_id}/secret", post(rotate_client_secret))
+        .with_state(state)
+}
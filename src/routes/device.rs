@@ -0,0 +1,17 @@
+use axum::{
+    Router,
+    routing::{get, post},
+};
+
+use crate::{
+    controllers::device::{authorize_device, find_device_authorization, verify_device},
+    states::services_state::ServicesState,
+};
+
+pub(super) fn device_routes(state: ServicesState) -> Router {
+    Router::new()
+        .route("/code", post(authorize_device))
+        .route("/verify", post(verify_device))
+        .route("/verify/{user_code}", get(find_device_authorization))
+        .with_state(state)
+}
@@ -0,0 +1,24 @@
+use axum::{Router, routing::get};
+
+use crate::{
+    controllers::identity_providers::{
+        create_identity_provider, delete_identity_provider, list_identity_providers,
+        retrieve_identity_provider, update_identity_provider,
+    },
+    states::services_state::ServicesState,
+};
+
+pub(super) fn identity_provider_routes(state: ServicesState) -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(list_identity_providers).post(create_identity_provider),
+        )
+        .route(
+            "/{slug}",
+            get(retrieve_identity_provider)
+                .put(update_identity_provider)
+                .delete(delete_identity_provider),
+        )
+        .with_state(state)
+}
@@ -1,4 +1,11 @@
 pub mod auth;
+pub mod clients;
+pub mod device;
+pub mod identity_providers;
+pub mod oauth;
 pub mod public;
 pub mod router;
+pub mod saml;
+pub mod service_providers;
 pub mod users;
+pub mod well_known;
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use axum::{
+    Router,
+    routing::{get, post},
+};
+
+use crate::{
+    controllers::oauth::{
+        authorize, exchange_token, introspect, revoke, start_authorization, userinfo,
+    },
+    middlewares::rate_limit::{RateLimitKey, RateLimitLayer, RateLimitPolicy},
+    states::services_state::ServicesState,
+};
+
+const TOKEN_POLICIES: &[RateLimitPolicy] = &[RateLimitPolicy::new(
+    "token:client",
+    RateLimitKey::ClientId,
+    120,
+    Duration::from_secs(60),
+)];
+
+pub(super) fn oauth_routes(state: ServicesState) -> Router {
+    let token_rate_limit = RateLimitLayer::new(
+        state.rate_limit_store.clone(),
+        state.network_config.clone(),
+        TOKEN_POLICIES,
+    );
+
+    Router::new()
+        .route("/authorize", get(start_authorization).post(authorize))
+        .route("/token", post(exchange_token).layer(token_rate_limit))
+        .route("/userinfo", get(userinfo).post(userinfo))
+        .route("/introspect", post(introspect))
+        .route("/revoke", post(revoke))
+        .with_state(state)
+}
@@ -2,30 +2,90 @@ use axum::{Router, http::StatusCode, response::IntoResponse};
 use sqlx::{Pool, Postgres};
 
 use crate::{
-    adapters::response::api_response::ApiResponseBuilder,
-    routes::{auth::authentication_routes, public::public_routes, users::user_routes},
+    adapters::{rate_limit::RateLimitBackend, response::api_response::ApiResponseBuilder},
+    config::{network::NetworkConfig, rate_limit::RateLimitConfig},
+    errors::app_error::AppError,
+    routes::{
+        auth::authentication_routes, clients::client_routes, device::device_routes,
+        identity_providers::identity_provider_routes, oauth::oauth_routes, public::public_routes,
+        saml::saml_routes, service_providers::service_provider_routes, users::user_routes,
+        well_known::well_known_routes,
+    },
     services::{
-        auth_service::AuthenticationService, root_service::RootService, user_service::UserService,
+        auth_service::AuthenticationService, client_service::ClientService,
+        federation_service::FederationService, identity_provider_service::IdentityProviderService,
+        mailer_service::MailerService, mfa_service::MfaService, oauth_service::OAuthService,
+        root_service::RootService, saml_service::SamlService,
+        service_provider_service::ServiceProviderService, token_service::TokenService,
+        user_service::UserService, webauthn_service::WebAuthnService,
     },
+    shared::{hashing_pool::HashingPool, secret_box::SecretBox},
     states::services_state::ServicesState,
 };
 
-pub fn load_routes(pool: Pool<Postgres>) -> Router {
+pub fn load_routes(pool: Pool<Postgres>) -> Result<Router, AppError> {
+    let mailer_service = MailerService::init()?;
+    let hashing_pool = HashingPool::from_env();
+    let token_service = TokenService::init(&pool)?;
+    token_service.spawn_revocation_cleanup();
+    let secret_box = SecretBox::from_env()?;
+    let mfa_service = MfaService::init(&pool, &secret_box);
+    let webauthn_service = WebAuthnService::init(&pool);
+    let federation_service = FederationService::init(&pool, &secret_box)?;
+    let oauth_service = OAuthService::init(&pool, &token_service);
+    oauth_service.spawn_device_code_cleanup();
+    let rate_limit_config = RateLimitConfig::from_env();
+    let rate_limit_store = RateLimitBackend::from_config(&rate_limit_config, &pool);
+    rate_limit_store.spawn_cleanup(rate_limit_config.cleanup_interval);
     let state = ServicesState {
         user_service: UserService::init(&pool),
-        root_service: RootService::init(),
-        auth_service: AuthenticationService::init(&pool),
+        root_service: RootService::init(&hashing_pool),
+        auth_service: AuthenticationService::init(
+            &pool,
+            &mailer_service,
+            &token_service,
+            &mfa_service,
+            &webauthn_service,
+            &federation_service,
+            &hashing_pool,
+        )?,
+        oauth_service,
+        client_service: ClientService::init(&pool),
+        identity_provider_service: IdentityProviderService::init(&pool, &secret_box),
+        saml_service: SamlService::init(&pool)?,
+        service_provider_service: ServiceProviderService::init(&pool),
+        network_config: NetworkConfig::from_env(),
+        rate_limit_store,
+        mailer_service,
+        token_service,
     };
 
-    Router::new()
+    let router = Router::new()
         .merge(public_routes(state.clone()))
+        .nest("/.well-known", well_known_routes(state.clone()))
         .merge(authentication_routes(state.clone()))
+        .nest("/oauth", oauth_routes(state.clone()))
+        .nest("/device", device_routes(state.clone()))
         .nest("/users", user_routes(state.clone()))
+        .nest("/clients", client_routes(state.clone()))
+        .nest(
+            "/identity-providers",
+            identity_provider_routes(state.clone()),
+        )
+        .nest("/saml", saml_routes(state.clone()))
+        .nest(
+            "/saml/service-providers",
+            service_provider_routes(state.clone()),
+        )
         .fallback(async || {
             ApiResponseBuilder::<()>::new()
-                .message("the resource you're looking does not exist or it has been permanently moved")
+                .message(
+                    "the resource you're looking does not exist or it has been permanently moved",
+                )
                 .status_code(StatusCode::NOT_FOUND)
                 .build()
                 .into_response()
-        })
+        });
+
+    Ok(router)
 }
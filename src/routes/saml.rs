@@ -0,0 +1,20 @@
+use axum::{
+    Router,
+    routing::{get, post},
+};
+
+use crate::{
+    controllers::saml::{
+        complete_sso, identity_provider_metadata, initiate_sso, start_sso_post, start_sso_redirect,
+    },
+    states::services_state::ServicesState,
+};
+
+pub(super) fn saml_routes(state: ServicesState) -> Router {
+    Router::new()
+        .route("/metadata", get(identity_provider_metadata))
+        .route("/sso", get(start_sso_redirect).post(start_sso_post))
+        .route("/sso/complete", post(complete_sso))
+        .route("/sso/initiate", post(initiate_sso))
+        .with_state(state)
+}
@@ -0,0 +1,24 @@
+use axum::{Router, routing::get};
+
+use crate::{
+    controllers::service_providers::{
+        create_service_provider, delete_service_provider, list_service_providers,
+        retrieve_service_provider, update_service_provider,
+    },
+    states::services_state::ServicesState,
+};
+
+pub(super) fn service_provider_routes(state: ServicesState) -> Router {
+    Router::new()
+        .route(
+            "/",
+            get(list_service_providers).post(create_service_provider),
+        )
+        .route(
+            "/{identifier}",
+            get(retrieve_service_provider)
+                .put(update_service_provider)
+                .delete(delete_service_provider),
+        )
+        .with_state(state)
+}
@@ -1,9 +1,41 @@
-use axum::{Router, routing::get};
+use axum::{
+    Router,
+    routing::{delete, get, post},
+};
 
-use crate::{controllers::user::retrieve_information, states::services_state::ServicesState};
+use crate::{
+    controllers::{
+        mfa::{confirm_totp, disable_totp, enroll_totp, mfa_status, regenerate_recovery_codes},
+        user::{
+            list_sessions, retrieve_information, revoke_other_sessions, revoke_session,
+            sign_out_user, unlock_user,
+        },
+        webauthn::{
+            list_credentials, register_credential, registration_options, remove_credential,
+        },
+    },
+    states::services_state::ServicesState,
+};
 
 pub(super) fn user_routes(state: ServicesState) -> Router {
     Router::new()
         .route("/profile", get(retrieve_information))
+        .route("/mfa", get(mfa_status))
+        .route("/mfa/totp", post(enroll_totp))
+        .route("/mfa/totp/confirm", post(confirm_totp))
+        .route("/mfa/totp/disable", post(disable_totp))
+        .route("/mfa/recovery-codes", post(regenerate_recovery_codes))
+        .route("/sessions", get(list_sessions))
+        .route("/sessions/others", delete(revoke_other_sessions))
+        .route("/sessions/{identifier}", delete(revoke_session))
+        .route("/webauthn/credentials", get(list_credentials))
+        .route(
+            "/webauthn/credentials/{identifier}",
+            delete(remove_credential),
+        )
+        .route("/webauthn/register", post(register_credential))
+        .route("/webauthn/register/options", post(registration_options))
+        .route("/{identifier}/sign-out", post(sign_out_user))
+        .route("/{identifier}/unlock", post(unlock_user))
         .with_state(state)
 }
@@ -0,0 +1,13 @@
+use axum::{Router, routing::get};
+
+use crate::{
+    controllers::well_known::{jwks, openid_configuration},
+    states::services_state::ServicesState,
+};
+
+pub(super) fn well_known_routes(state: ServicesState) -> Router {
+    Router::new()
+        .route("/jwks.json", get(jwks))
+        .route("/openid-configuration", get(openid_configuration))
+        .with_state(state)
+}
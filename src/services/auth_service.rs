@@ -1,79 +1,867 @@
+use std::net::IpAddr;
+
 use sqlx::{Pool, Postgres};
+use uuid::Uuid;
 
-use crate::adapters::dto::jwt::{Claims, JwtCredentials, TEN_MINUTES, TWENTY_FIVE_MINUTES};
+use crate::adapters::dto::federation::{FederatedAccount, FederatedProfile};
+use crate::adapters::dto::jwt::{Claims, JwtCredentials, TEN_MINUTES, TokenUse};
+use crate::adapters::dto::oauth::OAuthGrant;
+use crate::adapters::dto::otp::OtpKind;
+use crate::adapters::dto::session::DeviceInfo;
+use crate::config::email_login::EmailLoginConfig;
+use crate::config::oauth::OAuthConfig;
+use crate::config::password_reset::PasswordResetConfig;
+use crate::config::webauthn::WebAuthnConfig;
+use crate::entities::oauth_client::OAuthClientEntity;
+use crate::entities::session::SessionEntity;
 use crate::entities::user::UserEntity;
+use crate::errors::app_error::AppError;
+use crate::errors::common_service_error::ServiceError;
+use crate::errors::federation_error::FederationError;
+use crate::repositories::oauth_client_repository::{
+    OAuthClientRepository, OAuthClientRepositoryTrait,
+};
+use crate::services::federation_service::{FederationService, FederationServiceTrait};
+use crate::services::lockout_service::{LockoutService, LockoutServiceTrait};
+use crate::services::mailer_service::{MailerService, MailerServiceTrait};
+use crate::services::mfa_service::{MfaService, MfaServiceTrait};
+use crate::services::otp_service::{OtpService, OtpServiceTrait};
+use crate::services::session_service::{SessionService, SessionServiceTrait};
+use crate::services::token_service::{RotatedRefreshToken, TokenService, TokenServiceTrait};
+use crate::services::webauthn_service::{WebAuthnService, WebAuthnServiceTrait};
+use crate::shared::crypto::generate_opaque_token;
+use crate::shared::hashing_pool::HashingPool;
+use crate::shared::password_policy::{PasswordContext, PasswordPolicy};
 use crate::{
     adapters::{
         requests::auth::{
-            CreateUserRequest, ForgottenPasswordRequest, LoginRequest, RefreshTokenRequest,
-            SetNewPasswordRequest, VerifyAccountRequest,
+            ChangeEmailRequest, ConfirmEmailChangeRequest, CreateUserRequest, EmailLoginMethod,
+            EmailLoginRequest, FederatedCallbackRequest, FederatedLoginRequest,
+            ForgottenPasswordRequest, LoginRequest, LogoutRequest, MfaLoginRequest,
+            RefreshTokenRequest, RevokeTokenRequest, SetNewPasswordRequest, VerifyAccountRequest,
+            VerifyEmailLoginRequest,
+        },
+        requests::mfa::{ConfirmTotpRequest, DisableTotpRequest, RegenerateRecoveryCodesRequest},
+        requests::webauthn::{
+            AssertionCredential, MfaWebAuthnOptionsRequest, PasskeyLoginRequest,
+            RegisterCredentialRequest, RegistrationOptionsRequest,
         },
         response::auth::{
-            ForgottenPasswordResponse, LoginResponse, RefreshTokenResponse, SetNewPasswordResponse,
-            VerifyAccountResponse,
+            ChangeEmailResponse, ConfirmEmailChangeResponse, CreateUserResponse,
+            EmailLoginResponse, ForgottenPasswordResponse, LoginResponse, LogoutEverywhereResponse,
+            LogoutResponse, RefreshTokenResponse, ResendVerificationResponse, RevokeTokenResponse,
+            SetNewPasswordResponse, UnlockUserResponse, VerifyAccountResponse,
+        },
+        response::mfa::{
+            DisableTotpResponse, MfaStatusResponse, RecoveryCodesResponse, TotpEnrollmentResponse,
+        },
+        response::sessions::{RevokeOtherSessionsResponse, RevokeSessionResponse, SessionResponse},
+        response::webauthn::{
+            CredentialCreationOptionsResponse, CredentialRequestOptionsResponse,
+            RemoveWebAuthnCredentialResponse, WebAuthnCredentialResponse,
         },
     },
     errors::{
-        auth_service_error::AuthenticationServiceError, user_service_error::UserServiceError,
+        auth_service_error::AuthenticationServiceError, mfa_service_error::MfaServiceError,
+        otp_service_error::OtpServiceError, user_service_error::UserServiceError,
     },
     repositories::user_repository::{UserRepository, UserRepositoryTrait},
     services::user_helper_service::{UserHelperService, UserHelperServiceTrait},
 };
 
+/// second factors as named in a login's mfa challenge
+const MFA_METHOD_TOTP: &str = "totp";
+const MFA_METHOD_WEBAUTHN: &str = "webauthn";
+
 #[derive(Clone)]
 pub struct AuthenticationService {
     user_repository: UserRepository,
     user_helper_service: UserHelperService,
+    otp_service: OtpService,
+    lockout_service: LockoutService,
+    session_service: SessionService,
+    mfa_service: MfaService,
+    webauthn_service: WebAuthnService,
+    federation_service: FederationService,
+    token_service: TokenService,
+    mailer_service: MailerService,
+    oauth_client_repository: OAuthClientRepository,
+    password_reset_config: PasswordResetConfig,
+    email_login_config: EmailLoginConfig,
+    oauth_config: OAuthConfig,
+    webauthn_config: WebAuthnConfig,
+    password_policy: PasswordPolicy,
 }
 
 impl AuthenticationService {
-    pub fn init(pool: &Pool<Postgres>) -> Self {
-        Self {
+    pub fn init(
+        pool: &Pool<Postgres>,
+        mailer_service: &MailerService,
+        token_service: &TokenService,
+        mfa_service: &MfaService,
+        webauthn_service: &WebAuthnService,
+        federation_service: &FederationService,
+        hashing_pool: &HashingPool,
+    ) -> Result<Self, AppError> {
+        Ok(Self {
             user_repository: UserRepository::init(pool),
-            user_helper_service: UserHelperService::init(),
+            user_helper_service: UserHelperService::init(hashing_pool)?,
+            otp_service: OtpService::init(pool),
+            lockout_service: LockoutService::init(pool),
+            session_service: SessionService::init(pool),
+            mfa_service: mfa_service.clone(),
+            webauthn_service: webauthn_service.clone(),
+            federation_service: federation_service.clone(),
+            token_service: token_service.clone(),
+            mailer_service: mailer_service.clone(),
+            oauth_client_repository: OAuthClientRepository::init(pool),
+            password_reset_config: PasswordResetConfig::from_env(),
+            email_login_config: EmailLoginConfig::from_env(),
+            oauth_config: OAuthConfig::from_env(),
+            webauthn_config: WebAuthnConfig::from_env(),
+            password_policy: PasswordPolicy::from_env()?,
+        })
+    }
+
+    /// the deployment's password rules, the violations are reported as field errors
+    fn check_password(
+        &self,
+        password: &str,
+        context: &PasswordContext,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.password_policy
+            .check(password, context)
+            .map_err(|errors| ServiceError::ValidationError(errors).into())
+    }
+
+    /// the first-party client a direct login is bound to, None only when no client was asked for
+    /// and none is configured
+    async fn resolve_client(
+        &self,
+        client_id: Option<&str>,
+    ) -> Result<Option<OAuthClientEntity>, AuthenticationServiceError> {
+        let Some(client_id) = client_id.or(self
+            .oauth_config
+            .first_party_client
+            .as_ref()
+            .map(|client| client.client_id.as_str()))
+        else {
+            return Ok(None);
+        };
+
+        self.oauth_client_repository
+            .find_by_client_id(client_id)
+            .await?
+            .filter(|client| client.first_party)
+            .map(Some)
+            .ok_or(AuthenticationServiceError::InvalidClient)
+    }
+
+    /// only first-party clients, or none at all, get this far, so the token is always first-party
+    fn generate_access_token(
+        &self,
+        user: &UserEntity,
+        client: Option<&OAuthClientEntity>,
+        session: &SessionEntity,
+    ) -> Result<String, AuthenticationServiceError> {
+        let credentials = JwtCredentials::new(
+            &user.email,
+            &user.identifier,
+            user.token_version,
+            TokenUse::Access,
+        )
+        .with_session(session)
+        .with_first_party(true);
+
+        match client {
+            Some(client) => self.token_service.generate_token(
+                &credentials.with_grant(client_grant(client)),
+                client.access_token_ttl(),
+            ),
+            None => self.token_service.generate_token(&credentials, TEN_MINUTES),
         }
     }
+
+    /// every completed sign-in starts a session of its own
+    async fn issue_login_tokens(
+        &self,
+        user: &UserEntity,
+        client: Option<&OAuthClientEntity>,
+        device: &DeviceInfo,
+    ) -> Result<LoginResponse, AuthenticationServiceError> {
+        let session = self.session_service.start(&user.identifier, device).await?;
+        let token = self.generate_access_token(user, client, &session)?;
+        let refresh_token = self
+            .token_service
+            .issue_refresh_token(
+                &user.identifier,
+                Some(session.family_identifier),
+                client.map(client_grant).as_ref(),
+            )
+            .await?;
+
+        Ok(LoginResponse::Authenticated {
+            token,
+            refresh_token,
+        })
+    }
+
+    /// the second factors the user has set up, empty when the password is enough
+    async fn second_factors(
+        &self,
+        user: &UserEntity,
+    ) -> Result<Vec<String>, AuthenticationServiceError> {
+        let mut methods = Vec::new();
+        if self.mfa_service.is_enrolled(&user.identifier).await? {
+            methods.push(MFA_METHOD_TOTP.to_string());
+        }
+        if self
+            .webauthn_service
+            .has_credentials(&user.identifier)
+            .await?
+        {
+            methods.push(MFA_METHOD_WEBAUTHN.to_string());
+        }
+
+        Ok(methods)
+    }
+
+    /// the password step of a login for a user with a second factor, the client is carried in
+    /// the challenge so the final tokens are bound to the same one
+    fn issue_mfa_challenge(
+        &self,
+        user: &UserEntity,
+        client: Option<&OAuthClientEntity>,
+        methods: Vec<String>,
+    ) -> Result<LoginResponse, AuthenticationServiceError> {
+        let mut credentials = JwtCredentials::new(
+            &user.email,
+            &user.identifier,
+            user.token_version,
+            TokenUse::MfaChallenge,
+        );
+        if let Some(client) = client {
+            credentials = credentials.with_grant(client_grant(client));
+        }
+
+        let expiry = self.mfa_service.challenge_expiry();
+        Ok(LoginResponse::MfaRequired {
+            mfa_token: self.token_service.generate_token(&credentials, expiry)?,
+            expires_in: expiry.as_secs(),
+            methods,
+        })
+    }
+
+    /// a passkey, a code from the authenticator or, failing those, one of the recovery codes
+    async fn verify_second_factor(
+        &self,
+        user: &UserEntity,
+        code: Option<&str>,
+        recovery_code: Option<&str>,
+        credential: Option<&AssertionCredential>,
+    ) -> Result<(), AuthenticationServiceError> {
+        match (credential, code, recovery_code) {
+            (Some(credential), _, _) => {
+                self.webauthn_service
+                    .authenticate(credential, Some(&user.identifier))
+                    .await?;
+            }
+            (None, Some(code), _) => self.mfa_service.verify_totp(&user.identifier, code).await?,
+            (None, None, Some(recovery_code)) => {
+                self.mfa_service
+                    .redeem_recovery_code(&user.identifier, recovery_code)
+                    .await?;
+                let remaining = self
+                    .mfa_service
+                    .recovery_codes_remaining(&user.identifier)
+                    .await?;
+                self.mailer_service.send_security_notification(
+                    &user.email,
+                    &user.first_name,
+                    &format!(
+                        "A recovery code was used to sign in to your account, {remaining} remain."
+                    ),
+                );
+            }
+            (None, None, None) => return Err(MfaServiceError::MissingCode.into()),
+        }
+
+        Ok(())
+    }
+
+    async fn ensure_email_is_free(&self, email: &str) -> Result<(), AuthenticationServiceError> {
+        if self.user_repository.find_by_email(email).await.is_some() {
+            return Err(AuthenticationServiceError::from(
+                UserServiceError::ConflictError("User with the email already exists".to_string()),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// ends every session and tells the user, after a second factor was added or removed
+    async fn finish_mfa_change(
+        &self,
+        user: &UserEntity,
+        notification: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.token_service
+            .revoke_all_tokens(&user.identifier)
+            .await?;
+        self.mailer_service
+            .send_security_notification(&user.email, &user.first_name, notification);
+
+        Ok(())
+    }
+
+    /// emails a fresh verification code and returns the token the code is redeemed with
+    async fn start_account_verification(
+        &self,
+        user: &UserEntity,
+    ) -> Result<String, AuthenticationServiceError> {
+        let otp = self
+            .otp_service
+            .issue(&user.identifier, OtpKind::AccountVerification)
+            .await?;
+        self.mailer_service.send_account_verification_email(
+            &user.email,
+            &user.first_name,
+            &otp,
+            self.otp_service.expiry(),
+        );
+
+        self.token_service.generate_token(
+            &JwtCredentials::new(
+                &user.email,
+                &user.identifier,
+                user.token_version,
+                TokenUse::Verification,
+            ),
+            self.otp_service.expiry(),
+        )
+    }
+
+    async fn send_password_reset_instructions(
+        &self,
+        email: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        let Some(UserEntity {
+            email,
+            identifier,
+            first_name,
+            ..
+        }) = self.user_repository.find_by_email(email).await
+        else {
+            return Ok(());
+        };
+
+        let token = self
+            .otp_service
+            .issue_token(
+                &identifier,
+                OtpKind::PasswordReset,
+                self.password_reset_config.expiry,
+            )
+            .await?;
+        self.mailer_service.send_password_reset_email(
+            &email,
+            &first_name,
+            &self.password_reset_config.reset_link(&token),
+            self.password_reset_config.expiry,
+        );
+
+        Ok(())
+    }
+
+    /// counts the failure and tells the owner, if there is one, when it locked the account
+    async fn record_login_failure(
+        &self,
+        email: &str,
+        ip_address: Option<IpAddr>,
+    ) -> Result<(), AuthenticationServiceError> {
+        let Some(lockout_duration) = self
+            .lockout_service
+            .record_failure(email, ip_address)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        if let Some(user) = self.user_repository.find_by_email(email).await {
+            self.mailer_service.send_security_notification(
+                &user.email,
+                &user.first_name,
+                &format!(
+                    "Sign-in to your account has been paused for {} minutes after repeated failed attempts. If these were not you, consider changing your password.",
+                    lockout_duration.as_secs() / 60
+                ),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// a new link or code replaces any sent before it, both live in the same one-time code slot
+    async fn send_email_login(
+        &self,
+        email: &str,
+        method: EmailLoginMethod,
+    ) -> Result<(), AuthenticationServiceError> {
+        let Some(user) = self.user_repository.find_by_email(email).await else {
+            return Ok(());
+        };
+
+        let expiry = self.email_login_config.expiry;
+        match method {
+            EmailLoginMethod::Link => {
+                let token = self
+                    .otp_service
+                    .issue_token(&user.identifier, OtpKind::EmailLogin, expiry)
+                    .await?;
+                self.mailer_service.send_login_link_email(
+                    &user.email,
+                    &user.first_name,
+                    &self.email_login_config.login_link(&token),
+                    expiry,
+                );
+            }
+            EmailLoginMethod::Code => {
+                let code = self
+                    .otp_service
+                    .issue_code(&user.identifier, OtpKind::EmailLogin, expiry)
+                    .await?;
+                self.mailer_service.send_login_code_email(
+                    &user.email,
+                    &user.first_name,
+                    &code,
+                    expiry,
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// checks the password, a hash made with a legacy algorithm or outdated parameters is
+    /// replaced once the password is known to be right
+    async fn verify_password(
+        &self,
+        user: &UserEntity,
+        raw_password: &str,
+    ) -> Result<bool, AuthenticationServiceError> {
+        if !self
+            .user_helper_service
+            .validate_password(raw_password, &user.password)
+            .await?
+        {
+            return Ok(false);
+        }
+
+        if self.user_helper_service.needs_rehash(&user.password)
+            && let Err(err) = self.rehash_password(user, raw_password).await
+        {
+            // the password was right, the old hash keeps working until the next attempt
+            log::error!(
+                "error upgrading the password hash of {}: {err}",
+                user.identifier
+            );
+        }
+
+        Ok(true)
+    }
+
+    /// a passkey signs in on its own, so adding one takes the password again unless the session
+    /// was only just signed in to, a leaked access token is not enough
+    async fn ensure_recent_authentication(
+        &self,
+        claims: &Claims,
+        user: &UserEntity,
+        password: Option<&str>,
+    ) -> Result<(), AuthenticationServiceError> {
+        if let Some(password) = password {
+            if !self.verify_password(user, password).await? {
+                return Err(AuthenticationServiceError::WrongCredentials);
+            }
+            return Ok(());
+        }
+
+        let window = chrono::Duration::from_std(self.webauthn_config.reauthentication_window)
+            .unwrap_or(chrono::Duration::zero());
+        match claims.auth_time {
+            Some(auth_time) if auth_time >= (chrono::Utc::now() - window).timestamp() => Ok(()),
+            _ => Err(AuthenticationServiceError::ReauthenticationRequired),
+        }
+    }
+
+    async fn rehash_password(
+        &self,
+        user: &UserEntity,
+        raw_password: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        let new_password = self.user_helper_service.hash_password(raw_password).await?;
+        self.user_repository
+            .upgrade_password_hash(&user.identifier, &user.password, &new_password)
+            .await?;
+
+        Ok(())
+    }
+
+    /// the user the provider account is linked to, on its first sign-in that is the user with the
+    /// same email address or, when there is none, a new one
+    async fn federated_user(
+        &self,
+        account: &FederatedAccount,
+    ) -> Result<UserEntity, AuthenticationServiceError> {
+        if let Some(user_identifier) = self.federation_service.linked_user(account).await? {
+            return self
+                .user_repository
+                .find_by_identifier(&user_identifier)
+                .await
+                .ok_or(AuthenticationServiceError::InvalidToken);
+        }
+
+        let profile = &account.profile;
+        let user = match self.user_repository.find_by_email(&profile.email).await {
+            // both sides have to have verified the address before it can join the accounts
+            Some(user)
+                if profile.email_verified && self.federation_service.can_link_by_email(&user) =>
+            {
+                user
+            }
+            Some(_) => return Err(FederationError::AccountExists.into()),
+            None => self.create_federated_user(profile).await?,
+        };
+        self.federation_service
+            .link(account, &user.identifier)
+            .await?;
+
+        Ok(user)
+    }
+
+    /// the account gets a random password nobody knows, a password reset sets a real one
+    async fn create_federated_user(
+        &self,
+        profile: &FederatedProfile,
+    ) -> Result<UserEntity, AuthenticationServiceError> {
+        let password_hash = self
+            .user_helper_service
+            .hash_password(&generate_opaque_token())
+            .await?;
+        let identifier = self
+            .user_repository
+            .create_user(CreateUserRequest {
+                email: profile.email.to_owned(),
+                password: password_hash,
+                first_name: profile.first_name.to_owned(),
+                last_name: profile.last_name.to_owned(),
+            })
+            .await?;
+        if profile.email_verified {
+            self.user_repository
+                .update_account_status(&identifier)
+                .await?;
+        }
+
+        self.user_repository
+            .find_by_identifier(&identifier)
+            .await
+            .ok_or_else(|| {
+                UserServiceError::OperationFailed("error retrieving the created account".into())
+                    .into()
+            })
+    }
+
+    /// every password change goes through here so outstanding reset links and tokens die with the
+    /// old password
+    async fn change_password(
+        &self,
+        user: &UserEntity,
+        raw_password: &str,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.check_password(
+            raw_password,
+            &PasswordContext {
+                email: &user.email,
+                first_name: &user.first_name,
+                last_name: &user.last_name,
+            },
+        )?;
+        let new_password = self.user_helper_service.hash_password(raw_password).await?;
+
+        self.user_repository
+            .update_password(&user.identifier, &new_password)
+            .await?;
+        self.otp_service
+            .revoke(&user.identifier, OtpKind::PasswordReset)
+            .await?;
+        self.token_service
+            .revoke_all_tokens(&user.identifier)
+            .await?;
+        self.mailer_service.send_security_notification(
+            &user.email,
+            &user.first_name,
+            "The password on your account was just changed.",
+        );
+
+        Ok(())
+    }
+}
+/// a first-party login is granted every scope its client allows
+fn client_grant(client: &OAuthClientEntity) -> OAuthGrant {
+    OAuthGrant {
+        client_id: client.client_id.to_string(),
+        scope: client.scopes.join(" "),
+    }
 }
+
 pub trait AuthenticationServiceTrait {
     fn create_account(
         &self,
         request: &CreateUserRequest,
-    ) -> impl std::future::Future<Output = Result<(), AuthenticationServiceError>> + Send;
+    ) -> impl std::future::Future<Output = Result<CreateUserResponse, AuthenticationServiceError>> + Send;
 
+    /// refused with `TooManyAttempts` while the email address or the client address is backing
+    /// off after failures, the same whether or not the account exists
     fn login(
         &self,
         request: &LoginRequest,
+        device: &DeviceInfo,
+    ) -> impl std::future::Future<Output = Result<LoginResponse, AuthenticationServiceError>> + Send;
+
+    /// exchanges the challenge from `login` and a second factor for the tokens
+    fn complete_mfa_login(
+        &self,
+        request: &MfaLoginRequest,
+        device: &DeviceInfo,
+    ) -> impl std::future::Future<Output = Result<LoginResponse, AuthenticationServiceError>> + Send;
+
+    /// the assertion options for a passkey used as the second factor of a login in progress
+    fn mfa_webauthn_options(
+        &self,
+        request: &MfaWebAuthnOptionsRequest,
+    ) -> impl std::future::Future<
+        Output = Result<CredentialRequestOptionsResponse, AuthenticationServiceError>,
+    > + Send;
+
+    fn passkey_login_options(
+        &self,
+    ) -> impl std::future::Future<
+        Output = Result<CredentialRequestOptionsResponse, AuthenticationServiceError>,
+    > + Send;
+
+    /// passwordless login, the passkey must have verified the user so it stands in for both
+    /// factors
+    fn passkey_login(
+        &self,
+        request: &PasskeyLoginRequest,
+        device: &DeviceInfo,
+    ) -> impl std::future::Future<Output = Result<LoginResponse, AuthenticationServiceError>> + Send;
+
+    /// emails a sign-in link or code, the response does not say whether the account exists
+    fn email_login(
+        &self,
+        request: &EmailLoginRequest,
+    ) -> impl std::future::Future<Output = Result<EmailLoginResponse, AuthenticationServiceError>> + Send;
+
+    /// the emailed link or code stands in for the password, a second factor is still asked for
+    fn verify_email_login(
+        &self,
+        request: &VerifyEmailLoginRequest,
+        device: &DeviceInfo,
+    ) -> impl std::future::Future<Output = Result<LoginResponse, AuthenticationServiceError>> + Send;
+
+    /// the page at the identity provider to send the user to
+    fn start_federated_login(
+        &self,
+        provider: &str,
+        request: &FederatedLoginRequest,
+    ) -> impl std::future::Future<Output = Result<String, AuthenticationServiceError>> + Send;
+
+    /// the provider stands in for the password, a second factor is still asked for, and the
+    /// user is created the first time the provider account signs in
+    fn complete_federated_login(
+        &self,
+        provider: &str,
+        request: &FederatedCallbackRequest,
+        device: &DeviceInfo,
     ) -> impl std::future::Future<Output = Result<LoginResponse, AuthenticationServiceError>> + Send;
 
     fn forgotten_password(
         &self,
 
         request: &ForgottenPasswordRequest,
-    ) -> impl std::future::Future<Output = Result<ForgottenPasswordResponse, AuthenticationServiceError>> + Send;
+    ) -> impl std::future::Future<
+        Output = Result<ForgottenPasswordResponse, AuthenticationServiceError>,
+    > + Send;
 
     fn set_new_password(
         &self,
         request: &SetNewPasswordRequest,
-        claims: &Claims,
-    ) -> impl std::future::Future<Output = Result<SetNewPasswordResponse, AuthenticationServiceError>> + Send;
+    ) -> impl std::future::Future<
+        Output = Result<SetNewPasswordResponse, AuthenticationServiceError>,
+    > + Send;
 
     fn verify_account(
         &self,
         claims: &Claims,
         request: &VerifyAccountRequest,
-    ) -> impl std::future::Future<Output = Result<VerifyAccountResponse, AuthenticationServiceError>> + Send;
+    ) -> impl std::future::Future<Output = Result<VerifyAccountResponse, AuthenticationServiceError>>
+    + Send;
 
+    fn resend_verification(
+        &self,
+        claims: &Claims,
+    ) -> impl std::future::Future<
+        Output = Result<ResendVerificationResponse, AuthenticationServiceError>,
+    > + Send;
+
+    /// also records activity on the session the refresh token belongs to
     fn request_refresh_token(
         &self,
         request: &RefreshTokenRequest,
+        device: &DeviceInfo,
     ) -> impl std::future::Future<Output = Result<RefreshTokenResponse, AuthenticationServiceError>> + Send;
+
+    fn logout(
+        &self,
+        claims: &Claims,
+        request: &LogoutRequest,
+    ) -> impl std::future::Future<Output = Result<LogoutResponse, AuthenticationServiceError>> + Send;
+
+    fn revoke_token(
+        &self,
+        claims: &Claims,
+        request: &RevokeTokenRequest,
+    ) -> impl std::future::Future<Output = Result<RevokeTokenResponse, AuthenticationServiceError>> + Send;
+
+    fn logout_everywhere(
+        &self,
+        claims: &Claims,
+    ) -> impl std::future::Future<
+        Output = Result<LogoutEverywhereResponse, AuthenticationServiceError>,
+    > + Send;
+
+    /// administrative "sign out everywhere" for another user
+    fn logout_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<
+        Output = Result<LogoutEverywhereResponse, AuthenticationServiceError>,
+    > + Send;
+
+    /// administrative unlock of an account locked out after failed sign-ins
+    fn unlock_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<UnlockUserResponse, AuthenticationServiceError>> + Send;
+
+    /// nothing changes yet, the new address is kept pending and sent a code to prove it is theirs
+    fn change_email(
+        &self,
+        claims: &Claims,
+        request: &ChangeEmailRequest,
+    ) -> impl std::future::Future<Output = Result<ChangeEmailResponse, AuthenticationServiceError>> + Send;
+
+    /// switches to the address `change_email` parked once the code sent there is confirmed
+    fn confirm_email_change(
+        &self,
+        claims: &Claims,
+        request: &ConfirmEmailChangeRequest,
+    ) -> impl std::future::Future<
+        Output = Result<ConfirmEmailChangeResponse, AuthenticationServiceError>,
+    > + Send;
+
+    fn mfa_status(
+        &self,
+        claims: &Claims,
+    ) -> impl std::future::Future<Output = Result<MfaStatusResponse, AuthenticationServiceError>> + Send;
+
+    /// the authenticator is not asked for at login until it is confirmed
+    fn enroll_totp(
+        &self,
+        claims: &Claims,
+    ) -> impl std::future::Future<
+        Output = Result<TotpEnrollmentResponse, AuthenticationServiceError>,
+    > + Send;
+
+    fn confirm_totp(
+        &self,
+        claims: &Claims,
+        request: &ConfirmTotpRequest,
+    ) -> impl std::future::Future<Output = Result<RecoveryCodesResponse, AuthenticationServiceError>>
+    + Send;
+
+    fn disable_totp(
+        &self,
+        claims: &Claims,
+        request: &DisableTotpRequest,
+    ) -> impl std::future::Future<Output = Result<DisableTotpResponse, AuthenticationServiceError>> + Send;
+
+    fn regenerate_recovery_codes(
+        &self,
+        claims: &Claims,
+        request: &RegenerateRecoveryCodesRequest,
+    ) -> impl std::future::Future<Output = Result<RecoveryCodesResponse, AuthenticationServiceError>>
+    + Send;
+
+    fn webauthn_registration_options(
+        &self,
+        claims: &Claims,
+        request: &RegistrationOptionsRequest,
+    ) -> impl std::future::Future<
+        Output = Result<CredentialCreationOptionsResponse, AuthenticationServiceError>,
+    > + Send;
+
+    fn register_webauthn_credential(
+        &self,
+        claims: &Claims,
+        request: &RegisterCredentialRequest,
+    ) -> impl std::future::Future<
+        Output = Result<WebAuthnCredentialResponse, AuthenticationServiceError>,
+    > + Send;
+
+    fn list_webauthn_credentials(
+        &self,
+        claims: &Claims,
+    ) -> impl std::future::Future<
+        Output = Result<Vec<WebAuthnCredentialResponse>, AuthenticationServiceError>,
+    > + Send;
+
+    fn remove_webauthn_credential(
+        &self,
+        claims: &Claims,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<
+        Output = Result<RemoveWebAuthnCredentialResponse, AuthenticationServiceError>,
+    > + Send;
+
+    /// the sessions still signed in, most recently seen first
+    fn list_sessions(
+        &self,
+        claims: &Claims,
+    ) -> impl std::future::Future<Output = Result<Vec<SessionResponse>, AuthenticationServiceError>> + Send;
+
+    fn revoke_session(
+        &self,
+        claims: &Claims,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<RevokeSessionResponse, AuthenticationServiceError>>
+    + Send;
+
+    /// signs out every session except the one making the request
+    fn revoke_other_sessions(
+        &self,
+        claims: &Claims,
+    ) -> impl std::future::Future<
+        Output = Result<RevokeOtherSessionsResponse, AuthenticationServiceError>,
+    > + Send;
 }
 
 impl AuthenticationServiceTrait for AuthenticationService {
     async fn create_account(
         &self,
         request: &CreateUserRequest,
-    ) -> Result<(), AuthenticationServiceError> {
+    ) -> Result<CreateUserResponse, AuthenticationServiceError> {
         if self
             .user_repository
             .find_by_email(&request.email)
@@ -85,7 +873,18 @@ impl AuthenticationServiceTrait for AuthenticationService {
             ));
         }
 
-        let password_hash = self.user_helper_service.hash_password(&request.password)?;
+        self.check_password(
+            &request.password,
+            &PasswordContext {
+                email: &request.email,
+                first_name: &request.first_name,
+                last_name: &request.last_name,
+            },
+        )?;
+        let password_hash = self
+            .user_helper_service
+            .hash_password(&request.password)
+            .await?;
         let user = CreateUserRequest {
             password: password_hash,
             first_name: request.first_name.to_owned(),
@@ -93,71 +892,306 @@ impl AuthenticationServiceTrait for AuthenticationService {
             last_name: request.last_name.to_owned(),
         };
 
-        self.user_repository.create_user(user).await.map_err(|err| {
-            log::error!("{}", err.to_string());
-            AuthenticationServiceError::from(err)
+        let identifier = self
+            .user_repository
+            .create_user(user)
+            .await
+            .map_err(|err| {
+                log::error!("{}", err);
+                AuthenticationServiceError::from(err)
+            })?;
+        let Some(user) = self.user_repository.find_by_identifier(&identifier).await else {
+            return Err(AuthenticationServiceError::from(
+                UserServiceError::OperationFailed(
+                    "error retrieving the created account".to_string(),
+                ),
+            ));
+        };
+
+        let verification_token = self.start_account_verification(&user).await?;
+
+        Ok(CreateUserResponse {
+            email: request.email.to_owned(),
+            first_name: request.first_name.to_owned(),
+            last_name: request.last_name.to_owned(),
+            verification_token,
         })
     }
 
     async fn login(
         &self,
         request: &LoginRequest,
+        device: &DeviceInfo,
     ) -> Result<LoginResponse, AuthenticationServiceError> {
+        let ip_address = device.ip_address;
+        self.lockout_service
+            .check(&request.email, ip_address)
+            .await?;
+
         let Some(user) = self.user_repository.find_by_email(&request.email).await else {
+            // spend the time a password check would so the response does not give away that
+            // there is no account
+            self.user_helper_service
+                .hash_password(&request.password)
+                .await?;
+            self.record_login_failure(&request.email, ip_address)
+                .await?;
             return Err(AuthenticationServiceError::WrongCredentials);
         };
 
-        let valid_password = self
-            .user_helper_service
-            .validate_password(&request.password, &user.password)?;
-        if !valid_password {
+        if !self.verify_password(&user, &request.password).await? {
+            self.record_login_failure(&request.email, ip_address)
+                .await?;
             return Err(AuthenticationServiceError::WrongCredentials);
         }
 
-        let token =
-            JwtCredentials::new(&user.email, &user.identifier).generate_token(TEN_MINUTES)?;
+        let client = self.resolve_client(request.client_id.as_deref()).await?;
+        let methods = self.second_factors(&user).await?;
+        if !methods.is_empty() {
+            // the failures are only forgotten once the second factor was passed as well
+            return self.issue_mfa_challenge(&user, client.as_ref(), methods);
+        }
+        self.lockout_service.record_success(&request.email).await?;
 
-        Ok(LoginResponse { token })
+        self.issue_login_tokens(&user, client.as_ref(), device)
+            .await
     }
 
-    async fn forgotten_password(
+    async fn complete_mfa_login(
         &self,
-        request: &ForgottenPasswordRequest,
-    ) -> Result<ForgottenPasswordResponse, AuthenticationServiceError> {
-        let user = self.user_repository.find_by_email(&request.email).await;
-        if user.is_none() {
-            return Err(AuthenticationServiceError::WrongCredentials);
+        request: &MfaLoginRequest,
+        device: &DeviceInfo,
+    ) -> Result<LoginResponse, AuthenticationServiceError> {
+        let claims = self
+            .token_service
+            .decode_token(&request.mfa_token, TokenUse::MfaChallenge)
+            .await?;
+        // a challenge is good for one attempt, a wrong code means starting over with the password
+        self.token_service.revoke_token(&claims).await?;
+
+        let Some(user) = self.user_repository.find_by_identifier(&claims.sub).await else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+        self.lockout_service
+            .check(&user.email, device.ip_address)
+            .await?;
+        // a wrong code counts towards the lockout just like a wrong password
+        match self
+            .verify_second_factor(
+                &user,
+                request.code.as_deref(),
+                request.recovery_code.as_deref(),
+                request.credential.as_ref(),
+            )
+            .await
+        {
+            Err(AuthenticationServiceError::MfaServiceError(MfaServiceError::InvalidCode)) => {
+                self.record_login_failure(&user.email, device.ip_address)
+                    .await?;
+                return Err(MfaServiceError::InvalidCode.into());
+            }
+            result => result?,
+        }
+        self.lockout_service.record_success(&user.email).await?;
+
+        let client = self.resolve_client(claims.client_id.as_deref()).await?;
+        self.issue_login_tokens(&user, client.as_ref(), device)
+            .await
+    }
+
+    async fn mfa_webauthn_options(
+        &self,
+        request: &MfaWebAuthnOptionsRequest,
+    ) -> Result<CredentialRequestOptionsResponse, AuthenticationServiceError> {
+        let claims = self
+            .token_service
+            .decode_token(&request.mfa_token, TokenUse::MfaChallenge)
+            .await?;
+
+        Ok(self
+            .webauthn_service
+            .request_options(Some(&claims.sub))
+            .await?)
+    }
+
+    async fn passkey_login_options(
+        &self,
+    ) -> Result<CredentialRequestOptionsResponse, AuthenticationServiceError> {
+        Ok(self.webauthn_service.request_options(None).await?)
+    }
+
+    async fn passkey_login(
+        &self,
+        request: &PasskeyLoginRequest,
+        device: &DeviceInfo,
+    ) -> Result<LoginResponse, AuthenticationServiceError> {
+        let user_identifier = self
+            .webauthn_service
+            .authenticate(&request.credential, None)
+            .await?;
+        let Some(user) = self
+            .user_repository
+            .find_by_identifier(&user_identifier)
+            .await
+        else {
+            return Err(AuthenticationServiceError::InvalidToken);
         };
 
-        tokio::task::spawn(async move { todo!("send account retrival email") });
+        let client = self.resolve_client(request.client_id.as_deref()).await?;
+        self.issue_login_tokens(&user, client.as_ref(), device)
+            .await
+    }
 
-        let UserEntity {
-            email, identifier, ..
-        } = user.unwrap();
+    async fn email_login(
+        &self,
+        request: &EmailLoginRequest,
+    ) -> Result<EmailLoginResponse, AuthenticationServiceError> {
+        // as with password resets the email goes out off the request
+        let auth_service = self.clone();
+        let email = request.email.to_owned();
+        let method = request.method;
+        tokio::task::spawn(async move {
+            if let Err(err) = auth_service.send_email_login(&email, method).await {
+                log::error!("error sending email sign-in: {err}");
+            }
+        });
 
-        let token = JwtCredentials::new(&email, &identifier).generate_token(TEN_MINUTES)?;
-        Ok(ForgottenPasswordResponse { token })
+        Ok(EmailLoginResponse {})
+    }
+
+    async fn verify_email_login(
+        &self,
+        request: &VerifyEmailLoginRequest,
+        device: &DeviceInfo,
+    ) -> Result<LoginResponse, AuthenticationServiceError> {
+        let ip_address = device.ip_address;
+        let user = match (&request.token, &request.email, &request.code) {
+            (Some(token), _, _) => {
+                let user_identifier = self
+                    .otp_service
+                    .redeem_token(OtpKind::EmailLogin, token)
+                    .await?;
+                let Some(user) = self
+                    .user_repository
+                    .find_by_identifier(&user_identifier)
+                    .await
+                else {
+                    return Err(AuthenticationServiceError::InvalidToken);
+                };
+                // a link cannot be guessed, but it does not get around a lockout either
+                self.lockout_service.check(&user.email, ip_address).await?;
+                user
+            }
+            (None, Some(email), Some(code)) => {
+                // codes are short enough to guess, so they are throttled like passwords
+                self.lockout_service.check(email, ip_address).await?;
+
+                // an unknown address fails the same way a wrong code does
+                let verified = match self.user_repository.find_by_email(email).await {
+                    Some(user) => self
+                        .otp_service
+                        .verify(&user.identifier, OtpKind::EmailLogin, code)
+                        .await
+                        .map(|_| user),
+                    None => Err(OtpServiceError::InvalidCode),
+                };
+                match verified {
+                    Ok(user) => user,
+                    Err(err) => {
+                        self.record_login_failure(email, ip_address).await?;
+                        return Err(err.into());
+                    }
+                }
+            }
+            _ => return Err(AuthenticationServiceError::MissingEmailLoginCode),
+        };
+
+        let client = self.resolve_client(request.client_id.as_deref()).await?;
+        let methods = self.second_factors(&user).await?;
+        if !methods.is_empty() {
+            // as with a password the failures stand until the second factor was passed
+            return self.issue_mfa_challenge(&user, client.as_ref(), methods);
+        }
+        self.lockout_service.record_success(&user.email).await?;
+
+        self.issue_login_tokens(&user, client.as_ref(), device)
+            .await
+    }
+
+    async fn start_federated_login(
+        &self,
+        provider: &str,
+        request: &FederatedLoginRequest,
+    ) -> Result<String, AuthenticationServiceError> {
+        // checked now rather than after the round trip through the provider
+        let client = self.resolve_client(request.client_id.as_deref()).await?;
+
+        Ok(self
+            .federation_service
+            .start_login(
+                provider,
+                client.as_ref().map(|client| client.client_id.as_str()),
+            )
+            .await?)
+    }
+
+    async fn complete_federated_login(
+        &self,
+        provider: &str,
+        request: &FederatedCallbackRequest,
+        device: &DeviceInfo,
+    ) -> Result<LoginResponse, AuthenticationServiceError> {
+        let account = self
+            .federation_service
+            .complete_login(provider, request)
+            .await?;
+        let user = self.federated_user(&account).await?;
+
+        let client = self.resolve_client(account.client_id.as_deref()).await?;
+        let methods = self.second_factors(&user).await?;
+        if !methods.is_empty() {
+            return self.issue_mfa_challenge(&user, client.as_ref(), methods);
+        }
+
+        self.issue_login_tokens(&user, client.as_ref(), device)
+            .await
+    }
+
+    async fn forgotten_password(
+        &self,
+        request: &ForgottenPasswordRequest,
+    ) -> Result<ForgottenPasswordResponse, AuthenticationServiceError> {
+        // the work happens off the request so the response is the same, in content and timing,
+        // whether or not the account exists
+        let auth_service = self.clone();
+        let email = request.email.to_owned();
+        tokio::task::spawn(async move {
+            if let Err(err) = auth_service.send_password_reset_instructions(&email).await {
+                log::error!("error sending password reset instructions: {err}");
+            }
+        });
+
+        Ok(ForgottenPasswordResponse {})
     }
 
     async fn set_new_password(
         &self,
         request: &SetNewPasswordRequest,
-        claims: &Claims,
     ) -> Result<SetNewPasswordResponse, AuthenticationServiceError> {
-        let new_password = self.user_helper_service.hash_password(&request.password)?;
-    
-        if self
+        let user_identifier = self
+            .otp_service
+            .redeem_token(OtpKind::PasswordReset, &request.token)
+            .await?;
+
+        let Some(user) = self
             .user_repository
-            .find_by_identifier(&claims.identifier)
+            .find_by_identifier(&user_identifier)
             .await
-            .is_none()
-        {
+        else {
             return Err(AuthenticationServiceError::InvalidToken);
         };
 
-        self.user_repository
-            .update_password(&claims.identifier, &new_password)
-            .await?;
+        self.change_password(&user, &request.password).await?;
 
         Ok(SetNewPasswordResponse {})
     }
@@ -165,33 +1199,433 @@ impl AuthenticationServiceTrait for AuthenticationService {
     async fn verify_account(
         &self,
         claims: &Claims,
-        _request: &VerifyAccountRequest,
+        request: &VerifyAccountRequest,
     ) -> Result<VerifyAccountResponse, AuthenticationServiceError> {
         if self
             .user_repository
-            .find_by_identifier(&claims.identifier)
+            .find_by_identifier(&claims.sub)
             .await
             .is_none()
         {
             return Err(AuthenticationServiceError::InvalidToken);
         };
 
-        //todo: validate account credentials
+        self.otp_service
+            .verify(&claims.sub, OtpKind::AccountVerification, &request.otp)
+            .await?;
+
         self.user_repository
-            .update_account_status(&claims.identifier)
+            .update_account_status(&claims.sub)
             .await?;
         Ok(VerifyAccountResponse {})
     }
 
+    async fn resend_verification(
+        &self,
+        claims: &Claims,
+    ) -> Result<ResendVerificationResponse, AuthenticationServiceError> {
+        let Some(user) = self.user_repository.find_by_identifier(&claims.sub).await else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+
+        if user.is_active {
+            return Err(AuthenticationServiceError::AccountAlreadyVerified);
+        }
+
+        let verification_token = self.start_account_verification(&user).await?;
+
+        Ok(ResendVerificationResponse { verification_token })
+    }
+
     async fn request_refresh_token(
         &self,
         request: &RefreshTokenRequest,
+        device: &DeviceInfo,
     ) -> Result<RefreshTokenResponse, AuthenticationServiceError> {
-        let refresh_token = JwtCredentials::new(&request.email, &request.identifier)
-            .generate_token(TWENTY_FIVE_MINUTES)?;
+        let client = self.resolve_client(request.client_id.as_deref()).await?;
+        let RotatedRefreshToken {
+            user_identifier,
+            family_identifier,
+            refresh_token,
+            ..
+        } = self
+            .token_service
+            .rotate_refresh_token(
+                &request.refresh_token,
+                client.as_ref().map(|client| client.client_id.as_str()),
+            )
+            .await?;
+
+        let Some(user) = self
+            .user_repository
+            .find_by_identifier(&user_identifier)
+            .await
+        else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+
+        let session = self
+            .session_service
+            .touch(&user_identifier, &family_identifier, device)
+            .await?;
+        let token = self.generate_access_token(&user, client.as_ref(), &session)?;
 
         Ok(RefreshTokenResponse {
-            token: refresh_token,
+            token,
+            refresh_token,
         })
     }
+
+    async fn logout(
+        &self,
+        claims: &Claims,
+        request: &LogoutRequest,
+    ) -> Result<LogoutResponse, AuthenticationServiceError> {
+        if let Some(refresh_token) = &request.refresh_token {
+            self.token_service
+                .revoke_refresh_token(refresh_token, &claims.sub)
+                .await?;
+        }
+        self.token_service.revoke_token(claims).await?;
+
+        Ok(LogoutResponse {})
+    }
+
+    async fn revoke_token(
+        &self,
+        claims: &Claims,
+        request: &RevokeTokenRequest,
+    ) -> Result<RevokeTokenResponse, AuthenticationServiceError> {
+        // signed tokens are recognisable by their segments, anything else is a refresh token
+        if request.token.contains('.') {
+            let revoked_claims = self.token_service.verify_token(&request.token).await?;
+            if revoked_claims.sub != claims.sub {
+                return Err(AuthenticationServiceError::InvalidToken);
+            }
+            self.token_service.revoke_token(&revoked_claims).await?;
+        } else {
+            self.token_service
+                .revoke_refresh_token(&request.token, &claims.sub)
+                .await?;
+        }
+
+        Ok(RevokeTokenResponse {})
+    }
+
+    async fn logout_everywhere(
+        &self,
+        claims: &Claims,
+    ) -> Result<LogoutEverywhereResponse, AuthenticationServiceError> {
+        self.token_service.revoke_all_tokens(&claims.sub).await?;
+
+        Ok(LogoutEverywhereResponse {})
+    }
+
+    async fn logout_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> Result<LogoutEverywhereResponse, AuthenticationServiceError> {
+        if self
+            .user_repository
+            .find_by_identifier(user_identifier)
+            .await
+            .is_none()
+        {
+            return Err(AuthenticationServiceError::from(
+                UserServiceError::NotFound("user not found".to_string()),
+            ));
+        }
+        self.token_service
+            .revoke_all_tokens(user_identifier)
+            .await?;
+
+        Ok(LogoutEverywhereResponse {})
+    }
+
+    async fn unlock_user(
+        &self,
+        user_identifier: &Uuid,
+    ) -> Result<UnlockUserResponse, AuthenticationServiceError> {
+        let Some(user) = self
+            .user_repository
+            .find_by_identifier(user_identifier)
+            .await
+        else {
+            return Err(AuthenticationServiceError::from(
+                UserServiceError::NotFound("user not found".to_string()),
+            ));
+        };
+        self.lockout_service.unlock(&user.email).await?;
+
+        Ok(UnlockUserResponse {})
+    }
+
+    async fn change_email(
+        &self,
+        claims: &Claims,
+        request: &ChangeEmailRequest,
+    ) -> Result<ChangeEmailResponse, AuthenticationServiceError> {
+        let Some(user) = self.user_repository.find_by_identifier(&claims.sub).await else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+
+        if !self.verify_password(&user, &request.password).await? {
+            return Err(AuthenticationServiceError::WrongCredentials);
+        }
+
+        self.ensure_email_is_free(&request.email).await?;
+
+        self.user_repository
+            .set_pending_email(&user.identifier, &request.email)
+            .await?;
+        let otp = self
+            .otp_service
+            .issue(&user.identifier, OtpKind::EmailChange)
+            .await?;
+        self.mailer_service.send_email_change_email(
+            &request.email,
+            &user.first_name,
+            &otp,
+            self.otp_service.expiry(),
+        );
+
+        Ok(ChangeEmailResponse {})
+    }
+
+    async fn confirm_email_change(
+        &self,
+        claims: &Claims,
+        request: &ConfirmEmailChangeRequest,
+    ) -> Result<ConfirmEmailChangeResponse, AuthenticationServiceError> {
+        let Some(user) = self.user_repository.find_by_identifier(&claims.sub).await else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+        let Some(pending_email) = user.pending_email.as_deref() else {
+            return Err(AuthenticationServiceError::NoPendingEmailChange);
+        };
+
+        self.otp_service
+            .verify(&user.identifier, OtpKind::EmailChange, &request.otp)
+            .await?;
+        // the address may have been taken while the code was on its way
+        self.ensure_email_is_free(pending_email).await?;
+
+        if self
+            .user_repository
+            .confirm_pending_email(&user.identifier)
+            .await?
+            .is_none()
+        {
+            return Err(AuthenticationServiceError::NoPendingEmailChange);
+        }
+        self.token_service
+            .revoke_all_tokens(&user.identifier)
+            .await?;
+        // the previous address is told, in case it is the account owner who did not make the change
+        self.mailer_service.send_security_notification(
+            &user.email,
+            &user.first_name,
+            "The email address on your account was just changed.",
+        );
+
+        Ok(ConfirmEmailChangeResponse {})
+    }
+
+    async fn mfa_status(
+        &self,
+        claims: &Claims,
+    ) -> Result<MfaStatusResponse, AuthenticationServiceError> {
+        Ok(MfaStatusResponse {
+            totp_enabled: self.mfa_service.is_enrolled(&claims.sub).await?,
+            passkeys: self
+                .webauthn_service
+                .list_credentials(&claims.sub)
+                .await?
+                .len(),
+            recovery_codes_remaining: self
+                .mfa_service
+                .recovery_codes_remaining(&claims.sub)
+                .await?,
+        })
+    }
+
+    async fn enroll_totp(
+        &self,
+        claims: &Claims,
+    ) -> Result<TotpEnrollmentResponse, AuthenticationServiceError> {
+        let Some(user) = self.user_repository.find_by_identifier(&claims.sub).await else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+
+        Ok(self.mfa_service.start_totp_enrollment(&user).await?)
+    }
+
+    async fn confirm_totp(
+        &self,
+        claims: &Claims,
+        request: &ConfirmTotpRequest,
+    ) -> Result<RecoveryCodesResponse, AuthenticationServiceError> {
+        let Some(user) = self.user_repository.find_by_identifier(&claims.sub).await else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+
+        let recovery_codes = self
+            .mfa_service
+            .confirm_totp_enrollment(&user.identifier, &request.code)
+            .await?;
+        self.finish_mfa_change(
+            &user,
+            "Two-factor authentication was just turned on for your account.",
+        )
+        .await?;
+
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    async fn disable_totp(
+        &self,
+        claims: &Claims,
+        request: &DisableTotpRequest,
+    ) -> Result<DisableTotpResponse, AuthenticationServiceError> {
+        let Some(user) = self.user_repository.find_by_identifier(&claims.sub).await else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+
+        if !self.verify_password(&user, &request.password).await? {
+            return Err(AuthenticationServiceError::WrongCredentials);
+        }
+        self.verify_second_factor(
+            &user,
+            request.code.as_deref(),
+            request.recovery_code.as_deref(),
+            None,
+        )
+        .await?;
+
+        self.mfa_service.remove(&user.identifier).await?;
+        self.finish_mfa_change(
+            &user,
+            "Two-factor authentication was just turned off for your account.",
+        )
+        .await?;
+
+        Ok(DisableTotpResponse {})
+    }
+
+    async fn regenerate_recovery_codes(
+        &self,
+        claims: &Claims,
+        request: &RegenerateRecoveryCodesRequest,
+    ) -> Result<RecoveryCodesResponse, AuthenticationServiceError> {
+        let Some(user) = self.user_repository.find_by_identifier(&claims.sub).await else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+
+        self.mfa_service
+            .verify_totp(&user.identifier, &request.code)
+            .await?;
+        let recovery_codes = self
+            .mfa_service
+            .regenerate_recovery_codes(&user.identifier)
+            .await?;
+        self.mailer_service.send_security_notification(
+            &user.email,
+            &user.first_name,
+            "New recovery codes were just generated for your account, the previous ones no longer work.",
+        );
+
+        Ok(RecoveryCodesResponse { recovery_codes })
+    }
+
+    async fn webauthn_registration_options(
+        &self,
+        claims: &Claims,
+        request: &RegistrationOptionsRequest,
+    ) -> Result<CredentialCreationOptionsResponse, AuthenticationServiceError> {
+        let Some(user) = self.user_repository.find_by_identifier(&claims.sub).await else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+        self.ensure_recent_authentication(claims, &user, request.password.as_deref())
+            .await?;
+
+        Ok(self.webauthn_service.creation_options(&user).await?)
+    }
+
+    async fn register_webauthn_credential(
+        &self,
+        claims: &Claims,
+        request: &RegisterCredentialRequest,
+    ) -> Result<WebAuthnCredentialResponse, AuthenticationServiceError> {
+        let Some(user) = self.user_repository.find_by_identifier(&claims.sub).await else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+        self.ensure_recent_authentication(claims, &user, request.password.as_deref())
+            .await?;
+
+        let credential = self
+            .webauthn_service
+            .register(&user.identifier, request)
+            .await?;
+        self.finish_mfa_change(&user, "A passkey was just added to your account.")
+            .await?;
+
+        Ok(credential)
+    }
+
+    async fn list_webauthn_credentials(
+        &self,
+        claims: &Claims,
+    ) -> Result<Vec<WebAuthnCredentialResponse>, AuthenticationServiceError> {
+        Ok(self.webauthn_service.list_credentials(&claims.sub).await?)
+    }
+
+    async fn remove_webauthn_credential(
+        &self,
+        claims: &Claims,
+        identifier: &Uuid,
+    ) -> Result<RemoveWebAuthnCredentialResponse, AuthenticationServiceError> {
+        let Some(user) = self.user_repository.find_by_identifier(&claims.sub).await else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+
+        self.webauthn_service
+            .remove_credential(&user.identifier, identifier)
+            .await?;
+        self.finish_mfa_change(&user, "A passkey was just removed from your account.")
+            .await?;
+
+        Ok(RemoveWebAuthnCredentialResponse {})
+    }
+
+    async fn list_sessions(
+        &self,
+        claims: &Claims,
+    ) -> Result<Vec<SessionResponse>, AuthenticationServiceError> {
+        Ok(self
+            .session_service
+            .list(&claims.sub, claims.sid.as_ref())
+            .await?)
+    }
+
+    async fn revoke_session(
+        &self,
+        claims: &Claims,
+        identifier: &Uuid,
+    ) -> Result<RevokeSessionResponse, AuthenticationServiceError> {
+        self.session_service.revoke(&claims.sub, identifier).await?;
+
+        Ok(RevokeSessionResponse {})
+    }
+
+    async fn revoke_other_sessions(
+        &self,
+        claims: &Claims,
+    ) -> Result<RevokeOtherSessionsResponse, AuthenticationServiceError> {
+        let revoked = self
+            .session_service
+            .revoke_others(&claims.sub, claims.sid.as_ref())
+            .await?;
+
+        Ok(RevokeOtherSessionsResponse { revoked })
+    }
 }
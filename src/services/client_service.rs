@@ -0,0 +1,212 @@
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    adapters::{
+        dto::oauth::GRANT_CLIENT_CREDENTIALS,
+        requests::clients::{CreateClientRequest, UpdateClientRequest, requires_redirect_uris},
+        response::clients::{ClientResponse, ClientSecretResponse, CreateClientResponse},
+    },
+    entities::oauth_client::OAuthClientEntity,
+    errors::client_service_error::ClientServiceError,
+    repositories::oauth_client_repository::{OAuthClientRepository, OAuthClientRepositoryTrait},
+    shared::crypto::{generate_opaque_token, sha256_hex},
+};
+
+const DEFAULT_ACCESS_TOKEN_TTL_SECONDS: i32 = 10 * 60;
+const DEFAULT_REFRESH_TOKEN_TTL_SECONDS: i32 = 30 * 24 * 60 * 60;
+
+#[derive(Clone)]
+pub struct ClientService {
+    oauth_client_repository: OAuthClientRepository,
+}
+
+impl ClientService {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            oauth_client_repository: OAuthClientRepository::init(pool),
+        }
+    }
+
+    async fn find_client(&self, client_id: &str) -> Result<OAuthClientEntity, ClientServiceError> {
+        self.oauth_client_repository
+            .find_by_client_id(client_id)
+            .await?
+            .ok_or(ClientServiceError::NotFound)
+    }
+}
+
+/// secrets are 256 random bits, a plain SHA-256 is enough to keep them safe at rest
+fn generate_client_secret() -> (String, String) {
+    let client_secret = generate_opaque_token();
+    let client_secret_hash = sha256_hex(&client_secret);
+    (client_secret, client_secret_hash)
+}
+
+fn check_grant_types(
+    grant_types: &[String],
+    redirect_uris: &[String],
+    confidential: bool,
+) -> Result<(), ClientServiceError> {
+    if requires_redirect_uris(grant_types, redirect_uris) {
+        return Err(ClientServiceError::InvalidRequest(
+            "clients using the authorization code grant need at least one redirect uri".into(),
+        ));
+    }
+
+    if !confidential
+        && grant_types
+            .iter()
+            .any(|grant_type| grant_type == GRANT_CLIENT_CREDENTIALS)
+    {
+        return Err(ClientServiceError::InvalidRequest(
+            "only confidential clients can use the client credentials grant".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub trait ClientServiceTrait {
+    fn list_clients(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Vec<ClientResponse>, ClientServiceError>> + Send;
+
+    fn create_client(
+        &self,
+        request: &CreateClientRequest,
+    ) -> impl std::future::Future<Output = Result<CreateClientResponse, ClientServiceError>> + Send;
+
+    fn retrieve_client(
+        &self,
+        client_id: &str,
+    ) -> impl std::future::Future<Output = Result<ClientResponse, ClientServiceError>> + Send;
+
+    fn update_client(
+        &self,
+        client_id: &str,
+        request: &UpdateClientRequest,
+    ) -> impl std::future::Future<Output = Result<ClientResponse, ClientServiceError>> + Send;
+
+    fn delete_client(
+        &self,
+        client_id: &str,
+    ) -> impl std::future::Future<Output = Result<(), ClientServiceError>> + Send;
+
+    /// replaces the secret of a confidential client, the old one stops working immediately
+    fn rotate_client_secret(
+        &self,
+        client_id: &str,
+    ) -> impl std::future::Future<Output = Result<ClientSecretResponse, ClientServiceError>> + Send;
+}
+
+impl ClientServiceTrait for ClientService {
+    async fn list_clients(&self) -> Result<Vec<ClientResponse>, ClientServiceError> {
+        let clients = self.oauth_client_repository.find_all().await?;
+
+        Ok(clients.into_iter().map(ClientResponse::from).collect())
+    }
+
+    async fn create_client(
+        &self,
+        request: &CreateClientRequest,
+    ) -> Result<CreateClientResponse, ClientServiceError> {
+        check_grant_types(
+            &request.grant_types,
+            &request.redirect_uris,
+            request.confidential,
+        )?;
+
+        let (client_secret, client_secret_hash) = if request.confidential {
+            let (client_secret, client_secret_hash) = generate_client_secret();
+            (Some(client_secret), Some(client_secret_hash))
+        } else {
+            (None, None)
+        };
+
+        let now = chrono::Utc::now();
+        let client = OAuthClientEntity {
+            client_id: uuid::Uuid::new_v4().to_string(),
+            name: request.name.to_owned(),
+            redirect_uris: request.redirect_uris.to_owned(),
+            created_at: now,
+            updated_at: None,
+            client_secret_hash,
+            grant_types: request.grant_types.to_owned(),
+            scopes: request.scopes.to_owned(),
+            access_token_ttl_seconds: request
+                .access_token_ttl_seconds
+                .unwrap_or(DEFAULT_ACCESS_TOKEN_TTL_SECONDS),
+            refresh_token_ttl_seconds: request
+                .refresh_token_ttl_seconds
+                .unwrap_or(DEFAULT_REFRESH_TOKEN_TTL_SECONDS),
+            first_party: request.first_party,
+        };
+        self.oauth_client_repository.create(&client).await?;
+
+        Ok(CreateClientResponse {
+            client: ClientResponse::from(client),
+            client_secret,
+        })
+    }
+
+    async fn retrieve_client(&self, client_id: &str) -> Result<ClientResponse, ClientServiceError> {
+        self.find_client(client_id).await.map(ClientResponse::from)
+    }
+
+    async fn update_client(
+        &self,
+        client_id: &str,
+        request: &UpdateClientRequest,
+    ) -> Result<ClientResponse, ClientServiceError> {
+        let client = self.find_client(client_id).await?;
+        check_grant_types(
+            &request.grant_types,
+            &request.redirect_uris,
+            client.is_confidential(),
+        )?;
+
+        let client = OAuthClientEntity {
+            name: request.name.to_owned(),
+            redirect_uris: request.redirect_uris.to_owned(),
+            grant_types: request.grant_types.to_owned(),
+            scopes: request.scopes.to_owned(),
+            access_token_ttl_seconds: request.access_token_ttl_seconds,
+            refresh_token_ttl_seconds: request.refresh_token_ttl_seconds,
+            first_party: request.first_party,
+            ..client
+        };
+        self.oauth_client_repository.update(&client).await?;
+
+        self.retrieve_client(client_id).await
+    }
+
+    async fn delete_client(&self, client_id: &str) -> Result<(), ClientServiceError> {
+        if !self.oauth_client_repository.delete(client_id).await? {
+            return Err(ClientServiceError::NotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn rotate_client_secret(
+        &self,
+        client_id: &str,
+    ) -> Result<ClientSecretResponse, ClientServiceError> {
+        let client = self.find_client(client_id).await?;
+        if !client.is_confidential() {
+            return Err(ClientServiceError::InvalidRequest(
+                "public clients do not have a secret".into(),
+            ));
+        }
+
+        let (client_secret, client_secret_hash) = generate_client_secret();
+        self.oauth_client_repository
+            .update_secret(&client.client_id, Some(&client_secret_hash))
+            .await?;
+
+        Ok(ClientSecretResponse {
+            client_id: client.client_id,
+            client_secret,
+        })
+    }
+}
@@ -0,0 +1,230 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    adapters::{
+        dto::federation::{
+            ClaimMapping, FederatedAccount, UpstreamAuthorization, UpstreamProvider,
+        },
+        requests::auth::FederatedCallbackRequest,
+    },
+    config::federation::FederationConfig,
+    entities::{identity_provider::IdentityProviderEntity, user::UserEntity},
+    errors::{app_error::AppError, federation_error::FederationError},
+    repositories::{
+        federated_identity_repository::{
+            FederatedIdentityRepository, FederatedIdentityRepositoryTrait,
+        },
+        federated_login_repository::{FederatedLoginRepository, FederatedLoginRepositoryTrait},
+        identity_provider_repository::{
+            IdentityProviderRepository, IdentityProviderRepositoryTrait,
+        },
+    },
+    shared::{crypto::generate_opaque_token, oidc_client::OidcClient, secret_box::SecretBox},
+};
+
+#[derive(Clone)]
+pub struct FederationService {
+    identity_provider_repository: IdentityProviderRepository,
+    federated_login_repository: FederatedLoginRepository,
+    federated_identity_repository: FederatedIdentityRepository,
+    oidc_client: OidcClient,
+    secret_box: SecretBox,
+    config: FederationConfig,
+}
+
+impl FederationService {
+    pub fn init(pool: &Pool<Postgres>, secret_box: &SecretBox) -> Result<Self, AppError> {
+        let config = FederationConfig::from_env();
+
+        Ok(Self {
+            identity_provider_repository: IdentityProviderRepository::init(pool),
+            federated_login_repository: FederatedLoginRepository::init(pool),
+            federated_identity_repository: FederatedIdentityRepository::init(pool),
+            oidc_client: OidcClient::new(&config)?,
+            secret_box: secret_box.clone(),
+            config,
+        })
+    }
+
+    /// a disabled provider is as good as unknown to the login routes
+    async fn find_provider(&self, slug: &str) -> Result<IdentityProviderEntity, FederationError> {
+        self.identity_provider_repository
+            .find_by_slug(slug)
+            .await?
+            .filter(|provider| provider.enabled)
+            .ok_or(FederationError::ProviderNotFound)
+    }
+
+    fn upstream_provider(
+        &self,
+        provider: &IdentityProviderEntity,
+    ) -> Result<UpstreamProvider, FederationError> {
+        let client_secret = String::from_utf8(self.secret_box.open(&provider.client_secret)?)
+            .map_err(|err| AppError::OperationFailed(err.to_string()))?;
+
+        Ok(UpstreamProvider {
+            issuer: provider.issuer.to_owned(),
+            client_id: provider.client_id.to_owned(),
+            client_secret,
+            scopes: provider.scopes.to_owned(),
+            claim_mapping: ClaimMapping {
+                email: provider.email_claim.to_owned(),
+                first_name: provider.first_name_claim.to_owned(),
+                last_name: provider.last_name_claim.to_owned(),
+            },
+            trust_email: provider.trust_email,
+        })
+    }
+}
+
+pub trait FederationServiceTrait {
+    /// records the sign-in and returns the page at the provider to send the user to
+    fn start_login(
+        &self,
+        slug: &str,
+        client_id: Option<&str>,
+    ) -> impl std::future::Future<Output = Result<String, FederationError>> + Send;
+
+    /// redeems the code the provider redirected back with, the state must belong to a sign-in
+    /// started with the same provider
+    fn complete_login(
+        &self,
+        slug: &str,
+        request: &FederatedCallbackRequest,
+    ) -> impl std::future::Future<Output = Result<FederatedAccount, FederationError>> + Send;
+
+    /// the user the provider account is linked to, if it has signed in before
+    fn linked_user(
+        &self,
+        account: &FederatedAccount,
+    ) -> impl std::future::Future<Output = Result<Option<Uuid>, FederationError>> + Send;
+
+    fn link(
+        &self,
+        account: &FederatedAccount,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), FederationError>> + Send;
+
+    /// whether an existing user can be joined to a provider account by their email address alone,
+    /// which takes an address the user proved they own and has not just moved to
+    fn can_link_by_email(&self, user: &UserEntity) -> bool;
+}
+
+impl FederationServiceTrait for FederationService {
+    async fn start_login(
+        &self,
+        slug: &str,
+        client_id: Option<&str>,
+    ) -> Result<String, FederationError> {
+        let provider = self.find_provider(slug).await?;
+        let authorization = UpstreamAuthorization {
+            redirect_uri: self.config.redirect_uri(&provider.slug),
+            state: generate_opaque_token(),
+            nonce: generate_opaque_token(),
+            code_verifier: generate_opaque_token(),
+        };
+        let authorization_url = self
+            .oidc_client
+            .authorization_url(&self.upstream_provider(&provider)?, &authorization)
+            .await?;
+
+        self.federated_login_repository
+            .create(
+                &authorization.state,
+                &provider.identifier,
+                &authorization.nonce,
+                &authorization.code_verifier,
+                client_id,
+                chrono::Utc::now() + self.config.login_ttl,
+            )
+            .await?;
+
+        Ok(authorization_url)
+    }
+
+    async fn complete_login(
+        &self,
+        slug: &str,
+        request: &FederatedCallbackRequest,
+    ) -> Result<FederatedAccount, FederationError> {
+        let provider = self.find_provider(slug).await?;
+        let Some(state) = request.state.as_deref() else {
+            return Err(FederationError::LoginExpired);
+        };
+        let login = self
+            .federated_login_repository
+            .consume(state, &provider.identifier)
+            .await?
+            .ok_or(FederationError::LoginExpired)?;
+
+        if let Some(error) = &request.error {
+            return Err(FederationError::Denied(
+                request
+                    .error_description
+                    .to_owned()
+                    .unwrap_or(error.to_owned()),
+            ));
+        }
+        let Some(code) = request.code.as_deref() else {
+            return Err(FederationError::InvalidResponse(
+                "the redirect has no authorization code".into(),
+            ));
+        };
+
+        let profile = self
+            .oidc_client
+            .authenticate(
+                &self.upstream_provider(&provider)?,
+                code,
+                &UpstreamAuthorization {
+                    redirect_uri: self.config.redirect_uri(&provider.slug),
+                    state: login.state,
+                    nonce: login.nonce,
+                    code_verifier: login.code_verifier,
+                },
+            )
+            .await?;
+
+        Ok(FederatedAccount {
+            provider_identifier: provider.identifier,
+            profile,
+            client_id: login.client_id,
+        })
+    }
+
+    async fn linked_user(
+        &self,
+        account: &FederatedAccount,
+    ) -> Result<Option<Uuid>, FederationError> {
+        Ok(self
+            .federated_identity_repository
+            .record_login(&account.provider_identifier, &account.profile.subject)
+            .await?)
+    }
+
+    async fn link(
+        &self,
+        account: &FederatedAccount,
+        user_identifier: &Uuid,
+    ) -> Result<(), FederationError> {
+        Ok(self
+            .federated_identity_repository
+            .link(
+                &account.provider_identifier,
+                &account.profile.subject,
+                user_identifier,
+            )
+            .await?)
+    }
+
+    fn can_link_by_email(&self, user: &UserEntity) -> bool {
+        if !user.is_active || user.email_verified_at.is_none() {
+            return false;
+        }
+
+        user.email_changed_at.is_none_or(|changed_at| {
+            changed_at + self.config.email_change_link_delay <= chrono::Utc::now()
+        })
+    }
+}
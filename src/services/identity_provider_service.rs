@@ -0,0 +1,178 @@
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    adapters::{
+        requests::identity_providers::{
+            CreateIdentityProviderRequest, UpdateIdentityProviderRequest,
+        },
+        response::identity_providers::IdentityProviderResponse,
+    },
+    config::federation::FederationConfig,
+    entities::identity_provider::IdentityProviderEntity,
+    errors::identity_provider_service_error::IdentityProviderServiceError,
+    repositories::identity_provider_repository::{
+        IdentityProviderRepository, IdentityProviderRepositoryTrait,
+    },
+    shared::secret_box::SecretBox,
+};
+
+#[derive(Clone)]
+pub struct IdentityProviderService {
+    identity_provider_repository: IdentityProviderRepository,
+    secret_box: SecretBox,
+    config: FederationConfig,
+}
+
+impl IdentityProviderService {
+    pub fn init(pool: &Pool<Postgres>, secret_box: &SecretBox) -> Self {
+        Self {
+            identity_provider_repository: IdentityProviderRepository::init(pool),
+            secret_box: secret_box.clone(),
+            config: FederationConfig::from_env(),
+        }
+    }
+
+    async fn find_provider(
+        &self,
+        slug: &str,
+    ) -> Result<IdentityProviderEntity, IdentityProviderServiceError> {
+        self.identity_provider_repository
+            .find_by_slug(slug)
+            .await?
+            .ok_or(IdentityProviderServiceError::NotFound)
+    }
+
+    fn response(&self, provider: IdentityProviderEntity) -> IdentityProviderResponse {
+        let redirect_uri = self.config.redirect_uri(&provider.slug);
+        IdentityProviderResponse::new(provider, redirect_uri)
+    }
+}
+
+pub trait IdentityProviderServiceTrait {
+    fn list_providers(
+        &self,
+    ) -> impl std::future::Future<
+        Output = Result<Vec<IdentityProviderResponse>, IdentityProviderServiceError>,
+    > + Send;
+
+    fn create_provider(
+        &self,
+        request: &CreateIdentityProviderRequest,
+    ) -> impl std::future::Future<
+        Output = Result<IdentityProviderResponse, IdentityProviderServiceError>,
+    > + Send;
+
+    fn retrieve_provider(
+        &self,
+        slug: &str,
+    ) -> impl std::future::Future<
+        Output = Result<IdentityProviderResponse, IdentityProviderServiceError>,
+    > + Send;
+
+    fn update_provider(
+        &self,
+        slug: &str,
+        request: &UpdateIdentityProviderRequest,
+    ) -> impl std::future::Future<
+        Output = Result<IdentityProviderResponse, IdentityProviderServiceError>,
+    > + Send;
+
+    /// also unlinks every account signed in through the provider, their users remain
+    fn delete_provider(
+        &self,
+        slug: &str,
+    ) -> impl std::future::Future<Output = Result<(), IdentityProviderServiceError>> + Send;
+}
+
+impl IdentityProviderServiceTrait for IdentityProviderService {
+    async fn list_providers(
+        &self,
+    ) -> Result<Vec<IdentityProviderResponse>, IdentityProviderServiceError> {
+        let providers = self.identity_provider_repository.find_all().await?;
+
+        Ok(providers
+            .into_iter()
+            .map(|provider| self.response(provider))
+            .collect())
+    }
+
+    async fn create_provider(
+        &self,
+        request: &CreateIdentityProviderRequest,
+    ) -> Result<IdentityProviderResponse, IdentityProviderServiceError> {
+        if self
+            .identity_provider_repository
+            .find_by_slug(&request.slug)
+            .await?
+            .is_some()
+        {
+            return Err(IdentityProviderServiceError::SlugTaken);
+        }
+
+        let provider = IdentityProviderEntity {
+            identifier: uuid::Uuid::new_v4(),
+            slug: request.slug.to_owned(),
+            name: request.name.to_owned(),
+            issuer: request.issuer.to_owned(),
+            client_id: request.client_id.to_owned(),
+            client_secret: self.secret_box.seal(request.client_secret.as_bytes())?,
+            scopes: request.scopes.to_owned(),
+            email_claim: request.email_claim.to_owned(),
+            first_name_claim: request.first_name_claim.to_owned(),
+            last_name_claim: request.last_name_claim.to_owned(),
+            trust_email: request.trust_email,
+            enabled: request.enabled,
+            created_at: chrono::Utc::now(),
+            updated_at: None,
+        };
+        self.identity_provider_repository.create(&provider).await?;
+
+        Ok(self.response(provider))
+    }
+
+    async fn retrieve_provider(
+        &self,
+        slug: &str,
+    ) -> Result<IdentityProviderResponse, IdentityProviderServiceError> {
+        self.find_provider(slug)
+            .await
+            .map(|provider| self.response(provider))
+    }
+
+    async fn update_provider(
+        &self,
+        slug: &str,
+        request: &UpdateIdentityProviderRequest,
+    ) -> Result<IdentityProviderResponse, IdentityProviderServiceError> {
+        let provider = self.find_provider(slug).await?;
+        let client_secret = match &request.client_secret {
+            Some(client_secret) => self.secret_box.seal(client_secret.as_bytes())?,
+            None => provider.client_secret.clone(),
+        };
+
+        let provider = IdentityProviderEntity {
+            name: request.name.to_owned(),
+            issuer: request.issuer.to_owned(),
+            client_id: request.client_id.to_owned(),
+            client_secret,
+            scopes: request.scopes.to_owned(),
+            email_claim: request.email_claim.to_owned(),
+            first_name_claim: request.first_name_claim.to_owned(),
+            last_name_claim: request.last_name_claim.to_owned(),
+            trust_email: request.trust_email,
+            enabled: request.enabled,
+            ..provider
+        };
+        self.identity_provider_repository.update(&provider).await?;
+
+        self.retrieve_provider(slug).await
+    }
+
+    async fn delete_provider(&self, slug: &str) -> Result<(), IdentityProviderServiceError> {
+        if !self.identity_provider_repository.delete(slug).await? {
+            return Err(IdentityProviderServiceError::NotFound);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,170 @@
+use std::{net::IpAddr, time::Duration};
+
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    config::lockout::LockoutConfig,
+    errors::lockout_service_error::LockoutServiceError,
+    repositories::login_failure_repository::{LoginFailureRepository, LoginFailureRepositoryTrait},
+};
+
+const SCOPE_ACCOUNT: &str = "account";
+const SCOPE_IP: &str = "ip";
+
+/// throttles password guessing, failures are counted against the email address that was tried,
+/// whether or not an account has it, and against the address the attempt came from
+#[derive(Clone)]
+pub struct LockoutService {
+    login_failure_repository: LoginFailureRepository,
+    config: LockoutConfig,
+}
+
+impl LockoutService {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            login_failure_repository: LoginFailureRepository::init(pool),
+            config: LockoutConfig::from_env(),
+        }
+    }
+
+    fn account_subject(email: &str) -> String {
+        email.trim().to_lowercase()
+    }
+
+    async fn retry_after(&self, scope: &str, subject: &str) -> Result<u64, LockoutServiceError> {
+        let Some(blocked_until) = self
+            .login_failure_repository
+            .find(scope, subject)
+            .await?
+            .and_then(|failure| failure.blocked_until)
+        else {
+            return Ok(0);
+        };
+
+        let remaining = (blocked_until - chrono::Utc::now()).num_milliseconds();
+        Ok(if remaining > 0 {
+            (remaining as u64).div_ceil(1000)
+        } else {
+            0
+        })
+    }
+
+    async fn block(
+        &self,
+        scope: &str,
+        subject: &str,
+        duration: Duration,
+    ) -> Result<(), LockoutServiceError> {
+        self.login_failure_repository
+            .block_until(scope, subject, chrono::Utc::now() + duration)
+            .await?;
+
+        Ok(())
+    }
+}
+
+pub trait LockoutServiceTrait {
+    /// refuses the attempt while the account or the address is backing off or locked out
+    fn check(
+        &self,
+        email: &str,
+        ip_address: Option<IpAddr>,
+    ) -> impl std::future::Future<Output = Result<(), LockoutServiceError>> + Send;
+
+    /// counts a failed attempt, the account backs off exponentially and both the account and the
+    /// address are locked out once their threshold is reached. Returns the lockout duration when
+    /// this failure locked the account
+    fn record_failure(
+        &self,
+        email: &str,
+        ip_address: Option<IpAddr>,
+    ) -> impl std::future::Future<Output = Result<Option<Duration>, LockoutServiceError>> + Send;
+
+    /// a successful sign-in forgets the account's failures, the address keeps its count
+    fn record_success(
+        &self,
+        email: &str,
+    ) -> impl std::future::Future<Output = Result<(), LockoutServiceError>> + Send;
+
+    /// administrative unlock, returns false when there was nothing to clear
+    fn unlock(
+        &self,
+        email: &str,
+    ) -> impl std::future::Future<Output = Result<bool, LockoutServiceError>> + Send;
+}
+
+impl LockoutServiceTrait for LockoutService {
+    async fn check(
+        &self,
+        email: &str,
+        ip_address: Option<IpAddr>,
+    ) -> Result<(), LockoutServiceError> {
+        let mut retry_after = self
+            .retry_after(SCOPE_ACCOUNT, &Self::account_subject(email))
+            .await?;
+        if let Some(ip_address) = ip_address {
+            retry_after =
+                retry_after.max(self.retry_after(SCOPE_IP, &ip_address.to_string()).await?);
+        }
+
+        if retry_after > 0 {
+            return Err(LockoutServiceError::TooManyAttempts { retry_after });
+        }
+
+        Ok(())
+    }
+
+    async fn record_failure(
+        &self,
+        email: &str,
+        ip_address: Option<IpAddr>,
+    ) -> Result<Option<Duration>, LockoutServiceError> {
+        let window_start = chrono::Utc::now() - self.config.failure_window;
+
+        if let Some(ip_address) = ip_address {
+            let subject = ip_address.to_string();
+            let failures = self
+                .login_failure_repository
+                .record_failure(SCOPE_IP, &subject, window_start)
+                .await?;
+            if failures as u32 >= self.config.ip_threshold {
+                log::warn!("locking out {subject} after {failures} failed sign-in attempts");
+                self.block(SCOPE_IP, &subject, self.config.lockout_duration)
+                    .await?;
+            }
+        }
+
+        let subject = Self::account_subject(email);
+        let failures = self
+            .login_failure_repository
+            .record_failure(SCOPE_ACCOUNT, &subject, window_start)
+            .await? as u32;
+        if failures >= self.config.account_threshold {
+            self.block(SCOPE_ACCOUNT, &subject, self.config.lockout_duration)
+                .await?;
+            return Ok(Some(self.config.lockout_duration));
+        }
+
+        let backoff = self.config.backoff(failures);
+        if !backoff.is_zero() {
+            self.block(SCOPE_ACCOUNT, &subject, backoff).await?;
+        }
+
+        Ok(None)
+    }
+
+    async fn record_success(&self, email: &str) -> Result<(), LockoutServiceError> {
+        self.login_failure_repository
+            .clear(SCOPE_ACCOUNT, &Self::account_subject(email))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn unlock(&self, email: &str) -> Result<bool, LockoutServiceError> {
+        Ok(self
+            .login_failure_repository
+            .clear(SCOPE_ACCOUNT, &Self::account_subject(email))
+            .await?)
+    }
+}
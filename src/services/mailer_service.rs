@@ -0,0 +1,191 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    adapters::mailer::{Email, MailTransport, Mailer, templates::EmailTemplate},
+    config::mailer::MailerConfig,
+    errors::{app_error::AppError, mailer_error::MailerError},
+};
+
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Clone)]
+pub struct MailerService {
+    transport: Arc<MailTransport>,
+    from: String,
+    max_retries: u32,
+}
+
+impl MailerService {
+    pub fn init() -> Result<Self, AppError> {
+        let config = MailerConfig::from_env()?;
+        let transport = MailTransport::from_config(&config).map_err(|err| {
+            AppError::StartupError(format!("error initializing the mail transport: {err}"))
+        })?;
+        if matches!(transport, MailTransport::Log) {
+            log::warn!("MAIL_TRANSPORT is log, emails are not delivered");
+        }
+
+        Ok(Self {
+            transport: Arc::new(transport),
+            from: config.from,
+            max_retries: config.max_retries,
+        })
+    }
+
+    /// sends the email, retrying with an exponential back-off until `max_retries` is exhausted
+    pub async fn deliver(&self, email: &Email) -> Result<(), MailerError> {
+        let mut attempt = 0;
+        loop {
+            match self.transport.send(email).await {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt < self.max_retries => {
+                    let delay = RETRY_BASE_DELAY * 2u32.pow(attempt);
+                    log::warn!(
+                        "error sending \"{}\" email, retrying in {}s: {err}",
+                        email.subject,
+                        delay.as_secs()
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn dispatch(&self, to: &str, template: EmailTemplate) {
+        let rendered = template.render();
+        let email = Email {
+            from: self.from.clone(),
+            to: to.to_string(),
+            subject: rendered.subject,
+            text_body: rendered.text_body,
+            html_body: rendered.html_body,
+        };
+
+        let mailer = self.clone();
+        tokio::task::spawn(async move {
+            if let Err(err) = mailer.deliver(&email).await {
+                log::error!("giving up on \"{}\" email: {err}", email.subject);
+            }
+        });
+    }
+}
+
+pub trait MailerServiceTrait {
+    fn send_account_verification_email(
+        &self,
+        to: &str,
+        first_name: &str,
+        otp: &str,
+        expires_in: Duration,
+    );
+
+    /// goes to the new address, the change is not made until the code in it is confirmed
+    fn send_email_change_email(&self, to: &str, first_name: &str, otp: &str, expires_in: Duration);
+
+    fn send_password_reset_email(
+        &self,
+        to: &str,
+        first_name: &str,
+        reset_link: &str,
+        expires_in: Duration,
+    );
+
+    fn send_login_link_email(
+        &self,
+        to: &str,
+        first_name: &str,
+        login_link: &str,
+        expires_in: Duration,
+    );
+
+    fn send_login_code_email(&self, to: &str, first_name: &str, code: &str, expires_in: Duration);
+
+    fn send_security_notification(&self, to: &str, first_name: &str, event: &str);
+}
+
+impl MailerServiceTrait for MailerService {
+    fn send_account_verification_email(
+        &self,
+        to: &str,
+        first_name: &str,
+        otp: &str,
+        expires_in: Duration,
+    ) {
+        self.dispatch(
+            to,
+            EmailTemplate::AccountVerification {
+                first_name: first_name.to_string(),
+                otp: otp.to_string(),
+                expires_in_minutes: expires_in.as_secs() / 60,
+            },
+        );
+    }
+
+    fn send_email_change_email(&self, to: &str, first_name: &str, otp: &str, expires_in: Duration) {
+        self.dispatch(
+            to,
+            EmailTemplate::EmailChange {
+                first_name: first_name.to_string(),
+                otp: otp.to_string(),
+                expires_in_minutes: expires_in.as_secs() / 60,
+            },
+        );
+    }
+
+    fn send_password_reset_email(
+        &self,
+        to: &str,
+        first_name: &str,
+        reset_link: &str,
+        expires_in: Duration,
+    ) {
+        self.dispatch(
+            to,
+            EmailTemplate::PasswordReset {
+                first_name: first_name.to_string(),
+                reset_link: reset_link.to_string(),
+                expires_in_minutes: expires_in.as_secs() / 60,
+            },
+        );
+    }
+
+    fn send_login_link_email(
+        &self,
+        to: &str,
+        first_name: &str,
+        login_link: &str,
+        expires_in: Duration,
+    ) {
+        self.dispatch(
+            to,
+            EmailTemplate::LoginLink {
+                first_name: first_name.to_string(),
+                login_link: login_link.to_string(),
+                expires_in_minutes: expires_in.as_secs() / 60,
+            },
+        );
+    }
+
+    fn send_login_code_email(&self, to: &str, first_name: &str, code: &str, expires_in: Duration) {
+        self.dispatch(
+            to,
+            EmailTemplate::LoginCode {
+                first_name: first_name.to_string(),
+                code: code.to_string(),
+                expires_in_minutes: expires_in.as_secs() / 60,
+            },
+        );
+    }
+
+    fn send_security_notification(&self, to: &str, first_name: &str, event: &str) {
+        self.dispatch(
+            to,
+            EmailTemplate::SecurityNotification {
+                first_name: first_name.to_string(),
+                event: event.to_string(),
+            },
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    adapters::response::mfa::TotpEnrollmentResponse,
+    config::mfa::MfaConfig,
+    entities::{totp_authenticator::TotpAuthenticatorEntity, user::UserEntity},
+    errors::mfa_service_error::MfaServiceError,
+    repositories::{
+        recovery_code_repository::{RecoveryCodeRepository, RecoveryCodeRepositoryTrait},
+        totp_authenticator_repository::{
+            TotpAuthenticatorRepository, TotpAuthenticatorRepositoryTrait,
+        },
+    },
+    shared::{
+        crypto::{generate_user_code, sha256_hex},
+        secret_box::SecretBox,
+        totp,
+    },
+};
+
+const RECOVERY_CODE_LENGTH: usize = 10;
+
+#[derive(Clone)]
+pub struct MfaService {
+    totp_authenticator_repository: TotpAuthenticatorRepository,
+    recovery_code_repository: RecoveryCodeRepository,
+    secret_box: SecretBox,
+    config: MfaConfig,
+}
+
+impl MfaService {
+    pub fn init(pool: &Pool<Postgres>, secret_box: &SecretBox) -> Self {
+        Self {
+            totp_authenticator_repository: TotpAuthenticatorRepository::init(pool),
+            recovery_code_repository: RecoveryCodeRepository::init(pool),
+            secret_box: secret_box.clone(),
+            config: MfaConfig::from_env(),
+        }
+    }
+
+    pub fn challenge_expiry(&self) -> std::time::Duration {
+        self.config.challenge_expiry
+    }
+
+    /// codes are compared without the separator and regardless of case
+    fn hash_recovery_code(user_identifier: &Uuid, code: &str) -> String {
+        let code: String = code
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .map(|character| character.to_ascii_uppercase())
+            .collect();
+
+        sha256_hex(&format!("{user_identifier}:{code}"))
+    }
+
+    /// the time step the code belongs to, if it is valid for the authenticator
+    fn check_code(
+        &self,
+        authenticator: &TotpAuthenticatorEntity,
+        code: &str,
+    ) -> Result<i64, MfaServiceError> {
+        let secret = self.secret_box.open(&authenticator.secret_ciphertext)?;
+
+        totp::verify_code(&secret, code, chrono::Utc::now().timestamp())
+            .ok_or(MfaServiceError::InvalidCode)
+    }
+
+    async fn confirmed_authenticator(
+        &self,
+        user_identifier: &Uuid,
+    ) -> Result<TotpAuthenticatorEntity, MfaServiceError> {
+        self.totp_authenticator_repository
+            .find_by_user(user_identifier)
+            .await?
+            .filter(|authenticator| authenticator.confirmed_at.is_some())
+            .ok_or(MfaServiceError::NotEnrolled)
+    }
+}
+
+pub trait MfaServiceTrait {
+    /// true once an authenticator has been confirmed, login asks for a code from then on
+    fn is_enrolled(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<bool, MfaServiceError>> + Send;
+
+    fn recovery_codes_remaining(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<i64, MfaServiceError>> + Send;
+
+    /// generates a new secret, replacing one from an enrollment that was never confirmed
+    fn start_totp_enrollment(
+        &self,
+        user: &UserEntity,
+    ) -> impl std::future::Future<Output = Result<TotpEnrollmentResponse, MfaServiceError>> + Send;
+
+    /// activates the pending authenticator once a code from it is presented and returns the
+    /// first set of recovery codes
+    fn confirm_totp_enrollment(
+        &self,
+        user_identifier: &Uuid,
+        code: &str,
+    ) -> impl std::future::Future<Output = Result<Vec<String>, MfaServiceError>> + Send;
+
+    /// a code is accepted once, codes from the same or an earlier time step are refused after it
+    fn verify_totp(
+        &self,
+        user_identifier: &Uuid,
+        code: &str,
+    ) -> impl std::future::Future<Output = Result<(), MfaServiceError>> + Send;
+
+    /// consumes the recovery code, it cannot be used again once this returns Ok
+    fn redeem_recovery_code(
+        &self,
+        user_identifier: &Uuid,
+        code: &str,
+    ) -> impl std::future::Future<Output = Result<(), MfaServiceError>> + Send;
+
+    /// the previous codes stop working
+    fn regenerate_recovery_codes(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<Vec<String>, MfaServiceError>> + Send;
+
+    /// removes the authenticator along with the recovery codes
+    fn remove(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), MfaServiceError>> + Send;
+}
+
+impl MfaServiceTrait for MfaService {
+    async fn is_enrolled(&self, user_identifier: &Uuid) -> Result<bool, MfaServiceError> {
+        Ok(self
+            .totp_authenticator_repository
+            .find_by_user(user_identifier)
+            .await?
+            .is_some_and(|authenticator| authenticator.confirmed_at.is_some()))
+    }
+
+    async fn recovery_codes_remaining(
+        &self,
+        user_identifier: &Uuid,
+    ) -> Result<i64, MfaServiceError> {
+        Ok(self
+            .recovery_code_repository
+            .count_unused(user_identifier)
+            .await?)
+    }
+
+    async fn start_totp_enrollment(
+        &self,
+        user: &UserEntity,
+    ) -> Result<TotpEnrollmentResponse, MfaServiceError> {
+        if self.is_enrolled(&user.identifier).await? {
+            return Err(MfaServiceError::AlreadyEnrolled);
+        }
+
+        let secret = totp::generate_secret();
+        self.totp_authenticator_repository
+            .upsert_pending(&user.identifier, &self.secret_box.seal(&secret)?)
+            .await?;
+
+        Ok(TotpEnrollmentResponse {
+            secret: totp::encode_secret(&secret),
+            provisioning_uri: totp::provisioning_uri(
+                &self.config.totp_issuer,
+                &user.email,
+                &secret,
+            ),
+        })
+    }
+
+    async fn confirm_totp_enrollment(
+        &self,
+        user_identifier: &Uuid,
+        code: &str,
+    ) -> Result<Vec<String>, MfaServiceError> {
+        let Some(authenticator) = self
+            .totp_authenticator_repository
+            .find_by_user(user_identifier)
+            .await?
+        else {
+            return Err(MfaServiceError::NotEnrolled);
+        };
+        if authenticator.confirmed_at.is_some() {
+            return Err(MfaServiceError::AlreadyEnrolled);
+        }
+
+        let step = self.check_code(&authenticator, code)?;
+        if !self
+            .totp_authenticator_repository
+            .confirm(user_identifier, step)
+            .await?
+        {
+            return Err(MfaServiceError::AlreadyEnrolled);
+        }
+
+        self.regenerate_recovery_codes(user_identifier).await
+    }
+
+    async fn verify_totp(&self, user_identifier: &Uuid, code: &str) -> Result<(), MfaServiceError> {
+        let authenticator = self.confirmed_authenticator(user_identifier).await?;
+        let step = self.check_code(&authenticator, code)?;
+
+        if !self
+            .totp_authenticator_repository
+            .record_used_step(user_identifier, step)
+            .await?
+        {
+            return Err(MfaServiceError::InvalidCode);
+        }
+
+        Ok(())
+    }
+
+    async fn redeem_recovery_code(
+        &self,
+        user_identifier: &Uuid,
+        code: &str,
+    ) -> Result<(), MfaServiceError> {
+        self.confirmed_authenticator(user_identifier).await?;
+
+        if !self
+            .recovery_code_repository
+            .mark_as_used(
+                user_identifier,
+                &Self::hash_recovery_code(user_identifier, code),
+            )
+            .await?
+        {
+            return Err(MfaServiceError::InvalidCode);
+        }
+
+        Ok(())
+    }
+
+    async fn regenerate_recovery_codes(
+        &self,
+        user_identifier: &Uuid,
+    ) -> Result<Vec<String>, MfaServiceError> {
+        let codes: Vec<String> = (0..self.config.recovery_code_count)
+            .map(|_| {
+                let code = generate_user_code(RECOVERY_CODE_LENGTH);
+                let (head, tail) = code.split_at(RECOVERY_CODE_LENGTH / 2);
+                format!("{head}-{tail}")
+            })
+            .collect();
+        let code_hashes: Vec<String> = codes
+            .iter()
+            .map(|code| Self::hash_recovery_code(user_identifier, code))
+            .collect();
+
+        self.recovery_code_repository
+            .replace(user_identifier, &code_hashes)
+            .await?;
+
+        Ok(codes)
+    }
+
+    async fn remove(&self, user_identifier: &Uuid) -> Result<(), MfaServiceError> {
+        self.totp_authenticator_repository
+            .delete(user_identifier)
+            .await?;
+        self.recovery_code_repository
+            .delete_by_user(user_identifier)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,17 @@
 pub mod auth_service;
+pub mod client_service;
+pub mod federation_service;
+pub mod identity_provider_service;
+pub mod lockout_service;
+pub mod mailer_service;
+pub mod mfa_service;
+pub mod oauth_service;
+pub mod otp_service;
 pub mod root_service;
+pub mod saml_service;
+pub mod service_provider_service;
+pub mod session_service;
+pub mod token_service;
 pub mod user_helper_service;
 pub mod user_service;
+pub mod webauthn_service;
@@ -0,0 +1,1248 @@
+use std::time::Duration;
+
+use sqlx::{Pool, Postgres};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    adapters::{
+        dto::{
+            jwt::{Actor, Claims, ClientClaims, JwtCredentials, SubjectType, TokenUse},
+            oauth::{
+                GRANT_AUTHORIZATION_CODE, GRANT_CLIENT_CREDENTIALS, GRANT_DEVICE_CODE,
+                GRANT_REFRESH_TOKEN, GRANT_TOKEN_EXCHANGE, IdTokenClaims, OAuthGrant, SCOPE_EMAIL,
+                SCOPE_OFFLINE_ACCESS, SCOPE_OPENID, SCOPE_PROFILE, SUPPORTED_GRANT_TYPES,
+                SUPPORTED_SCOPES, TOKEN_TYPE_ACCESS_TOKEN, TOKEN_TYPE_JWT, has_scope,
+            },
+            permission::Permission,
+        },
+        requests::oauth::{
+            AuthorizationRequest, ClientCredentials, DeviceAuthorizationRequest,
+            DeviceVerificationRequest, IntrospectionRequest, RevocationRequest, TokenRequest,
+        },
+        response::oauth::{
+            AuthorizationResponse, DeviceAuthorizationResponse, DeviceVerificationResponse,
+            IntrospectionResponse, OpenIdConfiguration, TokenResponse, UserInfoResponse,
+        },
+    },
+    config::{jwt::JwtConfig, oauth::OAuthConfig},
+    entities::{
+        authorization_code::AuthorizationCodeEntity, device_code::DeviceCodeEntity,
+        oauth_client::OAuthClientEntity, token_exchange_audit::TokenExchangeAuditEntity,
+        user::UserEntity,
+    },
+    errors::{
+        app_error::AppError,
+        auth_service_error::AuthenticationServiceError,
+        oauth_error::{AuthorizationError, OAuthError},
+    },
+    repositories::{
+        authorization_code_repository::{
+            AuthorizationCodeRepository, AuthorizationCodeRepositoryTrait,
+        },
+        device_code_repository::{DeviceCodeRepository, DeviceCodeRepositoryTrait},
+        oauth_client_repository::{OAuthClientRepository, OAuthClientRepositoryTrait},
+        token_exchange_audit_repository::{
+            TokenExchangeAuditRepository, TokenExchangeAuditRepositoryTrait,
+        },
+        user_repository::{UserRepository, UserRepositoryTrait},
+    },
+    services::token_service::{RotatedRefreshToken, TokenService, TokenServiceTrait},
+    shared::crypto::{
+        constant_time_eq, generate_opaque_token, generate_user_code, sha256_base64url, sha256_hex,
+    },
+};
+
+const USER_CODE_LENGTH: usize = 8;
+/// fresh user codes tried before giving up, a collision among pending requests is already rare
+const USER_CODE_ATTEMPTS: usize = 5;
+/// added to a device's polling interval each time it polls too fast, as RFC 8628 asks
+const SLOW_DOWN_SECONDS: i32 = 5;
+
+#[derive(Clone)]
+pub struct OAuthService {
+    oauth_client_repository: OAuthClientRepository,
+    authorization_code_repository: AuthorizationCodeRepository,
+    device_code_repository: DeviceCodeRepository,
+    token_exchange_audit_repository: TokenExchangeAuditRepository,
+    user_repository: UserRepository,
+    token_service: TokenService,
+    jwt_config: JwtConfig,
+    config: OAuthConfig,
+}
+
+/// an authorization request that passed validation, its redirect uri can be trusted from here on
+struct ValidatedAuthorization {
+    client_id: String,
+    redirect_uri: Url,
+    scope: String,
+    state: Option<String>,
+    nonce: Option<String>,
+    code_challenge: String,
+}
+
+impl OAuthService {
+    pub fn init(pool: &Pool<Postgres>, token_service: &TokenService) -> Self {
+        Self {
+            oauth_client_repository: OAuthClientRepository::init(pool),
+            authorization_code_repository: AuthorizationCodeRepository::init(pool),
+            device_code_repository: DeviceCodeRepository::init(pool),
+            token_exchange_audit_repository: TokenExchangeAuditRepository::init(pool),
+            user_repository: UserRepository::init(pool),
+            token_service: token_service.clone(),
+            jwt_config: JwtConfig::from_env(),
+            config: OAuthConfig::from_env(),
+        }
+    }
+
+    /// periodically removes device requests that have expired, which also frees their user codes
+    pub fn spawn_device_code_cleanup(&self) {
+        let device_code_repository = self.device_code_repository.clone();
+        let period = self.config.device_code_cleanup_interval;
+
+        tokio::task::spawn(async move {
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                interval.tick().await;
+                match device_code_repository.delete_expired().await {
+                    Ok(0) => {}
+                    Ok(deleted) => log::info!("removed {deleted} expired device requests"),
+                    Err(err) => log::error!("error removing expired device requests: {err}"),
+                }
+            }
+        });
+    }
+
+    /// keeps the client the first-party apps sign in with in line with the environment
+    pub async fn register_first_party_client(pool: &Pool<Postgres>) -> Result<(), AppError> {
+        let Some(client) = OAuthConfig::from_env().first_party_client else {
+            return Ok(());
+        };
+
+        OAuthClientRepository::init(pool)
+            .upsert_first_party(&client.client_id, "first party", &client.redirect_uris)
+            .await
+            .map_err(|err| AppError::StartupError(err.to_string()))
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}{}", self.jwt_config.issuer.trim_end_matches('/'), path)
+    }
+
+    async fn validate_authorization_request(
+        &self,
+        request: &AuthorizationRequest,
+    ) -> Result<ValidatedAuthorization, AuthorizationError> {
+        let Some(client_id) = request.client_id.as_deref() else {
+            return Err(OAuthError::InvalidRequest("client_id is required".into()).into());
+        };
+        let Some(client) = self
+            .oauth_client_repository
+            .find_by_client_id(client_id)
+            .await
+            .map_err(OAuthError::from)?
+        else {
+            return Err(OAuthError::InvalidRequest("the client is not registered".into()).into());
+        };
+
+        // redirect uris are compared exactly, anything looser lets codes leak to other hosts
+        let redirect_uri = request
+            .redirect_uri
+            .as_deref()
+            .filter(|redirect_uri| client.redirect_uris.iter().any(|uri| uri == redirect_uri))
+            .and_then(|redirect_uri| Url::parse(redirect_uri).ok())
+            .ok_or_else(|| {
+                OAuthError::InvalidRequest("redirect_uri is not registered for the client".into())
+            })?;
+
+        let redirect_error = |error: OAuthError| AuthorizationError {
+            error,
+            redirect_uri: Some(redirect_uri.clone()),
+            state: request.state.clone(),
+        };
+
+        if request.response_type.as_deref() != Some("code") {
+            return Err(redirect_error(OAuthError::UnsupportedResponseType));
+        }
+        if !client.allows_grant_type(GRANT_AUTHORIZATION_CODE) {
+            return Err(redirect_error(OAuthError::UnauthorizedClient));
+        }
+
+        let scope = request.scope.as_deref().unwrap_or_default();
+        if scope.split_whitespace().next().is_none() {
+            return Err(redirect_error(OAuthError::InvalidScope(
+                "scope is required".into(),
+            )));
+        }
+        if let Some(unsupported) = scope
+            .split_whitespace()
+            .find(|scope| !client.allows_scope(scope))
+        {
+            return Err(redirect_error(OAuthError::InvalidScope(format!(
+                "the {unsupported} scope is not available to the client"
+            ))));
+        }
+
+        if request.code_challenge_method.as_deref() != Some("S256") {
+            return Err(redirect_error(OAuthError::InvalidRequest(
+                "code_challenge_method must be S256".into(),
+            )));
+        }
+        let Some(code_challenge) = request
+            .code_challenge
+            .as_deref()
+            .filter(|challenge| is_valid_code_challenge(challenge))
+        else {
+            return Err(redirect_error(OAuthError::InvalidRequest(
+                "code_challenge must be a base64url encoded SHA-256 digest".into(),
+            )));
+        };
+
+        Ok(ValidatedAuthorization {
+            client_id: client.client_id,
+            redirect_uri,
+            scope: scope.split_whitespace().collect::<Vec<_>>().join(" "),
+            state: request.state.clone(),
+            nonce: request.nonce.clone(),
+            code_challenge: code_challenge.to_string(),
+        })
+    }
+
+    /// confidential clients must present their secret, public clients are identified by id alone
+    async fn authenticate_client(
+        &self,
+        credentials: &ClientCredentials,
+    ) -> Result<OAuthClientEntity, OAuthError> {
+        let client = self
+            .oauth_client_repository
+            .find_by_client_id(
+                credentials
+                    .client_id
+                    .as_deref()
+                    .ok_or(OAuthError::InvalidClient)?,
+            )
+            .await?
+            .ok_or(OAuthError::InvalidClient)?;
+
+        if let Some(client_secret_hash) = &client.client_secret_hash {
+            let client_secret = credentials
+                .client_secret
+                .as_deref()
+                .ok_or(OAuthError::InvalidClient)?;
+            if !constant_time_eq(
+                sha256_hex(client_secret).as_bytes(),
+                client_secret_hash.as_bytes(),
+            ) {
+                return Err(OAuthError::InvalidClient);
+            }
+        }
+
+        Ok(client)
+    }
+
+    async fn exchange_authorization_code(
+        &self,
+        client: &OAuthClientEntity,
+        request: &TokenRequest,
+    ) -> Result<TokenResponse, OAuthError> {
+        let code = request
+            .code
+            .as_deref()
+            .ok_or_else(|| OAuthError::InvalidRequest("code is required".into()))?;
+        let code_verifier = request
+            .code_verifier
+            .as_deref()
+            .ok_or_else(|| OAuthError::InvalidRequest("code_verifier is required".into()))?;
+
+        let invalid_grant =
+            || OAuthError::InvalidGrant("the authorization code is invalid or expired".into());
+        let authorization_code = self
+            .authorization_code_repository
+            .consume(&sha256_hex(code))
+            .await?
+            .ok_or_else(invalid_grant)?;
+
+        if authorization_code.client_id != client.client_id
+            || request.redirect_uri.as_deref() != Some(authorization_code.redirect_uri.as_str())
+            || authorization_code.expires_at < chrono::Utc::now()
+        {
+            return Err(invalid_grant());
+        }
+        if !constant_time_eq(
+            sha256_base64url(code_verifier).as_bytes(),
+            authorization_code.code_challenge.as_bytes(),
+        ) {
+            return Err(OAuthError::InvalidGrant(
+                "the code verifier does not match the code challenge".into(),
+            ));
+        }
+
+        let user = self
+            .user_repository
+            .find_by_identifier(&authorization_code.user_identifier)
+            .await
+            .ok_or_else(invalid_grant)?;
+        let grant = OAuthGrant {
+            client_id: client.client_id.to_string(),
+            scope: authorization_code.scope,
+        };
+
+        self.grant_user_tokens(
+            client,
+            &user,
+            grant,
+            authorization_code.auth_time.timestamp(),
+            authorization_code.nonce,
+        )
+        .await
+    }
+
+    /// polled by the device until the user has approved or denied it, polling faster than the
+    /// interval earns a slow_down and a longer interval from then on
+    async fn exchange_device_code(
+        &self,
+        client: &OAuthClientEntity,
+        request: &TokenRequest,
+    ) -> Result<TokenResponse, OAuthError> {
+        let device_code = request
+            .device_code
+            .as_deref()
+            .ok_or_else(|| OAuthError::InvalidRequest("device_code is required".into()))?;
+
+        let invalid_grant = || OAuthError::InvalidGrant("the device code is invalid".into());
+        let device_code = self
+            .device_code_repository
+            .find_by_device_code_hash(&sha256_hex(device_code))
+            .await?
+            .filter(|device_code| device_code.client_id == client.client_id)
+            .ok_or_else(invalid_grant)?;
+
+        let now = chrono::Utc::now();
+        if device_code.consumed_at.is_some() {
+            return Err(invalid_grant());
+        }
+        if device_code.expires_at < now {
+            return Err(OAuthError::ExpiredToken);
+        }
+        if device_code.denied_at.is_some() {
+            return Err(OAuthError::AuthorizationDenied);
+        }
+
+        let interval = device_code.interval_seconds;
+        if device_code.last_polled_at.is_some_and(|last_polled_at| {
+            now < last_polled_at + chrono::Duration::seconds(interval.into())
+        }) {
+            self.device_code_repository
+                .record_poll(&device_code.identifier, now, interval + SLOW_DOWN_SECONDS)
+                .await?;
+            return Err(OAuthError::SlowDown);
+        }
+        self.device_code_repository
+            .record_poll(&device_code.identifier, now, interval)
+            .await?;
+
+        if device_code.approved_at.is_none() {
+            return Err(OAuthError::AuthorizationPending);
+        }
+        let device_code = self
+            .device_code_repository
+            .consume(&device_code.identifier)
+            .await?
+            .ok_or_else(invalid_grant)?;
+        let (Some(user_identifier), Some(auth_time)) =
+            (device_code.user_identifier, device_code.auth_time)
+        else {
+            return Err(invalid_grant());
+        };
+
+        let user = self
+            .user_repository
+            .find_by_identifier(&user_identifier)
+            .await
+            .ok_or_else(invalid_grant)?;
+        let grant = OAuthGrant {
+            client_id: client.client_id.to_string(),
+            scope: device_code.scope,
+        };
+
+        self.grant_user_tokens(client, &user, grant, auth_time.timestamp(), None)
+            .await
+    }
+
+    /// the access token, a refresh token with offline_access and an id token with openid
+    async fn grant_user_tokens(
+        &self,
+        client: &OAuthClientEntity,
+        user: &UserEntity,
+        grant: OAuthGrant,
+        auth_time: i64,
+        nonce: Option<String>,
+    ) -> Result<TokenResponse, OAuthError> {
+        let refresh_token = if grant.has_scope(SCOPE_OFFLINE_ACCESS)
+            && client.allows_grant_type(GRANT_REFRESH_TOKEN)
+        {
+            Some(
+                self.token_service
+                    .issue_refresh_token(&user.identifier, None, Some(&grant))
+                    .await?,
+            )
+        } else {
+            None
+        };
+        let id_token = if grant.has_scope(SCOPE_OPENID) {
+            Some(self.generate_id_token(client, user, &grant, auth_time, nonce)?)
+        } else {
+            None
+        };
+
+        self.token_response(client, user, grant, refresh_token, id_token)
+    }
+
+    async fn exchange_refresh_token(
+        &self,
+        client: &OAuthClientEntity,
+        request: &TokenRequest,
+    ) -> Result<TokenResponse, OAuthError> {
+        let refresh_token = request
+            .refresh_token
+            .as_deref()
+            .ok_or_else(|| OAuthError::InvalidRequest("refresh_token is required".into()))?;
+
+        let RotatedRefreshToken {
+            user_identifier,
+            refresh_token,
+            grant,
+            ..
+        } = self
+            .token_service
+            .rotate_refresh_token(refresh_token, Some(&client.client_id))
+            .await?;
+
+        let user = self
+            .user_repository
+            .find_by_identifier(&user_identifier)
+            .await
+            .ok_or_else(|| OAuthError::InvalidGrant("the refresh token is invalid".into()))?;
+        let grant = grant.ok_or_else(|| {
+            OAuthError::InvalidGrant("the refresh token was not issued to a client".into())
+        })?;
+
+        self.token_response(client, &user, grant, Some(refresh_token), None)
+    }
+
+    /// tokens for the client itself, confidential clients only since the secret is the sole proof
+    /// of who is asking
+    fn exchange_client_credentials(
+        &self,
+        client: &OAuthClientEntity,
+        request: &TokenRequest,
+    ) -> Result<TokenResponse, OAuthError> {
+        if !client.is_confidential() {
+            return Err(OAuthError::UnauthorizedClient);
+        }
+
+        let scope = requested_scope(client, request.scope.as_deref())?;
+        let access_token = self
+            .token_service
+            .generate_client_token(&ClientClaims::new(
+                &self.jwt_config,
+                &client.client_id,
+                &scope,
+                client.access_token_ttl(),
+            ))?;
+
+        Ok(TokenResponse {
+            access_token,
+            token_type: "Bearer".into(),
+            expires_in: client.access_token_ttl().as_secs(),
+            refresh_token: None,
+            id_token: None,
+            scope,
+            issued_token_type: None,
+        })
+    }
+
+    /// swaps a user access token for one with a narrower scope or another audience, the client
+    /// is recorded as the actor. With `requested_subject` the subject token must belong to an admin
+    /// allowed to impersonate users, who is then recorded as the actor instead. Every exchange that
+    /// gets as far as a valid subject token is audited, refused ones with the reason
+    async fn exchange_subject_token(
+        &self,
+        client: &OAuthClientEntity,
+        request: &TokenRequest,
+    ) -> Result<TokenResponse, OAuthError> {
+        if !client.is_confidential() {
+            return Err(OAuthError::UnauthorizedClient);
+        }
+        let subject_token = request
+            .subject_token
+            .as_deref()
+            .ok_or_else(|| OAuthError::InvalidRequest("subject_token is required".into()))?;
+        if !matches!(
+            request.subject_token_type.as_deref(),
+            Some(TOKEN_TYPE_ACCESS_TOKEN | TOKEN_TYPE_JWT)
+        ) {
+            return Err(OAuthError::InvalidRequest(
+                "subject_token_type must be an access token or jwt token type".into(),
+            ));
+        }
+        if request.actor_token.is_some() || request.actor_token_type.is_some() {
+            return Err(OAuthError::InvalidRequest(
+                "actor tokens are not supported, the authenticated client is the actor".into(),
+            ));
+        }
+        if !matches!(
+            request.requested_token_type.as_deref(),
+            None | Some(TOKEN_TYPE_ACCESS_TOKEN | TOKEN_TYPE_JWT)
+        ) {
+            return Err(OAuthError::InvalidRequest(
+                "only access tokens can be requested".into(),
+            ));
+        }
+
+        let subject = active(
+            self.token_service
+                .verify_token_for_any_audience(subject_token)
+                .await,
+        )?
+        .filter(|claims| claims.token_use == TokenUse::Access)
+        .ok_or_else(|| {
+            OAuthError::InvalidGrant("the subject token is invalid, expired or revoked".into())
+        })?;
+        // from here on every outcome is audited, the fields are refined as the request is checked
+        let requested_subject = request.requested_subject.as_deref();
+        let mut audit = TokenExchangeAuditEntity {
+            identifier: Uuid::new_v4(),
+            client_id: client.client_id.to_string(),
+            subject_identifier: requested_subject
+                .and_then(|requested_subject| Uuid::parse_str(requested_subject).ok())
+                .unwrap_or(subject.sub),
+            subject_token_jti: subject.jti,
+            actor: match requested_subject {
+                Some(_) => subject.sub.to_string(),
+                None => client.client_id.to_string(),
+            },
+            actor_type: match requested_subject {
+                Some(_) => SubjectType::User.as_str().into(),
+                None => SubjectType::Client.as_str().into(),
+            },
+            impersonation: requested_subject.is_some(),
+            scope: request.scope.as_deref().unwrap_or_default().to_string(),
+            audience: request
+                .audience
+                .as_deref()
+                .unwrap_or(&self.jwt_config.audience)
+                .to_string(),
+            granted: false,
+            reason: None,
+            created_at: chrono::Utc::now(),
+        };
+
+        let exchanged = self
+            .issue_exchanged_token(client, request, &subject, &mut audit)
+            .await;
+        match &exchanged {
+            Ok(_) => audit.granted = true,
+            Err(err) => {
+                audit.reason.get_or_insert_with(|| err.to_string());
+            }
+        }
+        self.token_exchange_audit_repository.record(&audit).await?;
+
+        exchanged
+    }
+
+    /// the checks and the token behind `exchange_subject_token`, which audits whatever comes out.
+    /// A refusal whose audited reason should say more than the error sets `audit.reason` itself
+    async fn issue_exchanged_token(
+        &self,
+        client: &OAuthClientEntity,
+        request: &TokenRequest,
+        subject: &Claims,
+        audit: &mut TokenExchangeAuditEntity,
+    ) -> Result<TokenResponse, OAuthError> {
+        let audience = self.requested_audience(request.audience.as_deref()).await?;
+        audit.audience = audience.to_string();
+        let scope = requested_scope(client, request.scope.as_deref())?;
+        audit.scope = scope.to_string();
+        // the exchanged token may only narrow what the subject token was granted
+        let scope = match (&subject.scope, &request.scope) {
+            (Some(granted), None) => scope
+                .split_whitespace()
+                .filter(|scope| has_scope(granted, scope))
+                .collect::<Vec<_>>()
+                .join(" "),
+            (Some(granted), Some(_)) => {
+                if let Some(ungranted) = scope
+                    .split_whitespace()
+                    .find(|scope| !has_scope(granted, scope))
+                {
+                    return Err(OAuthError::InvalidScope(format!(
+                        "the subject token was not granted the {ungranted} scope"
+                    )));
+                }
+                scope
+            }
+            (None, _) => scope,
+        };
+        if scope.is_empty() {
+            return Err(OAuthError::InvalidScope(
+                "the subject token and the client have no scope in common".into(),
+            ));
+        }
+        audit.scope = scope.to_string();
+
+        // never outlives the token it was exchanged for
+        let remaining =
+            Duration::from_secs((subject.exp - chrono::Utc::now().timestamp()).max(0) as u64);
+        let validity = client.access_token_ttl().min(remaining);
+        let credentials = match request.requested_subject.as_deref() {
+            None => {
+                let actor = Actor {
+                    sub: client.client_id.to_string(),
+                    sub_type: SubjectType::Client,
+                    act: subject.act.clone().map(Box::new),
+                };
+                JwtCredentials::new(&subject.email, &subject.sub, subject.ver, TokenUse::Access)
+                    .with_actor(actor)
+                    .with_first_party(client.first_party)
+                    .with_grant(OAuthGrant {
+                        client_id: client.client_id.to_string(),
+                        scope,
+                    })
+            }
+            Some(requested_subject) => {
+                // only an admin's own token will do, signed in here or issued to this very client,
+                // and not one that was itself exchanged
+                let admin = self.user_repository.find_by_identifier(&subject.sub).await;
+                let refusal = if subject.act.is_some() {
+                    Some("the subject token was itself obtained through a token exchange")
+                } else if !subject.first_party
+                    && subject.client_id.as_deref() != Some(client.client_id.as_str())
+                {
+                    Some("the subject token was issued to another third-party client")
+                } else if !admin.as_ref().is_some_and(|admin| {
+                    admin
+                        .permissions
+                        .iter()
+                        .any(|granted| granted == Permission::ImpersonateUsers.as_str())
+                }) {
+                    Some("the actor lacks the users:impersonate permission")
+                } else {
+                    None
+                };
+                if let Some(reason) = refusal {
+                    audit.reason = Some(reason.into());
+                    return Err(OAuthError::AccessDenied(format!(
+                        "impersonation requires the {} permission and the admin's own access token",
+                        Permission::ImpersonateUsers
+                    )));
+                }
+
+                let unknown_subject =
+                    || OAuthError::InvalidRequest("requested_subject is not a known user".into());
+                let target_identifier =
+                    Uuid::parse_str(requested_subject).map_err(|_| unknown_subject())?;
+                let target = self
+                    .user_repository
+                    .find_by_identifier(&target_identifier)
+                    .await
+                    .ok_or_else(unknown_subject)?;
+
+                let actor = Actor {
+                    sub: subject.sub.to_string(),
+                    sub_type: SubjectType::User,
+                    act: subject.act.clone().map(Box::new),
+                };
+                JwtCredentials::new(
+                    &target.email,
+                    &target.identifier,
+                    target.token_version,
+                    TokenUse::Access,
+                )
+                .with_actor(actor)
+                .with_first_party(client.first_party)
+                .with_grant(OAuthGrant {
+                    client_id: client.client_id.to_string(),
+                    scope,
+                })
+            }
+        };
+
+        let access_token = self
+            .token_service
+            .generate_token(&credentials.with_audience(&audience), validity)?;
+
+        Ok(TokenResponse {
+            access_token,
+            token_type: "Bearer".into(),
+            expires_in: validity.as_secs(),
+            refresh_token: None,
+            id_token: None,
+            scope: audit.scope.to_string(),
+            issued_token_type: Some(TOKEN_TYPE_ACCESS_TOKEN.into()),
+        })
+    }
+
+    /// tokens are meant for this api unless another registered client is named as the audience
+    async fn requested_audience(&self, audience: Option<&str>) -> Result<String, OAuthError> {
+        match audience {
+            None => Ok(self.jwt_config.audience.to_string()),
+            Some(audience) if audience == self.jwt_config.audience => Ok(audience.to_string()),
+            Some(audience) => self
+                .oauth_client_repository
+                .find_by_client_id(audience)
+                .await?
+                .map(|client| client.client_id)
+                .ok_or_else(|| {
+                    OAuthError::InvalidTarget("the audience is not a registered client".into())
+                }),
+        }
+    }
+
+    fn generate_id_token(
+        &self,
+        client: &OAuthClientEntity,
+        user: &UserEntity,
+        grant: &OAuthGrant,
+        auth_time: i64,
+        nonce: Option<String>,
+    ) -> Result<String, OAuthError> {
+        let now = chrono::Utc::now().timestamp();
+        let profile = grant.has_scope(SCOPE_PROFILE);
+        let claims = IdTokenClaims {
+            iss: self.jwt_config.issuer.to_string(),
+            sub: user.identifier,
+            aud: grant.client_id.to_string(),
+            iat: now,
+            exp: now + client.access_token_ttl().as_secs() as i64,
+            auth_time,
+            nonce,
+            email: grant.has_scope(SCOPE_EMAIL).then(|| user.email.to_string()),
+            given_name: profile.then(|| user.first_name.to_string()),
+            family_name: profile.then(|| user.last_name.to_string()),
+        };
+
+        Ok(self.token_service.generate_id_token(&claims)?)
+    }
+
+    fn token_response(
+        &self,
+        client: &OAuthClientEntity,
+        user: &UserEntity,
+        grant: OAuthGrant,
+        refresh_token: Option<String>,
+        id_token: Option<String>,
+    ) -> Result<TokenResponse, OAuthError> {
+        let scope = grant.scope.to_string();
+        let access_token = self.token_service.generate_token(
+            &JwtCredentials::new(
+                &user.email,
+                &user.identifier,
+                user.token_version,
+                TokenUse::Access,
+            )
+            .with_grant(grant)
+            .with_first_party(client.first_party),
+            client.access_token_ttl(),
+        )?;
+
+        Ok(TokenResponse {
+            access_token,
+            token_type: "Bearer".into(),
+            expires_in: client.access_token_ttl().as_secs(),
+            refresh_token,
+            id_token,
+            scope,
+            issued_token_type: None,
+        })
+    }
+}
+
+/// 32 bytes of SHA-256 in unpadded base64url are always 43 characters
+fn is_valid_code_challenge(challenge: &str) -> bool {
+    challenge.len() == 43
+        && challenge
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+}
+
+/// the requested scopes, all of the client's scopes when none were requested
+fn requested_scope(client: &OAuthClientEntity, scope: Option<&str>) -> Result<String, OAuthError> {
+    let Some(scope) = scope else {
+        return Ok(client.scopes.join(" "));
+    };
+    if let Some(unavailable) = scope
+        .split_whitespace()
+        .find(|scope| !client.allows_scope(scope))
+    {
+        return Err(OAuthError::InvalidScope(format!(
+            "the {unavailable} scope is not available to the client"
+        )));
+    }
+
+    Ok(scope.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+/// user codes are stored without the dash and compared case insensitively
+fn normalize_user_code(user_code: &str) -> String {
+    user_code
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|character| character.to_ascii_uppercase())
+        .collect()
+}
+
+/// `BCDF-GHJK`, easier to read off a screen than eight letters in a row
+fn format_user_code(user_code: &str) -> String {
+    let (first, second) = user_code.split_at(user_code.len() / 2);
+    format!("{first}-{second}")
+}
+
+/// a token that failed verification is merely inactive, any other error is the server's
+fn active<T>(verified: Result<T, AuthenticationServiceError>) -> Result<Option<T>, OAuthError> {
+    match verified {
+        Ok(verified) => Ok(Some(verified)),
+        Err(AuthenticationServiceError::InvalidToken) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn required_token(token: Option<&str>) -> Result<&str, OAuthError> {
+    token.ok_or_else(|| OAuthError::InvalidRequest("token is required".into()))
+}
+
+pub trait OAuthServiceTrait {
+    fn openid_configuration(&self) -> OpenIdConfiguration;
+
+    /// validates the request before the user agent is sent to the login page, the page completes
+    /// the request through `authorize` once the user has signed in
+    fn start_authorization(
+        &self,
+        request: &AuthorizationRequest,
+        query: &str,
+    ) -> impl std::future::Future<Output = Result<String, AuthorizationError>> + Send;
+
+    fn authorize(
+        &self,
+        claims: &Claims,
+        request: &AuthorizationRequest,
+    ) -> impl std::future::Future<Output = Result<AuthorizationResponse, OAuthError>> + Send;
+
+    fn exchange_token(
+        &self,
+        credentials: &ClientCredentials,
+        request: &TokenRequest,
+    ) -> impl std::future::Future<Output = Result<TokenResponse, OAuthError>> + Send;
+
+    fn userinfo(
+        &self,
+        claims: &Claims,
+    ) -> impl std::future::Future<Output = Result<UserInfoResponse, OAuthError>> + Send;
+
+    /// starts the device flow, the device shows the user code and polls the token endpoint with
+    /// the device code while the user approves it on another screen
+    fn authorize_device(
+        &self,
+        credentials: &ClientCredentials,
+        request: &DeviceAuthorizationRequest,
+    ) -> impl std::future::Future<Output = Result<DeviceAuthorizationResponse, OAuthError>> + Send;
+
+    /// the pending request behind a user code, for the verification page to show
+    fn find_device_authorization(
+        &self,
+        user_code: &str,
+    ) -> impl std::future::Future<Output = Result<DeviceVerificationResponse, OAuthError>> + Send;
+
+    fn verify_device(
+        &self,
+        claims: &Claims,
+        request: &DeviceVerificationRequest,
+    ) -> impl std::future::Future<Output = Result<(), OAuthError>> + Send;
+
+    /// tells a confidential client such as the api gateway whether a token is active, tokens
+    /// that are expired, revoked or not access or refresh tokens are reported as inactive
+    fn introspect(
+        &self,
+        credentials: &ClientCredentials,
+        request: &IntrospectionRequest,
+    ) -> impl std::future::Future<Output = Result<IntrospectionResponse, OAuthError>> + Send;
+
+    /// revokes an access or refresh token issued to the calling client, unknown and already
+    /// invalid tokens are ignored as RFC 7009 asks
+    fn revoke(
+        &self,
+        credentials: &ClientCredentials,
+        request: &RevocationRequest,
+    ) -> impl std::future::Future<Output = Result<(), OAuthError>> + Send;
+}
+
+impl OAuthServiceTrait for OAuthService {
+    fn openid_configuration(&self) -> OpenIdConfiguration {
+        let to_strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+
+        OpenIdConfiguration {
+            issuer: self.jwt_config.issuer.to_string(),
+            authorization_endpoint: self.endpoint("/oauth/authorize"),
+            token_endpoint: self.endpoint("/oauth/token"),
+            device_authorization_endpoint: self.endpoint("/device/code"),
+            introspection_endpoint: self.endpoint("/oauth/introspect"),
+            revocation_endpoint: self.endpoint("/oauth/revoke"),
+            userinfo_endpoint: self.endpoint("/oauth/userinfo"),
+            jwks_uri: self.endpoint("/.well-known/jwks.json"),
+            response_types_supported: to_strings(&["code"]),
+            grant_types_supported: to_strings(&SUPPORTED_GRANT_TYPES),
+            subject_types_supported: to_strings(&["public"]),
+            id_token_signing_alg_values_supported: vec![format!(
+                "{:?}",
+                self.token_service.signing_algorithm()
+            )],
+            scopes_supported: to_strings(&SUPPORTED_SCOPES),
+            token_endpoint_auth_methods_supported: to_strings(&[
+                "none",
+                "client_secret_basic",
+                "client_secret_post",
+            ]),
+            introspection_endpoint_auth_methods_supported: to_strings(&[
+                "client_secret_basic",
+                "client_secret_post",
+            ]),
+            revocation_endpoint_auth_methods_supported: to_strings(&[
+                "none",
+                "client_secret_basic",
+                "client_secret_post",
+            ]),
+            code_challenge_methods_supported: to_strings(&["S256"]),
+            claims_supported: to_strings(&[
+                "sub",
+                "iss",
+                "aud",
+                "exp",
+                "iat",
+                "auth_time",
+                "nonce",
+                "email",
+                "name",
+                "given_name",
+                "family_name",
+            ]),
+        }
+    }
+
+    async fn start_authorization(
+        &self,
+        request: &AuthorizationRequest,
+        query: &str,
+    ) -> Result<String, AuthorizationError> {
+        self.validate_authorization_request(request).await?;
+
+        Ok(self.config.login_redirect(query))
+    }
+
+    async fn authorize(
+        &self,
+        claims: &Claims,
+        request: &AuthorizationRequest,
+    ) -> Result<AuthorizationResponse, OAuthError> {
+        let ValidatedAuthorization {
+            client_id,
+            mut redirect_uri,
+            scope,
+            state,
+            nonce,
+            code_challenge,
+        } = self
+            .validate_authorization_request(request)
+            .await
+            .map_err(|err| err.error)?;
+
+        // a token without it did not come from a sign-in, so there is nothing to vouch for
+        let auth_time = claims
+            .auth_time
+            .and_then(|auth_time| chrono::DateTime::from_timestamp(auth_time, 0))
+            .ok_or_else(|| OAuthError::AccessDenied("sign in again to continue".into()))?;
+        let code = generate_opaque_token();
+        let now = chrono::Utc::now();
+        self.authorization_code_repository
+            .create(&AuthorizationCodeEntity {
+                identifier: Uuid::new_v4(),
+                code_hash: sha256_hex(&code),
+                client_id,
+                user_identifier: claims.sub,
+                redirect_uri: redirect_uri.to_string(),
+                scope,
+                nonce,
+                code_challenge,
+                auth_time,
+                expires_at: now + self.config.authorization_code_ttl,
+                used_at: None,
+                created_at: now,
+            })
+            .await?;
+
+        {
+            let mut query = redirect_uri.query_pairs_mut();
+            query.append_pair("code", &code);
+            if let Some(state) = &state {
+                query.append_pair("state", state);
+            }
+        }
+
+        Ok(AuthorizationResponse {
+            redirect_to: redirect_uri.to_string(),
+        })
+    }
+
+    async fn exchange_token(
+        &self,
+        credentials: &ClientCredentials,
+        request: &TokenRequest,
+    ) -> Result<TokenResponse, OAuthError> {
+        let grant_type = match request.grant_type.as_deref() {
+            Some(
+                grant_type @ (GRANT_AUTHORIZATION_CODE
+                | GRANT_REFRESH_TOKEN
+                | GRANT_CLIENT_CREDENTIALS
+                | GRANT_DEVICE_CODE
+                | GRANT_TOKEN_EXCHANGE),
+            ) => grant_type,
+            Some(_) => return Err(OAuthError::UnsupportedGrantType),
+            None => return Err(OAuthError::InvalidRequest("grant_type is required".into())),
+        };
+
+        let client = self.authenticate_client(credentials).await?;
+        if !client.allows_grant_type(grant_type) {
+            return Err(OAuthError::UnauthorizedClient);
+        }
+
+        match grant_type {
+            GRANT_AUTHORIZATION_CODE => self.exchange_authorization_code(&client, request).await,
+            GRANT_REFRESH_TOKEN => self.exchange_refresh_token(&client, request).await,
+            GRANT_DEVICE_CODE => self.exchange_device_code(&client, request).await,
+            GRANT_TOKEN_EXCHANGE => self.exchange_subject_token(&client, request).await,
+            _ => self.exchange_client_credentials(&client, request),
+        }
+    }
+
+    async fn userinfo(&self, claims: &Claims) -> Result<UserInfoResponse, OAuthError> {
+        // first-party tokens carry no scope and see everything, client tokens only what was granted
+        let granted = |scope: &str| {
+            claims
+                .scope
+                .as_deref()
+                .is_none_or(|scopes| has_scope(scopes, scope))
+        };
+        if !granted(SCOPE_OPENID) {
+            return Err(OAuthError::AccessDenied(
+                "the access token was not granted the openid scope".into(),
+            ));
+        }
+
+        let user = self
+            .user_repository
+            .retrieve_information(&claims.sub)
+            .await
+            .map_err(|err| OAuthError::ServerError(err.to_string()))?;
+        let profile = granted(SCOPE_PROFILE);
+
+        Ok(UserInfoResponse {
+            sub: user.identifier,
+            email: granted(SCOPE_EMAIL).then_some(user.email),
+            name: profile.then(|| format!("{} {}", user.first_name, user.last_name)),
+            given_name: profile.then_some(user.first_name),
+            family_name: profile.then_some(user.last_name),
+        })
+    }
+
+    async fn authorize_device(
+        &self,
+        credentials: &ClientCredentials,
+        request: &DeviceAuthorizationRequest,
+    ) -> Result<DeviceAuthorizationResponse, OAuthError> {
+        let client = self.authenticate_client(credentials).await?;
+        if !client.allows_grant_type(GRANT_DEVICE_CODE) {
+            return Err(OAuthError::UnauthorizedClient);
+        }
+        let scope = requested_scope(&client, request.scope.as_deref())?;
+
+        let device_code = generate_opaque_token();
+        let now = chrono::Utc::now();
+        let mut user_code = None;
+        for _ in 0..USER_CODE_ATTEMPTS {
+            let candidate = generate_user_code(USER_CODE_LENGTH);
+            let created = self
+                .device_code_repository
+                .create(&DeviceCodeEntity {
+                    identifier: Uuid::new_v4(),
+                    device_code_hash: sha256_hex(&device_code),
+                    user_code: candidate.to_string(),
+                    client_id: client.client_id.to_string(),
+                    scope: scope.to_string(),
+                    user_identifier: None,
+                    interval_seconds: self.config.device_poll_interval.as_secs() as i32,
+                    last_polled_at: None,
+                    approved_at: None,
+                    auth_time: None,
+                    denied_at: None,
+                    consumed_at: None,
+                    expires_at: now + self.config.device_code_ttl,
+                    created_at: now,
+                })
+                .await?;
+            if created {
+                user_code = Some(candidate);
+                break;
+            }
+        }
+        let user_code = user_code
+            .ok_or_else(|| OAuthError::ServerError("no free user code could be found".into()))?;
+
+        let user_code = format_user_code(&user_code);
+        Ok(DeviceAuthorizationResponse {
+            device_code,
+            verification_uri: self.config.device_verification_url.to_string(),
+            verification_uri_complete: self.config.device_verification_redirect(&user_code),
+            user_code,
+            expires_in: self.config.device_code_ttl.as_secs(),
+            interval: self.config.device_poll_interval.as_secs(),
+        })
+    }
+
+    async fn find_device_authorization(
+        &self,
+        user_code: &str,
+    ) -> Result<DeviceVerificationResponse, OAuthError> {
+        let invalid_code =
+            || OAuthError::InvalidRequest("the code is invalid or has expired".into());
+        let device_code = self
+            .device_code_repository
+            .find_pending_by_user_code(&normalize_user_code(user_code))
+            .await?
+            .ok_or_else(invalid_code)?;
+        let client = self
+            .oauth_client_repository
+            .find_by_client_id(&device_code.client_id)
+            .await?
+            .ok_or_else(invalid_code)?;
+
+        Ok(DeviceVerificationResponse {
+            user_code: format_user_code(&device_code.user_code),
+            client_id: client.client_id,
+            client_name: client.name,
+            scope: device_code.scope,
+        })
+    }
+
+    async fn verify_device(
+        &self,
+        claims: &Claims,
+        request: &DeviceVerificationRequest,
+    ) -> Result<(), OAuthError> {
+        let invalid_code =
+            || OAuthError::InvalidRequest("the code is invalid or has expired".into());
+        let device_code = self
+            .device_code_repository
+            .find_pending_by_user_code(&normalize_user_code(&request.user_code))
+            .await?
+            .ok_or_else(invalid_code)?;
+
+        let decided = if request.approve {
+            let auth_time = claims
+                .auth_time
+                .and_then(|auth_time| chrono::DateTime::from_timestamp(auth_time, 0))
+                .ok_or_else(|| OAuthError::AccessDenied("sign in again to continue".into()))?;
+            self.device_code_repository
+                .approve(&device_code.identifier, &claims.sub, auth_time)
+                .await?
+        } else {
+            self.device_code_repository
+                .deny(&device_code.identifier)
+                .await?
+        };
+        if !decided {
+            return Err(invalid_code());
+        }
+
+        Ok(())
+    }
+
+    async fn introspect(
+        &self,
+        credentials: &ClientCredentials,
+        request: &IntrospectionRequest,
+    ) -> Result<IntrospectionResponse, OAuthError> {
+        let client = self.authenticate_client(credentials).await?;
+        if !client.is_confidential() {
+            return Err(OAuthError::InvalidClient);
+        }
+        let token = required_token(request.token.as_deref())?;
+
+        if !token.contains('.') {
+            return Ok(
+                active(self.token_service.find_active_refresh_token(token).await)?
+                    .map(IntrospectionResponse::from)
+                    .unwrap_or_else(IntrospectionResponse::inactive),
+            );
+        }
+
+        if let Some(claims) = active(
+            self.token_service
+                .verify_token_for_any_audience(token)
+                .await,
+        )? {
+            if claims.token_use == TokenUse::Access {
+                return Ok(IntrospectionResponse::from(claims));
+            }
+            return Ok(IntrospectionResponse::inactive());
+        }
+
+        Ok(active(self.token_service.verify_client_token(token).await)?
+            .map(IntrospectionResponse::from)
+            .unwrap_or_else(IntrospectionResponse::inactive))
+    }
+
+    async fn revoke(
+        &self,
+        credentials: &ClientCredentials,
+        request: &RevocationRequest,
+    ) -> Result<(), OAuthError> {
+        let client = self.authenticate_client(credentials).await?;
+        let token = required_token(request.token.as_deref())?;
+        let issued_to_client = |client_id: Option<&str>| {
+            if client_id == Some(client.client_id.as_str()) {
+                Ok(())
+            } else {
+                Err(OAuthError::InvalidRequest(
+                    "the token was not issued to the client".into(),
+                ))
+            }
+        };
+
+        if !token.contains('.') {
+            if let Some(refresh_token) =
+                active(self.token_service.find_active_refresh_token(token).await)?
+            {
+                issued_to_client(refresh_token.client_id.as_deref())?;
+                self.token_service
+                    .revoke_refresh_token(token, &refresh_token.user_identifier)
+                    .await?;
+            }
+            return Ok(());
+        }
+
+        if let Some(claims) = active(
+            self.token_service
+                .verify_token_for_any_audience(token)
+                .await,
+        )? {
+            issued_to_client(claims.client_id.as_deref())?;
+            self.token_service.revoke_token(&claims).await?;
+        } else if let Some(claims) = active(self.token_service.verify_client_token(token).await)? {
+            issued_to_client(Some(&claims.client_id))?;
+            self.token_service.revoke_client_token(&claims).await?;
+        }
+
+        Ok(())
+    }
+}
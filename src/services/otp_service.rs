@@ -0,0 +1,193 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    adapters::dto::otp::OtpKind,
+    config::otp::{OTP_LENGTH, OtpConfig},
+    errors::otp_service_error::OtpServiceError,
+    repositories::otp_repository::{OtpRepository, OtpRepositoryTrait},
+    shared::crypto::{constant_time_eq, generate_numeric_code, generate_opaque_token, sha256_hex},
+};
+
+#[derive(Clone)]
+pub struct OtpService {
+    otp_repository: OtpRepository,
+    config: OtpConfig,
+}
+
+impl OtpService {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            otp_repository: OtpRepository::init(pool),
+            config: OtpConfig::from_env(),
+        }
+    }
+
+    pub fn expiry(&self) -> std::time::Duration {
+        self.config.expiry
+    }
+
+    fn hash_code(user_identifier: &Uuid, kind: OtpKind, code: &str) -> String {
+        sha256_hex(&format!("{user_identifier}:{kind}:{code}"))
+    }
+}
+
+pub trait OtpServiceTrait {
+    /// generates a new code for the user, replacing any code previously issued for the same kind
+    fn issue(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+    ) -> impl std::future::Future<Output = Result<String, OtpServiceError>> + Send;
+
+    /// like `issue` for codes that should not live as long as the configured expiry
+    fn issue_code(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+        validity: std::time::Duration,
+    ) -> impl std::future::Future<Output = Result<String, OtpServiceError>> + Send;
+
+    /// consumes the code, it cannot be used again once this returns Ok
+    fn verify(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+        code: &str,
+    ) -> impl std::future::Future<Output = Result<(), OtpServiceError>> + Send;
+
+    /// like `issue` but for links, the value is a long random token rather than a short code
+    fn issue_token(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+        validity: std::time::Duration,
+    ) -> impl std::future::Future<Output = Result<String, OtpServiceError>> + Send;
+
+    /// consumes the token and returns the identifier of the user it was issued to
+    fn redeem_token(
+        &self,
+        kind: OtpKind,
+        token: &str,
+    ) -> impl std::future::Future<Output = Result<Uuid, OtpServiceError>> + Send;
+
+    fn revoke(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+    ) -> impl std::future::Future<Output = Result<(), OtpServiceError>> + Send;
+}
+
+impl OtpServiceTrait for OtpService {
+    async fn issue(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+    ) -> Result<String, OtpServiceError> {
+        self.issue_code(user_identifier, kind, self.config.expiry)
+            .await
+    }
+
+    async fn issue_code(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+        validity: std::time::Duration,
+    ) -> Result<String, OtpServiceError> {
+        let code = generate_numeric_code(OTP_LENGTH);
+        let expires_at = chrono::Utc::now() + validity;
+
+        self.otp_repository
+            .upsert(
+                user_identifier,
+                kind,
+                &Self::hash_code(user_identifier, kind, &code),
+                expires_at,
+            )
+            .await?;
+
+        Ok(code)
+    }
+
+    async fn verify(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+        code: &str,
+    ) -> Result<(), OtpServiceError> {
+        let Some(otp) = self
+            .otp_repository
+            .record_attempt(user_identifier, kind, self.config.max_attempts)
+            .await?
+        else {
+            // nothing left to try, the stored code tells why
+            let otp = self
+                .otp_repository
+                .find_by_user(user_identifier, kind)
+                .await?;
+            return Err(match otp {
+                Some(otp) if otp.used_at.is_some() => OtpServiceError::InvalidCode,
+                Some(otp) if otp.expires_at <= chrono::Utc::now() => OtpServiceError::ExpiredCode,
+                Some(_) => OtpServiceError::TooManyAttempts,
+                None => OtpServiceError::InvalidCode,
+            });
+        };
+
+        let code_hash = Self::hash_code(user_identifier, kind, code.trim());
+        if !constant_time_eq(code_hash.as_bytes(), otp.code_hash.as_bytes()) {
+            return Err(OtpServiceError::InvalidCode);
+        }
+
+        if !self.otp_repository.mark_as_used(&otp.identifier).await? {
+            return Err(OtpServiceError::InvalidCode);
+        }
+
+        Ok(())
+    }
+
+    async fn issue_token(
+        &self,
+        user_identifier: &Uuid,
+        kind: OtpKind,
+        validity: std::time::Duration,
+    ) -> Result<String, OtpServiceError> {
+        let token = generate_opaque_token();
+        let expires_at = chrono::Utc::now() + validity;
+
+        self.otp_repository
+            .upsert(user_identifier, kind, &sha256_hex(&token), expires_at)
+            .await?;
+
+        Ok(token)
+    }
+
+    async fn redeem_token(&self, kind: OtpKind, token: &str) -> Result<Uuid, OtpServiceError> {
+        let Some(otp) = self
+            .otp_repository
+            .find_by_code_hash(kind, &sha256_hex(token.trim()))
+            .await?
+        else {
+            return Err(OtpServiceError::InvalidCode);
+        };
+
+        if otp.used_at.is_some() {
+            return Err(OtpServiceError::InvalidCode);
+        }
+        if otp.expires_at < chrono::Utc::now() {
+            return Err(OtpServiceError::ExpiredCode);
+        }
+        if !self.otp_repository.mark_as_used(&otp.identifier).await? {
+            return Err(OtpServiceError::InvalidCode);
+        }
+
+        Ok(otp.user_identifier)
+    }
+
+    async fn revoke(&self, user_identifier: &Uuid, kind: OtpKind) -> Result<(), OtpServiceError> {
+        self.otp_repository
+            .delete_by_user(user_identifier, kind)
+            .await?;
+
+        Ok(())
+    }
+}
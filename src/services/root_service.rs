@@ -1,20 +1,28 @@
+use crate::adapters::response::root::HealthCheckResponse;
 use crate::errors::app_error::AppError;
+use crate::shared::hashing_pool::HashingPool;
 
 #[derive(Clone)]
-pub struct RootService {}
+pub struct RootService {
+    hashing_pool: HashingPool,
+}
 
 impl RootService {
-    pub fn init() -> Self {
-        Self {}
+    pub fn init(hashing_pool: &HashingPool) -> Self {
+        Self {
+            hashing_pool: hashing_pool.clone(),
+        }
     }
 }
 pub trait RootServiceTrait {
-    fn health_check(&self) -> Result<(), AppError>;
+    fn health_check(&self) -> Result<HealthCheckResponse, AppError>;
 }
 
 impl RootServiceTrait for RootService {
-    fn health_check(&self) -> Result<(), AppError> {
+    fn health_check(&self) -> Result<HealthCheckResponse, AppError> {
         log::info!("application is healthy ...");
-        Ok(())
+        Ok(HealthCheckResponse {
+            password_hashing: self.hashing_pool.metrics(),
+        })
     }
 }
@@ -0,0 +1,331 @@
+use std::sync::Arc;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use sqlx::{Pool, Postgres};
+use url::Url;
+
+use crate::{
+    adapters::{
+        dto::{
+            jwt::Claims,
+            saml::{AuthnRequest, NameIdFormat, SamlAssertion, SamlAttribute, SamlBinding},
+        },
+        requests::saml::{CompleteSamlSsoRequest, InitiateSamlSsoRequest, SamlSsoRequest},
+        response::saml::SamlSsoResponse,
+    },
+    config::saml::SamlConfig,
+    entities::{saml_service_provider::SamlServiceProviderEntity, user::UserEntity},
+    errors::{app_error::AppError, saml_error::SamlError},
+    repositories::{
+        saml_service_provider_repository::{
+            SamlServiceProviderRepository, SamlServiceProviderRepositoryTrait,
+        },
+        user_repository::{UserRepository, UserRepositoryTrait},
+    },
+    shared::{
+        crypto::{generate_opaque_token, sha256_hex},
+        saml::{
+            BINDING_HTTP_POST, build_response, decode_request, encode_request,
+            identity_provider_metadata, parse_authn_request,
+        },
+        saml_signer::SamlSigner,
+    },
+};
+
+/// an authentication request checked against the registry
+struct ValidatedAuthnRequest {
+    provider: SamlServiceProviderEntity,
+    request: AuthnRequest,
+    assertion_consumer_service: String,
+    name_id_format: NameIdFormat,
+}
+
+#[derive(Clone)]
+pub struct SamlService {
+    service_provider_repository: SamlServiceProviderRepository,
+    user_repository: UserRepository,
+    signer: Arc<SamlSigner>,
+    config: SamlConfig,
+}
+
+impl SamlService {
+    pub fn init(pool: &Pool<Postgres>) -> Result<Self, AppError> {
+        let config = SamlConfig::from_env();
+
+        Ok(Self {
+            service_provider_repository: SamlServiceProviderRepository::init(pool),
+            user_repository: UserRepository::init(pool),
+            signer: Arc::new(SamlSigner::from_config(&config)?),
+            config,
+        })
+    }
+
+    /// a disabled service provider is as good as unknown
+    async fn find_provider(&self, entity_id: &str) -> Result<SamlServiceProviderEntity, SamlError> {
+        self.service_provider_repository
+            .find_by_entity_id(entity_id)
+            .await?
+            .filter(|provider| provider.enabled)
+            .ok_or(SamlError::UnknownServiceProvider)
+    }
+
+    async fn validate_request(&self, xml: &str) -> Result<ValidatedAuthnRequest, SamlError> {
+        let request = parse_authn_request(xml)?;
+        if request
+            .destination
+            .as_deref()
+            .is_some_and(|destination| destination != self.config.sso_url)
+        {
+            return Err(SamlError::InvalidRequest(
+                "the request is addressed to another identity provider".into(),
+            ));
+        }
+        if request
+            .protocol_binding
+            .as_deref()
+            .is_some_and(|binding| binding != BINDING_HTTP_POST)
+        {
+            return Err(SamlError::InvalidRequest(
+                "responses can only be sent with the HTTP-POST binding".into(),
+            ));
+        }
+
+        let provider = self.find_provider(&request.issuer).await?;
+        let assertion_consumer_service = match request.assertion_consumer_service_url.as_deref() {
+            Some(location) => provider
+                .assertion_consumer_services
+                .iter()
+                .find(|registered| registered.as_str() == location)
+                .cloned()
+                .ok_or(SamlError::UnregisteredAssertionConsumerService)?,
+            None => default_assertion_consumer_service(&provider)?,
+        };
+        // the format is the one the provider was registered with, a request can leave it to us
+        // but not ask for another, which could hand out an identifier it was not meant to see
+        let name_id_format = provider_name_id_format(&provider)?;
+        if let Some(format) = request.name_id_format.as_deref()
+            && format != NameIdFormat::Unspecified.as_str()
+        {
+            let requested: NameIdFormat = format
+                .parse()
+                .map_err(|_| SamlError::UnsupportedNameIdFormat(format.into()))?;
+            if requested != name_id_format {
+                return Err(SamlError::InvalidRequest(format!(
+                    "the service provider is registered for the {} name id format",
+                    name_id_format.as_str()
+                )));
+            }
+        }
+
+        Ok(ValidatedAuthnRequest {
+            provider,
+            request,
+            assertion_consumer_service,
+            name_id_format,
+        })
+    }
+
+    async fn find_user(&self, claims: &Claims) -> Result<UserEntity, SamlError> {
+        self.user_repository
+            .find_by_identifier(&claims.sub)
+            .await
+            .ok_or(SamlError::UserNotFound)
+    }
+
+    async fn respond(
+        &self,
+        claims: &Claims,
+        provider: &SamlServiceProviderEntity,
+        assertion_consumer_service: String,
+        name_id_format: NameIdFormat,
+        in_response_to: Option<String>,
+        relay_state: Option<String>,
+    ) -> Result<SamlSsoResponse, SamlError> {
+        let user = self.find_user(claims).await?;
+        // when the user signed in, not when the access token was last refreshed
+        let authn_instant = claims
+            .auth_time
+            .and_then(|auth_time| chrono::DateTime::from_timestamp(auth_time, 0))
+            .ok_or(SamlError::SignInRequired)?;
+        let assertion = SamlAssertion {
+            issuer: self.config.entity_id.to_owned(),
+            audience: provider.entity_id.to_owned(),
+            destination: assertion_consumer_service.to_owned(),
+            in_response_to,
+            name_id: name_id(name_id_format, provider, &user),
+            name_id_format,
+            session_index: claims.sid.map(|sid| sid.to_string()),
+            authn_instant,
+            attributes: attributes(provider, &user),
+            lifetime: self.config.assertion_ttl,
+        };
+        let response = build_response(&assertion, &self.signer)?;
+
+        Ok(SamlSsoResponse {
+            destination: assertion_consumer_service,
+            saml_response: STANDARD.encode(response),
+            relay_state,
+        })
+    }
+}
+
+pub trait SamlServiceTrait {
+    /// the identity provider's metadata document, for service providers to register
+    fn metadata(&self) -> String;
+
+    /// checks an authentication request from a service provider and sends the user to the login
+    /// page with it
+    fn start_sso(
+        &self,
+        request: &SamlSsoRequest,
+        binding: SamlBinding,
+    ) -> impl std::future::Future<Output = Result<String, SamlError>> + Send;
+
+    /// answers the authentication request for the signed-in user
+    fn complete_sso(
+        &self,
+        claims: &Claims,
+        request: &CompleteSamlSsoRequest,
+    ) -> impl std::future::Future<Output = Result<SamlSsoResponse, SamlError>> + Send;
+
+    /// signs the user in at a service provider unasked, the response goes to its default assertion
+    /// consumer service
+    fn initiate_sso(
+        &self,
+        claims: &Claims,
+        request: &InitiateSamlSsoRequest,
+    ) -> impl std::future::Future<Output = Result<SamlSsoResponse, SamlError>> + Send;
+}
+
+impl SamlServiceTrait for SamlService {
+    fn metadata(&self) -> String {
+        identity_provider_metadata(
+            &self.config.entity_id,
+            &self.config.sso_url,
+            &self.signer.certificate(),
+        )
+    }
+
+    async fn start_sso(
+        &self,
+        request: &SamlSsoRequest,
+        binding: SamlBinding,
+    ) -> Result<String, SamlError> {
+        let saml_request = request.saml_request.as_deref().ok_or_else(|| {
+            SamlError::InvalidRequest("the SAMLRequest parameter is missing".into())
+        })?;
+        let xml = decode_request(saml_request, binding)?;
+        // checked now so that the user is not asked to sign in for nothing
+        self.validate_request(&xml).await?;
+
+        let mut login_url = Url::parse(&self.config.login_url)
+            .map_err(|err| AppError::OperationFailed(err.to_string()))?;
+        {
+            let mut query = login_url.query_pairs_mut();
+            query.append_pair("SAMLRequest", &encode_request(&xml));
+            if let Some(relay_state) = &request.relay_state {
+                query.append_pair("RelayState", relay_state);
+            }
+        }
+
+        Ok(login_url.into())
+    }
+
+    async fn complete_sso(
+        &self,
+        claims: &Claims,
+        request: &CompleteSamlSsoRequest,
+    ) -> Result<SamlSsoResponse, SamlError> {
+        let xml = decode_request(&request.saml_request, SamlBinding::Redirect)?;
+        let ValidatedAuthnRequest {
+            provider,
+            request: authn_request,
+            assertion_consumer_service,
+            name_id_format,
+        } = self.validate_request(&xml).await?;
+
+        self.respond(
+            claims,
+            &provider,
+            assertion_consumer_service,
+            name_id_format,
+            Some(authn_request.id),
+            request.relay_state.to_owned(),
+        )
+        .await
+    }
+
+    async fn initiate_sso(
+        &self,
+        claims: &Claims,
+        request: &InitiateSamlSsoRequest,
+    ) -> Result<SamlSsoResponse, SamlError> {
+        let provider = self.find_provider(&request.entity_id).await?;
+        let assertion_consumer_service = default_assertion_consumer_service(&provider)?;
+        let name_id_format = provider_name_id_format(&provider)?;
+
+        self.respond(
+            claims,
+            &provider,
+            assertion_consumer_service,
+            name_id_format,
+            None,
+            request.relay_state.to_owned(),
+        )
+        .await
+    }
+}
+
+fn default_assertion_consumer_service(
+    provider: &SamlServiceProviderEntity,
+) -> Result<String, SamlError> {
+    provider
+        .assertion_consumer_services
+        .first()
+        .cloned()
+        .ok_or(SamlError::UnregisteredAssertionConsumerService)
+}
+
+fn provider_name_id_format(
+    provider: &SamlServiceProviderEntity,
+) -> Result<NameIdFormat, SamlError> {
+    provider
+        .name_id_format
+        .parse()
+        .map_err(|_| SamlError::UnsupportedNameIdFormat(provider.name_id_format.to_owned()))
+}
+
+/// persistent identifiers are derived from the entity id so that service providers cannot match
+/// their users up with each other's
+fn name_id(
+    format: NameIdFormat,
+    provider: &SamlServiceProviderEntity,
+    user: &UserEntity,
+) -> String {
+    match format {
+        NameIdFormat::EmailAddress => user.email.to_owned(),
+        NameIdFormat::Persistent => {
+            sha256_hex(&format!("{}:{}", provider.entity_id, user.identifier))
+        }
+        NameIdFormat::Transient => generate_opaque_token(),
+        NameIdFormat::Unspecified => user.identifier.to_string(),
+    }
+}
+
+fn attributes(provider: &SamlServiceProviderEntity, user: &UserEntity) -> Vec<SamlAttribute> {
+    let display_name = format!("{} {}", user.first_name, user.last_name);
+    [
+        (&provider.email_attribute, user.email.as_str()),
+        (&provider.first_name_attribute, user.first_name.as_str()),
+        (&provider.last_name_attribute, user.last_name.as_str()),
+        (&provider.display_name_attribute, display_name.trim()),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| {
+        name.as_ref().map(|name| SamlAttribute {
+            name: name.to_owned(),
+            value: value.to_string(),
+        })
+    })
+    .collect()
+}
@@ -0,0 +1,180 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    adapters::{
+        dto::saml::{NameIdFormat, ServiceProviderMetadata},
+        requests::service_providers::{CreateServiceProviderRequest, UpdateServiceProviderRequest},
+        response::service_providers::ServiceProviderResponse,
+    },
+    entities::saml_service_provider::SamlServiceProviderEntity,
+    errors::service_provider_service_error::ServiceProviderServiceError,
+    repositories::saml_service_provider_repository::{
+        SamlServiceProviderRepository, SamlServiceProviderRepositoryTrait,
+    },
+    shared::saml::parse_service_provider_metadata,
+};
+
+#[derive(Clone)]
+pub struct ServiceProviderService {
+    service_provider_repository: SamlServiceProviderRepository,
+}
+
+impl ServiceProviderService {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            service_provider_repository: SamlServiceProviderRepository::init(pool),
+        }
+    }
+
+    async fn find_provider(
+        &self,
+        identifier: &Uuid,
+    ) -> Result<SamlServiceProviderEntity, ServiceProviderServiceError> {
+        self.service_provider_repository
+            .find_by_identifier(identifier)
+            .await?
+            .ok_or(ServiceProviderServiceError::NotFound)
+    }
+
+    /// the entity id is unique, a provider may keep its own when its metadata is replaced
+    async fn ensure_entity_id_available(
+        &self,
+        metadata: &ServiceProviderMetadata,
+        identifier: Option<&Uuid>,
+    ) -> Result<(), ServiceProviderServiceError> {
+        match self
+            .service_provider_repository
+            .find_by_entity_id(&metadata.entity_id)
+            .await?
+        {
+            Some(existing) if Some(&existing.identifier) != identifier => {
+                Err(ServiceProviderServiceError::EntityIdTaken)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub trait ServiceProviderServiceTrait {
+    fn list_providers(
+        &self,
+    ) -> impl std::future::Future<
+        Output = Result<Vec<ServiceProviderResponse>, ServiceProviderServiceError>,
+    > + Send;
+
+    fn create_provider(
+        &self,
+        request: &CreateServiceProviderRequest,
+    ) -> impl std::future::Future<
+        Output = Result<ServiceProviderResponse, ServiceProviderServiceError>,
+    > + Send;
+
+    fn retrieve_provider(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<
+        Output = Result<ServiceProviderResponse, ServiceProviderServiceError>,
+    > + Send;
+
+    fn update_provider(
+        &self,
+        identifier: &Uuid,
+        request: &UpdateServiceProviderRequest,
+    ) -> impl std::future::Future<
+        Output = Result<ServiceProviderResponse, ServiceProviderServiceError>,
+    > + Send;
+
+    fn delete_provider(
+        &self,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), ServiceProviderServiceError>> + Send;
+}
+
+impl ServiceProviderServiceTrait for ServiceProviderService {
+    async fn list_providers(
+        &self,
+    ) -> Result<Vec<ServiceProviderResponse>, ServiceProviderServiceError> {
+        let providers = self.service_provider_repository.find_all().await?;
+
+        Ok(providers.into_iter().map(Into::into).collect())
+    }
+
+    async fn create_provider(
+        &self,
+        request: &CreateServiceProviderRequest,
+    ) -> Result<ServiceProviderResponse, ServiceProviderServiceError> {
+        let metadata = parse_service_provider_metadata(&request.metadata)?;
+        self.ensure_entity_id_available(&metadata, None).await?;
+
+        let name_id_format = request.name_id_format.unwrap_or_else(|| {
+            metadata
+                .name_id_formats
+                .iter()
+                .find_map(|format| format.parse::<NameIdFormat>().ok())
+                .unwrap_or(NameIdFormat::EmailAddress)
+        });
+        let provider = SamlServiceProviderEntity {
+            identifier: Uuid::new_v4(),
+            entity_id: metadata.entity_id,
+            name: request.name.to_owned(),
+            assertion_consumer_services: metadata.assertion_consumer_services,
+            name_id_format: name_id_format.to_string(),
+            email_attribute: request.email_attribute.to_owned(),
+            first_name_attribute: request.first_name_attribute.to_owned(),
+            last_name_attribute: request.last_name_attribute.to_owned(),
+            display_name_attribute: request.display_name_attribute.to_owned(),
+            metadata: request.metadata.to_owned(),
+            enabled: request.enabled,
+            created_at: chrono::Utc::now(),
+            updated_at: None,
+        };
+        self.service_provider_repository.create(&provider).await?;
+
+        Ok(provider.into())
+    }
+
+    async fn retrieve_provider(
+        &self,
+        identifier: &Uuid,
+    ) -> Result<ServiceProviderResponse, ServiceProviderServiceError> {
+        self.find_provider(identifier).await.map(Into::into)
+    }
+
+    async fn update_provider(
+        &self,
+        identifier: &Uuid,
+        request: &UpdateServiceProviderRequest,
+    ) -> Result<ServiceProviderResponse, ServiceProviderServiceError> {
+        let provider = self.find_provider(identifier).await?;
+        let metadata = request.metadata.as_deref().unwrap_or(&provider.metadata);
+        let parsed = parse_service_provider_metadata(metadata)?;
+        self.ensure_entity_id_available(&parsed, Some(identifier))
+            .await?;
+
+        let provider = SamlServiceProviderEntity {
+            entity_id: parsed.entity_id,
+            name: request.name.to_owned(),
+            assertion_consumer_services: parsed.assertion_consumer_services,
+            name_id_format: request.name_id_format.to_string(),
+            email_attribute: request.email_attribute.to_owned(),
+            first_name_attribute: request.first_name_attribute.to_owned(),
+            last_name_attribute: request.last_name_attribute.to_owned(),
+            display_name_attribute: request.display_name_attribute.to_owned(),
+            metadata: metadata.to_owned(),
+            enabled: request.enabled,
+            ..provider
+        };
+        self.service_provider_repository.update(&provider).await?;
+
+        self.retrieve_provider(identifier).await
+    }
+
+    async fn delete_provider(&self, identifier: &Uuid) -> Result<(), ServiceProviderServiceError> {
+        if !self.service_provider_repository.delete(identifier).await? {
+            return Err(ServiceProviderServiceError::NotFound);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    adapters::{dto::session::DeviceInfo, response::sessions::SessionResponse},
+    entities::session::SessionEntity,
+    errors::session_service_error::SessionServiceError,
+    repositories::{
+        refresh_token_repository::{RefreshTokenRepository, RefreshTokenRepositoryTrait},
+        session_repository::{SessionRepository, SessionRepositoryTrait},
+    },
+};
+
+#[derive(Clone)]
+pub struct SessionService {
+    session_repository: SessionRepository,
+    refresh_token_repository: RefreshTokenRepository,
+}
+
+impl SessionService {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            session_repository: SessionRepository::init(pool),
+            refresh_token_repository: RefreshTokenRepository::init(pool),
+        }
+    }
+}
+
+fn new_session(
+    user_identifier: &Uuid,
+    family_identifier: &Uuid,
+    device: &DeviceInfo,
+) -> SessionEntity {
+    let now = chrono::Utc::now();
+
+    SessionEntity {
+        identifier: Uuid::new_v4(),
+        user_identifier: *user_identifier,
+        family_identifier: *family_identifier,
+        user_agent: device.user_agent.clone(),
+        ip_address: device.ip_address.map(|ip_address| ip_address.to_string()),
+        created_at: now,
+        last_seen_at: now,
+    }
+}
+
+pub trait SessionServiceTrait {
+    /// records a sign-in, the refresh token issued for it must start the returned family
+    fn start(
+        &self,
+        user_identifier: &Uuid,
+        device: &DeviceInfo,
+    ) -> impl std::future::Future<Output = Result<SessionEntity, SessionServiceError>> + Send;
+
+    /// called when the family's refresh token is rotated, a family from before sessions were
+    /// recorded gets one on its first refresh
+    fn touch(
+        &self,
+        user_identifier: &Uuid,
+        family_identifier: &Uuid,
+        device: &DeviceInfo,
+    ) -> impl std::future::Future<Output = Result<SessionEntity, SessionServiceError>> + Send;
+
+    fn list(
+        &self,
+        user_identifier: &Uuid,
+        current_session: Option<&Uuid>,
+    ) -> impl std::future::Future<Output = Result<Vec<SessionResponse>, SessionServiceError>> + Send;
+
+    /// revokes the session's refresh token family, its access tokens stop working with it
+    fn revoke(
+        &self,
+        user_identifier: &Uuid,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), SessionServiceError>> + Send;
+
+    /// revokes every session but the current one, returns how many were revoked
+    fn revoke_others(
+        &self,
+        user_identifier: &Uuid,
+        current_session: Option<&Uuid>,
+    ) -> impl std::future::Future<Output = Result<usize, SessionServiceError>> + Send;
+}
+
+impl SessionServiceTrait for SessionService {
+    async fn start(
+        &self,
+        user_identifier: &Uuid,
+        device: &DeviceInfo,
+    ) -> Result<SessionEntity, SessionServiceError> {
+        let session = new_session(user_identifier, &Uuid::new_v4(), device);
+        self.session_repository.create(&session).await?;
+
+        Ok(session)
+    }
+
+    async fn touch(
+        &self,
+        user_identifier: &Uuid,
+        family_identifier: &Uuid,
+        device: &DeviceInfo,
+    ) -> Result<SessionEntity, SessionServiceError> {
+        let ip_address = device.ip_address.map(|ip_address| ip_address.to_string());
+        if let Some(session) = self
+            .session_repository
+            .touch(family_identifier, ip_address.as_deref())
+            .await?
+        {
+            return Ok(session);
+        }
+
+        let session = new_session(user_identifier, family_identifier, device);
+        self.session_repository.create(&session).await?;
+
+        Ok(session)
+    }
+
+    async fn list(
+        &self,
+        user_identifier: &Uuid,
+        current_session: Option<&Uuid>,
+    ) -> Result<Vec<SessionResponse>, SessionServiceError> {
+        Ok(self
+            .session_repository
+            .find_active_by_user(user_identifier)
+            .await?
+            .into_iter()
+            .map(|session| SessionResponse::new(session, current_session))
+            .collect())
+    }
+
+    async fn revoke(
+        &self,
+        user_identifier: &Uuid,
+        identifier: &Uuid,
+    ) -> Result<(), SessionServiceError> {
+        let session = self
+            .session_repository
+            .find_active(identifier, user_identifier)
+            .await?
+            .ok_or(SessionServiceError::SessionNotFound)?;
+        self.refresh_token_repository
+            .revoke_family(&session.family_identifier)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_others(
+        &self,
+        user_identifier: &Uuid,
+        current_session: Option<&Uuid>,
+    ) -> Result<usize, SessionServiceError> {
+        let others: Vec<SessionEntity> = self
+            .session_repository
+            .find_active_by_user(user_identifier)
+            .await?
+            .into_iter()
+            .filter(|session| Some(&session.identifier) != current_session)
+            .collect();
+        for session in &others {
+            self.refresh_token_repository
+                .revoke_family(&session.family_identifier)
+                .await?;
+        }
+
+        Ok(others.len())
+    }
+}
@@ -0,0 +1,495 @@
+use std::{sync::Arc, time::Duration};
+
+use jsonwebtoken::{Algorithm, Validation, jwk::JwkSet};
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    adapters::dto::{
+        jwt::{Claims, ClientClaims, JwtCredentials, SubjectType, TokenUse},
+        oauth::{IdTokenClaims, OAuthGrant},
+    },
+    config::{jwt::JwtConfig, token::TokenConfig},
+    entities::refresh_token::RefreshTokenEntity,
+    errors::{app_error::AppError, auth_service_error::AuthenticationServiceError},
+    repositories::{
+        oauth_client_repository::{OAuthClientRepository, OAuthClientRepositoryTrait},
+        refresh_token_repository::{RefreshTokenRepository, RefreshTokenRepositoryTrait},
+        revoked_token_repository::{RevokedTokenRepository, RevokedTokenRepositoryTrait},
+        session_repository::{SessionRepository, SessionRepositoryTrait},
+        user_repository::{UserRepository, UserRepositoryTrait},
+    },
+    shared::{
+        crypto::{generate_opaque_token, sha256_hex},
+        key_store::KeyStore,
+    },
+};
+
+#[derive(Clone)]
+pub struct TokenService {
+    refresh_token_repository: RefreshTokenRepository,
+    revoked_token_repository: RevokedTokenRepository,
+    session_repository: SessionRepository,
+    user_repository: UserRepository,
+    oauth_client_repository: OAuthClientRepository,
+    key_store: Arc<KeyStore>,
+    jwt_config: JwtConfig,
+    config: TokenConfig,
+}
+
+impl TokenService {
+    pub fn init(pool: &Pool<Postgres>) -> Result<Self, AppError> {
+        Ok(Self {
+            refresh_token_repository: RefreshTokenRepository::init(pool),
+            revoked_token_repository: RevokedTokenRepository::init(pool),
+            session_repository: SessionRepository::init(pool),
+            user_repository: UserRepository::init(pool),
+            oauth_client_repository: OAuthClientRepository::init(pool),
+            key_store: Arc::new(KeyStore::from_env()?),
+            jwt_config: JwtConfig::from_env(),
+            config: TokenConfig::from_env(),
+        })
+    }
+
+    /// periodically drops revocation entries for tokens that have expired anyway, the signature
+    /// check rejects those on its own
+    pub fn spawn_revocation_cleanup(&self) {
+        let revoked_token_repository = self.revoked_token_repository.clone();
+        let period = self.config.revocation_cleanup_interval;
+
+        tokio::task::spawn(async move {
+            let mut interval =
+                tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            loop {
+                interval.tick().await;
+                match revoked_token_repository.delete_expired().await {
+                    Ok(0) => {}
+                    Ok(deleted) => log::info!("removed {deleted} expired token revocations"),
+                    Err(err) => log::error!("error removing expired token revocations: {err}"),
+                }
+            }
+        });
+    }
+
+    async fn store_refresh_token(
+        &self,
+        user_identifier: &Uuid,
+        family_identifier: &Uuid,
+        grant: Option<&OAuthGrant>,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<String, AuthenticationServiceError> {
+        let refresh_token = generate_opaque_token();
+
+        self.refresh_token_repository
+            .create(
+                user_identifier,
+                family_identifier,
+                &sha256_hex(&refresh_token),
+                expires_at,
+                grant,
+            )
+            .await?;
+
+        Ok(refresh_token)
+    }
+
+    async fn verify_user_token(
+        &self,
+        token: &str,
+        validation: &Validation,
+    ) -> Result<Claims, AuthenticationServiceError> {
+        let claims = self
+            .key_store
+            .verify::<Claims>(token, validation)
+            .map_err(|_| AuthenticationServiceError::InvalidToken)?
+            .claims;
+        if claims.sub_type != SubjectType::User {
+            return Err(AuthenticationServiceError::InvalidToken);
+        }
+
+        if self
+            .revoked_token_repository
+            .is_revoked(&claims.jti)
+            .await?
+        {
+            return Err(AuthenticationServiceError::InvalidToken);
+        }
+
+        let token_version = self.user_repository.find_token_version(&claims.sub).await?;
+        if token_version != Some(claims.ver) {
+            return Err(AuthenticationServiceError::InvalidToken);
+        }
+
+        if let Some(session) = &claims.sid
+            && !self.session_repository.is_active(session).await?
+        {
+            return Err(AuthenticationServiceError::InvalidToken);
+        }
+
+        Ok(claims)
+    }
+}
+
+pub struct RotatedRefreshToken {
+    pub user_identifier: Uuid,
+    pub family_identifier: Uuid,
+    pub refresh_token: String,
+    pub grant: Option<OAuthGrant>,
+}
+
+pub trait TokenServiceTrait {
+    fn generate_token(
+        &self,
+        credentials: &JwtCredentials,
+        validity: Duration,
+    ) -> Result<String, AuthenticationServiceError>;
+
+    fn generate_id_token(
+        &self,
+        claims: &IdTokenClaims,
+    ) -> Result<String, AuthenticationServiceError>;
+
+    fn generate_client_token(
+        &self,
+        claims: &ClientClaims,
+    ) -> Result<String, AuthenticationServiceError>;
+
+    /// verifies the token and rejects it unless it was issued for `token_use`
+    fn decode_token(
+        &self,
+        token: &str,
+        token_use: TokenUse,
+    ) -> impl std::future::Future<Output = Result<Claims, AuthenticationServiceError>> + Send;
+
+    /// verifies the signature, expiry, revocation status and token version of a token of any use
+    fn verify_token(
+        &self,
+        token: &str,
+    ) -> impl std::future::Future<Output = Result<Claims, AuthenticationServiceError>> + Send;
+
+    /// like `verify_token` but also accepts tokens exchanged for another audience
+    fn verify_token_for_any_audience(
+        &self,
+        token: &str,
+    ) -> impl std::future::Future<Output = Result<Claims, AuthenticationServiceError>> + Send;
+
+    /// verifies an access token issued through the client credentials grant, the client must
+    /// still be registered
+    fn verify_client_token(
+        &self,
+        token: &str,
+    ) -> impl std::future::Future<Output = Result<ClientClaims, AuthenticationServiceError>> + Send;
+
+    /// the token is rejected from now on, the entry is kept until the token expires
+    fn revoke_token(
+        &self,
+        claims: &Claims,
+    ) -> impl std::future::Future<Output = Result<(), AuthenticationServiceError>> + Send;
+
+    fn revoke_client_token(
+        &self,
+        claims: &ClientClaims,
+    ) -> impl std::future::Future<Output = Result<(), AuthenticationServiceError>> + Send;
+
+    /// bumps the user's token version and revokes every refresh token family, signing the user
+    /// out everywhere
+    fn revoke_all_tokens(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), AuthenticationServiceError>> + Send;
+
+    fn jwks(&self) -> JwkSet;
+
+    fn signing_algorithm(&self) -> Algorithm;
+
+    /// starts a new family when `family_identifier` is None, tokens issued to a client live as
+    /// long as the client's refresh token lifetime
+    fn issue_refresh_token(
+        &self,
+        user_identifier: &Uuid,
+        family_identifier: Option<Uuid>,
+        grant: Option<&OAuthGrant>,
+    ) -> impl std::future::Future<Output = Result<String, AuthenticationServiceError>> + Send;
+
+    /// exchanges the refresh token for a new one in the same family, presenting a token that
+    /// was already rotated revokes the whole family. The token is only accepted from the client
+    /// it was issued to, `client_id` is None for first-party logins
+    fn rotate_refresh_token(
+        &self,
+        refresh_token: &str,
+        client_id: Option<&str>,
+    ) -> impl std::future::Future<Output = Result<RotatedRefreshToken, AuthenticationServiceError>> + Send;
+
+    /// the stored refresh token, provided it can still be rotated
+    fn find_active_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> impl std::future::Future<Output = Result<RefreshTokenEntity, AuthenticationServiceError>> + Send;
+
+    /// revokes the family the refresh token belongs to, provided it was issued to `user_identifier`
+    fn revoke_refresh_token(
+        &self,
+        refresh_token: &str,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), AuthenticationServiceError>> + Send;
+}
+
+impl TokenServiceTrait for TokenService {
+    fn generate_token(
+        &self,
+        credentials: &JwtCredentials,
+        validity: Duration,
+    ) -> Result<String, AuthenticationServiceError> {
+        let claims = credentials.to_claims(&self.jwt_config, validity);
+
+        self.key_store
+            .sign(&claims)
+            .map_err(AuthenticationServiceError::from)
+    }
+
+    fn generate_id_token(
+        &self,
+        claims: &IdTokenClaims,
+    ) -> Result<String, AuthenticationServiceError> {
+        self.key_store
+            .sign(claims)
+            .map_err(AuthenticationServiceError::from)
+    }
+
+    fn generate_client_token(
+        &self,
+        claims: &ClientClaims,
+    ) -> Result<String, AuthenticationServiceError> {
+        self.key_store
+            .sign(claims)
+            .map_err(AuthenticationServiceError::from)
+    }
+
+    async fn decode_token(
+        &self,
+        token: &str,
+        token_use: TokenUse,
+    ) -> Result<Claims, AuthenticationServiceError> {
+        let claims = self.verify_token(token).await?;
+        if claims.token_use != token_use {
+            return Err(AuthenticationServiceError::InvalidToken);
+        }
+
+        Ok(claims)
+    }
+
+    async fn verify_token(&self, token: &str) -> Result<Claims, AuthenticationServiceError> {
+        self.verify_user_token(token, &Claims::validation(&self.jwt_config))
+            .await
+    }
+
+    async fn verify_token_for_any_audience(
+        &self,
+        token: &str,
+    ) -> Result<Claims, AuthenticationServiceError> {
+        self.verify_user_token(
+            token,
+            &Claims::validation_for_any_audience(&self.jwt_config),
+        )
+        .await
+    }
+
+    async fn verify_client_token(
+        &self,
+        token: &str,
+    ) -> Result<ClientClaims, AuthenticationServiceError> {
+        let claims = self
+            .key_store
+            .verify::<ClientClaims>(token, &Claims::validation(&self.jwt_config))
+            .map_err(|_| AuthenticationServiceError::InvalidToken)?
+            .claims;
+        if claims.sub_type != SubjectType::Client || claims.token_use != TokenUse::Access {
+            return Err(AuthenticationServiceError::InvalidToken);
+        }
+
+        if self
+            .revoked_token_repository
+            .is_revoked(&claims.jti)
+            .await?
+            || self
+                .oauth_client_repository
+                .find_by_client_id(&claims.client_id)
+                .await?
+                .is_none()
+        {
+            return Err(AuthenticationServiceError::InvalidToken);
+        }
+
+        Ok(claims)
+    }
+
+    async fn revoke_token(&self, claims: &Claims) -> Result<(), AuthenticationServiceError> {
+        let expires_at = chrono::DateTime::from_timestamp(claims.exp, 0)
+            .ok_or(AuthenticationServiceError::InvalidToken)?;
+
+        self.revoked_token_repository
+            .revoke(&claims.jti, Some(&claims.sub), expires_at)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_client_token(
+        &self,
+        claims: &ClientClaims,
+    ) -> Result<(), AuthenticationServiceError> {
+        let expires_at = chrono::DateTime::from_timestamp(claims.exp, 0)
+            .ok_or(AuthenticationServiceError::InvalidToken)?;
+
+        self.revoked_token_repository
+            .revoke(&claims.jti, None, expires_at)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_all_tokens(
+        &self,
+        user_identifier: &Uuid,
+    ) -> Result<(), AuthenticationServiceError> {
+        self.user_repository
+            .increment_token_version(user_identifier)
+            .await?;
+        self.refresh_token_repository
+            .revoke_all_for_user(user_identifier)
+            .await?;
+
+        Ok(())
+    }
+
+    fn jwks(&self) -> JwkSet {
+        self.key_store.jwks()
+    }
+
+    fn signing_algorithm(&self) -> Algorithm {
+        self.key_store.signing_algorithm()
+    }
+
+    async fn issue_refresh_token(
+        &self,
+        user_identifier: &Uuid,
+        family_identifier: Option<Uuid>,
+        grant: Option<&OAuthGrant>,
+    ) -> Result<String, AuthenticationServiceError> {
+        let refresh_token_ttl = match grant {
+            Some(grant) => self
+                .oauth_client_repository
+                .find_by_client_id(&grant.client_id)
+                .await?
+                .ok_or(AuthenticationServiceError::InvalidToken)?
+                .refresh_token_ttl(),
+            None => self.config.refresh_token_ttl,
+        };
+
+        self.store_refresh_token(
+            user_identifier,
+            &family_identifier.unwrap_or_else(Uuid::new_v4),
+            grant,
+            chrono::Utc::now() + refresh_token_ttl,
+        )
+        .await
+    }
+
+    async fn rotate_refresh_token(
+        &self,
+        refresh_token: &str,
+        client_id: Option<&str>,
+    ) -> Result<RotatedRefreshToken, AuthenticationServiceError> {
+        let Some(stored_token) = self
+            .refresh_token_repository
+            .find_by_hash(&sha256_hex(refresh_token))
+            .await?
+        else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+
+        if stored_token.revoked_at.is_some() || stored_token.client_id.as_deref() != client_id {
+            return Err(AuthenticationServiceError::InvalidToken);
+        }
+        // checked first so that an expired token is not marked as rotated for nothing
+        if stored_token.expires_at < chrono::Utc::now() {
+            return Err(AuthenticationServiceError::InvalidToken);
+        }
+
+        if stored_token.rotated_at.is_some()
+            || !self
+                .refresh_token_repository
+                .mark_as_rotated(&stored_token.identifier)
+                .await?
+        {
+            log::warn!(
+                "refresh token reuse detected, revoking token family {}",
+                stored_token.family_identifier
+            );
+            self.refresh_token_repository
+                .revoke_family(&stored_token.family_identifier)
+                .await?;
+            return Err(AuthenticationServiceError::InvalidToken);
+        }
+
+        let grant = stored_token
+            .client_id
+            .zip(stored_token.scope)
+            .map(|(client_id, scope)| OAuthGrant { client_id, scope });
+        // the family keeps the expiry of its first token, rotating does not extend a login
+        let refresh_token = self
+            .store_refresh_token(
+                &stored_token.user_identifier,
+                &stored_token.family_identifier,
+                grant.as_ref(),
+                stored_token.expires_at,
+            )
+            .await?;
+
+        Ok(RotatedRefreshToken {
+            user_identifier: stored_token.user_identifier,
+            family_identifier: stored_token.family_identifier,
+            refresh_token,
+            grant,
+        })
+    }
+
+    async fn find_active_refresh_token(
+        &self,
+        refresh_token: &str,
+    ) -> Result<RefreshTokenEntity, AuthenticationServiceError> {
+        self.refresh_token_repository
+            .find_by_hash(&sha256_hex(refresh_token))
+            .await?
+            .filter(|stored_token| {
+                stored_token.rotated_at.is_none()
+                    && stored_token.revoked_at.is_none()
+                    && stored_token.expires_at > chrono::Utc::now()
+            })
+            .ok_or(AuthenticationServiceError::InvalidToken)
+    }
+
+    async fn revoke_refresh_token(
+        &self,
+        refresh_token: &str,
+        user_identifier: &Uuid,
+    ) -> Result<(), AuthenticationServiceError> {
+        let Some(stored_token) = self
+            .refresh_token_repository
+            .find_by_hash(&sha256_hex(refresh_token))
+            .await?
+        else {
+            return Err(AuthenticationServiceError::InvalidToken);
+        };
+
+        if stored_token.user_identifier != *user_identifier {
+            return Err(AuthenticationServiceError::InvalidToken);
+        }
+
+        self.refresh_token_repository
+            .revoke_family(&stored_token.family_identifier)
+            .await?;
+
+        Ok(())
+    }
+}
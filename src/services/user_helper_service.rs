@@ -1,26 +1,60 @@
-use crate::errors::user_service_error::UserServiceError;
-use bcrypt::{DEFAULT_COST, hash, verify};
+use crate::errors::{app_error::AppError, user_service_error::UserServiceError};
+use crate::shared::{hashing_pool::HashingPool, password_hash::PasswordHasher};
 
 #[derive(Clone)]
-pub struct UserHelperService {}
+pub struct UserHelperService {
+    password_hasher: PasswordHasher,
+    hashing_pool: HashingPool,
+}
 
 impl UserHelperService {
-    pub fn init() -> Self {
-        Self {}
+    pub fn init(hashing_pool: &HashingPool) -> Result<Self, AppError> {
+        Ok(Self {
+            password_hasher: PasswordHasher::from_env()?,
+            hashing_pool: hashing_pool.clone(),
+        })
     }
 }
 
+/// hashing and verifying run on the hashing pool, off the async runtime
 pub trait UserHelperServiceTrait {
-    fn hash_password(&self, raw_password: &str) -> Result<String, UserServiceError>;
-    fn validate_password(&self, raw_password: &str, hash: &str) -> Result<bool, UserServiceError>;
+    fn hash_password(
+        &self,
+        raw_password: &str,
+    ) -> impl std::future::Future<Output = Result<String, UserServiceError>> + Send;
+    fn validate_password(
+        &self,
+        raw_password: &str,
+        hash: &str,
+    ) -> impl std::future::Future<Output = Result<bool, UserServiceError>> + Send;
+    /// the stored hash should be replaced once the password has been checked against it
+    fn needs_rehash(&self, hash: &str) -> bool;
 }
 
 impl UserHelperServiceTrait for UserHelperService {
-    fn hash_password(&self, raw_password: &str) -> Result<String, UserServiceError> {
-        hash(raw_password.trim(), DEFAULT_COST)
+    async fn hash_password(&self, raw_password: &str) -> Result<String, UserServiceError> {
+        let password_hasher = self.password_hasher.clone();
+        let raw_password = raw_password.to_string();
+
+        self.hashing_pool
+            .run(move || password_hasher.hash(&raw_password))
+            .await?
+            .map_err(|err| UserServiceError::OperationFailed(err.to_string()))
+    }
+    async fn validate_password(
+        &self,
+        password: &str,
+        hash: &str,
+    ) -> Result<bool, UserServiceError> {
+        let password_hasher = self.password_hasher.clone();
+        let (password, hash) = (password.to_string(), hash.to_string());
+
+        self.hashing_pool
+            .run(move || password_hasher.verify(&password, &hash))
+            .await?
             .map_err(|err| UserServiceError::OperationFailed(err.to_string()))
     }
-    fn validate_password(&self, password: &str, hash: &str) -> Result<bool, UserServiceError> {
-        verify(password, hash).map_err(|err| UserServiceError::OperationFailed(err.to_string()))
+    fn needs_rehash(&self, hash: &str) -> bool {
+        self.password_hasher.needs_rehash(hash)
     }
 }
@@ -1,6 +1,7 @@
 use sqlx::{Pool, Postgres};
 use uuid::Uuid;
 
+use crate::adapters::dto::permission::Permission;
 use crate::adapters::dto::user::UserDto;
 use crate::errors::user_service_error::UserServiceError;
 use crate::repositories::user_repository::{UserRepository, UserRepositoryTrait};
@@ -26,6 +27,12 @@ pub(crate) trait UserServiceTrait {
         &self,
         user_identifier: Uuid,
     ) -> Result<UserDto, UserServiceError>;
+
+    async fn has_permission(
+        &self,
+        user_identifier: &Uuid,
+        permission: Permission,
+    ) -> Result<bool, UserServiceError>;
 }
 
 impl UserServiceTrait for UserService {
@@ -37,4 +44,23 @@ impl UserServiceTrait for UserService {
             .retrieve_information(&user_identifier)
             .await
     }
+
+    async fn has_permission(
+        &self,
+        user_identifier: &Uuid,
+        permission: Permission,
+    ) -> Result<bool, UserServiceError> {
+        let Some(user) = self
+            .user_repository
+            .find_by_identifier(user_identifier)
+            .await
+        else {
+            return Ok(false);
+        };
+
+        Ok(user
+            .permissions
+            .iter()
+            .any(|granted| granted == permission.as_str()))
+    }
 }
@@ -0,0 +1,357 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::Rng;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    adapters::{
+        requests::webauthn::{AssertionCredential, RegisterCredentialRequest},
+        response::webauthn::{
+            AuthenticatorSelection, CredentialCreationOptions, CredentialCreationOptionsResponse,
+            CredentialRequestOptions, CredentialRequestOptionsResponse,
+            PublicKeyCredentialDescriptor, PublicKeyCredentialParameters, PublicKeyCredentialUser,
+            RelyingParty, WebAuthnCredentialResponse,
+        },
+    },
+    config::webauthn::WebAuthnConfig,
+    entities::{user::UserEntity, webauthn_credential::WebAuthnCredentialEntity},
+    errors::webauthn_service_error::WebAuthnServiceError,
+    repositories::{
+        webauthn_challenge_repository::{
+            WebAuthnChallengeRepository, WebAuthnChallengeRepositoryTrait,
+        },
+        webauthn_credential_repository::{
+            WebAuthnCredentialRepository, WebAuthnCredentialRepositoryTrait,
+        },
+    },
+    shared::webauthn::{
+        Assertion, CEREMONY_AUTHENTICATION, CEREMONY_REGISTRATION, CollectedClientData,
+        SUPPORTED_ALGORITHMS, verify_assertion, verify_registration,
+    },
+};
+
+const PUBLIC_KEY_CREDENTIAL_TYPE: &str = "public-key";
+const DEFAULT_CREDENTIAL_NAME: &str = "Passkey";
+
+#[derive(Clone)]
+pub struct WebAuthnService {
+    credential_repository: WebAuthnCredentialRepository,
+    challenge_repository: WebAuthnChallengeRepository,
+    config: WebAuthnConfig,
+}
+
+impl WebAuthnService {
+    pub fn init(pool: &Pool<Postgres>) -> Self {
+        Self {
+            credential_repository: WebAuthnCredentialRepository::init(pool),
+            challenge_repository: WebAuthnChallengeRepository::init(pool),
+            config: WebAuthnConfig::from_env(),
+        }
+    }
+
+    /// 32 random bytes, base64url encoded as they appear in the client data
+    async fn issue_challenge(
+        &self,
+        ceremony: &str,
+        user_identifier: Option<&Uuid>,
+    ) -> Result<String, WebAuthnServiceError> {
+        let mut challenge = [0u8; 32];
+        rand::thread_rng().fill(&mut challenge);
+        let challenge = URL_SAFE_NO_PAD.encode(challenge);
+
+        self.challenge_repository
+            .create(
+                &challenge,
+                ceremony,
+                user_identifier,
+                chrono::Utc::now() + self.config.challenge_ttl,
+            )
+            .await?;
+
+        Ok(challenge)
+    }
+
+    fn timeout(&self) -> u64 {
+        self.config.challenge_ttl.as_millis() as u64
+    }
+}
+
+fn decode(field: &str, value: &str) -> Result<Vec<u8>, WebAuthnServiceError> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| WebAuthnServiceError::InvalidResponse(format!("{field} is not base64url")))
+}
+
+fn descriptors(credentials: Vec<WebAuthnCredentialEntity>) -> Vec<PublicKeyCredentialDescriptor> {
+    credentials
+        .into_iter()
+        .map(|credential| PublicKeyCredentialDescriptor {
+            credential_type: PUBLIC_KEY_CREDENTIAL_TYPE.into(),
+            id: credential.credential_id,
+            transports: credential.transports,
+        })
+        .collect()
+}
+
+pub trait WebAuthnServiceTrait {
+    fn has_credentials(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<bool, WebAuthnServiceError>> + Send;
+
+    /// starts a registration, the user's existing credentials are excluded so an authenticator is
+    /// not registered twice
+    fn creation_options(
+        &self,
+        user: &UserEntity,
+    ) -> impl std::future::Future<
+        Output = Result<CredentialCreationOptionsResponse, WebAuthnServiceError>,
+    > + Send;
+
+    fn register(
+        &self,
+        user_identifier: &Uuid,
+        request: &RegisterCredentialRequest,
+    ) -> impl std::future::Future<Output = Result<WebAuthnCredentialResponse, WebAuthnServiceError>> + Send;
+
+    /// starts an authentication, limited to the user's credentials when the user is known and
+    /// open to any passkey otherwise
+    fn request_options(
+        &self,
+        user_identifier: Option<&Uuid>,
+    ) -> impl std::future::Future<
+        Output = Result<CredentialRequestOptionsResponse, WebAuthnServiceError>,
+    > + Send;
+
+    /// verifies the assertion and returns the identifier of the user the credential belongs to.
+    /// Without `user_identifier` the passkey is the only factor and must have verified the user
+    fn authenticate(
+        &self,
+        credential: &AssertionCredential,
+        user_identifier: Option<&Uuid>,
+    ) -> impl std::future::Future<Output = Result<Uuid, WebAuthnServiceError>> + Send;
+
+    fn list_credentials(
+        &self,
+        user_identifier: &Uuid,
+    ) -> impl std::future::Future<
+        Output = Result<Vec<WebAuthnCredentialResponse>, WebAuthnServiceError>,
+    > + Send;
+
+    fn remove_credential(
+        &self,
+        user_identifier: &Uuid,
+        identifier: &Uuid,
+    ) -> impl std::future::Future<Output = Result<(), WebAuthnServiceError>> + Send;
+}
+
+impl WebAuthnServiceTrait for WebAuthnService {
+    async fn has_credentials(&self, user_identifier: &Uuid) -> Result<bool, WebAuthnServiceError> {
+        Ok(!self
+            .credential_repository
+            .find_by_user(user_identifier)
+            .await?
+            .is_empty())
+    }
+
+    async fn creation_options(
+        &self,
+        user: &UserEntity,
+    ) -> Result<CredentialCreationOptionsResponse, WebAuthnServiceError> {
+        let challenge = self
+            .issue_challenge(CEREMONY_REGISTRATION, Some(&user.identifier))
+            .await?;
+        let existing_credentials = self
+            .credential_repository
+            .find_by_user(&user.identifier)
+            .await?;
+
+        Ok(CredentialCreationOptionsResponse {
+            public_key: CredentialCreationOptions {
+                rp: RelyingParty {
+                    id: self.config.rp_id.to_string(),
+                    name: self.config.rp_name.to_string(),
+                },
+                user: PublicKeyCredentialUser {
+                    id: URL_SAFE_NO_PAD.encode(user.identifier.as_bytes()),
+                    name: user.email.to_string(),
+                    display_name: format!("{} {}", user.first_name, user.last_name),
+                },
+                challenge,
+                pub_key_cred_params: SUPPORTED_ALGORITHMS
+                    .iter()
+                    .map(|alg| PublicKeyCredentialParameters {
+                        credential_type: PUBLIC_KEY_CREDENTIAL_TYPE.into(),
+                        alg: *alg,
+                    })
+                    .collect(),
+                timeout: self.timeout(),
+                exclude_credentials: descriptors(existing_credentials),
+                authenticator_selection: AuthenticatorSelection {
+                    resident_key: "preferred".into(),
+                    user_verification: "preferred".into(),
+                },
+                attestation: "none".into(),
+            },
+        })
+    }
+
+    async fn register(
+        &self,
+        user_identifier: &Uuid,
+        request: &RegisterCredentialRequest,
+    ) -> Result<WebAuthnCredentialResponse, WebAuthnServiceError> {
+        let response = &request.credential.response;
+        let client_data =
+            CollectedClientData::parse(&decode("clientDataJSON", &response.client_data_json)?)?;
+        self.challenge_repository
+            .consume(&client_data.challenge, CEREMONY_REGISTRATION)
+            .await?
+            .filter(|challenge| challenge.user_identifier.as_ref() == Some(user_identifier))
+            .ok_or(WebAuthnServiceError::ChallengeNotFound)?;
+
+        let registration = verify_registration(
+            &self.config,
+            &client_data,
+            &decode("attestationObject", &response.attestation_object)?,
+        )?;
+        let credential_id = URL_SAFE_NO_PAD.encode(&registration.credential_id);
+        if credential_id != request.credential.id.trim_end_matches('=') {
+            return Err(WebAuthnServiceError::InvalidResponse(
+                "the credential id does not match the attested credential".into(),
+            ));
+        }
+
+        let credential = WebAuthnCredentialEntity {
+            identifier: Uuid::new_v4(),
+            user_identifier: *user_identifier,
+            credential_id,
+            public_key: registration.public_key,
+            sign_count: i64::from(registration.sign_count),
+            transports: response.transports.clone(),
+            name: request
+                .name
+                .as_deref()
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .unwrap_or(DEFAULT_CREDENTIAL_NAME)
+                .to_string(),
+            last_used_at: None,
+            created_at: chrono::Utc::now(),
+        };
+        if !self.credential_repository.create(&credential).await? {
+            return Err(WebAuthnServiceError::AlreadyRegistered);
+        }
+
+        Ok(credential.into())
+    }
+
+    async fn request_options(
+        &self,
+        user_identifier: Option<&Uuid>,
+    ) -> Result<CredentialRequestOptionsResponse, WebAuthnServiceError> {
+        let challenge = self
+            .issue_challenge(CEREMONY_AUTHENTICATION, user_identifier)
+            .await?;
+        let allow_credentials = match user_identifier {
+            Some(user_identifier) => descriptors(
+                self.credential_repository
+                    .find_by_user(user_identifier)
+                    .await?,
+            ),
+            None => Vec::new(),
+        };
+
+        Ok(CredentialRequestOptionsResponse {
+            public_key: CredentialRequestOptions {
+                challenge,
+                timeout: self.timeout(),
+                rp_id: self.config.rp_id.to_string(),
+                allow_credentials,
+                user_verification: if user_identifier.is_some() {
+                    "preferred".into()
+                } else {
+                    "required".into()
+                },
+            },
+        })
+    }
+
+    async fn authenticate(
+        &self,
+        credential: &AssertionCredential,
+        user_identifier: Option<&Uuid>,
+    ) -> Result<Uuid, WebAuthnServiceError> {
+        let response = &credential.response;
+        let assertion = Assertion {
+            client_data_json: decode("clientDataJSON", &response.client_data_json)?,
+            authenticator_data: decode("authenticatorData", &response.authenticator_data)?,
+            signature: decode("signature", &response.signature)?,
+        };
+        let client_data = CollectedClientData::parse(&assertion.client_data_json)?;
+        self.challenge_repository
+            .consume(&client_data.challenge, CEREMONY_AUTHENTICATION)
+            .await?
+            .filter(|challenge| challenge.user_identifier.as_ref() == user_identifier)
+            .ok_or(WebAuthnServiceError::ChallengeNotFound)?;
+
+        let stored = self
+            .credential_repository
+            .find_by_credential_id(credential.id.trim_end_matches('='))
+            .await?
+            .filter(|stored| user_identifier.is_none_or(|user| *user == stored.user_identifier))
+            .ok_or(WebAuthnServiceError::VerificationFailed)?;
+        if let Some(user_handle) = &response.user_handle
+            && decode("userHandle", user_handle)? != stored.user_identifier.as_bytes()
+        {
+            return Err(WebAuthnServiceError::VerificationFailed);
+        }
+
+        let sign_count = verify_assertion(
+            &self.config,
+            &client_data,
+            &assertion,
+            &stored.public_key,
+            u32::try_from(stored.sign_count).unwrap_or(u32::MAX),
+            user_identifier.is_none(),
+        )?;
+        if !self
+            .credential_repository
+            .record_use(&stored.identifier, stored.sign_count, i64::from(sign_count))
+            .await?
+        {
+            return Err(WebAuthnServiceError::VerificationFailed);
+        }
+
+        Ok(stored.user_identifier)
+    }
+
+    async fn list_credentials(
+        &self,
+        user_identifier: &Uuid,
+    ) -> Result<Vec<WebAuthnCredentialResponse>, WebAuthnServiceError> {
+        Ok(self
+            .credential_repository
+            .find_by_user(user_identifier)
+            .await?
+            .into_iter()
+            .map(WebAuthnCredentialResponse::from)
+            .collect())
+    }
+
+    async fn remove_credential(
+        &self,
+        user_identifier: &Uuid,
+        identifier: &Uuid,
+    ) -> Result<(), WebAuthnServiceError> {
+        if !self
+            .credential_repository
+            .delete(identifier, user_identifier)
+            .await?
+        {
+            return Err(WebAuthnServiceError::CredentialNotFound);
+        }
+
+        Ok(())
+    }
+}
@@ -16,3 +16,19 @@ pub fn extract_env<T: FromStr>(env_key: &str) -> Result<T, AppError> {
 
     Ok(env)
 }
+
+/// for settings that have a sensible default, a missing variable is not an error
+pub fn extract_optional_env<T: FromStr>(env_key: &str) -> Option<T> {
+    let value = env::var(env_key)
+        .ok()
+        .filter(|value| !value.trim().is_empty())?;
+
+    value
+        .parse::<T>()
+        .map_err(|_| log::error!("error parsing env {}, using the default", env_key))
+        .ok()
+}
+
+pub fn extract_env_or<T: FromStr>(env_key: &str, default: T) -> T {
+    extract_optional_env(env_key).unwrap_or(default)
+}
@@ -0,0 +1,179 @@
+use std::{
+    panic::AssertUnwindSafe,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+        mpsc::{Receiver, SyncSender, TrySendError, sync_channel},
+    },
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::{
+    config::password_hashing::PasswordHashingConfig, errors::hashing_pool_error::HashingPoolError,
+};
+
+/// runs the work and hands back what delivers its result, so that the worker can count the job
+/// before the caller hears about it
+type Job = Box<dyn FnOnce() -> Box<dyn FnOnce() + Send> + Send>;
+
+struct QueuedJob {
+    queued_at: Instant,
+    job: Job,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    queued: AtomicU64,
+    running: AtomicU64,
+    completed: AtomicU64,
+    rejected: AtomicU64,
+    total_queue_time_micros: AtomicU64,
+    max_queue_time_micros: AtomicU64,
+}
+
+/// counters since startup, queue time is how long a hash waited for a free thread
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HashingPoolMetrics {
+    pub workers: usize,
+    pub queued: u64,
+    pub running: u64,
+    pub completed: u64,
+    pub rejected: u64,
+    pub average_queue_time_ms: f64,
+    pub max_queue_time_ms: f64,
+}
+
+/// runs password hashing on threads of its own so a burst of logins cannot stall the async
+/// runtime serving every other request
+///
+/// at most `max_queue` jobs wait for one of the `workers` threads, more are refused with
+/// [`HashingPoolError::Saturated`] rather than left to queue up
+#[derive(Clone)]
+pub struct HashingPool {
+    sender: SyncSender<QueuedJob>,
+    counters: Arc<Counters>,
+    workers: usize,
+}
+
+impl HashingPool {
+    pub fn new(config: &PasswordHashingConfig) -> Self {
+        let workers = config.workers.max(1);
+        let (sender, receiver) = sync_channel::<QueuedJob>(config.max_queue);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let counters = Arc::new(Counters::default());
+
+        for index in 0..workers {
+            let receiver = receiver.clone();
+            let counters = counters.clone();
+            std::thread::Builder::new()
+                .name(format!("password-hasher-{index}"))
+                .spawn(move || work(&receiver, &counters))
+                .expect("error spawning a password hashing thread");
+        }
+
+        Self {
+            sender,
+            counters,
+            workers,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(&PasswordHashingConfig::from_env())
+    }
+
+    pub async fn run<T, F>(&self, job: F) -> Result<T, HashingPoolError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (result_sender, result_receiver) = oneshot::channel();
+        let queued = QueuedJob {
+            queued_at: Instant::now(),
+            job: Box::new(move || {
+                let result = job();
+                Box::new(move || {
+                    // the caller may have gone away in the meantime
+                    let _ = result_sender.send(result);
+                })
+            }),
+        };
+
+        self.counters.queued.fetch_add(1, Ordering::Relaxed);
+        if let Err(err) = self.sender.try_send(queued) {
+            self.counters.queued.fetch_sub(1, Ordering::Relaxed);
+            return match err {
+                TrySendError::Full(_) => {
+                    self.counters.rejected.fetch_add(1, Ordering::Relaxed);
+                    log::warn!("the password hashing queue is full, refusing the request");
+                    Err(HashingPoolError::Saturated)
+                }
+                TrySendError::Disconnected(_) => Err(HashingPoolError::Failed),
+            };
+        }
+
+        // the sender is dropped without a result when the job panics
+        result_receiver.await.map_err(|_| HashingPoolError::Failed)
+    }
+
+    pub fn metrics(&self) -> HashingPoolMetrics {
+        let completed = self.counters.completed.load(Ordering::Relaxed);
+        let total_queue_time = self
+            .counters
+            .total_queue_time_micros
+            .load(Ordering::Relaxed);
+
+        HashingPoolMetrics {
+            workers: self.workers,
+            queued: self.counters.queued.load(Ordering::Relaxed),
+            running: self.counters.running.load(Ordering::Relaxed),
+            completed,
+            rejected: self.counters.rejected.load(Ordering::Relaxed),
+            average_queue_time_ms: if completed == 0 {
+                0.0
+            } else {
+                total_queue_time as f64 / completed as f64 / 1000.0
+            },
+            max_queue_time_ms: self.counters.max_queue_time_micros.load(Ordering::Relaxed) as f64
+                / 1000.0,
+        }
+    }
+}
+
+/// takes jobs until every sender is gone
+fn work(receiver: &Mutex<Receiver<QueuedJob>>, counters: &Counters) {
+    loop {
+        let next = receiver
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .recv();
+        let Ok(QueuedJob { queued_at, job }) = next else {
+            return;
+        };
+
+        let queue_time = queued_at.elapsed().as_micros() as u64;
+        counters.queued.fetch_sub(1, Ordering::Relaxed);
+        counters.running.fetch_add(1, Ordering::Relaxed);
+        counters
+            .total_queue_time_micros
+            .fetch_add(queue_time, Ordering::Relaxed);
+        counters
+            .max_queue_time_micros
+            .fetch_max(queue_time, Ordering::Relaxed);
+
+        let deliver = std::panic::catch_unwind(AssertUnwindSafe(job));
+        if deliver.is_err() {
+            log::error!("a password hashing job panicked");
+        }
+
+        counters.running.fetch_sub(1, Ordering::Relaxed);
+        counters.completed.fetch_add(1, Ordering::Relaxed);
+        if let Ok(deliver) = deliver {
+            deliver();
+        }
+    }
+}
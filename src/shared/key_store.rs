@@ -0,0 +1,289 @@
+use std::{collections::BTreeMap, path::Path};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation, decode, decode_header,
+    encode,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters,
+        EllipticCurveKeyType, Jwk, JwkSet, KeyAlgorithm, OctetKeyPairParameters, OctetKeyPairType,
+        PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+};
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use rsa::{pkcs1::DecodeRsaPrivateKey, pkcs1::DecodeRsaPublicKey, traits::PublicKeyParts};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{errors::app_error::AppError, shared::extract_env::extract_optional_env};
+
+struct SigningKey {
+    kid: String,
+    algorithm: Algorithm,
+    encoding: EncodingKey,
+}
+
+struct VerificationKey {
+    algorithm: Algorithm,
+    decoding: DecodingKey,
+    jwk: Jwk,
+}
+
+/// asymmetric keys used to sign and verify tokens, loaded once at startup
+///
+/// every `<kid>.pem` file in `JWT_KEYS_DIR` is a verification key, private keys can also sign
+/// and `JWT_SIGNING_KEY_ID` picks the one that does. Rotating is a matter of adding the new key,
+/// switching the signing kid once it has been published and removing the old key after the
+/// tokens it signed have expired.
+pub struct KeyStore {
+    signing_key: SigningKey,
+    verification_keys: BTreeMap<String, VerificationKey>,
+}
+
+impl KeyStore {
+    pub fn from_env() -> Result<Self, AppError> {
+        let Some(keys_dir) = extract_optional_env::<String>("JWT_KEYS_DIR") else {
+            log::warn!(
+                "JWT_KEYS_DIR is not set, signing with an ephemeral key, issued tokens will not survive a restart"
+            );
+            return Self::generate_ephemeral();
+        };
+
+        Self::load(
+            Path::new(&keys_dir),
+            extract_optional_env::<String>("JWT_SIGNING_KEY_ID").as_deref(),
+        )
+    }
+
+    pub fn load(keys_dir: &Path, signing_kid: Option<&str>) -> Result<Self, AppError> {
+        let entries = std::fs::read_dir(keys_dir).map_err(|err| {
+            AppError::StartupError(format!("error reading {}: {err}", keys_dir.display()))
+        })?;
+
+        let mut signing_keys = BTreeMap::new();
+        let mut verification_keys = BTreeMap::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|err| AppError::StartupError(err.to_string()))?
+                .path();
+            if path.extension().and_then(|extension| extension.to_str()) != Some("pem") {
+                continue;
+            }
+            let Some(kid) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let pem = std::fs::read_to_string(&path).map_err(|err| {
+                AppError::StartupError(format!("error reading {}: {err}", path.display()))
+            })?;
+            let (signing_key, verification_key) = parse_pem(kid, &pem)?;
+            if let Some(signing_key) = signing_key {
+                signing_keys.insert(kid.to_string(), signing_key);
+            }
+            verification_keys.insert(kid.to_string(), verification_key);
+        }
+
+        let signing_key = match signing_kid {
+            Some(kid) => signing_keys.remove(kid),
+            None if signing_keys.len() == 1 => signing_keys.into_values().next(),
+            None => {
+                return Err(AppError::StartupError(
+                    "JWT_SIGNING_KEY_ID must be set when more than one private key is available"
+                        .into(),
+                ));
+            }
+        }
+        .ok_or_else(|| {
+            AppError::StartupError(format!(
+                "no private signing key found in {}",
+                keys_dir.display()
+            ))
+        })?;
+
+        Ok(Self {
+            signing_key,
+            verification_keys,
+        })
+    }
+
+    pub fn generate_ephemeral() -> Result<Self, AppError> {
+        let key = ed25519_dalek::SigningKey::generate(&mut rand::rngs::OsRng);
+        let pem = key
+            .to_pkcs8_pem(Default::default())
+            .map_err(|err| AppError::StartupError(err.to_string()))?;
+
+        let kid = uuid::Uuid::new_v4().to_string();
+        let (signing_key, verification_key) = parse_pem(&kid, &pem)?;
+
+        Ok(Self {
+            signing_key: signing_key.expect("a generated key is always a private key"),
+            verification_keys: BTreeMap::from([(kid, verification_key)]),
+        })
+    }
+
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, jsonwebtoken::errors::Error> {
+        let mut header = Header::new(self.signing_key.algorithm);
+        header.kid = Some(self.signing_key.kid.clone());
+
+        encode(&header, claims, &self.signing_key.encoding)
+    }
+
+    /// checks the signature with the key named by the token's `kid`, the rest of `validation` is
+    /// applied as given
+    pub fn verify<T: DeserializeOwned>(
+        &self,
+        token: &str,
+        validation: &Validation,
+    ) -> Result<TokenData<T>, jsonwebtoken::errors::Error> {
+        let invalid =
+            || jsonwebtoken::errors::Error::from(jsonwebtoken::errors::ErrorKind::InvalidToken);
+
+        let kid = decode_header(token)?.kid.ok_or_else(invalid)?;
+        let key = self.verification_keys.get(&kid).ok_or_else(invalid)?;
+
+        let mut validation = validation.clone();
+        validation.algorithms = vec![key.algorithm];
+        decode::<T>(token, &key.decoding, &validation)
+    }
+
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .verification_keys
+                .values()
+                .map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+
+    pub fn signing_algorithm(&self) -> Algorithm {
+        self.signing_key.algorithm
+    }
+}
+
+fn parse_pem(kid: &str, pem: &str) -> Result<(Option<SigningKey>, VerificationKey), AppError> {
+    let invalid_key = |err: String| AppError::StartupError(format!("invalid key {kid}: {err}"));
+
+    let rsa_private_key = rsa::RsaPrivateKey::from_pkcs8_pem(pem)
+        .or_else(|_| rsa::RsaPrivateKey::from_pkcs1_pem(pem))
+        .ok();
+    if let Some(private_key) = rsa_private_key {
+        let encoding = EncodingKey::from_rsa_pem(pem.as_bytes())
+            .map_err(|err| invalid_key(err.to_string()))?;
+        let jwk = rsa_jwk(kid, &private_key.to_public_key());
+        return verification_key(jwk, Algorithm::RS256).map(|verification| {
+            (
+                Some(SigningKey {
+                    kid: kid.to_string(),
+                    algorithm: Algorithm::RS256,
+                    encoding,
+                }),
+                verification,
+            )
+        });
+    }
+
+    if let Ok(private_key) = p256::SecretKey::from_pkcs8_pem(pem) {
+        let encoding =
+            EncodingKey::from_ec_pem(pem.as_bytes()).map_err(|err| invalid_key(err.to_string()))?;
+        let jwk = ec_jwk(kid, &private_key.public_key());
+        return verification_key(jwk, Algorithm::ES256).map(|verification| {
+            (
+                Some(SigningKey {
+                    kid: kid.to_string(),
+                    algorithm: Algorithm::ES256,
+                    encoding,
+                }),
+                verification,
+            )
+        });
+    }
+
+    if let Ok(private_key) = ed25519_dalek::SigningKey::from_pkcs8_pem(pem) {
+        let encoding =
+            EncodingKey::from_ed_pem(pem.as_bytes()).map_err(|err| invalid_key(err.to_string()))?;
+        let jwk = ed_jwk(kid, &private_key.verifying_key());
+        return verification_key(jwk, Algorithm::EdDSA).map(|verification| {
+            (
+                Some(SigningKey {
+                    kid: kid.to_string(),
+                    algorithm: Algorithm::EdDSA,
+                    encoding,
+                }),
+                verification,
+            )
+        });
+    }
+
+    let public_key = if let Ok(public_key) = rsa::RsaPublicKey::from_public_key_pem(pem)
+        .or_else(|_| rsa::RsaPublicKey::from_pkcs1_pem(pem))
+    {
+        verification_key(rsa_jwk(kid, &public_key), Algorithm::RS256)?
+    } else if let Ok(public_key) = p256::PublicKey::from_public_key_pem(pem) {
+        verification_key(ec_jwk(kid, &public_key), Algorithm::ES256)?
+    } else if let Ok(public_key) = ed25519_dalek::VerifyingKey::from_public_key_pem(pem) {
+        verification_key(ed_jwk(kid, &public_key), Algorithm::EdDSA)?
+    } else {
+        return Err(invalid_key(
+            "expected an RSA, P-256 or Ed25519 key in PEM format".into(),
+        ));
+    };
+
+    Ok((None, public_key))
+}
+
+fn verification_key(jwk: Jwk, algorithm: Algorithm) -> Result<VerificationKey, AppError> {
+    let decoding =
+        DecodingKey::from_jwk(&jwk).map_err(|err| AppError::StartupError(err.to_string()))?;
+
+    Ok(VerificationKey {
+        algorithm,
+        decoding,
+        jwk,
+    })
+}
+
+fn common_parameters(kid: &str, algorithm: KeyAlgorithm) -> CommonParameters {
+    CommonParameters {
+        public_key_use: Some(PublicKeyUse::Signature),
+        key_algorithm: Some(algorithm),
+        key_id: Some(kid.to_string()),
+        ..Default::default()
+    }
+}
+
+fn rsa_jwk(kid: &str, public_key: &rsa::RsaPublicKey) -> Jwk {
+    Jwk {
+        common: common_parameters(kid, KeyAlgorithm::RS256),
+        algorithm: AlgorithmParameters::RSA(RSAKeyParameters {
+            key_type: RSAKeyType::RSA,
+            n: URL_SAFE_NO_PAD.encode(public_key.n().to_bytes_be()),
+            e: URL_SAFE_NO_PAD.encode(public_key.e().to_bytes_be()),
+        }),
+    }
+}
+
+fn ec_jwk(kid: &str, public_key: &p256::PublicKey) -> Jwk {
+    let point = public_key.to_encoded_point(false);
+
+    Jwk {
+        common: common_parameters(kid, KeyAlgorithm::ES256),
+        algorithm: AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+            key_type: EllipticCurveKeyType::EC,
+            curve: EllipticCurve::P256,
+            x: URL_SAFE_NO_PAD.encode(point.x().map(|x| x.as_slice()).unwrap_or_default()),
+            y: URL_SAFE_NO_PAD.encode(point.y().map(|y| y.as_slice()).unwrap_or_default()),
+        }),
+    }
+}
+
+fn ed_jwk(kid: &str, public_key: &ed25519_dalek::VerifyingKey) -> Jwk {
+    Jwk {
+        common: common_parameters(kid, KeyAlgorithm::EdDSA),
+        algorithm: AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x: URL_SAFE_NO_PAD.encode(public_key.as_bytes()),
+        }),
+    }
+}
@@ -1 +1,13 @@
+pub mod crypto;
 pub mod extract_env;
+pub mod hashing_pool;
+pub mod key_store;
+pub mod oidc_client;
+pub mod password_hash;
+pub mod password_policy;
+pub mod saml;
+pub mod saml_signer;
+pub mod secret_box;
+pub mod totp;
+pub mod webauthn;
+pub mod xml;
@@ -0,0 +1,405 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
+
+use axum::http::header;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation, decode, decode_header,
+    jwk::{Jwk, JwkSet},
+};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use url::Url;
+use validator::ValidateEmail;
+
+use crate::{
+    adapters::dto::federation::{
+        FederatedProfile, ProviderMetadata, UpstreamAuthorization, UpstreamErrorResponse,
+        UpstreamProvider, UpstreamTokenResponse,
+    },
+    config::federation::FederationConfig,
+    errors::{app_error::AppError, federation_error::FederationError},
+    shared::crypto::{constant_time_eq, sha256_base64url},
+};
+
+const DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+
+/// the symmetric algorithms are left out, they would need the client secret as the key
+const SUPPORTED_ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+type Claims = Map<String, Value>;
+
+struct ProviderKeys {
+    metadata: ProviderMetadata,
+    jwks: JwkSet,
+    fetched_at: Instant,
+}
+
+/// the relying party side of OpenID Connect, signs users in at an upstream provider with the
+/// authorization code flow and PKCE
+///
+/// discovery documents and signing keys are cached per issuer for `metadata_ttl`
+#[derive(Clone)]
+pub struct OidcClient {
+    http: reqwest::Client,
+    metadata_ttl: Duration,
+    providers: Arc<RwLock<HashMap<String, Arc<ProviderKeys>>>>,
+}
+
+impl OidcClient {
+    pub fn new(config: &FederationConfig) -> Result<Self, AppError> {
+        let http = reqwest::Client::builder()
+            .timeout(config.http_timeout)
+            // a provider has no business sending the code or the secret anywhere else
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .map_err(|err| AppError::StartupError(err.to_string()))?;
+
+        Ok(Self {
+            http,
+            metadata_ttl: config.metadata_ttl,
+            providers: Arc::default(),
+        })
+    }
+
+    /// the page at the provider the user is sent to, it redirects back with a code and the state
+    pub async fn authorization_url(
+        &self,
+        provider: &UpstreamProvider,
+        authorization: &UpstreamAuthorization,
+    ) -> Result<String, FederationError> {
+        let keys = self.provider_keys(&provider.issuer, false).await?;
+        let mut url = Url::parse(&keys.metadata.authorization_endpoint).map_err(|err| {
+            FederationError::InvalidResponse(format!(
+                "the authorization endpoint is invalid: {err}"
+            ))
+        })?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &provider.client_id)
+            .append_pair("redirect_uri", &authorization.redirect_uri)
+            .append_pair("scope", &provider.scopes.join(" "))
+            .append_pair("state", &authorization.state)
+            .append_pair("nonce", &authorization.nonce)
+            .append_pair(
+                "code_challenge",
+                &sha256_base64url(&authorization.code_verifier),
+            )
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    /// redeems the code and reads the profile from the verified id token, claims the token leaves
+    /// out are looked up at the userinfo endpoint
+    pub async fn authenticate(
+        &self,
+        provider: &UpstreamProvider,
+        code: &str,
+        authorization: &UpstreamAuthorization,
+    ) -> Result<FederatedProfile, FederationError> {
+        let keys = self.provider_keys(&provider.issuer, false).await?;
+        let tokens = self
+            .exchange_code(&keys.metadata, provider, code, authorization)
+            .await?;
+        let id_token = tokens.id_token.as_deref().ok_or_else(|| {
+            FederationError::InvalidResponse("the token response has no id token".into())
+        })?;
+        let mut claims = self
+            .verify_id_token(provider, &keys, id_token, &authorization.nonce)
+            .await?;
+
+        let mapping = &provider.claim_mapping;
+        if let Some(userinfo_endpoint) = &keys.metadata.userinfo_endpoint
+            && [&mapping.email, &mapping.first_name, &mapping.last_name]
+                .iter()
+                .any(|claim| !claims.contains_key(claim.as_str()))
+        {
+            let userinfo = self
+                .userinfo(userinfo_endpoint, &tokens.access_token)
+                .await?;
+            // OpenID Connect Core 1.0 section 5.3.2, the response may be for another account
+            if userinfo.get("sub") != claims.get("sub") {
+                return Err(FederationError::InvalidResponse(
+                    "the userinfo response is for another subject".into(),
+                ));
+            }
+            for (name, value) in userinfo {
+                claims.entry(name).or_insert(value);
+            }
+        }
+
+        profile(provider, &claims)
+    }
+
+    /// the discovery document and keys of the issuer, fetched again once they are stale or when
+    /// `refresh` is set
+    async fn provider_keys(
+        &self,
+        issuer: &str,
+        refresh: bool,
+    ) -> Result<Arc<ProviderKeys>, FederationError> {
+        if !refresh
+            && let Some(keys) = self
+                .providers
+                .read()
+                .expect("the identity provider cache lock is not poisoned")
+                .get(issuer)
+                .filter(|keys| keys.fetched_at.elapsed() < self.metadata_ttl)
+        {
+            return Ok(keys.clone());
+        }
+
+        let metadata = self
+            .get_json::<ProviderMetadata>(&format!(
+                "{}{DISCOVERY_PATH}",
+                issuer.trim_end_matches('/')
+            ))
+            .await?;
+        // OpenID Connect Discovery 1.0 section 4.3, the document must be for the issuer asked for
+        if metadata.issuer != issuer {
+            return Err(FederationError::InvalidResponse(format!(
+                "the discovery document is for the issuer {}",
+                metadata.issuer
+            )));
+        }
+        let jwks = self.get_json::<JwkSet>(&metadata.jwks_uri).await?;
+
+        let keys = Arc::new(ProviderKeys {
+            metadata,
+            jwks,
+            fetched_at: Instant::now(),
+        });
+        self.providers
+            .write()
+            .expect("the identity provider cache lock is not poisoned")
+            .insert(issuer.to_string(), keys.clone());
+
+        Ok(keys)
+    }
+
+    async fn exchange_code(
+        &self,
+        metadata: &ProviderMetadata,
+        provider: &UpstreamProvider,
+        code: &str,
+        authorization: &UpstreamAuthorization,
+    ) -> Result<UpstreamTokenResponse, FederationError> {
+        let response = self
+            .http
+            .post(&metadata.token_endpoint)
+            .header(header::AUTHORIZATION, client_authorization(provider))
+            .header(header::ACCEPT, "application/json")
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &authorization.redirect_uri),
+                ("code_verifier", &authorization.code_verifier),
+            ])
+            .send()
+            .await
+            .map_err(unavailable)?;
+
+        let status = response.status();
+        if status.is_client_error() {
+            let error = response
+                .json::<UpstreamErrorResponse>()
+                .await
+                .map_err(|err| FederationError::InvalidResponse(err.to_string()))?;
+            return Err(FederationError::Denied(
+                error.error_description.unwrap_or(error.error),
+            ));
+        }
+        if !status.is_success() {
+            return Err(FederationError::Unavailable(format!(
+                "the token endpoint answered {status}"
+            )));
+        }
+
+        response
+            .json::<UpstreamTokenResponse>()
+            .await
+            .map_err(|err| FederationError::InvalidResponse(err.to_string()))
+    }
+
+    /// the signature, issuer, audience, expiry and nonce are all checked before any claim is used
+    async fn verify_id_token(
+        &self,
+        provider: &UpstreamProvider,
+        keys: &ProviderKeys,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<Claims, FederationError> {
+        let header = decode_header(id_token).map_err(|_| FederationError::InvalidIdToken)?;
+        if !SUPPORTED_ALGORITHMS.contains(&header.alg) {
+            return Err(FederationError::InvalidIdToken);
+        }
+
+        let jwk = match find_key(&keys.jwks, header.kid.as_deref()) {
+            Some(jwk) => jwk.clone(),
+            // the provider may have rotated its keys since they were cached
+            None => {
+                let keys = self.provider_keys(&provider.issuer, true).await?;
+                find_key(&keys.jwks, header.kid.as_deref())
+                    .cloned()
+                    .ok_or(FederationError::InvalidIdToken)?
+            }
+        };
+        let key = DecodingKey::from_jwk(&jwk).map_err(|_| FederationError::InvalidIdToken)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&provider.issuer]);
+        validation.set_audience(&[&provider.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = decode::<Claims>(id_token, &key, &validation)
+            .map_err(|err| {
+                log::warn!("rejected an id token from {}: {err}", provider.issuer);
+                FederationError::InvalidIdToken
+            })?
+            .claims;
+
+        let nonce_matches = claims
+            .get("nonce")
+            .and_then(Value::as_str)
+            .is_some_and(|claimed| constant_time_eq(claimed.as_bytes(), nonce.as_bytes()));
+        // OpenID Connect Core 1.0 section 3.1.3.7, a token for several audiences names the party
+        // it was issued to
+        let audiences = claims
+            .get("aud")
+            .and_then(Value::as_array)
+            .map_or(1, Vec::len);
+        let authorized_party_matches = audiences <= 1
+            || claims.get("azp").and_then(Value::as_str) == Some(provider.client_id.as_str());
+        if !nonce_matches
+            || !authorized_party_matches
+            || !claims.get("sub").is_some_and(Value::is_string)
+        {
+            log::warn!(
+                "rejected an id token from {} with a wrong nonce, authorized party or subject",
+                provider.issuer
+            );
+            return Err(FederationError::InvalidIdToken);
+        }
+
+        Ok(claims)
+    }
+
+    async fn userinfo(
+        &self,
+        endpoint: &str,
+        access_token: &str,
+    ) -> Result<Claims, FederationError> {
+        let response = self
+            .http
+            .get(endpoint)
+            .bearer_auth(access_token)
+            .header(header::ACCEPT, "application/json")
+            .send()
+            .await
+            .map_err(unavailable)?;
+        if !response.status().is_success() {
+            return Err(FederationError::Unavailable(format!(
+                "the userinfo endpoint answered {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json::<Claims>()
+            .await
+            .map_err(|err| FederationError::InvalidResponse(err.to_string()))
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, FederationError> {
+        let response = self.http.get(url).send().await.map_err(unavailable)?;
+        if !response.status().is_success() {
+            return Err(FederationError::Unavailable(format!(
+                "{url} answered {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json::<T>()
+            .await
+            .map_err(|err| FederationError::InvalidResponse(format!("{url}: {err}")))
+    }
+}
+
+fn unavailable(err: reqwest::Error) -> FederationError {
+    FederationError::Unavailable(err.to_string())
+}
+
+/// client_secret_basic, RFC 6749 section 2.3.1 has the id and secret form encoded before they
+/// are joined
+fn client_authorization(provider: &UpstreamProvider) -> String {
+    let encode =
+        |value: &str| url::form_urlencoded::byte_serialize(value.as_bytes()).collect::<String>();
+
+    format!(
+        "Basic {}",
+        STANDARD.encode(format!(
+            "{}:{}",
+            encode(&provider.client_id),
+            encode(&provider.client_secret)
+        ))
+    )
+}
+
+/// the key named by the token, or the only key when the token names none
+fn find_key<'a>(jwks: &'a JwkSet, kid: Option<&str>) -> Option<&'a Jwk> {
+    match kid {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+}
+
+fn profile(
+    provider: &UpstreamProvider,
+    claims: &Claims,
+) -> Result<FederatedProfile, FederationError> {
+    let claim = |name: &str| {
+        claims
+            .get(name)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(String::from)
+    };
+
+    let mapping = &provider.claim_mapping;
+    let email = claim(&mapping.email)
+        .filter(|email| email.validate_email())
+        .ok_or_else(|| {
+            FederationError::InvalidResponse(format!(
+                "the {} claim is not an email address",
+                mapping.email
+            ))
+        })?;
+    // some providers send the flag as a string
+    let email_verified = provider.trust_email
+        || matches!(claims.get("email_verified"), Some(Value::Bool(true)))
+        || claims.get("email_verified").and_then(Value::as_str) == Some("true");
+
+    Ok(FederatedProfile {
+        subject: claim("sub").ok_or(FederationError::InvalidIdToken)?,
+        email,
+        email_verified,
+        first_name: claim(&mapping.first_name).unwrap_or_default(),
+        last_name: claim(&mapping.last_name).unwrap_or_default(),
+    })
+}
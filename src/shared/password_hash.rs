@@ -0,0 +1,117 @@
+use argon2::{
+    Algorithm, Argon2, Params, PasswordHash, PasswordVerifier, Version,
+    password_hash::{self, PasswordHasher as _, SaltString, rand_core::OsRng},
+};
+
+use crate::{config::password_hashing::PasswordHashingConfig, errors::app_error::AppError};
+
+const ARGON2ID_PREFIX: &str = "$argon2id$";
+const BCRYPT_PREFIXES: &[&str] = &["$2a$", "$2b$", "$2x$", "$2y$"];
+
+/// how a stored hash was made, recognised from its prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordHashScheme {
+    Argon2id,
+    /// what accounts created before Argon2id was adopted still have
+    Bcrypt,
+}
+
+impl PasswordHashScheme {
+    pub fn detect(hash: &str) -> Option<Self> {
+        if hash.starts_with(ARGON2ID_PREFIX) {
+            Some(Self::Argon2id)
+        } else if BCRYPT_PREFIXES
+            .iter()
+            .any(|prefix| hash.starts_with(prefix))
+        {
+            Some(Self::Bcrypt)
+        } else {
+            None
+        }
+    }
+}
+
+/// hashes new passwords with Argon2id and verifies both Argon2id and legacy bcrypt hashes
+///
+/// surrounding whitespace is not part of a password, it is trimmed before hashing and before
+/// verifying so a password is accepted however it was typed when it was set
+#[derive(Clone)]
+pub struct PasswordHasher {
+    argon2: Argon2<'static>,
+}
+
+impl PasswordHasher {
+    pub fn from_config(config: &PasswordHashingConfig) -> Result<Self, AppError> {
+        let params = Params::new(
+            config.memory_kib,
+            config.iterations,
+            config.parallelism,
+            None,
+        )
+        .map_err(|err| {
+            AppError::StartupError(format!("invalid password hashing parameters: {err}"))
+        })?;
+
+        Ok(Self {
+            argon2: Argon2::new(Algorithm::Argon2id, Version::V0x13, params),
+        })
+    }
+
+    pub fn from_env() -> Result<Self, AppError> {
+        Self::from_config(&PasswordHashingConfig::from_env())
+    }
+
+    pub fn hash(&self, password: &str) -> Result<String, AppError> {
+        let salt = SaltString::generate(&mut OsRng);
+
+        self.argon2
+            .hash_password(normalize(password).as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|err| AppError::OperationFailed(err.to_string()))
+    }
+
+    pub fn verify(&self, password: &str, hash: &str) -> Result<bool, AppError> {
+        let password = normalize(password);
+
+        match PasswordHashScheme::detect(hash) {
+            Some(PasswordHashScheme::Argon2id) => {
+                let hash = PasswordHash::new(hash)
+                    .map_err(|err| AppError::OperationFailed(err.to_string()))?;
+                // the parameters are taken from the stored hash, not from the configuration
+                match self.argon2.verify_password(password.as_bytes(), &hash) {
+                    Ok(()) => Ok(true),
+                    Err(password_hash::Error::Password) => Ok(false),
+                    Err(err) => Err(AppError::OperationFailed(err.to_string())),
+                }
+            }
+            Some(PasswordHashScheme::Bcrypt) => bcrypt::verify(password, hash)
+                .map_err(|err| AppError::OperationFailed(err.to_string())),
+            None => Err(AppError::OperationFailed(
+                "the stored password hash is in an unknown format".into(),
+            )),
+        }
+    }
+
+    /// true for legacy bcrypt hashes and for Argon2id hashes made with other parameters
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        if PasswordHashScheme::detect(hash) != Some(PasswordHashScheme::Argon2id) {
+            return true;
+        }
+        let Ok(hash) = PasswordHash::new(hash) else {
+            return true;
+        };
+        let Ok(params) = Params::try_from(&hash) else {
+            return true;
+        };
+
+        let current = self.argon2.params();
+        hash.version != Some(Version::V0x13.into())
+            || params.m_cost() != current.m_cost()
+            || params.t_cost() != current.t_cost()
+            || params.p_cost() != current.p_cost()
+    }
+}
+
+fn normalize(password: &str) -> &str {
+    password.trim()
+}
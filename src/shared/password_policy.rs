@@ -0,0 +1,399 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+    path::Path,
+    sync::Arc,
+};
+
+use sha1::{Digest, Sha1};
+use validator::{ValidationError, ValidationErrors};
+
+use crate::{config::password_policy::PasswordPolicyConfig, errors::app_error::AppError};
+
+const PASSWORD_FIELD: &str = "password";
+/// personal details shorter than this are too likely to appear by chance
+const MIN_PERSONAL_INFORMATION_LENGTH: usize = 3;
+/// what a password built around a common word is assumed to be worth, about the size of the
+/// dictionaries guessing tools start with
+const COMMON_WORD_BITS: f64 = 10.0;
+/// how much a character that repeats the previous one or continues a run counts for
+const PREDICTABLE_CHARACTER_WEIGHT: f64 = 0.25;
+
+/// the start of the most common passwords and the words they are built from, matched after
+/// undoing the usual letter substitutions
+const COMMON_WORDS: &[&str] = &[
+    "password",
+    "passwort",
+    "letmein",
+    "welcome",
+    "qwerty",
+    "qwertz",
+    "azerty",
+    "asdf",
+    "zxcv",
+    "admin",
+    "login",
+    "iloveyou",
+    "monkey",
+    "dragon",
+    "master",
+    "shadow",
+    "sunshine",
+    "princess",
+    "football",
+    "baseball",
+    "soccer",
+    "hockey",
+    "batman",
+    "superman",
+    "trustno1",
+    "whatever",
+    "freedom",
+    "starwars",
+    "pokemon",
+    "secret",
+    "summer",
+    "winter",
+    "spring",
+    "autumn",
+    "january",
+    "february",
+    "october",
+    "november",
+    "december",
+    "monday",
+    "friday",
+    "michael",
+    "jennifer",
+    "jordan",
+    "charlie",
+    "thomas",
+    "hunter",
+    "ranger",
+    "buster",
+    "tigger",
+    "computer",
+    "internet",
+    "cheese",
+    "chocolate",
+    "flower",
+    "orange",
+    "banana",
+    "purple",
+    "silver",
+    "ginger",
+    "pepper",
+    "cookie",
+    "killer",
+    "lovely",
+    "hello",
+    "abc123",
+    "changeme",
+    "default",
+    "uranium",
+];
+
+/// keyboard and alphabet runs, a password walking along one is no better than a repeat
+const SEQUENCES: &[&str] = &[
+    "abcdefghijklmnopqrstuvwxyz",
+    "01234567890",
+    "qwertyuiop",
+    "asdfghjkl",
+    "zxcvbnm",
+];
+
+/// what the password is checked against besides its own content
+#[derive(Debug, Clone, Default)]
+pub struct PasswordContext<'a> {
+    pub email: &'a str,
+    pub first_name: &'a str,
+    pub last_name: &'a str,
+}
+
+/// the deployment's password rules, see [`PasswordPolicyConfig`]
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    config: PasswordPolicyConfig,
+    /// breached SHA-1 prefixes, grouped by length so a hash is looked up once per length
+    breached_prefixes: Arc<BTreeMap<usize, HashSet<String>>>,
+}
+
+impl PasswordPolicy {
+    pub fn from_config(config: PasswordPolicyConfig) -> Result<Self, AppError> {
+        let breached_prefixes = match &config.breached_corpus_path {
+            Some(path) => load_breached_corpus(path)?,
+            None => BTreeMap::new(),
+        };
+
+        Ok(Self {
+            config,
+            breached_prefixes: Arc::new(breached_prefixes),
+        })
+    }
+
+    pub fn from_env() -> Result<Self, AppError> {
+        Self::from_config(PasswordPolicyConfig::from_env())
+    }
+
+    /// every rule the password breaks, reported against the `password` field
+    pub fn check(&self, password: &str, context: &PasswordContext) -> Result<(), ValidationErrors> {
+        let password = password.trim();
+        let length = password.chars().count();
+        let mut violations = Vec::new();
+
+        if length < self.config.min_length {
+            violations.push(violation(
+                "too_short",
+                format!(
+                    "password must be at least {} characters",
+                    self.config.min_length
+                ),
+            ));
+        }
+        if length > self.config.max_length {
+            violations.push(violation(
+                "too_long",
+                format!(
+                    "password cannot be longer than {} characters",
+                    self.config.max_length
+                ),
+            ));
+            // nothing else is worth checking on a password that will not be accepted anyway
+            return Err(into_errors(violations));
+        }
+        if character_classes(password) < self.config.min_character_classes {
+            violations.push(violation(
+                "character_classes",
+                format!(
+                    "password must mix at least {} of lowercase letters, uppercase letters, digits and symbols",
+                    self.config.min_character_classes
+                ),
+            ));
+        }
+        if self.config.forbid_personal_information
+            && contains_personal_information(password, context)
+        {
+            violations.push(violation(
+                "personal_information",
+                "password cannot contain your email address or name".into(),
+            ));
+        }
+        if estimate_strength(password) < self.config.min_strength {
+            violations.push(violation(
+                "too_weak",
+                "password is too easy to guess, try a longer passphrase".into(),
+            ));
+        }
+        if self.is_breached(password) {
+            violations.push(violation(
+                "breached",
+                "password has appeared in a data breach, choose another one".into(),
+            ));
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(into_errors(violations))
+        }
+    }
+
+    pub fn is_breached(&self, password: &str) -> bool {
+        if self.breached_prefixes.is_empty() {
+            return false;
+        }
+
+        let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+        self.breached_prefixes
+            .iter()
+            .any(|(length, prefixes)| prefixes.contains(&hash[..*length]))
+    }
+}
+
+/// a rough estimate of how hard the password is to guess, from 0 (trivial) to 4 (very strong)
+///
+/// entropy from the length and the alphabet the password draws on, where repeated characters,
+/// keyboard or alphabet runs and common words count for little
+pub fn estimate_strength(password: &str) -> u8 {
+    let characters: Vec<char> = password.chars().collect();
+    if characters.is_empty() {
+        return 0;
+    }
+
+    let alphabet = alphabet_size(password) as f64;
+    let normalized = undo_substitutions(password);
+    let mut weights = vec![1.0; characters.len()];
+
+    for index in 1..characters.len() {
+        let previous = characters[index - 1].to_ascii_lowercase();
+        let current = characters[index].to_ascii_lowercase();
+        let pair = format!("{previous}{current}");
+        let reversed = format!("{current}{previous}");
+        if current == previous
+            || SEQUENCES
+                .iter()
+                .any(|sequence| sequence.contains(&pair) || sequence.contains(&reversed))
+        {
+            weights[index] = PREDICTABLE_CHARACTER_WEIGHT;
+        }
+    }
+
+    let mut common_word_bits = 0.0;
+    for word in COMMON_WORDS {
+        let Some(start) = normalized.find(word) else {
+            continue;
+        };
+        // the characters of the word are replaced by the cost of picking it from a dictionary
+        let word_range = start..start + word.chars().count();
+        if weights[word_range.clone()].contains(&0.0) {
+            continue;
+        }
+        weights[word_range]
+            .iter_mut()
+            .for_each(|weight| *weight = 0.0);
+        common_word_bits += COMMON_WORD_BITS;
+    }
+
+    let bits = weights.iter().sum::<f64>() * alphabet.log2() + common_word_bits;
+    match bits {
+        bits if bits < 28.0 => 0,
+        bits if bits < 36.0 => 1,
+        bits if bits < 50.0 => 2,
+        bits if bits < 70.0 => 3,
+        _ => 4,
+    }
+}
+
+fn violation(code: &'static str, message: String) -> ValidationError {
+    ValidationError::new(code).with_message(Cow::Owned(message))
+}
+
+fn into_errors(violations: Vec<ValidationError>) -> ValidationErrors {
+    let mut errors = ValidationErrors::new();
+    for violation in violations {
+        errors.add(PASSWORD_FIELD, violation);
+    }
+    errors
+}
+
+fn character_classes(password: &str) -> usize {
+    [
+        password.chars().any(|character| character.is_lowercase()),
+        password.chars().any(|character| character.is_uppercase()),
+        password.chars().any(|character| character.is_numeric()),
+        password
+            .chars()
+            .any(|character| !character.is_alphanumeric()),
+    ]
+    .into_iter()
+    .filter(|present| *present)
+    .count()
+}
+
+fn alphabet_size(password: &str) -> usize {
+    let mut size = 0;
+    if password
+        .chars()
+        .any(|character| character.is_ascii_lowercase())
+    {
+        size += 26;
+    }
+    if password
+        .chars()
+        .any(|character| character.is_ascii_uppercase())
+    {
+        size += 26;
+    }
+    if password.chars().any(|character| character.is_ascii_digit()) {
+        size += 10;
+    }
+    if password
+        .chars()
+        .any(|character| character.is_ascii() && !character.is_ascii_alphanumeric())
+    {
+        size += 33;
+    }
+    if !password.is_ascii() {
+        size += 100;
+    }
+    size.max(2)
+}
+
+/// lowercased, with `p@ssw0rd` style substitutions turned back into letters, one character
+/// for each character of the password
+fn undo_substitutions(password: &str) -> String {
+    password
+        .chars()
+        .map(|character| match character.to_ascii_lowercase() {
+            '0' => 'o',
+            '1' | '!' => 'i',
+            '3' => 'e',
+            '4' | '@' => 'a',
+            '5' | '$' => 's',
+            '7' => 't',
+            '8' => 'b',
+            '9' => 'g',
+            other if other.is_ascii() => other,
+            // keeps byte offsets in line with character offsets
+            _ => '?',
+        })
+        .collect()
+}
+
+fn contains_personal_information(password: &str, context: &PasswordContext) -> bool {
+    let password = password.to_lowercase();
+    let email = context.email.trim().to_lowercase();
+    let local_part = email.split('@').next().unwrap_or_default().to_string();
+
+    [
+        email,
+        local_part,
+        context.first_name.trim().to_lowercase(),
+        context.last_name.trim().to_lowercase(),
+    ]
+    .iter()
+    .filter(|value| value.chars().count() >= MIN_PERSONAL_INFORMATION_LENGTH)
+    .any(|value| password.contains(value.as_str()))
+}
+
+/// one hex prefix per line, optionally followed by `:count` as in the Pwned Passwords downloads,
+/// blank lines and lines starting with `#` are skipped
+fn load_breached_corpus(path: &Path) -> Result<BTreeMap<usize, HashSet<String>>, AppError> {
+    let corpus = std::fs::read_to_string(path).map_err(|err| {
+        AppError::StartupError(format!(
+            "error reading the breached password corpus {}: {err}",
+            path.display()
+        ))
+    })?;
+
+    let mut prefixes: BTreeMap<usize, HashSet<String>> = BTreeMap::new();
+    for line in corpus.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let prefix = line.split(':').next().unwrap_or_default().trim();
+        if prefix.is_empty()
+            || prefix.len() > 40
+            || !prefix
+                .chars()
+                .all(|character| character.is_ascii_hexdigit())
+        {
+            return Err(AppError::StartupError(format!(
+                "the breached password corpus {} has an invalid line: {line}",
+                path.display()
+            )));
+        }
+        prefixes
+            .entry(prefix.len())
+            .or_default()
+            .insert(prefix.to_ascii_uppercase());
+    }
+    log::info!(
+        "loaded {} breached password prefixes",
+        prefixes.values().map(HashSet::len).sum::<usize>()
+    );
+
+    Ok(prefixes)
+}
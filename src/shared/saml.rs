@@ -0,0 +1,281 @@
+use std::io::{Read, Write};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, SecondsFormat, Utc};
+use flate2::{Compression, read::DeflateDecoder, write::DeflateEncoder};
+use roxmltree::{Document, Node};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    adapters::dto::saml::{
+        AuthnRequest, NameIdFormat, SamlAssertion, SamlBinding, ServiceProviderMetadata,
+    },
+    errors::saml_error::SamlError,
+    shared::{
+        saml_signer::SamlSigner,
+        xml::{escape_attribute, escape_text},
+    },
+};
+
+pub const PROTOCOL_NAMESPACE: &str = "urn:oasis:names:tc:SAML:2.0:protocol";
+pub const ASSERTION_NAMESPACE: &str = "urn:oasis:names:tc:SAML:2.0:assertion";
+pub const METADATA_NAMESPACE: &str = "urn:oasis:names:tc:SAML:2.0:metadata";
+pub const BINDING_HTTP_POST: &str = "urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST";
+pub const BINDING_HTTP_REDIRECT: &str = "urn:oasis:names:tc:SAML:2.0:bindings:HTTP-Redirect";
+
+const STATUS_SUCCESS: &str = "urn:oasis:names:tc:SAML:2.0:status:Success";
+const CONFIRMATION_BEARER: &str = "urn:oasis:names:tc:SAML:2.0:cm:bearer";
+/// the assertion does not say how the user signed in, it may have been any of the first-party ways
+const AUTHN_CONTEXT_UNSPECIFIED: &str = "urn:oasis:names:tc:SAML:2.0:ac:classes:unspecified";
+const ATTRIBUTE_NAME_FORMAT_BASIC: &str = "urn:oasis:names:tc:SAML:2.0:attrname-format:basic";
+const ATTRIBUTE_NAME_FORMAT_URI: &str = "urn:oasis:names:tc:SAML:2.0:attrname-format:uri";
+
+/// requests are small, anything that inflates past this is refused rather than read on
+const MAX_MESSAGE_SIZE: u64 = 64 * 1024;
+
+/// the authentication request as XML, from the `SAMLRequest` parameter of either binding
+pub fn decode_request(encoded: &str, binding: SamlBinding) -> Result<String, SamlError> {
+    let invalid = |err: &dyn std::fmt::Display| SamlError::InvalidRequest(err.to_string());
+
+    // the POST binding allows line breaks in the encoded message
+    let encoded: String = encoded.split_whitespace().collect();
+    let decoded = STANDARD.decode(encoded).map_err(|err| invalid(&err))?;
+
+    let mut xml = String::new();
+    match binding {
+        SamlBinding::Redirect => DeflateDecoder::new(decoded.as_slice())
+            .take(MAX_MESSAGE_SIZE + 1)
+            .read_to_string(&mut xml)
+            .map_err(|err| invalid(&err))?,
+        SamlBinding::Post => decoded
+            .as_slice()
+            .take(MAX_MESSAGE_SIZE + 1)
+            .read_to_string(&mut xml)
+            .map_err(|err| invalid(&err))?,
+    };
+    if xml.len() as u64 > MAX_MESSAGE_SIZE {
+        return Err(SamlError::InvalidRequest("the request is too large".into()));
+    }
+
+    Ok(xml)
+}
+
+/// deflated and base64 encoded as the redirect binding carries it
+pub fn encode_request(xml: &str) -> String {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(xml.as_bytes())
+        .and_then(|_| encoder.finish())
+        .map(|deflated| STANDARD.encode(deflated))
+        .expect("deflating into memory does not fail")
+}
+
+pub fn parse_authn_request(xml: &str) -> Result<AuthnRequest, SamlError> {
+    let invalid = |message: &str| SamlError::InvalidRequest(message.into());
+
+    let document =
+        Document::parse(xml).map_err(|err| SamlError::InvalidRequest(err.to_string()))?;
+    let request = document.root_element();
+    if !request.has_tag_name((PROTOCOL_NAMESPACE, "AuthnRequest")) {
+        return Err(invalid("the message is not an AuthnRequest"));
+    }
+    if request.attribute("Version") != Some("2.0") {
+        return Err(invalid("only SAML 2.0 requests are supported"));
+    }
+
+    let issuer = child(request, ASSERTION_NAMESPACE, "Issuer")
+        .and_then(|issuer| issuer.text())
+        .map(str::trim)
+        .filter(|issuer| !issuer.is_empty())
+        .ok_or_else(|| invalid("the request has no issuer"))?;
+
+    Ok(AuthnRequest {
+        id: request
+            .attribute("ID")
+            .ok_or_else(|| invalid("the request has no id"))?
+            .to_string(),
+        issuer: issuer.to_string(),
+        destination: request.attribute("Destination").map(String::from),
+        assertion_consumer_service_url: request
+            .attribute("AssertionConsumerServiceURL")
+            .map(String::from),
+        protocol_binding: request.attribute("ProtocolBinding").map(String::from),
+        name_id_format: child(request, PROTOCOL_NAMESPACE, "NameIDPolicy")
+            .and_then(|policy| policy.attribute("Format"))
+            .map(String::from),
+    })
+}
+
+/// reads the entity id, the assertion consumer services that take the POST binding and the
+/// preferred name id formats from a service provider's `EntityDescriptor`
+pub fn parse_service_provider_metadata(xml: &str) -> Result<ServiceProviderMetadata, SamlError> {
+    let invalid = |message: &str| SamlError::InvalidMetadata(message.into());
+
+    let document =
+        Document::parse(xml).map_err(|err| SamlError::InvalidMetadata(err.to_string()))?;
+    let entity = document.root_element();
+    if !entity.has_tag_name((METADATA_NAMESPACE, "EntityDescriptor")) {
+        return Err(invalid("the metadata is not a single EntityDescriptor"));
+    }
+    let entity_id = entity
+        .attribute("entityID")
+        .filter(|entity_id| !entity_id.is_empty())
+        .ok_or_else(|| invalid("the entity has no entityID"))?;
+    let descriptor = child(entity, METADATA_NAMESPACE, "SPSSODescriptor")
+        .ok_or_else(|| invalid("the entity has no SPSSODescriptor"))?;
+
+    let mut services = Vec::new();
+    for service in descriptor
+        .children()
+        .filter(|node| node.has_tag_name((METADATA_NAMESPACE, "AssertionConsumerService")))
+        .filter(|node| node.attribute("Binding") == Some(BINDING_HTTP_POST))
+    {
+        let location = service
+            .attribute("Location")
+            .ok_or_else(|| invalid("an assertion consumer service has no location"))?;
+        if !is_acceptable_location(location) {
+            return Err(SamlError::InvalidMetadata(format!(
+                "{location} has to be an https url"
+            )));
+        }
+        let is_default = service.attribute("isDefault") == Some("true");
+        let index = service
+            .attribute("index")
+            .and_then(|index| index.parse::<u32>().ok())
+            .unwrap_or(u32::MAX);
+        services.push((!is_default, index, location.to_string()));
+    }
+    if services.is_empty() {
+        return Err(invalid(
+            "the entity has no assertion consumer service with the HTTP-POST binding",
+        ));
+    }
+    // SAML 2.0 Metadata section 2.2.3, the default is the one marked so or else the first
+    services.sort_by_key(|(not_default, index, _)| (*not_default, *index));
+
+    Ok(ServiceProviderMetadata {
+        entity_id: entity_id.to_string(),
+        assertion_consumer_services: services
+            .into_iter()
+            .map(|(_, _, location)| location)
+            .collect(),
+        name_id_formats: descriptor
+            .children()
+            .filter(|node| node.has_tag_name((METADATA_NAMESPACE, "NameIDFormat")))
+            .filter_map(|node| node.text())
+            .map(|format| format.trim().to_string())
+            .collect(),
+    })
+}
+
+/// the identity provider's `EntityDescriptor`, for service providers to register
+pub fn identity_provider_metadata(entity_id: &str, sso_url: &str, certificate: &str) -> String {
+    let name_id_formats: String = NameIdFormat::ALL
+        .iter()
+        .map(|format| format!("<md:NameIDFormat>{format}</md:NameIDFormat>"))
+        .collect();
+    let sso_url = escape_attribute(sso_url);
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><md:EntityDescriptor xmlns:md="{METADATA_NAMESPACE}" entityID="{}"><md:IDPSSODescriptor WantAuthnRequestsSigned="false" protocolSupportEnumeration="{PROTOCOL_NAMESPACE}"><md:KeyDescriptor use="signing"><ds:KeyInfo xmlns:ds="http://www.w3.org/2000/09/xmldsig#"><ds:X509Data><ds:X509Certificate>{certificate}</ds:X509Certificate></ds:X509Data></ds:KeyInfo></md:KeyDescriptor>{name_id_formats}<md:SingleSignOnService Binding="{BINDING_HTTP_REDIRECT}" Location="{sso_url}"></md:SingleSignOnService><md:SingleSignOnService Binding="{BINDING_HTTP_POST}" Location="{sso_url}"></md:SingleSignOnService></md:IDPSSODescriptor></md:EntityDescriptor>"#,
+        escape_attribute(entity_id),
+    )
+}
+
+/// a successful `Response` carrying the assertion, which is signed
+pub fn build_response(assertion: &SamlAssertion, signer: &SamlSigner) -> Result<String, SamlError> {
+    let issue_instant = Utc::now();
+    let not_on_or_after = issue_instant + assertion.lifetime;
+    let response_id = generate_id();
+    let assertion_id = generate_id();
+
+    let issuer = format!(
+        "<saml:Issuer>{}</saml:Issuer>",
+        escape_text(&assertion.issuer)
+    );
+    let in_response_to = assertion
+        .in_response_to
+        .as_deref()
+        .map(|id| format!(r#" InResponseTo="{}""#, escape_attribute(id)))
+        .unwrap_or_default();
+    let session_index = assertion
+        .session_index
+        .as_deref()
+        .map(|index| format!(r#" SessionIndex="{}""#, escape_attribute(index)))
+        .unwrap_or_default();
+    let attributes: String = assertion
+        .attributes
+        .iter()
+        .map(|attribute| {
+            // names that are uris are meant to be compared as such
+            let name_format = if attribute.name.contains(':') {
+                ATTRIBUTE_NAME_FORMAT_URI
+            } else {
+                ATTRIBUTE_NAME_FORMAT_BASIC
+            };
+            format!(
+                r#"<saml:Attribute Name="{}" NameFormat="{name_format}"><saml:AttributeValue>{}</saml:AttributeValue></saml:Attribute>"#,
+                escape_attribute(&attribute.name),
+                escape_text(&attribute.value)
+            )
+        })
+        .collect();
+    let attribute_statement = if attributes.is_empty() {
+        String::new()
+    } else {
+        format!("<saml:AttributeStatement>{attributes}</saml:AttributeStatement>")
+    };
+
+    let unsigned_assertion = format!(
+        r#"<saml:Assertion xmlns:saml="{ASSERTION_NAMESPACE}" ID="{assertion_id}" IssueInstant="{}" Version="2.0">{issuer}<saml:Subject><saml:NameID Format="{}">{}</saml:NameID><saml:SubjectConfirmation Method="{CONFIRMATION_BEARER}"><saml:SubjectConfirmationData{in_response_to} NotOnOrAfter="{}" Recipient="{}"></saml:SubjectConfirmationData></saml:SubjectConfirmation></saml:Subject><saml:Conditions NotBefore="{}" NotOnOrAfter="{}"><saml:AudienceRestriction><saml:Audience>{}</saml:Audience></saml:AudienceRestriction></saml:Conditions><saml:AuthnStatement AuthnInstant="{}"{session_index}><saml:AuthnContext><saml:AuthnContextClassRef>{AUTHN_CONTEXT_UNSPECIFIED}</saml:AuthnContextClassRef></saml:AuthnContext></saml:AuthnStatement>{attribute_statement}</saml:Assertion>"#,
+        timestamp(issue_instant),
+        assertion.name_id_format,
+        escape_text(&assertion.name_id),
+        timestamp(not_on_or_after),
+        escape_attribute(&assertion.destination),
+        timestamp(issue_instant),
+        timestamp(not_on_or_after),
+        escape_text(&assertion.audience),
+        timestamp(assertion.authn_instant),
+    );
+    let signed_assertion = signer.sign_enveloped(&unsigned_assertion, &assertion_id)?;
+
+    Ok(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?><samlp:Response xmlns:samlp="{PROTOCOL_NAMESPACE}" xmlns:saml="{ASSERTION_NAMESPACE}" Destination="{}" ID="{response_id}"{in_response_to} IssueInstant="{}" Version="2.0">{issuer}<samlp:Status><samlp:StatusCode Value="{STATUS_SUCCESS}"></samlp:StatusCode></samlp:Status>{signed_assertion}</samlp:Response>"#,
+        escape_attribute(&assertion.destination),
+        timestamp(issue_instant),
+    ))
+}
+
+/// assertions are posted to these, so they have to be protected in transit, plain http is only
+/// accepted for the loopback interface while developing
+pub fn is_acceptable_location(location: &str) -> bool {
+    let Ok(url) = Url::parse(location) else {
+        return false;
+    };
+
+    match url.scheme() {
+        "https" => true,
+        "http" => matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]")),
+        _ => false,
+    }
+}
+
+fn child<'a, 'input>(
+    node: Node<'a, 'input>,
+    namespace: &str,
+    name: &str,
+) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.has_tag_name((namespace, name)))
+}
+
+/// xs:ID values are NCNames, which cannot start with a digit
+fn generate_id() -> String {
+    format!("_{}", Uuid::new_v4().simple())
+}
+
+fn timestamp(instant: DateTime<Utc>) -> String {
+    instant.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
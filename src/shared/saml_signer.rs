@@ -0,0 +1,239 @@
+use std::{path::Path, str::FromStr, time::Duration};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use p256::{
+    ecdsa::{Signature as EcdsaSignature, SigningKey as EcdsaSigningKey},
+    pkcs8::{DecodePrivateKey, EncodePublicKey},
+};
+use rsa::{
+    pkcs1::DecodeRsaPrivateKey,
+    pkcs1v15::{Signature as RsaSignature, SigningKey as RsaSigningKey},
+    signature::{SignatureEncoding, Signer},
+};
+use sha2::{Digest, Sha256};
+use x509_cert::{
+    Certificate,
+    builder::{Builder, CertificateBuilder, Profile},
+    der::{DecodePem, Encode},
+    name::Name,
+    serial_number::SerialNumber,
+    spki::SubjectPublicKeyInfoOwned,
+    time::Validity,
+};
+
+use crate::{
+    config::saml::SamlConfig,
+    errors::{app_error::AppError, saml_error::SamlError},
+    shared::{
+        saml::ASSERTION_NAMESPACE,
+        xml::{canonicalize, escape_attribute},
+    },
+};
+
+const DSIG_NAMESPACE: &str = "http://www.w3.org/2000/09/xmldsig#";
+const EXCLUSIVE_C14N: &str = "http://www.w3.org/2001/10/xml-exc-c14n#";
+const ENVELOPED_SIGNATURE: &str = "http://www.w3.org/2000/09/xmldsig#enveloped-signature";
+const DIGEST_SHA256: &str = "http://www.w3.org/2001/04/xmlenc#sha256";
+const SIGNATURE_RSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#rsa-sha256";
+const SIGNATURE_ECDSA_SHA256: &str = "http://www.w3.org/2001/04/xmldsig-more#ecdsa-sha256";
+
+/// an ephemeral certificate outlives any restart it would not survive anyway
+const EPHEMERAL_CERTIFICATE_VALIDITY: Duration = Duration::from_secs(365 * 24 * 60 * 60);
+const EPHEMERAL_KEY_BITS: usize = 2048;
+
+enum SigningKey {
+    Rsa(Box<RsaSigningKey<Sha256>>),
+    Ecdsa(EcdsaSigningKey),
+}
+
+/// signs SAML assertions with XML Signature, loaded once at startup
+///
+/// service providers are given the certificate in the identity provider's metadata and trust
+/// assertions signed with the matching key. RSA keys are the ones every service provider accepts,
+/// so the ephemeral key development setups can opt in to is one too.
+pub struct SamlSigner {
+    key: SigningKey,
+    certificate: Vec<u8>,
+}
+
+impl SamlSigner {
+    pub fn from_config(config: &SamlConfig) -> Result<Self, AppError> {
+        match (&config.certificate_path, &config.private_key_path) {
+            (Some(certificate_path), Some(private_key_path)) => {
+                Self::load(Path::new(certificate_path), Path::new(private_key_path))
+            }
+            (None, None) if config.ephemeral_key => {
+                log::warn!(
+                    "SAML_EPHEMERAL_KEY is set, signing assertions with an ephemeral key, service providers have to be given the new metadata after a restart"
+                );
+                Self::generate_ephemeral()
+            }
+            // service providers would stop trusting the assertions after a restart, or whenever
+            // another replica signed them
+            (None, None) => Err(AppError::StartupError(
+                "SAML_CERTIFICATE_PATH and SAML_PRIVATE_KEY_PATH are not set, set SAML_EPHEMERAL_KEY=true to sign with a throwaway key in development".into(),
+            )),
+            _ => Err(AppError::StartupError(
+                "SAML_CERTIFICATE_PATH and SAML_PRIVATE_KEY_PATH have to be set together".into(),
+            )),
+        }
+    }
+
+    pub fn load(certificate_path: &Path, private_key_path: &Path) -> Result<Self, AppError> {
+        let read = |path: &Path| {
+            std::fs::read_to_string(path).map_err(|err| {
+                AppError::StartupError(format!("error reading {}: {err}", path.display()))
+            })
+        };
+
+        let certificate = Certificate::from_pem(read(certificate_path)?).map_err(|err| {
+            AppError::StartupError(format!(
+                "{} is not a PEM certificate: {err}",
+                certificate_path.display()
+            ))
+        })?;
+        let (key, public_key) = parse_private_key(&read(private_key_path)?).ok_or_else(|| {
+            AppError::StartupError(format!(
+                "{} is not a PEM RSA or P-256 private key",
+                private_key_path.display()
+            ))
+        })?;
+
+        let certificate_public_key = certificate
+            .tbs_certificate
+            .subject_public_key_info
+            .to_der()
+            .map_err(|err| AppError::StartupError(err.to_string()))?;
+        if certificate_public_key != public_key {
+            return Err(AppError::StartupError(format!(
+                "{} is not the certificate for the key in {}",
+                certificate_path.display(),
+                private_key_path.display()
+            )));
+        }
+
+        Ok(Self {
+            key,
+            certificate: certificate
+                .to_der()
+                .map_err(|err| AppError::StartupError(err.to_string()))?,
+        })
+    }
+
+    /// an RSA key and a self-signed certificate for it
+    pub fn generate_ephemeral() -> Result<Self, AppError> {
+        let startup_error = |err: &dyn std::fmt::Display| AppError::StartupError(err.to_string());
+
+        let private_key = rsa::RsaPrivateKey::new(&mut rand::rngs::OsRng, EPHEMERAL_KEY_BITS)
+            .map_err(|e| startup_error(&e))?;
+        let public_key = SubjectPublicKeyInfoOwned::from_key(private_key.to_public_key())
+            .map_err(|e| startup_error(&e))?;
+        let key = RsaSigningKey::<Sha256>::new(private_key);
+        let certificate = CertificateBuilder::new(
+            Profile::Root,
+            SerialNumber::from(rand::random::<u32>()),
+            Validity::from_now(EPHEMERAL_CERTIFICATE_VALIDITY).map_err(|e| startup_error(&e))?,
+            Name::from_str("CN=uranium").map_err(|e| startup_error(&e))?,
+            public_key,
+            &key,
+        )
+        .map_err(|e| startup_error(&e))?
+        .build::<RsaSignature>()
+        .map_err(|e| startup_error(&e))?;
+
+        Ok(Self {
+            key: SigningKey::Rsa(Box::new(key)),
+            certificate: certificate.to_der().map_err(|e| startup_error(&e))?,
+        })
+    }
+
+    /// the DER certificate, base64 encoded as metadata and signatures carry it
+    pub fn certificate(&self) -> String {
+        STANDARD.encode(&self.certificate)
+    }
+
+    /// adds an enveloped signature over the element with the `ID` attribute `reference_id`, placed
+    /// after the element's issuer as the SAML schema requires
+    pub fn sign_enveloped(&self, xml: &str, reference_id: &str) -> Result<String, SamlError> {
+        let document = roxmltree::Document::parse(xml)
+            .map_err(|err| SamlError::SigningFailed(err.to_string()))?;
+        let element = document
+            .descendants()
+            .find(|node| node.attribute("ID") == Some(reference_id))
+            .ok_or_else(|| {
+                SamlError::SigningFailed(format!("no element with the id {reference_id}"))
+            })?;
+        let issuer = element
+            .children()
+            .find(|node| node.has_tag_name((ASSERTION_NAMESPACE, "Issuer")))
+            .ok_or_else(|| SamlError::SigningFailed("the signed element has no issuer".into()))?;
+
+        let digest = STANDARD.encode(Sha256::digest(canonicalize(element).as_bytes()));
+        let signed_info = format!(
+            r##"<ds:CanonicalizationMethod Algorithm="{EXCLUSIVE_C14N}"></ds:CanonicalizationMethod><ds:SignatureMethod Algorithm="{}"></ds:SignatureMethod><ds:Reference URI="#{}"><ds:Transforms><ds:Transform Algorithm="{ENVELOPED_SIGNATURE}"></ds:Transform><ds:Transform Algorithm="{EXCLUSIVE_C14N}"></ds:Transform></ds:Transforms><ds:DigestMethod Algorithm="{DIGEST_SHA256}"></ds:DigestMethod><ds:DigestValue>{digest}</ds:DigestValue></ds:Reference>"##,
+            self.signature_algorithm(),
+            escape_attribute(reference_id),
+        );
+
+        // canonicalized on its own the signed info carries the declaration it inherits in place
+        let canonical_signed_info =
+            format!(r#"<ds:SignedInfo xmlns:ds="{DSIG_NAMESPACE}">{signed_info}</ds:SignedInfo>"#);
+        let canonical_signed_info = canonicalize(
+            roxmltree::Document::parse(&canonical_signed_info)
+                .map_err(|err| SamlError::SigningFailed(err.to_string()))?
+                .root_element(),
+        );
+        let signature_value = STANDARD.encode(self.sign(canonical_signed_info.as_bytes())?);
+
+        let signature = format!(
+            r#"<ds:Signature xmlns:ds="{DSIG_NAMESPACE}"><ds:SignedInfo>{signed_info}</ds:SignedInfo><ds:SignatureValue>{signature_value}</ds:SignatureValue><ds:KeyInfo><ds:X509Data><ds:X509Certificate>{}</ds:X509Certificate></ds:X509Data></ds:KeyInfo></ds:Signature>"#,
+            self.certificate()
+        );
+        let position = issuer.range().end;
+        Ok(format!(
+            "{}{signature}{}",
+            &xml[..position],
+            &xml[position..]
+        ))
+    }
+
+    fn signature_algorithm(&self) -> &'static str {
+        match self.key {
+            SigningKey::Rsa(_) => SIGNATURE_RSA_SHA256,
+            SigningKey::Ecdsa(_) => SIGNATURE_ECDSA_SHA256,
+        }
+    }
+
+    /// RFC 4051 section 2.3.6, ECDSA signature values are `r` and `s` side by side rather than DER
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SamlError> {
+        match &self.key {
+            SigningKey::Rsa(key) => key
+                .try_sign(message)
+                .map(|signature| signature.to_vec())
+                .map_err(|err| SamlError::SigningFailed(err.to_string())),
+            SigningKey::Ecdsa(key) => {
+                let signature: EcdsaSignature = key
+                    .try_sign(message)
+                    .map_err(|err| SamlError::SigningFailed(err.to_string()))?;
+                Ok(signature.to_bytes().to_vec())
+            }
+        }
+    }
+}
+
+/// the key and its public half as DER SubjectPublicKeyInfo, to be checked against the certificate
+fn parse_private_key(pem: &str) -> Option<(SigningKey, Vec<u8>)> {
+    if let Ok(key) = EcdsaSigningKey::from_pkcs8_pem(pem) {
+        let public_key = key.verifying_key().to_public_key_der().ok()?;
+        return Some((SigningKey::Ecdsa(key), public_key.into_vec()));
+    }
+
+    let key = rsa::RsaPrivateKey::from_pkcs8_pem(pem)
+        .or_else(|_| rsa::RsaPrivateKey::from_pkcs1_pem(pem))
+        .ok()?;
+    let public_key = key.to_public_key().to_public_key_der().ok()?;
+    Some((
+        SigningKey::Rsa(Box::new(RsaSigningKey::new(key))),
+        public_key.into_vec(),
+    ))
+}
@@ -0,0 +1,68 @@
+use aes_gcm::{
+    Aes256Gcm, Key, KeyInit, Nonce,
+    aead::{Aead, AeadCore, OsRng},
+};
+
+use crate::{errors::app_error::AppError, shared::extract_env::extract_optional_env};
+
+const NONCE_LENGTH: usize = 12;
+
+/// encrypts secrets the application has to read back, such as TOTP seeds and identity provider
+/// client secrets, before they are stored
+///
+/// AES-256-GCM with the 32 byte, hex encoded key in `SECRET_ENCRYPTION_KEY`, the random nonce is
+/// stored in front of the ciphertext
+#[derive(Clone)]
+pub struct SecretBox {
+    cipher: Aes256Gcm,
+}
+
+impl SecretBox {
+    pub fn from_env() -> Result<Self, AppError> {
+        // an ephemeral key would lock users out of their enrolled authenticators on every restart
+        let key = extract_optional_env::<String>("SECRET_ENCRYPTION_KEY").ok_or_else(|| {
+            AppError::StartupError(
+                "SECRET_ENCRYPTION_KEY is not set, generate one with `openssl rand -hex 32`".into(),
+            )
+        })?;
+
+        let key = hex::decode(key.trim())
+            .ok()
+            .filter(|key| key.len() == 32)
+            .ok_or_else(|| {
+                AppError::StartupError("SECRET_ENCRYPTION_KEY must be 32 hex encoded bytes".into())
+            })?;
+
+        Ok(Self::new(Key::<Aes256Gcm>::from_slice(&key)))
+    }
+
+    fn new(key: &Key<Aes256Gcm>) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(key),
+        }
+    }
+
+    pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, AppError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|err| AppError::OperationFailed(err.to_string()))?;
+
+        Ok([nonce.as_slice(), &ciphertext].concat())
+    }
+
+    /// fails when the value was sealed with another key or has been tampered with
+    pub fn open(&self, sealed: &[u8]) -> Result<Vec<u8>, AppError> {
+        if sealed.len() < NONCE_LENGTH {
+            return Err(AppError::OperationFailed(
+                "sealed value is truncated".into(),
+            ));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|err| AppError::OperationFailed(err.to_string()))
+    }
+}
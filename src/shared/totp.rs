@@ -0,0 +1,75 @@
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha1::Sha1;
+use url::Url;
+
+use crate::shared::crypto::constant_time_eq;
+
+pub const TOTP_DIGITS: u32 = 6;
+pub const TOTP_PERIOD_SECONDS: i64 = 30;
+const SECRET_LENGTH: usize = 20;
+/// codes from the step before and after the current one are accepted to allow for clock drift
+const ALLOWED_DRIFT_STEPS: i64 = 1;
+
+/// 160 random bits, the secret length RFC 4226 recommends for HMAC-SHA1
+pub fn generate_secret() -> Vec<u8> {
+    let mut secret = vec![0u8; SECRET_LENGTH];
+    rand::thread_rng().fill(secret.as_mut_slice());
+    secret
+}
+
+/// the unpadded base32 form authenticator apps accept when the secret is typed in by hand
+pub fn encode_secret(secret: &[u8]) -> String {
+    BASE32_NOPAD.encode(secret)
+}
+
+/// the `otpauth://` uri authenticator apps read from a QR code
+pub fn provisioning_uri(issuer: &str, account_name: &str, secret: &[u8]) -> String {
+    let mut uri = Url::parse("otpauth://totp/").expect("the otpauth base uri is valid");
+    uri.set_path(&format!("{issuer}:{account_name}"));
+    uri.query_pairs_mut()
+        .append_pair("secret", &encode_secret(secret))
+        .append_pair("issuer", issuer)
+        .append_pair("algorithm", "SHA1")
+        .append_pair("digits", &TOTP_DIGITS.to_string())
+        .append_pair("period", &TOTP_PERIOD_SECONDS.to_string());
+
+    uri.to_string()
+}
+
+/// the RFC 6238 time step a unix timestamp falls in
+pub fn time_step(timestamp: i64) -> i64 {
+    timestamp.div_euclid(TOTP_PERIOD_SECONDS)
+}
+
+/// the code for a time step, HOTP (RFC 4226) over the step counter
+pub fn generate_code(secret: &[u8], step: i64) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(TOTP_DIGITS),
+        width = TOTP_DIGITS as usize
+    )
+}
+
+/// the time step the code was generated for, if it is valid within the allowed drift of
+/// `timestamp`
+pub fn verify_code(secret: &[u8], code: &str, timestamp: i64) -> Option<i64> {
+    let code = code.trim();
+    let current_step = time_step(timestamp);
+
+    (current_step - ALLOWED_DRIFT_STEPS..=current_step + ALLOWED_DRIFT_STEPS)
+        .find(|step| constant_time_eq(generate_code(secret, *step).as_bytes(), code.as_bytes()))
+}
@@ -0,0 +1,343 @@
+use std::io::Cursor;
+
+use ciborium::Value;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::webauthn::WebAuthnConfig, errors::webauthn_service_error::WebAuthnServiceError,
+};
+
+pub const CEREMONY_REGISTRATION: &str = "webauthn.create";
+pub const CEREMONY_AUTHENTICATION: &str = "webauthn.get";
+
+/// COSE algorithm identifiers, in order of preference
+pub const COSE_ALGORITHM_ES256: i64 = -7;
+pub const COSE_ALGORITHM_EDDSA: i64 = -8;
+pub const COSE_ALGORITHM_RS256: i64 = -257;
+pub const SUPPORTED_ALGORITHMS: [i64; 3] = [
+    COSE_ALGORITHM_ES256,
+    COSE_ALGORITHM_EDDSA,
+    COSE_ALGORITHM_RS256,
+];
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+const COSE_KEY_TYPE: i64 = 1;
+const COSE_KEY_ALGORITHM: i64 = 3;
+const COSE_KEY_TYPE_OKP: i64 = 1;
+const COSE_KEY_TYPE_EC2: i64 = 2;
+const COSE_KEY_TYPE_RSA: i64 = 3;
+const COSE_CURVE_P256: i64 = 1;
+const COSE_CURVE_ED25519: i64 = 6;
+
+fn invalid(reason: &str) -> WebAuthnServiceError {
+    WebAuthnServiceError::InvalidResponse(reason.to_string())
+}
+
+/// the `clientDataJSON` the browser hands to the authenticator, its hash is part of what the
+/// authenticator signs
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectedClientData {
+    #[serde(rename = "type")]
+    pub ceremony: String,
+    /// base64url, as generated by the server
+    pub challenge: String,
+    pub origin: String,
+}
+
+impl CollectedClientData {
+    pub fn parse(client_data_json: &[u8]) -> Result<Self, WebAuthnServiceError> {
+        serde_json::from_slice(client_data_json).map_err(|_| invalid("malformed client data"))
+    }
+}
+
+pub struct AttestedCredential {
+    pub credential_id: Vec<u8>,
+    /// the COSE encoded key, stored as is
+    pub public_key: Vec<u8>,
+}
+
+/// the binary structure every authenticator response carries, see section 6.1 of WebAuthn level 2
+pub struct AuthenticatorData {
+    pub rp_id_hash: Vec<u8>,
+    pub flags: u8,
+    pub sign_count: u32,
+    pub attested_credential: Option<AttestedCredential>,
+}
+
+impl AuthenticatorData {
+    pub fn parse(bytes: &[u8]) -> Result<Self, WebAuthnServiceError> {
+        if bytes.len() < 37 {
+            return Err(invalid("authenticator data is truncated"));
+        }
+        let flags = bytes[32];
+        let sign_count = u32::from_be_bytes([bytes[33], bytes[34], bytes[35], bytes[36]]);
+
+        let attested_credential = if flags & FLAG_ATTESTED_CREDENTIAL_DATA != 0 {
+            // aaguid (16 bytes), credential id length (2 bytes), credential id, public key
+            let id_length = bytes
+                .get(53..55)
+                .map(|length| u16::from_be_bytes([length[0], length[1]]) as usize)
+                .ok_or_else(|| invalid("attested credential data is truncated"))?;
+            let rest = &bytes[55..];
+            let credential_id = rest
+                .get(..id_length)
+                .ok_or_else(|| invalid("credential id is truncated"))?
+                .to_vec();
+
+            let mut key = Cursor::new(&rest[id_length..]);
+            ciborium::de::from_reader::<Value, _>(&mut key)
+                .map_err(|_| invalid("malformed credential public key"))?;
+            let key_length = key.position() as usize;
+
+            Some(AttestedCredential {
+                credential_id,
+                public_key: rest[id_length..id_length + key_length].to_vec(),
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            rp_id_hash: bytes[..32].to_vec(),
+            flags,
+            sign_count,
+            attested_credential,
+        })
+    }
+
+    pub fn user_present(&self) -> bool {
+        self.flags & FLAG_USER_PRESENT != 0
+    }
+
+    pub fn user_verified(&self) -> bool {
+        self.flags & FLAG_USER_VERIFIED != 0
+    }
+}
+
+/// the authenticator data out of an attestation object, the attestation statement is not
+/// checked since credentials are registered with the `none` conveyance preference
+pub fn parse_attestation_object(bytes: &[u8]) -> Result<AuthenticatorData, WebAuthnServiceError> {
+    let Value::Map(entries) = ciborium::de::from_reader::<Value, _>(bytes)
+        .map_err(|_| invalid("malformed attestation object"))?
+    else {
+        return Err(invalid("malformed attestation object"));
+    };
+
+    let auth_data = entries
+        .iter()
+        .find_map(|(key, value)| match (key, value) {
+            (Value::Text(key), Value::Bytes(auth_data)) if key == "authData" => Some(auth_data),
+            _ => None,
+        })
+        .ok_or_else(|| invalid("attestation object has no authenticator data"))?;
+
+    AuthenticatorData::parse(auth_data)
+}
+
+pub enum CredentialPublicKey {
+    Es256(p256::ecdsa::VerifyingKey),
+    EdDsa(ed25519_dalek::VerifyingKey),
+    Rs256(rsa::RsaPublicKey),
+}
+
+fn cose_parameter(entries: &[(Value, Value)], label: i64) -> Option<&Value> {
+    entries.iter().find_map(|(key, value)| match key {
+        Value::Integer(key) if i128::from(*key) == i128::from(label) => Some(value),
+        _ => None,
+    })
+}
+
+fn cose_integer(entries: &[(Value, Value)], label: i64) -> Option<i64> {
+    match cose_parameter(entries, label)? {
+        Value::Integer(value) => i64::try_from(*value).ok(),
+        _ => None,
+    }
+}
+
+fn cose_bytes(entries: &[(Value, Value)], label: i64) -> Option<&[u8]> {
+    match cose_parameter(entries, label)? {
+        Value::Bytes(value) => Some(value),
+        _ => None,
+    }
+}
+
+impl CredentialPublicKey {
+    /// accepts the key types of [`SUPPORTED_ALGORITHMS`] (RFC 9053)
+    pub fn from_cose(bytes: &[u8]) -> Result<Self, WebAuthnServiceError> {
+        let unsupported = || invalid("unsupported credential public key");
+        let Ok(Value::Map(entries)) = ciborium::de::from_reader::<Value, _>(bytes) else {
+            return Err(invalid("malformed credential public key"));
+        };
+
+        let key_type = cose_integer(&entries, COSE_KEY_TYPE).ok_or_else(unsupported)?;
+        let algorithm = cose_integer(&entries, COSE_KEY_ALGORITHM).ok_or_else(unsupported)?;
+        match (key_type, algorithm) {
+            (COSE_KEY_TYPE_EC2, COSE_ALGORITHM_ES256) => {
+                if cose_integer(&entries, -1) != Some(COSE_CURVE_P256) {
+                    return Err(unsupported());
+                }
+                let x = cose_bytes(&entries, -2).ok_or_else(unsupported)?;
+                let y = cose_bytes(&entries, -3).ok_or_else(unsupported)?;
+                let point = [&[0x04], x, y].concat();
+
+                p256::ecdsa::VerifyingKey::from_sec1_bytes(&point)
+                    .map(Self::Es256)
+                    .map_err(|_| unsupported())
+            }
+            (COSE_KEY_TYPE_OKP, COSE_ALGORITHM_EDDSA) => {
+                if cose_integer(&entries, -1) != Some(COSE_CURVE_ED25519) {
+                    return Err(unsupported());
+                }
+                let x: [u8; 32] = cose_bytes(&entries, -2)
+                    .and_then(|x| x.try_into().ok())
+                    .ok_or_else(unsupported)?;
+
+                ed25519_dalek::VerifyingKey::from_bytes(&x)
+                    .map(Self::EdDsa)
+                    .map_err(|_| unsupported())
+            }
+            (COSE_KEY_TYPE_RSA, COSE_ALGORITHM_RS256) => {
+                let n = cose_bytes(&entries, -1).ok_or_else(unsupported)?;
+                let e = cose_bytes(&entries, -2).ok_or_else(unsupported)?;
+
+                rsa::RsaPublicKey::new(
+                    rsa::BigUint::from_bytes_be(n),
+                    rsa::BigUint::from_bytes_be(e),
+                )
+                .map(Self::Rs256)
+                .map_err(|_| unsupported())
+            }
+            _ => Err(unsupported()),
+        }
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        match self {
+            Self::Es256(key) => {
+                use p256::ecdsa::signature::Verifier;
+                p256::ecdsa::Signature::from_der(signature)
+                    .is_ok_and(|signature| key.verify(message, &signature).is_ok())
+            }
+            Self::EdDsa(key) => ed25519_dalek::Signature::from_slice(signature)
+                .is_ok_and(|signature| key.verify_strict(message, &signature).is_ok()),
+            Self::Rs256(key) => {
+                use rsa::signature::Verifier;
+                rsa::pkcs1v15::Signature::try_from(signature).is_ok_and(|signature| {
+                    rsa::pkcs1v15::VerifyingKey::<Sha256>::new(key.clone())
+                        .verify(message, &signature)
+                        .is_ok()
+                })
+            }
+        }
+    }
+}
+
+fn check_client_data(
+    config: &WebAuthnConfig,
+    client_data: &CollectedClientData,
+    ceremony: &str,
+) -> Result<(), WebAuthnServiceError> {
+    if client_data.ceremony != ceremony {
+        return Err(invalid("unexpected ceremony type"));
+    }
+    if !config.origins.contains(&client_data.origin) {
+        return Err(invalid("the origin is not allowed"));
+    }
+
+    Ok(())
+}
+
+fn check_authenticator_data(
+    config: &WebAuthnConfig,
+    auth_data: &AuthenticatorData,
+    require_user_verification: bool,
+) -> Result<(), WebAuthnServiceError> {
+    if auth_data.rp_id_hash != Sha256::digest(config.rp_id.as_bytes()).as_slice() {
+        return Err(invalid("the credential is scoped to another relying party"));
+    }
+    if !auth_data.user_present() {
+        return Err(invalid("the user was not present"));
+    }
+    if require_user_verification && !auth_data.user_verified() {
+        return Err(invalid("the user was not verified"));
+    }
+
+    Ok(())
+}
+
+/// a registration that passed the checks of section 7.1 of WebAuthn level 2, the challenge is
+/// checked by the caller
+pub struct VerifiedRegistration {
+    pub credential_id: Vec<u8>,
+    pub public_key: Vec<u8>,
+    pub sign_count: u32,
+}
+
+pub fn verify_registration(
+    config: &WebAuthnConfig,
+    client_data: &CollectedClientData,
+    attestation_object: &[u8],
+) -> Result<VerifiedRegistration, WebAuthnServiceError> {
+    check_client_data(config, client_data, CEREMONY_REGISTRATION)?;
+
+    let auth_data = parse_attestation_object(attestation_object)?;
+    check_authenticator_data(config, &auth_data, false)?;
+    let credential = auth_data
+        .attested_credential
+        .ok_or_else(|| invalid("no credential was attested"))?;
+    // rejects algorithms that could not be verified at login
+    CredentialPublicKey::from_cose(&credential.public_key)?;
+
+    Ok(VerifiedRegistration {
+        credential_id: credential.credential_id,
+        public_key: credential.public_key,
+        sign_count: auth_data.sign_count,
+    })
+}
+
+/// the decoded fields of an authenticator's assertion response
+pub struct Assertion {
+    pub client_data_json: Vec<u8>,
+    pub authenticator_data: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// checks an assertion against the stored credential as in section 7.2 of WebAuthn level 2 and
+/// returns the new sign count, the challenge is checked by the caller
+pub fn verify_assertion(
+    config: &WebAuthnConfig,
+    client_data: &CollectedClientData,
+    assertion: &Assertion,
+    public_key: &[u8],
+    stored_sign_count: u32,
+    require_user_verification: bool,
+) -> Result<u32, WebAuthnServiceError> {
+    check_client_data(config, client_data, CEREMONY_AUTHENTICATION)?;
+
+    let auth_data = AuthenticatorData::parse(&assertion.authenticator_data)?;
+    check_authenticator_data(config, &auth_data, require_user_verification)?;
+
+    let signed = [
+        assertion.authenticator_data.as_slice(),
+        Sha256::digest(&assertion.client_data_json).as_slice(),
+    ]
+    .concat();
+    if !CredentialPublicKey::from_cose(public_key)?.verify(&signed, &assertion.signature) {
+        return Err(WebAuthnServiceError::VerificationFailed);
+    }
+
+    // authenticators that keep a counter must increase it on every use, one that does not may
+    // have been cloned
+    if (auth_data.sign_count != 0 || stored_sign_count != 0)
+        && auth_data.sign_count <= stored_sign_count
+    {
+        return Err(WebAuthnServiceError::VerificationFailed);
+    }
+
+    Ok(auth_data.sign_count)
+}
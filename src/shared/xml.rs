@@ -0,0 +1,167 @@
+use roxmltree::{Node, NodeType};
+
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// character data as exclusive canonicalization writes it
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '\r' => escaped.push_str("&#xD;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+/// an attribute value as exclusive canonicalization writes it, to go between double quotes
+pub fn escape_attribute(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' => escaped.push_str("&#x9;"),
+            '\n' => escaped.push_str("&#xA;"),
+            '\r' => escaped.push_str("&#xD;"),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+/// Exclusive XML Canonicalization 1.0 without comments of `node` and everything below it
+///
+/// namespaces are declared on the outermost element that uses them, so the result does not depend
+/// on the document the element sits in. Prefixes are taken from the source as written.
+pub fn canonicalize(node: Node) -> String {
+    let mut canonical = String::new();
+    write_node(node, &mut Vec::new(), &mut canonical);
+    canonical
+}
+
+/// `(prefix, namespace)` pairs declared by the elements being written, innermost last
+type Declarations<'a> = Vec<(&'a str, &'a str)>;
+
+fn write_node<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    declared: &mut Declarations<'a>,
+    canonical: &mut String,
+) {
+    match node.node_type() {
+        NodeType::Element => write_element(node, declared, canonical),
+        NodeType::Text => canonical.push_str(&escape_text(node.text().unwrap_or_default())),
+        NodeType::PI => {
+            if let Some(pi) = node.pi() {
+                canonical.push_str("<?");
+                canonical.push_str(pi.target);
+                if let Some(value) = pi.value {
+                    canonical.push(' ');
+                    canonical.push_str(value);
+                }
+                canonical.push_str("?>");
+            }
+        }
+        NodeType::Root => {
+            for child in node.children() {
+                write_node(child, declared, canonical);
+            }
+        }
+        NodeType::Comment => {}
+    }
+}
+
+fn write_element<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    declared: &mut Declarations<'a>,
+    canonical: &mut String,
+) {
+    let input = node.document().input_text();
+    let qualified_name = element_qualified_name(node, input);
+
+    let mut used = vec![(
+        prefix_of(qualified_name),
+        node.tag_name().namespace().unwrap_or_default(),
+    )];
+    let mut attributes = Vec::new();
+    for attribute in node.attributes() {
+        let attribute_name = &input[attribute.range_qname()];
+        if let Some(namespace) = attribute.namespace()
+            && namespace != XML_NAMESPACE
+        {
+            used.push((prefix_of(attribute_name), namespace));
+        }
+        attributes.push((
+            attribute.namespace().unwrap_or_default(),
+            attribute.name(),
+            attribute_name,
+            attribute.value(),
+        ));
+    }
+    used.sort_unstable();
+    used.dedup();
+    attributes.sort_unstable_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+    let outer = declared.len();
+    canonical.push('<');
+    canonical.push_str(qualified_name);
+    for (prefix, namespace) in used {
+        let in_scope = declared
+            .iter()
+            .rev()
+            .find(|(declared_prefix, _)| *declared_prefix == prefix)
+            .map(|(_, declared_namespace)| *declared_namespace)
+            .unwrap_or_default();
+        if in_scope == namespace {
+            continue;
+        }
+
+        declared.push((prefix, namespace));
+        if prefix.is_empty() {
+            canonical.push_str(" xmlns=\"");
+        } else {
+            canonical.push_str(" xmlns:");
+            canonical.push_str(prefix);
+            canonical.push_str("=\"");
+        }
+        canonical.push_str(&escape_attribute(namespace));
+        canonical.push('"');
+    }
+    for (_, _, name, value) in attributes {
+        canonical.push(' ');
+        canonical.push_str(name);
+        canonical.push_str("=\"");
+        canonical.push_str(&escape_attribute(value));
+        canonical.push('"');
+    }
+    canonical.push('>');
+
+    for child in node.children() {
+        write_node(child, declared, canonical);
+    }
+
+    canonical.push_str("</");
+    canonical.push_str(qualified_name);
+    canonical.push('>');
+    declared.truncate(outer);
+}
+
+/// the name in the start tag, roxmltree keeps the namespace but not the prefix it was written with
+fn element_qualified_name<'a>(node: Node, input: &'a str) -> &'a str {
+    let start_tag = &input[node.range().start + 1..];
+    let end = start_tag
+        .find(|character: char| character.is_whitespace() || character == '>' || character == '/')
+        .unwrap_or(start_tag.len());
+    &start_tag[..end]
+}
+
+fn prefix_of(qualified_name: &str) -> &str {
+    qualified_name
+        .split_once(':')
+        .map(|(prefix, _)| prefix)
+        .unwrap_or_default()
+}
@@ -1,7 +1,13 @@
 use axum::extract::FromRef;
 
+use crate::adapters::rate_limit::RateLimitBackend;
+use crate::config::network::NetworkConfig;
 use crate::services::{
-    auth_service::AuthenticationService, root_service::RootService, user_service::UserService,
+    auth_service::AuthenticationService, client_service::ClientService,
+    identity_provider_service::IdentityProviderService, mailer_service::MailerService,
+    oauth_service::OAuthService, root_service::RootService, saml_service::SamlService,
+    service_provider_service::ServiceProviderService, token_service::TokenService,
+    user_service::UserService,
 };
 
 #[derive(Clone)]
@@ -9,6 +15,16 @@ pub struct ServicesState {
     pub user_service: UserService,
     pub root_service: RootService,
     pub auth_service: AuthenticationService,
+    pub mailer_service: MailerService,
+    pub token_service: TokenService,
+    pub oauth_service: OAuthService,
+    pub client_service: ClientService,
+    pub identity_provider_service: IdentityProviderService,
+    pub saml_service: SamlService,
+    pub service_provider_service: ServiceProviderService,
+    pub network_config: NetworkConfig,
+    /// shared by every rate limited route, the layers are built from it
+    pub rate_limit_store: RateLimitBackend,
 }
 
 impl FromRef<ServicesState> for UserService {
@@ -28,3 +44,51 @@ impl FromRef<ServicesState> for AuthenticationService {
         input.auth_service.clone()
     }
 }
+
+impl FromRef<ServicesState> for MailerService {
+    fn from_ref(input: &ServicesState) -> MailerService {
+        input.mailer_service.clone()
+    }
+}
+
+impl FromRef<ServicesState> for TokenService {
+    fn from_ref(input: &ServicesState) -> TokenService {
+        input.token_service.clone()
+    }
+}
+
+impl FromRef<ServicesState> for OAuthService {
+    fn from_ref(input: &ServicesState) -> OAuthService {
+        input.oauth_service.clone()
+    }
+}
+
+impl FromRef<ServicesState> for ClientService {
+    fn from_ref(input: &ServicesState) -> ClientService {
+        input.client_service.clone()
+    }
+}
+
+impl FromRef<ServicesState> for IdentityProviderService {
+    fn from_ref(input: &ServicesState) -> IdentityProviderService {
+        input.identity_provider_service.clone()
+    }
+}
+
+impl FromRef<ServicesState> for SamlService {
+    fn from_ref(input: &ServicesState) -> SamlService {
+        input.saml_service.clone()
+    }
+}
+
+impl FromRef<ServicesState> for ServiceProviderService {
+    fn from_ref(input: &ServicesState) -> ServiceProviderService {
+        input.service_provider_service.clone()
+    }
+}
+
+impl FromRef<ServicesState> for NetworkConfig {
+    fn from_ref(input: &ServicesState) -> NetworkConfig {
+        input.network_config.clone()
+    }
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use std::time::Duration;
+
+use axum_test::TestServer;
+use serde_json::Value;
+use uralium_lib::shared::crypto::sha256_base64url;
+use url::Url;
+
+const CODE_VERIFIER: &str = "a-code-verifier-long-enough-to-satisfy-the-pkce-rules-0123456789";
+
+/// runs the authorization code flow with the given access token and returns the token response
+async fn authorize(server: &TestServer, token: &str, client_id: &str) -> Value {
+    let response = server
+        .post("/oauth/authorize")
+        .authorization_bearer(token)
+        .json(&serde_json::json!({
+            "response_type": "code",
+            "client_id": client_id,
+            "redirect_uri": common::REDIRECT_URI,
+            "scope": "openid",
+            "nonce": "n-0S6_WzA2Mj",
+            "code_challenge": sha256_base64url(CODE_VERIFIER),
+            "code_challenge_method": "S256",
+        }))
+        .await;
+    response.assert_status_ok();
+    let redirect_to = Url::parse(
+        response.json::<Value>()["data"]["redirectTo"]
+            .as_str()
+            .unwrap(),
+    )
+    .unwrap();
+    let code = redirect_to
+        .query_pairs()
+        .find(|(key, _)| key == "code")
+        .unwrap()
+        .1
+        .to_string();
+
+    let response = server
+        .post("/oauth/token")
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", common::REDIRECT_URI),
+            ("client_id", client_id),
+            ("code_verifier", CODE_VERIFIER),
+        ])
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()
+}
+
+#[tokio::test]
+async fn test_the_id_token_carries_the_time_of_the_sign_in() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let client_id = common::create_client(&pool, None, &["authorization_code"], &["openid"]).await;
+    let server = common::server(pool);
+    let (token, refresh_token) = common::login(&server, &email).await;
+    let signed_in_at = common::jwt_claims(&token)["auth_time"].as_i64().unwrap();
+
+    // a refresh issues a newer access token, the sign-in it belongs to is no more recent
+    tokio::time::sleep(Duration::from_millis(1100)).await;
+    let response = server
+        .post("/refresh-token")
+        .json(&serde_json::json!({ "refreshToken": refresh_token }))
+        .await;
+    response.assert_status_ok();
+    let refreshed_token = response.json::<Value>()["data"]["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let refreshed_claims = common::jwt_claims(&refreshed_token);
+    assert_eq!(refreshed_claims["auth_time"].as_i64(), Some(signed_in_at));
+    assert!(refreshed_claims["iat"].as_i64().unwrap() > signed_in_at);
+
+    let tokens = authorize(&server, &refreshed_token, &client_id).await;
+    let id_token = common::jwt_claims(tokens["id_token"].as_str().unwrap());
+    assert_eq!(id_token["auth_time"].as_i64(), Some(signed_in_at));
+}
+
+#[tokio::test]
+async fn test_only_first_party_tokens_reach_the_first_party_api() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let client_id = common::create_client(&pool, None, &["authorization_code"], &["openid"]).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+    assert_eq!(common::jwt_claims(&token)["first_party"], true);
+
+    let tokens = authorize(&server, &token, &client_id).await;
+    let access_token = tokens["access_token"].as_str().unwrap();
+    assert!(
+        common::jwt_claims(access_token)
+            .get("first_party")
+            .is_none()
+    );
+
+    server
+        .get("/users/profile")
+        .authorization_bearer(access_token)
+        .await
+        .assert_status(axum::http::StatusCode::FORBIDDEN);
+    server
+        .get("/oauth/userinfo")
+        .authorization_bearer(access_token)
+        .await
+        .assert_status_ok();
+}
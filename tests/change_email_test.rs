@@ -0,0 +1,146 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use sqlx::PgPool;
+use uralium_lib::{
+    adapters::dto::otp::OtpKind,
+    repositories::user_repository::{UserRepository, UserRepositoryTrait},
+    services::otp_service::{OtpService, OtpServiceTrait},
+};
+use uuid::Uuid;
+
+async fn request_change(server: &TestServer, token: &str, email: &str) {
+    server
+        .post("/change-email")
+        .authorization_bearer(token)
+        .json(&serde_json::json!({ "email": email, "password": common::PASSWORD }))
+        .await
+        .assert_status_ok();
+}
+
+/// the code the change email carries, issuing again replaces the one that was sent
+async fn change_code(pool: &PgPool, user: &Uuid) -> String {
+    OtpService::init(pool)
+        .issue(user, OtpKind::EmailChange)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_the_address_only_changes_once_the_code_is_confirmed() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let new_email = common::unique_email();
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+
+    request_change(&server, &token, &new_email).await;
+
+    // nothing has moved yet, the old address still signs in and the session is untouched
+    let pending = UserRepository::init(&pool)
+        .find_by_identifier(&user)
+        .await
+        .unwrap();
+    assert_eq!(pending.email, email);
+    assert_eq!(pending.pending_email.as_deref(), Some(new_email.as_str()));
+    common::login(&server, &email).await;
+    server
+        .get("/users/profile")
+        .authorization_bearer(&token)
+        .await
+        .assert_status_ok();
+
+    let code = change_code(&pool, &user).await;
+    server
+        .post("/change-email/confirm")
+        .authorization_bearer(&token)
+        .json(&serde_json::json!({ "otp": code }))
+        .await
+        .assert_status_ok();
+
+    let changed = UserRepository::init(&pool)
+        .find_by_identifier(&user)
+        .await
+        .unwrap();
+    assert_eq!(changed.email, new_email);
+    assert!(changed.pending_email.is_none());
+    assert!(changed.email_changed_at.is_some());
+    common::login(&server, &new_email).await;
+    server
+        .post("/login")
+        .json(&serde_json::json!({ "email": email, "password": common::PASSWORD }))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_a_wrong_code_leaves_the_address_alone() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+
+    request_change(&server, &token, &common::unique_email()).await;
+    let code = change_code(&pool, &user).await;
+    let wrong_code = if code == "000000" { "111111" } else { "000000" };
+
+    server
+        .post("/change-email/confirm")
+        .authorization_bearer(&token)
+        .json(&serde_json::json!({ "otp": wrong_code }))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    let user = UserRepository::init(&pool)
+        .find_by_identifier(&user)
+        .await
+        .unwrap();
+    assert_eq!(user.email, email);
+}
+
+#[tokio::test]
+async fn test_there_is_nothing_to_confirm_without_a_change() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+
+    let response = server
+        .post("/change-email/confirm")
+        .authorization_bearer(&token)
+        .json(&serde_json::json!({ "otp": "123456" }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<serde_json::Value>()["message"],
+        "there is no email change waiting to be confirmed"
+    );
+}
+
+#[tokio::test]
+async fn test_an_address_taken_in_the_meantime_is_not_switched_to() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let new_email = common::unique_email();
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+
+    request_change(&server, &token, &new_email).await;
+    let code = change_code(&pool, &user).await;
+    sqlx::query(
+        r#"INSERT INTO users (identifier, first_name, last_name, email, password)
+        VALUES ($1, 'Grace', 'Hopper', $2, 'unused')"#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(&new_email)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    server
+        .post("/change-email/confirm")
+        .authorization_bearer(&token)
+        .json(&serde_json::json!({ "otp": code }))
+        .await
+        .assert_status(StatusCode::CONFLICT);
+}
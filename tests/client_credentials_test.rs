@@ -0,0 +1,139 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use serde_json::Value;
+
+const CLIENT_SECRET: &str = "reporting-client-secret";
+
+async fn request_token(server: &TestServer, client_id: &str, scope: Option<&str>) -> TestResponse {
+    let mut form = vec![
+        ("grant_type", "client_credentials"),
+        ("client_id", client_id),
+        ("client_secret", CLIENT_SECRET),
+    ];
+    if let Some(scope) = scope {
+        form.push(("scope", scope));
+    }
+
+    server.post("/oauth/token").form(&form).await
+}
+
+fn assert_oauth_error(response: &TestResponse, status: StatusCode, error: &str) {
+    response.assert_status(status);
+    assert_eq!(response.json::<Value>()["error"], error);
+}
+
+#[tokio::test]
+async fn test_the_token_is_limited_to_the_scopes_of_the_client() {
+    let pool = common::database().await;
+    let client_id = common::create_client(
+        &pool,
+        Some(CLIENT_SECRET),
+        &["client_credentials"],
+        &["reports:read", "reports:write"],
+    )
+    .await;
+    let server = common::server(pool);
+
+    // without a scope the client gets everything it is allowed
+    let response = request_token(&server, &client_id, None).await;
+    response.assert_status_ok();
+    let tokens = response.json::<Value>();
+    assert_eq!(tokens["scope"], "reports:read reports:write");
+    let claims = common::jwt_claims(tokens["access_token"].as_str().unwrap());
+    assert_eq!(claims["sub"], client_id.as_str());
+    assert_eq!(claims["sub_type"], "client");
+    assert_eq!(claims["scope"], "reports:read reports:write");
+
+    let response = request_token(&server, &client_id, Some("reports:read")).await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["scope"], "reports:read");
+
+    let response = request_token(&server, &client_id, Some("reports:read users:manage")).await;
+    assert_oauth_error(&response, StatusCode::BAD_REQUEST, "invalid_scope");
+}
+
+#[tokio::test]
+async fn test_the_client_has_to_authenticate_and_be_allowed_the_grant() {
+    let pool = common::database().await;
+    let client_id = common::create_client(
+        &pool,
+        Some(CLIENT_SECRET),
+        &["client_credentials"],
+        &["reports:read"],
+    )
+    .await;
+    let code_flow_client_id = common::create_client(
+        &pool,
+        Some(CLIENT_SECRET),
+        &["authorization_code"],
+        &["reports:read"],
+    )
+    .await;
+    let public_client_id =
+        common::create_client(&pool, None, &["client_credentials"], &["reports:read"]).await;
+    let server = common::server(pool);
+
+    let response = server
+        .post("/oauth/token")
+        .form(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id.as_str()),
+            ("client_secret", "not-the-secret"),
+        ])
+        .await;
+    assert_oauth_error(&response, StatusCode::UNAUTHORIZED, "invalid_client");
+
+    let response = request_token(&server, &code_flow_client_id, None).await;
+    assert_oauth_error(&response, StatusCode::BAD_REQUEST, "unauthorized_client");
+
+    // a public client has no secret to prove who is asking
+    let response = request_token(&server, &public_client_id, None).await;
+    assert_oauth_error(&response, StatusCode::BAD_REQUEST, "unauthorized_client");
+
+    // the secret is accepted in a basic authorization header as well
+    let response = server
+        .post("/oauth/token")
+        .add_header(
+            "authorization",
+            format!(
+                "Basic {}",
+                STANDARD.encode(format!("{client_id}:{CLIENT_SECRET}"))
+            ),
+        )
+        .form(&[("grant_type", "client_credentials")])
+        .await;
+    response.assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_client_tokens_do_not_reach_the_user_api() {
+    let pool = common::database().await;
+    let client_id = common::create_client(
+        &pool,
+        Some(CLIENT_SECRET),
+        &["client_credentials"],
+        &["reports:read"],
+    )
+    .await;
+    let server = common::server(pool);
+    let response = request_token(&server, &client_id, None).await;
+    response.assert_status_ok();
+    let access_token = response.json::<Value>()["access_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    server
+        .get("/users/profile")
+        .authorization_bearer(&access_token)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .get("/oauth/userinfo")
+        .authorization_bearer(&access_token)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{Value, json};
+use uralium_lib::adapters::dto::permission::Permission;
+
+#[tokio::test]
+async fn test_client_registry_requires_authentication() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server.get("/clients").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<serde_json::Value>()["message"],
+        "Missing authorization headers"
+    );
+}
+
+/// an administrator allowed to manage clients, signed in to the returned server
+async fn administrator() -> (axum_test::TestServer, String) {
+    let pool = common::database().await;
+    let (admin, email) = common::create_user(&pool).await;
+    common::grant_permissions(&pool, &admin, &[Permission::ManageClients]).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+
+    (server, token)
+}
+
+fn client_request(confidential: bool) -> Value {
+    json!({
+        "name": "Reporting",
+        "redirectUris": [common::REDIRECT_URI],
+        "grantTypes": ["authorization_code", "refresh_token"],
+        "scopes": ["openid", "email"],
+        "confidential": confidential,
+    })
+}
+
+#[tokio::test]
+async fn test_a_client_can_be_created_changed_and_deleted() {
+    let (server, token) = administrator().await;
+
+    let response = server
+        .post("/clients")
+        .authorization_bearer(&token)
+        .json(&client_request(true))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let created = &response.json::<Value>()["data"];
+    let client_id = created["clientId"].as_str().unwrap().to_string();
+    let client_secret = created["clientSecret"].as_str().unwrap().to_string();
+    assert_eq!(created["confidential"], true);
+    assert_eq!(created["firstParty"], false);
+
+    let response = server
+        .get(&format!("/clients/{client_id}"))
+        .authorization_bearer(&token)
+        .await;
+    response.assert_status_ok();
+    let retrieved = &response.json::<Value>()["data"];
+    assert_eq!(retrieved["name"], "Reporting");
+    // the secret is only shown when it is issued
+    assert!(retrieved.get("clientSecret").is_none());
+
+    let listed = server.get("/clients").authorization_bearer(&token).await;
+    listed.assert_status_ok();
+    assert!(
+        listed.json::<Value>()["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|client| client["clientId"] == client_id.as_str())
+    );
+
+    let response = server
+        .put(&format!("/clients/{client_id}"))
+        .authorization_bearer(&token)
+        .json(&json!({
+            "name": "Reporting dashboard",
+            "redirectUris": [common::REDIRECT_URI],
+            "grantTypes": ["authorization_code"],
+            "scopes": ["openid"],
+            "firstParty": false,
+            "accessTokenTtlSeconds": 300,
+            "refreshTokenTtlSeconds": 3600,
+        }))
+        .await;
+    response.assert_status_ok();
+    let updated = &response.json::<Value>()["data"];
+    assert_eq!(updated["name"], "Reporting dashboard");
+    assert_eq!(updated["grantTypes"], json!(["authorization_code"]));
+    assert_eq!(updated["accessTokenTtlSeconds"], 300);
+
+    let response = server
+        .post(&format!("/clients/{client_id}/secret"))
+        .authorization_bearer(&token)
+        .await;
+    response.assert_status_ok();
+    assert_ne!(
+        response.json::<Value>()["data"]["clientSecret"],
+        client_secret.as_str()
+    );
+
+    server
+        .delete(&format!("/clients/{client_id}"))
+        .authorization_bearer(&token)
+        .await
+        .assert_status_success();
+    server
+        .get(&format!("/clients/{client_id}"))
+        .authorization_bearer(&token)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_public_clients_have_no_secret() {
+    let (server, token) = administrator().await;
+
+    let response = server
+        .post("/clients")
+        .authorization_bearer(&token)
+        .json(&client_request(false))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let created = &response.json::<Value>()["data"];
+    assert!(created["clientSecret"].is_null());
+
+    server
+        .post(&format!(
+            "/clients/{}/secret",
+            created["clientId"].as_str().unwrap()
+        ))
+        .authorization_bearer(&token)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_inconsistent_clients_are_refused() {
+    let (server, token) = administrator().await;
+
+    let mut public_service = client_request(false);
+    public_service["grantTypes"] = json!(["client_credentials"]);
+    let mut nowhere_to_redirect = client_request(true);
+    nowhere_to_redirect["redirectUris"] = json!([]);
+    let mut unknown_grant = client_request(true);
+    unknown_grant["grantTypes"] = json!(["password"]);
+
+    for request in [public_service, nowhere_to_redirect, unknown_grant] {
+        server
+            .post("/clients")
+            .authorization_bearer(&token)
+            .json(&request)
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+}
+
+#[tokio::test]
+async fn test_managing_clients_takes_the_permission() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+
+    server
+        .get("/clients")
+        .authorization_bearer(&token)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+    server
+        .post("/clients")
+        .authorization_bearer(&token)
+        .json(&client_request(true))
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+}
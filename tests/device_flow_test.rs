@@ -0,0 +1,207 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use serde_json::Value;
+use sqlx::PgPool;
+use uralium_lib::{
+    adapters::dto::oauth::GRANT_DEVICE_CODE,
+    entities::device_code::DeviceCodeEntity,
+    repositories::device_code_repository::{DeviceCodeRepository, DeviceCodeRepositoryTrait},
+    shared::crypto::sha256_hex,
+};
+use uuid::Uuid;
+
+/// a device request from a fresh public client, returns the client id, device code and user code
+async fn start(pool: &PgPool, server: &TestServer) -> (String, String, String) {
+    let client_id = common::create_client(pool, None, &[GRANT_DEVICE_CODE], &["openid"]).await;
+    let response = server
+        .post("/device/code")
+        .form(&[("client_id", client_id.as_str()), ("scope", "openid")])
+        .await;
+    response.assert_status_ok();
+    let authorization = response.json::<Value>();
+
+    (
+        client_id,
+        authorization["device_code"].as_str().unwrap().to_string(),
+        authorization["user_code"].as_str().unwrap().to_string(),
+    )
+}
+
+async fn poll(server: &TestServer, client_id: &str, device_code: &str) -> TestResponse {
+    server
+        .post("/oauth/token")
+        .form(&[
+            ("grant_type", GRANT_DEVICE_CODE),
+            ("client_id", client_id),
+            ("device_code", device_code),
+        ])
+        .await
+}
+
+fn assert_oauth_error(response: &TestResponse, error: &str) {
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["error"], error);
+}
+
+async fn decide(server: &TestServer, token: &str, user_code: &str, approve: bool) {
+    server
+        .post("/device/verify")
+        .authorization_bearer(token)
+        .json(&serde_json::json!({ "userCode": user_code, "approve": approve }))
+        .await
+        .assert_status_ok();
+}
+
+/// runs an update on the device request, for what would otherwise take real time to happen
+async fn update(pool: &PgPool, device_code: &str, assignment: &str) {
+    sqlx::query(&format!(
+        "UPDATE device_codes SET {assignment} WHERE device_code_hash = $1"
+    ))
+    .bind(sha256_hex(device_code))
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_an_approved_device_gets_tokens_once() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+    let (client_id, device_code, user_code) = start(&pool, &server).await;
+
+    assert_oauth_error(
+        &poll(&server, &client_id, &device_code).await,
+        "authorization_pending",
+    );
+
+    decide(&server, &token, &user_code, true).await;
+    update(&pool, &device_code, "last_polled_at = NULL").await;
+    let response = poll(&server, &client_id, &device_code).await;
+    response.assert_status_ok();
+    let tokens = response.json::<Value>();
+    assert!(tokens["access_token"].is_string());
+    // the id token is as old as the sign-in that approved the device
+    assert_eq!(
+        common::jwt_claims(tokens["id_token"].as_str().unwrap())["auth_time"],
+        common::jwt_claims(&token)["auth_time"]
+    );
+
+    update(&pool, &device_code, "last_polled_at = NULL").await;
+    assert_oauth_error(
+        &poll(&server, &client_id, &device_code).await,
+        "invalid_grant",
+    );
+}
+
+#[tokio::test]
+async fn test_polling_too_fast_lengthens_the_interval() {
+    let pool = common::database().await;
+    let server = common::server(pool.clone());
+    let (client_id, device_code, _) = start(&pool, &server).await;
+    let interval = || {
+        sqlx::query_scalar::<_, i32>(
+            "SELECT interval_seconds FROM device_codes WHERE device_code_hash = $1",
+        )
+        .bind(sha256_hex(&device_code))
+        .fetch_one(&pool)
+    };
+    let initial_interval = interval().await.unwrap();
+
+    assert_oauth_error(
+        &poll(&server, &client_id, &device_code).await,
+        "authorization_pending",
+    );
+    assert_oauth_error(&poll(&server, &client_id, &device_code).await, "slow_down");
+    assert_oauth_error(&poll(&server, &client_id, &device_code).await, "slow_down");
+
+    // five seconds more for each poll that came too soon
+    assert_eq!(interval().await.unwrap(), initial_interval + 2 * 5);
+}
+
+#[tokio::test]
+async fn test_a_denied_device_gets_access_denied() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+    let (client_id, device_code, user_code) = start(&pool, &server).await;
+
+    decide(&server, &token, &user_code, false).await;
+
+    assert_oauth_error(
+        &poll(&server, &client_id, &device_code).await,
+        "access_denied",
+    );
+    // a decided request cannot be approved after all
+    server
+        .post("/device/verify")
+        .authorization_bearer(&token)
+        .json(&serde_json::json!({ "userCode": user_code, "approve": true }))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_an_expired_device_code_is_refused() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+    let (client_id, device_code, user_code) = start(&pool, &server).await;
+
+    update(
+        &pool,
+        &device_code,
+        "expires_at = NOW() - INTERVAL '1 second'",
+    )
+    .await;
+
+    assert_oauth_error(
+        &poll(&server, &client_id, &device_code).await,
+        "expired_token",
+    );
+    server
+        .post("/device/verify")
+        .authorization_bearer(&token)
+        .json(&serde_json::json!({ "userCode": user_code, "approve": true }))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_a_taken_user_code_is_not_issued_twice() {
+    let pool = common::database().await;
+    let client_id = common::create_client(&pool, None, &[GRANT_DEVICE_CODE], &["openid"]).await;
+    let repository = DeviceCodeRepository::init(&pool);
+    let user_code = Uuid::new_v4().simple().to_string()[..8].to_uppercase();
+    let device_code = |user_code: &str| {
+        let now = chrono::Utc::now();
+        DeviceCodeEntity {
+            identifier: Uuid::new_v4(),
+            device_code_hash: sha256_hex(&Uuid::new_v4().to_string()),
+            user_code: user_code.to_string(),
+            client_id: client_id.to_string(),
+            scope: "openid".into(),
+            user_identifier: None,
+            interval_seconds: 5,
+            last_polled_at: None,
+            approved_at: None,
+            auth_time: None,
+            denied_at: None,
+            consumed_at: None,
+            expires_at: now - chrono::Duration::seconds(1),
+            created_at: now,
+        }
+    };
+
+    assert!(repository.create(&device_code(&user_code)).await.unwrap());
+    assert!(!repository.create(&device_code(&user_code)).await.unwrap());
+
+    // expired requests are cleared out, which frees their user codes again
+    assert!(repository.delete_expired().await.unwrap() >= 1);
+    assert!(repository.create(&device_code(&user_code)).await.unwrap());
+}
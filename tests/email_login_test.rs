@@ -0,0 +1,188 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::{StatusCode, header};
+use axum_test::{TestResponse, TestServer};
+use serde_json::{Value, json};
+use uralium_lib::{
+    adapters::{dto::otp::OtpKind, mailer::templates::EmailTemplate},
+    config::{email_login::EmailLoginConfig, lockout::LockoutConfig},
+    services::otp_service::{OtpService, OtpServiceTrait},
+};
+
+#[test]
+fn test_login_link_email_carries_the_token() {
+    let config = EmailLoginConfig {
+        url: "https://app.uranium.test/login/email?source=mail".into(),
+        expiry: std::time::Duration::from_secs(600),
+    };
+    let login_link = config.login_link("abc123");
+    assert_eq!(
+        login_link,
+        "https://app.uranium.test/login/email?source=mail&token=abc123"
+    );
+
+    let rendered = EmailTemplate::LoginLink {
+        first_name: "Ada".to_string(),
+        login_link,
+        expires_in_minutes: 10,
+    }
+    .render();
+    assert!(rendered.html_body.contains("source=mail&amp;token=abc123"));
+    assert!(rendered.text_body.contains("source=mail&token=abc123"));
+    assert!(rendered.text_body.contains("10 minutes"));
+}
+
+#[tokio::test]
+async fn test_email_login_needs_a_token_or_a_code() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server
+        .post("/login/email/verify")
+        .json(&serde_json::json!({ "email": "ada@example.com" }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<serde_json::Value>()["message"],
+        "a sign-in token, or an email address and code, is required"
+    );
+}
+
+async fn verify(server: &TestServer, body: Value) -> TestResponse {
+    server.post("/login/email/verify").json(&body).await
+}
+
+#[tokio::test]
+async fn test_requesting_an_email_login_does_not_reveal_accounts() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+
+    for body in [
+        json!({ "email": email }),
+        json!({ "email": email, "method": "code" }),
+        json!({ "email": common::unique_email() }),
+    ] {
+        server
+            .post("/login/email")
+            .json(&body)
+            .await
+            .assert_status_ok();
+    }
+}
+
+#[tokio::test]
+async fn test_a_login_link_signs_in_once() {
+    let pool = common::database().await;
+    let (user, _) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let token = OtpService::init(&pool)
+        .issue_token(
+            &user,
+            OtpKind::EmailLogin,
+            EmailLoginConfig::from_env().expiry,
+        )
+        .await
+        .unwrap();
+
+    let response = verify(&server, json!({ "token": token })).await;
+    response.assert_status_ok();
+    let data = &response.json::<Value>()["data"];
+    assert!(data["refreshToken"].is_string());
+    let claims = common::jwt_claims(data["token"].as_str().unwrap());
+    assert_eq!(claims["sub"], user.to_string());
+
+    let response = verify(&server, json!({ "token": token })).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<Value>()["message"],
+        "the one-time code is invalid"
+    );
+}
+
+#[tokio::test]
+async fn test_a_login_code_signs_in_once() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let code = OtpService::init(&pool)
+        .issue_code(
+            &user,
+            OtpKind::EmailLogin,
+            EmailLoginConfig::from_env().expiry,
+        )
+        .await
+        .unwrap();
+    let body = json!({ "email": email, "code": code });
+
+    let response = verify(&server, body.clone()).await;
+    response.assert_status_ok();
+    let claims = common::jwt_claims(response.json::<Value>()["data"]["token"].as_str().unwrap());
+    assert_eq!(claims["sub"], user.to_string());
+
+    verify(&server, body)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_an_expired_link_or_code_is_refused() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let otp_service = OtpService::init(&pool);
+
+    let token = otp_service
+        .issue_token(&user, OtpKind::EmailLogin, Duration::ZERO)
+        .await
+        .unwrap();
+    let response = verify(&server, json!({ "token": token })).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<Value>()["message"],
+        "the one-time code has expired, request a new one"
+    );
+
+    let code = otp_service
+        .issue_code(&user, OtpKind::EmailLogin, Duration::ZERO)
+        .await
+        .unwrap();
+    let response = verify(&server, json!({ "email": email, "code": code })).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<Value>()["message"],
+        "the one-time code has expired, request a new one"
+    );
+}
+
+#[tokio::test]
+async fn test_wrong_codes_count_towards_the_lockout() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let otp_service = OtpService::init(&pool);
+    let expiry = EmailLoginConfig::from_env().expiry;
+    let code = otp_service
+        .issue_code(&user, OtpKind::EmailLogin, expiry)
+        .await
+        .unwrap();
+    let wrong_code = format!("{}{}", &code[..5], (code.as_bytes()[5] - b'0' + 1) % 10);
+
+    for _ in 0..LockoutConfig::from_env().backoff_after {
+        verify(&server, json!({ "email": email, "code": wrong_code }))
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+    // past the free attempts the right code has to wait out the back-off, and so does a link
+    let response = verify(&server, json!({ "email": email, "code": code })).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert!(response.maybe_header(header::RETRY_AFTER).is_some());
+    let token = otp_service
+        .issue_token(&user, OtpKind::EmailLogin, expiry)
+        .await
+        .unwrap();
+    verify(&server, json!({ "token": token }))
+        .await
+        .assert_status(StatusCode::TOO_MANY_REQUESTS);
+}
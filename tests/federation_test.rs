@@ -0,0 +1,316 @@
+mod common;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{
+    Form, Json, Router,
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use serde_json::{Value, json};
+use uralium_lib::{
+    adapters::dto::federation::{
+        ClaimMapping, FederatedProfile, UpstreamAuthorization, UpstreamProvider,
+    },
+    config::federation::FederationConfig,
+    errors::federation_error::FederationError,
+    repositories::user_repository::{UserRepository, UserRepositoryTrait},
+    services::federation_service::{FederationService, FederationServiceTrait},
+    shared::{key_store::KeyStore, oidc_client::OidcClient, secret_box::SecretBox},
+};
+use url::Url;
+
+const CLIENT_ID: &str = "uranium";
+const CLIENT_SECRET: &str = "mock-client-secret";
+const CODE: &str = "mock-authorization-code";
+const ACCESS_TOKEN: &str = "mock-access-token";
+
+/// a stand-in for an upstream provider, the id token it hands out is signed from `id_token_claims`
+#[derive(Clone)]
+struct MockIssuer {
+    issuer: String,
+    keys: Arc<KeyStore>,
+    id_token_claims: Arc<Mutex<Value>>,
+}
+
+async fn discovery(State(mock): State<MockIssuer>) -> Json<Value> {
+    Json(json!({
+        "issuer": mock.issuer,
+        "authorization_endpoint": format!("{}/authorize", mock.issuer),
+        "token_endpoint": format!("{}/token", mock.issuer),
+        "jwks_uri": format!("{}/jwks", mock.issuer),
+        "userinfo_endpoint": format!("{}/userinfo", mock.issuer),
+    }))
+}
+
+async fn jwks(State(mock): State<MockIssuer>) -> Json<Value> {
+    Json(serde_json::to_value(mock.keys.jwks()).unwrap())
+}
+
+async fn token(
+    State(mock): State<MockIssuer>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let expected_authorization = format!(
+        "Basic {}",
+        base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            format!("{CLIENT_ID}:{CLIENT_SECRET}")
+        )
+    );
+    if headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        != Some(expected_authorization.as_str())
+    {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid_client" })),
+        )
+            .into_response();
+    }
+    if form.get("code").map(String::as_str) != Some(CODE) || !form.contains_key("code_verifier") {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "invalid_grant",
+                "error_description": "the code has already been used",
+            })),
+        )
+            .into_response();
+    }
+
+    let claims = mock.id_token_claims.lock().unwrap().clone();
+    Json(json!({
+        "access_token": ACCESS_TOKEN,
+        "token_type": "Bearer",
+        "id_token": mock.keys.sign(&claims).unwrap(),
+    }))
+    .into_response()
+}
+
+async fn userinfo(headers: HeaderMap) -> Response {
+    if headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        != Some(format!("Bearer {ACCESS_TOKEN}").as_str())
+    {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    Json(json!({
+        "sub": "upstream-user-1",
+        "given_name": "Ada",
+        "family_name": "Lovelace",
+    }))
+    .into_response()
+}
+
+async fn start_mock_issuer() -> MockIssuer {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let mock = MockIssuer {
+        issuer: format!("http://{}", listener.local_addr().unwrap()),
+        keys: Arc::new(KeyStore::generate_ephemeral().unwrap()),
+        id_token_claims: Arc::default(),
+    };
+
+    let app = Router::new()
+        .route("/.well-known/openid-configuration", get(discovery))
+        .route("/jwks", get(jwks))
+        .route("/token", post(token))
+        .route("/userinfo", get(userinfo))
+        .with_state(mock.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    mock
+}
+
+fn provider(issuer: &str) -> UpstreamProvider {
+    UpstreamProvider {
+        issuer: issuer.to_string(),
+        client_id: CLIENT_ID.to_string(),
+        client_secret: CLIENT_SECRET.to_string(),
+        scopes: vec!["openid".into(), "email".into(), "profile".into()],
+        claim_mapping: ClaimMapping {
+            email: "email".into(),
+            first_name: "given_name".into(),
+            last_name: "family_name".into(),
+        },
+        trust_email: false,
+    }
+}
+
+fn authorization() -> UpstreamAuthorization {
+    UpstreamAuthorization {
+        redirect_uri: "http://localhost:5006/login/mock/callback".into(),
+        state: "mock-state".into(),
+        nonce: "mock-nonce".into(),
+        code_verifier: "mock-code-verifier".into(),
+    }
+}
+
+fn client() -> OidcClient {
+    OidcClient::new(&FederationConfig {
+        redirect_url: "http://localhost:5006/login/{provider}/callback".into(),
+        login_ttl: Duration::from_secs(600),
+        http_timeout: Duration::from_secs(5),
+        metadata_ttl: Duration::from_secs(3600),
+        email_change_link_delay: Duration::from_secs(7 * 24 * 3600),
+    })
+    .unwrap()
+}
+
+fn id_token_claims(issuer: &str) -> Value {
+    let now = chrono::Utc::now().timestamp();
+    json!({
+        "iss": issuer,
+        "aud": CLIENT_ID,
+        "sub": "upstream-user-1",
+        "iat": now,
+        "exp": now + 300,
+        "nonce": "mock-nonce",
+        "email": "ada@example.com",
+        "email_verified": true,
+    })
+}
+
+#[tokio::test]
+async fn test_authorization_url_carries_the_state_nonce_and_code_challenge() {
+    let mock = start_mock_issuer().await;
+    let url = client()
+        .authorization_url(&provider(&mock.issuer), &authorization())
+        .await
+        .unwrap();
+
+    let url = Url::parse(&url).unwrap();
+    assert_eq!(url.path(), "/authorize");
+    let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+    assert_eq!(query["response_type"], "code");
+    assert_eq!(query["client_id"], CLIENT_ID);
+    assert_eq!(
+        query["redirect_uri"],
+        "http://localhost:5006/login/mock/callback"
+    );
+    assert_eq!(query["scope"], "openid email profile");
+    assert_eq!(query["state"], "mock-state");
+    assert_eq!(query["nonce"], "mock-nonce");
+    assert_eq!(query["code_challenge_method"], "S256");
+    assert_ne!(query["code_challenge"], "mock-code-verifier");
+}
+
+#[tokio::test]
+async fn test_authenticate_reads_the_profile_and_fills_missing_claims_from_userinfo() {
+    let mock = start_mock_issuer().await;
+    *mock.id_token_claims.lock().unwrap() = id_token_claims(&mock.issuer);
+
+    let profile = client()
+        .authenticate(&provider(&mock.issuer), CODE, &authorization())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        profile,
+        FederatedProfile {
+            subject: "upstream-user-1".into(),
+            email: "ada@example.com".into(),
+            email_verified: true,
+            first_name: "Ada".into(),
+            last_name: "Lovelace".into(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_authenticate_rejects_id_tokens_for_another_login_or_client() {
+    let mock = start_mock_issuer().await;
+    let client = client();
+
+    let mut claims = id_token_claims(&mock.issuer);
+    claims["nonce"] = json!("another-nonce");
+    *mock.id_token_claims.lock().unwrap() = claims;
+    let result = client
+        .authenticate(&provider(&mock.issuer), CODE, &authorization())
+        .await;
+    assert!(matches!(result, Err(FederationError::InvalidIdToken)));
+
+    let mut claims = id_token_claims(&mock.issuer);
+    claims["aud"] = json!("another-client");
+    *mock.id_token_claims.lock().unwrap() = claims;
+    let result = client
+        .authenticate(&provider(&mock.issuer), CODE, &authorization())
+        .await;
+    assert!(matches!(result, Err(FederationError::InvalidIdToken)));
+
+    let mut claims = id_token_claims(&mock.issuer);
+    claims["iss"] = json!("https://issuer.example.com");
+    *mock.id_token_claims.lock().unwrap() = claims;
+    let result = client
+        .authenticate(&provider(&mock.issuer), CODE, &authorization())
+        .await;
+    assert!(matches!(result, Err(FederationError::InvalidIdToken)));
+}
+
+#[tokio::test]
+async fn test_authenticate_surfaces_a_rejected_code_as_denied() {
+    let mock = start_mock_issuer().await;
+    *mock.id_token_claims.lock().unwrap() = id_token_claims(&mock.issuer);
+
+    let result = client()
+        .authenticate(&provider(&mock.issuer), "reused-code", &authorization())
+        .await;
+    assert!(
+        matches!(result, Err(FederationError::Denied(description)) if description == "the code has already been used")
+    );
+}
+
+#[tokio::test]
+async fn test_identity_provider_registry_requires_authentication() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server.get("/identity-providers").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<Value>()["message"],
+        "Missing authorization headers"
+    );
+}
+
+#[tokio::test]
+async fn test_a_recently_changed_or_unverified_address_is_not_linked() {
+    let pool = common::database().await;
+    let (user, _) = common::create_user(&pool).await;
+    common::configure_environment();
+    let federation_service =
+        FederationService::init(&pool, &SecretBox::from_env().unwrap()).unwrap();
+    let users = UserRepository::init(&pool);
+    let set = |column: &'static str, value: &'static str| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query(&format!(
+                "UPDATE users SET {column} = {value} WHERE identifier = $1"
+            ))
+            .bind(user)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+    };
+
+    assert!(federation_service.can_link_by_email(&users.find_by_identifier(&user).await.unwrap()));
+
+    set("email_changed_at", "NOW()").await;
+    assert!(!federation_service.can_link_by_email(&users.find_by_identifier(&user).await.unwrap()));
+
+    set("email_changed_at", "NOW() - INTERVAL '8 days'").await;
+    assert!(federation_service.can_link_by_email(&users.find_by_identifier(&user).await.unwrap()));
+
+    set("email_verified_at", "NULL").await;
+    assert!(!federation_service.can_link_by_email(&users.find_by_identifier(&user).await.unwrap()));
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use std::{sync::mpsc, time::Duration};
+
+use axum::http::StatusCode;
+use uralium_lib::{
+    adapters::response::root::HealthCheckResponse,
+    config::password_hashing::PasswordHashingConfig,
+    errors::hashing_pool_error::HashingPoolError,
+    shared::hashing_pool::{HashingPool, HashingPoolMetrics},
+};
+
+fn pool(workers: usize, max_queue: usize) -> HashingPool {
+    HashingPool::new(&PasswordHashingConfig {
+        memory_kib: 1024,
+        iterations: 1,
+        parallelism: 1,
+        workers,
+        max_queue,
+    })
+}
+
+/// polls the metrics until the condition holds, giving up after a few seconds
+async fn wait_for(pool: &HashingPool, condition: impl Fn(&HashingPoolMetrics) -> bool) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !condition(&pool.metrics()) {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("the hashing pool never reached the expected state");
+}
+
+#[tokio::test]
+async fn test_jobs_run_off_the_runtime_and_are_counted() {
+    let pool = pool(2, 4);
+
+    let thread_name = pool
+        .run(|| std::thread::current().name().map(str::to_string))
+        .await
+        .unwrap();
+    assert!(thread_name.unwrap().starts_with("password-hasher-"));
+
+    let metrics = pool.metrics();
+    assert_eq!(metrics.workers, 2);
+    assert_eq!(metrics.completed, 1);
+    assert_eq!(metrics.queued, 0);
+    assert_eq!(metrics.rejected, 0);
+}
+
+#[tokio::test]
+async fn test_a_full_queue_refuses_work() {
+    let pool = pool(1, 1);
+    let (release, blocked) = mpsc::channel::<()>();
+
+    // one job holds the only worker, a second waits in the queue
+    let holding = tokio::spawn({
+        let pool = pool.clone();
+        async move { pool.run(move || blocked.recv().unwrap()).await }
+    });
+    wait_for(&pool, |metrics| metrics.running == 1).await;
+    let waiting = tokio::spawn({
+        let pool = pool.clone();
+        async move { pool.run(|| ()).await }
+    });
+    wait_for(&pool, |metrics| metrics.queued == 1).await;
+
+    assert!(matches!(
+        pool.run(|| ()).await,
+        Err(HashingPoolError::Saturated)
+    ));
+    assert_eq!(pool.metrics().rejected, 1);
+
+    release.send(()).unwrap();
+    holding.await.unwrap().unwrap();
+    waiting.await.unwrap().unwrap();
+    assert_eq!(pool.metrics().completed, 2);
+}
+
+#[tokio::test]
+async fn test_health_check_reports_the_hashing_pool() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server.get("/health").await;
+    response.assert_status(StatusCode::OK);
+    let health: HealthCheckResponse =
+        serde_json::from_value(response.json::<serde_json::Value>()["data"].clone()).unwrap();
+    assert!(health.password_hashing.workers >= 1);
+}
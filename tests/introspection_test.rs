@@ -0,0 +1,148 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use serde_json::Value;
+
+const CLIENT_SECRET: &str = "resource-server-secret";
+
+/// a confidential client for the resource server doing the introspecting
+async fn resource_server(pool: &sqlx::PgPool) -> String {
+    common::create_client(
+        pool,
+        Some(CLIENT_SECRET),
+        &["client_credentials"],
+        &["reports:read"],
+    )
+    .await
+}
+
+async fn introspect(server: &TestServer, client_id: &str, token: &str) -> Value {
+    let response = server
+        .post("/oauth/introspect")
+        .form(&[
+            ("client_id", client_id),
+            ("client_secret", CLIENT_SECRET),
+            ("token", token),
+        ])
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()
+}
+
+#[tokio::test]
+async fn test_user_tokens_are_active_until_revoked() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let client_id = resource_server(&pool).await;
+    let server = common::server(pool);
+    let (token, refresh_token) = common::login(&server, &email).await;
+
+    let introspection = introspect(&server, &client_id, &token).await;
+    assert_eq!(introspection["active"], true);
+    assert_eq!(introspection["sub"], user.to_string());
+    assert_eq!(introspection["username"], email.as_str());
+    assert_eq!(introspection["token_type"], "Bearer");
+
+    let introspection = introspect(&server, &client_id, &refresh_token).await;
+    assert_eq!(introspection["active"], true);
+    assert_eq!(introspection["sub"], user.to_string());
+    assert_eq!(introspection["token_type"], "refresh_token");
+
+    server
+        .post("/logout")
+        .authorization_bearer(&token)
+        .json(&serde_json::json!({ "refreshToken": refresh_token }))
+        .await
+        .assert_status_ok();
+
+    // an inactive token reveals nothing else about itself
+    for revoked in [&token, &refresh_token] {
+        assert_eq!(
+            introspect(&server, &client_id, revoked).await,
+            serde_json::json!({ "active": false })
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_client_tokens_are_active_until_revoked() {
+    let pool = common::database().await;
+    let client_id = resource_server(&pool).await;
+    let server = common::server(pool);
+    let response = server
+        .post("/oauth/token")
+        .form(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", client_id.as_str()),
+            ("client_secret", CLIENT_SECRET),
+        ])
+        .await;
+    response.assert_status_ok();
+    let access_token = response.json::<Value>()["access_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let introspection = introspect(&server, &client_id, &access_token).await;
+    assert_eq!(introspection["active"], true);
+    assert_eq!(introspection["client_id"], client_id.as_str());
+    assert_eq!(introspection["sub_type"], "client");
+    assert_eq!(introspection["scope"], "reports:read");
+
+    server
+        .post("/oauth/revoke")
+        .form(&[
+            ("client_id", client_id.as_str()),
+            ("client_secret", CLIENT_SECRET),
+            ("token", access_token.as_str()),
+        ])
+        .await
+        .assert_status_ok();
+
+    assert_eq!(
+        introspect(&server, &client_id, &access_token).await["active"],
+        false
+    );
+}
+
+#[tokio::test]
+async fn test_unknown_and_non_access_tokens_are_inactive() {
+    let pool = common::database().await;
+    let client_id = resource_server(&pool).await;
+    let server = common::server(pool);
+
+    for token in ["not-a-token", "header.payload.signature"] {
+        assert_eq!(
+            introspect(&server, &client_id, token).await,
+            serde_json::json!({ "active": false })
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_only_confidential_clients_may_introspect() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let client_id = resource_server(&pool).await;
+    let public_client_id =
+        common::create_client(&pool, None, &["authorization_code"], &["openid"]).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+
+    for (client_id, client_secret) in [
+        (public_client_id.as_str(), ""),
+        (client_id.as_str(), "not-the-secret"),
+    ] {
+        let response = server
+            .post("/oauth/introspect")
+            .form(&[
+                ("client_id", client_id),
+                ("client_secret", client_secret),
+                ("token", token.as_str()),
+            ])
+            .await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+        assert_eq!(response.json::<Value>()["error"], "invalid_client");
+    }
+}
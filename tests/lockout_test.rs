@@ -0,0 +1,131 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::{StatusCode, header};
+use axum_test::TestServer;
+use serde_json::json;
+use uralium_lib::{
+    adapters::dto::permission::Permission,
+    config::lockout::LockoutConfig,
+    services::lockout_service::{LockoutService, LockoutServiceTrait},
+};
+
+#[test]
+fn test_backoff_doubles_after_the_free_attempts() {
+    let config = LockoutConfig {
+        account_threshold: 10,
+        ip_threshold: 50,
+        lockout_duration: Duration::from_secs(900),
+        failure_window: Duration::from_secs(900),
+        backoff_after: 3,
+        backoff_base: Duration::from_secs(1),
+        backoff_max: Duration::from_secs(60),
+    };
+
+    let delays: Vec<u64> = (1..=10)
+        .map(|failures| config.backoff(failures).as_secs())
+        .collect();
+    assert_eq!(delays, vec![0, 0, 1, 2, 4, 8, 16, 32, 60, 60]);
+    assert_eq!(config.backoff(u32::MAX), Duration::from_secs(60));
+}
+
+#[tokio::test]
+async fn test_unlocking_an_account_requires_an_administrator() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server
+        .post(&format!("/users/{}/unlock", uuid::Uuid::new_v4()))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<serde_json::Value>()["message"],
+        "Missing authorization headers"
+    );
+}
+
+async fn login(server: &TestServer, email: &str, password: &str) -> axum_test::TestResponse {
+    server
+        .post("/login")
+        .json(&json!({ "email": email, "password": password }))
+        .await
+}
+
+#[tokio::test]
+async fn test_an_account_locks_after_the_threshold_until_unlocked() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let (admin, admin_email) = common::create_user(&pool).await;
+    common::grant_permissions(&pool, &admin, &[Permission::ManageUsers]).await;
+    let server = common::server(pool.clone());
+    let (admin_token, _) = common::login(&server, &admin_email).await;
+    let config = LockoutConfig::from_env();
+
+    for _ in 0..config.backoff_after {
+        login(&server, &email, "wrong-password")
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
+    // past the free attempts even the right password has to wait out the back-off
+    let response = login(&server, &email, common::PASSWORD).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert!(
+        response
+            .header(header::RETRY_AFTER)
+            .to_str()
+            .unwrap()
+            .parse::<u64>()
+            .unwrap()
+            > 0
+    );
+
+    // the remaining failures go to the service directly, the login route would back off
+    let lockout_service = LockoutService::init(&pool);
+    for _ in config.backoff_after + 1..config.account_threshold {
+        assert_eq!(
+            lockout_service.record_failure(&email, None).await.unwrap(),
+            None
+        );
+    }
+    assert_eq!(
+        lockout_service.record_failure(&email, None).await.unwrap(),
+        Some(config.lockout_duration)
+    );
+
+    let response = login(&server, &email, common::PASSWORD).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response
+        .header(header::RETRY_AFTER)
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after > config.lockout_duration.as_secs() - 60);
+    // the address is matched without regard to case
+    login(&server, &email.to_uppercase(), common::PASSWORD)
+        .await
+        .assert_status(StatusCode::TOO_MANY_REQUESTS);
+
+    server
+        .post(&format!("/users/{user}/unlock"))
+        .authorization_bearer(&admin_token)
+        .await
+        .assert_status_ok();
+    login(&server, &email, common::PASSWORD)
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_only_administrators_can_unlock_an_account() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+
+    server
+        .post(&format!("/users/{user}/unlock"))
+        .authorization_bearer(&token)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+}
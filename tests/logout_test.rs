@@ -0,0 +1,85 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+
+async fn profile_status(server: &TestServer, token: &str) -> StatusCode {
+    server
+        .get("/users/profile")
+        .authorization_bearer(token)
+        .await
+        .status_code()
+}
+
+#[tokio::test]
+async fn test_logout_revokes_the_access_and_refresh_tokens() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, refresh_token) = common::login(&server, &email).await;
+    assert_eq!(profile_status(&server, &token).await, StatusCode::OK);
+
+    server
+        .post("/logout")
+        .authorization_bearer(&token)
+        .json(&serde_json::json!({ "refreshToken": refresh_token }))
+        .await
+        .assert_status_ok();
+
+    assert_eq!(
+        profile_status(&server, &token).await,
+        StatusCode::UNAUTHORIZED
+    );
+    server
+        .post("/refresh-token")
+        .json(&serde_json::json!({ "refreshToken": refresh_token }))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_a_revoked_token_is_refused_while_others_still_work() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+    let (other_token, other_refresh_token) = common::login(&server, &email).await;
+
+    for revoked in [&other_token, &other_refresh_token] {
+        server
+            .post("/revoke-token")
+            .authorization_bearer(&token)
+            .json(&serde_json::json!({ "token": revoked }))
+            .await
+            .assert_status_ok();
+    }
+
+    assert_eq!(
+        profile_status(&server, &other_token).await,
+        StatusCode::UNAUTHORIZED
+    );
+    server
+        .post("/refresh-token")
+        .json(&serde_json::json!({ "refreshToken": other_refresh_token }))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(profile_status(&server, &token).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_tokens_of_another_user_cannot_be_revoked() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let (_, other_email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+    let (other_token, _) = common::login(&server, &other_email).await;
+
+    server
+        .post("/revoke-token")
+        .authorization_bearer(&token)
+        .json(&serde_json::json!({ "token": other_token }))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(profile_status(&server, &other_token).await, StatusCode::OK);
+}
@@ -0,0 +1,67 @@
+use uralium_lib::{
+    adapters::mailer::{Email, Mailer, file::FileMailer, templates::EmailTemplate},
+    config::mailer::{MailTransportConfig, MailerConfig},
+    errors::app_error::AppError,
+};
+
+#[tokio::test]
+async fn test_file_mailer_writes_rendered_email() {
+    let output_dir = std::env::temp_dir().join(format!("uranium-mails-{}", uuid::Uuid::new_v4()));
+    let mailer = FileMailer::new(&output_dir);
+
+    let rendered = EmailTemplate::AccountVerification {
+        first_name: "<Ada>".to_string(),
+        otp: "123456".to_string(),
+        expires_in_minutes: 10,
+    }
+    .render();
+    assert!(rendered.html_body.contains("&lt;Ada&gt;"));
+    assert!(rendered.text_body.contains("123456"));
+
+    let email = Email {
+        from: "no-reply@uranium.local".to_string(),
+        to: "ada@example.com".to_string(),
+        subject: rendered.subject,
+        text_body: rendered.text_body,
+        html_body: rendered.html_body,
+    };
+    mailer.send(&email).await.unwrap();
+
+    let mut entries = std::fs::read_dir(&output_dir).unwrap();
+    let written = std::fs::read_to_string(entries.next().unwrap().unwrap().path()).unwrap();
+    let written: serde_json::Value = serde_json::from_str(&written).unwrap();
+    assert_eq!(written["to"], "ada@example.com");
+    assert!(written["text_body"].as_str().unwrap().contains("123456"));
+
+    std::fs::remove_dir_all(output_dir).unwrap();
+}
+
+#[test]
+fn test_the_mail_transport_has_to_be_named() {
+    // SAFETY: the only test in this file that touches the environment
+    let set = |value: Option<&str>| unsafe {
+        match value {
+            Some(value) => std::env::set_var("MAIL_TRANSPORT", value),
+            None => std::env::remove_var("MAIL_TRANSPORT"),
+        }
+    };
+
+    for value in [None, Some(""), Some("smpt")] {
+        set(value);
+        assert!(matches!(
+            MailerConfig::from_env(),
+            Err(AppError::StartupError(_))
+        ));
+    }
+
+    set(Some("SMTP"));
+    assert!(matches!(
+        MailerConfig::from_env().unwrap().transport,
+        MailTransportConfig::Smtp { .. }
+    ));
+    set(Some("log"));
+    assert!(matches!(
+        MailerConfig::from_env().unwrap().transport,
+        MailTransportConfig::Log
+    ));
+}
@@ -0,0 +1,249 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use data_encoding::BASE32_NOPAD;
+use serde_json::{Value, json};
+use uralium_lib::{
+    config::{lockout::LockoutConfig, mfa::MfaConfig},
+    shared::totp,
+};
+
+#[test]
+fn test_totp_codes_match_the_rfc_6238_test_vectors() {
+    let secret = b"12345678901234567890";
+
+    assert_eq!(totp::generate_code(secret, totp::time_step(59)), "287082");
+    assert_eq!(
+        totp::generate_code(secret, totp::time_step(1111111109)),
+        "081804"
+    );
+    assert_eq!(
+        totp::generate_code(secret, totp::time_step(1234567890)),
+        "005924"
+    );
+    assert_eq!(totp::verify_code(secret, "287082", 59 + 30), Some(1));
+    assert_eq!(totp::verify_code(secret, "287082", 59 + 60), None);
+}
+
+#[tokio::test]
+async fn test_mfa_login_requires_a_challenge_token() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server
+        .post("/login/mfa")
+        .json(&serde_json::json!({ "mfaToken": "not-a-token", "code": "123456" }))
+        .await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.json::<serde_json::Value>()["message"],
+        "Invalid token"
+    );
+}
+
+/// enrolls an authenticator and confirms it with the current code, returns the secret, the time
+/// step the confirmation used up and the recovery codes
+async fn enroll(server: &TestServer, token: &str) -> (Vec<u8>, i64, Vec<String>) {
+    let response = server
+        .post("/users/mfa/totp")
+        .authorization_bearer(token)
+        .await;
+    response.assert_status_ok();
+    let secret = BASE32_NOPAD
+        .decode(
+            response.json::<Value>()["data"]["secret"]
+                .as_str()
+                .unwrap()
+                .as_bytes(),
+        )
+        .unwrap();
+
+    let step = totp::time_step(chrono::Utc::now().timestamp());
+    let response = server
+        .post("/users/mfa/totp/confirm")
+        .authorization_bearer(token)
+        .json(&json!({ "code": totp::generate_code(&secret, step) }))
+        .await;
+    response.assert_status_ok();
+    let recovery_codes = response.json::<Value>()["data"]["recoveryCodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|code| code.as_str().unwrap().to_string())
+        .collect();
+
+    (secret, step, recovery_codes)
+}
+
+/// the challenge a correct password earns once an authenticator is enrolled
+async fn password_step(server: &TestServer, email: &str) -> String {
+    let response = server
+        .post("/login")
+        .json(&json!({ "email": email, "password": common::PASSWORD }))
+        .await;
+    response.assert_status_ok();
+    let data = &response.json::<Value>()["data"];
+    assert_eq!(data["methods"], json!(["totp"]));
+
+    data["mfaToken"].as_str().unwrap().to_string()
+}
+
+async fn second_factor(server: &TestServer, email: &str, factor: Value) -> TestResponse {
+    let mut body = factor;
+    body["mfaToken"] = password_step(server, email).await.into();
+
+    server.post("/login/mfa").json(&body).await
+}
+
+#[tokio::test]
+async fn test_an_enrolled_authenticator_is_asked_for_at_login() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+
+    let (secret, step, recovery_codes) = enroll(&server, &token).await;
+    assert_eq!(
+        recovery_codes.len(),
+        MfaConfig::from_env().recovery_code_count
+    );
+    // turning it on ends every session
+    server
+        .get("/users/mfa")
+        .authorization_bearer(&token)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let response = second_factor(
+        &server,
+        &email,
+        json!({ "code": totp::generate_code(&secret, step + 1) }),
+    )
+    .await;
+    response.assert_status_ok();
+    let data = response.json::<Value>()["data"].clone();
+    assert!(data["refreshToken"].is_string());
+    let response = server
+        .get("/users/mfa")
+        .authorization_bearer(data["token"].as_str().unwrap())
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["data"]["totpEnabled"], true);
+}
+
+#[tokio::test]
+async fn test_a_totp_code_is_accepted_once() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+    let (secret, step, _) = enroll(&server, &token).await;
+
+    // the code that confirmed the enrollment is spent already
+    let code = json!({ "code": totp::generate_code(&secret, step) });
+    second_factor(&server, &email, code)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let code = json!({ "code": totp::generate_code(&secret, step + 1) });
+    second_factor(&server, &email, code.clone())
+        .await
+        .assert_status_ok();
+    let response = second_factor(&server, &email, code).await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.json::<Value>()["message"],
+        "the authentication code is invalid"
+    );
+}
+
+#[tokio::test]
+async fn test_a_recovery_code_is_accepted_once() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+    let (_, _, recovery_codes) = enroll(&server, &token).await;
+
+    let recovery_code = json!({ "recoveryCode": recovery_codes[0] });
+    let response = second_factor(&server, &email, recovery_code.clone()).await;
+    response.assert_status_ok();
+    let token = response.json::<Value>()["data"]["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    second_factor(&server, &email, recovery_code)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    let response = server.get("/users/mfa").authorization_bearer(&token).await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.json::<Value>()["data"]["recoveryCodesRemaining"],
+        recovery_codes.len() - 1
+    );
+}
+
+#[tokio::test]
+async fn test_turning_the_authenticator_off_takes_the_password() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+    let (_, _, recovery_codes) = enroll(&server, &token).await;
+    let response = second_factor(
+        &server,
+        &email,
+        json!({ "recoveryCode": recovery_codes[0] }),
+    )
+    .await;
+    response.assert_status_ok();
+    let token = response.json::<Value>()["data"]["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let response = server
+        .post("/users/mfa/totp/disable")
+        .authorization_bearer(&token)
+        .json(&json!({ "password": "not-the-password", "recoveryCode": recovery_codes[1] }))
+        .await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .post("/users/mfa/totp/disable")
+        .authorization_bearer(&token)
+        .json(&json!({ "password": common::PASSWORD, "recoveryCode": recovery_codes[1] }))
+        .await
+        .assert_status_ok();
+
+    // the password alone signs in again
+    let response = server
+        .post("/login")
+        .json(&json!({ "email": email, "password": common::PASSWORD }))
+        .await;
+    response.assert_status_ok();
+    assert!(response.json::<Value>()["data"]["token"].is_string());
+}
+
+#[tokio::test]
+async fn test_wrong_codes_count_towards_the_lockout() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+    let (secret, step, _) = enroll(&server, &token).await;
+    let config = LockoutConfig::from_env();
+
+    // the password is right every time, it must not wipe out the failed codes before it
+    for _ in 0..config.backoff_after {
+        let code = json!({ "code": totp::generate_code(&secret, step - 10) });
+        second_factor(&server, &email, code)
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
+    server
+        .post("/login")
+        .json(&json!({ "email": email, "password": common::PASSWORD }))
+        .await
+        .assert_status(StatusCode::TOO_MANY_REQUESTS);
+}
@@ -0,0 +1,75 @@
+mod common;
+
+use axum::http::StatusCode;
+
+#[tokio::test]
+async fn test_token_endpoint_rejects_unknown_grant_type() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server
+        .post("/oauth/token")
+        .form(&[("grant_type", "password")])
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<serde_json::Value>()["error"],
+        "unsupported_grant_type"
+    );
+}
+
+#[tokio::test]
+async fn test_authorize_requires_client_id() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server.get("/oauth/authorize?response_type=code").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<serde_json::Value>()["error"],
+        "invalid_request"
+    );
+}
+
+#[tokio::test]
+async fn test_client_credentials_grant_requires_client_authentication() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server
+        .post("/oauth/token")
+        .form(&[("grant_type", "client_credentials")])
+        .await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.json::<serde_json::Value>()["error"],
+        "invalid_client"
+    );
+}
+
+#[tokio::test]
+async fn test_introspection_requires_client_authentication() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server
+        .post("/oauth/introspect")
+        .form(&[("token", "opaque")])
+        .await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.json::<serde_json::Value>()["error"],
+        "invalid_client"
+    );
+}
+
+#[tokio::test]
+async fn test_device_authorization_requires_client() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server
+        .post("/device/code")
+        .form(&[("scope", "openid")])
+        .await;
+    response.assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        response.json::<serde_json::Value>()["error"],
+        "invalid_client"
+    );
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use std::time::Duration;
+
+use uralium_lib::{
+    adapters::dto::otp::OtpKind,
+    errors::otp_service_error::OtpServiceError,
+    services::otp_service::{OtpService, OtpServiceTrait},
+};
+
+/// OTP_MAX_ATTEMPTS is left at its default in the tests
+const MAX_ATTEMPTS: i32 = 5;
+
+/// a six digit code that cannot be the one issued
+fn wrong_code(code: &str) -> String {
+    let last = (code.as_bytes()[5] - b'0' + 1) % 10;
+    format!("{}{last}", &code[..5])
+}
+
+#[tokio::test]
+async fn test_a_code_verifies_once() {
+    let pool = common::database().await;
+    let (user, _) = common::create_user(&pool).await;
+    let otp_service = OtpService::init(&pool);
+
+    let code = otp_service
+        .issue(&user, OtpKind::AccountVerification)
+        .await
+        .unwrap();
+    assert_eq!(code.len(), 6);
+
+    // a code is bound to its purpose
+    assert!(matches!(
+        otp_service
+            .verify(&user, OtpKind::PasswordUpdate, &code)
+            .await,
+        Err(OtpServiceError::InvalidCode)
+    ));
+
+    otp_service
+        .verify(&user, OtpKind::AccountVerification, &code)
+        .await
+        .unwrap();
+    assert!(matches!(
+        otp_service
+            .verify(&user, OtpKind::AccountVerification, &code)
+            .await,
+        Err(OtpServiceError::InvalidCode)
+    ));
+}
+
+#[tokio::test]
+async fn test_issuing_a_code_replaces_the_previous_one() {
+    let pool = common::database().await;
+    let (user, _) = common::create_user(&pool).await;
+    let otp_service = OtpService::init(&pool);
+
+    let first = otp_service
+        .issue(&user, OtpKind::AccountVerification)
+        .await
+        .unwrap();
+    let second = otp_service
+        .issue(&user, OtpKind::AccountVerification)
+        .await
+        .unwrap();
+
+    if first != second {
+        assert!(matches!(
+            otp_service
+                .verify(&user, OtpKind::AccountVerification, &first)
+                .await,
+            Err(OtpServiceError::InvalidCode)
+        ));
+    }
+    otp_service
+        .verify(&user, OtpKind::AccountVerification, &second)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_an_expired_code_is_refused() {
+    let pool = common::database().await;
+    let (user, _) = common::create_user(&pool).await;
+    let otp_service = OtpService::init(&pool);
+
+    let code = otp_service
+        .issue_code(&user, OtpKind::AccountVerification, Duration::ZERO)
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        otp_service
+            .verify(&user, OtpKind::AccountVerification, &code)
+            .await,
+        Err(OtpServiceError::ExpiredCode)
+    ));
+}
+
+#[tokio::test]
+async fn test_a_code_is_locked_after_too_many_attempts() {
+    let pool = common::database().await;
+    let (user, _) = common::create_user(&pool).await;
+    let otp_service = OtpService::init(&pool);
+
+    let code = otp_service
+        .issue(&user, OtpKind::AccountVerification)
+        .await
+        .unwrap();
+    for _ in 0..MAX_ATTEMPTS {
+        assert!(matches!(
+            otp_service
+                .verify(&user, OtpKind::AccountVerification, &wrong_code(&code))
+                .await,
+            Err(OtpServiceError::InvalidCode)
+        ));
+    }
+
+    assert!(matches!(
+        otp_service
+            .verify(&user, OtpKind::AccountVerification, &code)
+            .await,
+        Err(OtpServiceError::TooManyAttempts)
+    ));
+}
+
+#[tokio::test]
+async fn test_concurrent_guesses_cannot_share_an_attempt() {
+    let pool = common::database().await;
+    let (user, _) = common::create_user(&pool).await;
+    let otp_service = OtpService::init(&pool);
+
+    let code = otp_service
+        .issue(&user, OtpKind::AccountVerification)
+        .await
+        .unwrap();
+    let guesses: Vec<_> = (0..4 * MAX_ATTEMPTS)
+        .map(|_| {
+            let otp_service = otp_service.clone();
+            let guess = wrong_code(&code);
+            tokio::spawn(async move {
+                otp_service
+                    .verify(&user, OtpKind::AccountVerification, &guess)
+                    .await
+            })
+        })
+        .collect();
+
+    let mut refused_as_invalid = 0;
+    for guess in guesses {
+        match guess.await.unwrap() {
+            Err(OtpServiceError::InvalidCode) => refused_as_invalid += 1,
+            Err(OtpServiceError::TooManyAttempts) => {}
+            other => panic!("unexpected outcome {other:?}"),
+        }
+    }
+    // only the allowed number of guesses were ever compared with the code
+    assert_eq!(refused_as_invalid, MAX_ATTEMPTS);
+
+    let attempts = sqlx::query_scalar::<_, i32>(
+        "SELECT attempts FROM otps WHERE user_identifier = $1 AND kind = $2",
+    )
+    .bind(user)
+    .bind(OtpKind::AccountVerification.to_string())
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(attempts, MAX_ATTEMPTS);
+}
@@ -0,0 +1,54 @@
+use uralium_lib::{
+    config::password_hashing::PasswordHashingConfig,
+    shared::password_hash::{PasswordHashScheme, PasswordHasher},
+};
+
+/// cheap parameters keep the tests fast, production uses the defaults from the environment
+fn hasher(iterations: u32) -> PasswordHasher {
+    PasswordHasher::from_config(&PasswordHashingConfig {
+        memory_kib: 1024,
+        iterations,
+        parallelism: 1,
+        workers: 1,
+        max_queue: 0,
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_argon2id_hashes_verify_regardless_of_surrounding_whitespace() {
+    let hasher = hasher(1);
+    let hash = hasher.hash("  Blue-Harbor-Lantern-42 ").unwrap();
+
+    assert_eq!(
+        PasswordHashScheme::detect(&hash),
+        Some(PasswordHashScheme::Argon2id)
+    );
+    assert!(hasher.verify("Blue-Harbor-Lantern-42", &hash).unwrap());
+    assert!(hasher.verify("Blue-Harbor-Lantern-42\n", &hash).unwrap());
+    assert!(!hasher.verify("Blue-Harbor-Lantern-43", &hash).unwrap());
+    assert!(!hasher.needs_rehash(&hash));
+}
+
+#[test]
+fn test_legacy_bcrypt_hashes_verify_and_need_a_rehash() {
+    let hasher = hasher(1);
+    let hash = bcrypt::hash("Blue-Harbor-Lantern-42", 4).unwrap();
+
+    assert_eq!(
+        PasswordHashScheme::detect(&hash),
+        Some(PasswordHashScheme::Bcrypt)
+    );
+    assert!(hasher.verify(" Blue-Harbor-Lantern-42 ", &hash).unwrap());
+    assert!(!hasher.verify("Blue-Harbor-Lantern-43", &hash).unwrap());
+    assert!(hasher.needs_rehash(&hash));
+}
+
+#[test]
+fn test_hashes_with_outdated_parameters_need_a_rehash() {
+    let hash = hasher(1).hash("Blue-Harbor-Lantern-42").unwrap();
+
+    assert!(hasher(2).verify("Blue-Harbor-Lantern-42", &hash).unwrap());
+    assert!(hasher(2).needs_rehash(&hash));
+    assert!(hasher(2).verify("anything", "plaintext").is_err());
+}
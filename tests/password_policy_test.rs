@@ -0,0 +1,131 @@
+mod common;
+
+use axum::http::StatusCode;
+use uralium_lib::{
+    config::password_policy::PasswordPolicyConfig,
+    shared::password_policy::{PasswordContext, PasswordPolicy, estimate_strength},
+};
+
+fn config() -> PasswordPolicyConfig {
+    PasswordPolicyConfig {
+        min_length: 10,
+        max_length: 128,
+        min_character_classes: 3,
+        min_strength: 3,
+        forbid_personal_information: true,
+        breached_corpus_path: None,
+    }
+}
+
+fn violations(policy: &PasswordPolicy, password: &str, context: &PasswordContext) -> Vec<String> {
+    let Err(errors) = policy.check(password, context) else {
+        return Vec::new();
+    };
+
+    errors.field_errors()["password"]
+        .iter()
+        .map(|error| error.code.to_string())
+        .collect()
+}
+
+#[test]
+fn test_strength_estimate_discounts_common_words_and_patterns() {
+    assert_eq!(estimate_strength(""), 0);
+    assert_eq!(estimate_strength("P@ssw0rd123"), 0);
+    assert_eq!(estimate_strength("aaaaaaaaaaaa"), 0);
+    assert_eq!(estimate_strength("abcdefghijkl"), 0);
+    assert!(estimate_strength("Summer2024!") < 3);
+    assert!(estimate_strength("Blue-Harbor-Lantern-42") >= 3);
+    assert_eq!(estimate_strength("k7#Qp2!vZm9@Lx4$"), 4);
+}
+
+#[test]
+fn test_policy_reports_every_violation_on_the_password_field() {
+    let policy = PasswordPolicy::from_config(config()).unwrap();
+    let context = PasswordContext {
+        email: "ada.lovelace@example.com",
+        first_name: "Ada",
+        last_name: "Lovelace",
+    };
+
+    assert!(violations(&policy, "Blue-Harbor-Lantern-42", &context).is_empty());
+    assert_eq!(
+        violations(&policy, "short1A", &context),
+        vec!["too_short", "too_weak"]
+    );
+    assert_eq!(
+        violations(&policy, "blue harbor lantern", &context),
+        vec!["character_classes"]
+    );
+    assert_eq!(
+        violations(&policy, "Lovelace-Harbor-Lantern-42", &context),
+        vec!["personal_information"]
+    );
+    assert_eq!(
+        violations(&policy, &"Ab1!".repeat(40), &context),
+        vec!["too_long"]
+    );
+}
+
+#[test]
+fn test_policy_rejects_passwords_from_the_breached_corpus() {
+    let path = std::env::temp_dir().join(format!("breached-{}.txt", uuid::Uuid::new_v4()));
+    // the first 10 characters of the SHA-1 of Blue-Harbor-Lantern-42, in the Pwned Passwords format
+    std::fs::write(
+        &path,
+        "# breached passwords\n\na7a37f083c:12\n0123456789ABCDEF\n",
+    )
+    .unwrap();
+
+    let policy = PasswordPolicy::from_config(PasswordPolicyConfig {
+        breached_corpus_path: Some(path.clone()),
+        ..config()
+    })
+    .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(policy.is_breached("Blue-Harbor-Lantern-42"));
+    assert!(!policy.is_breached("Green-Harbor-Lantern-42"));
+    assert_eq!(
+        violations(
+            &policy,
+            "Blue-Harbor-Lantern-42",
+            &PasswordContext::default()
+        ),
+        vec!["breached"]
+    );
+}
+
+#[test]
+fn test_invalid_breached_corpus_fails_startup() {
+    let path = std::env::temp_dir().join(format!("breached-{}.txt", uuid::Uuid::new_v4()));
+    std::fs::write(&path, "not-a-hash\n").unwrap();
+
+    let result = PasswordPolicy::from_config(PasswordPolicyConfig {
+        breached_corpus_path: Some(path.clone()),
+        ..config()
+    });
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_validation_errors_are_reported_per_field() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server
+        .post("/signup")
+        .json(&serde_json::json!({
+            "email": "ada.lovelace@example.com",
+            "password": "",
+            "firstName": "Ada",
+            "lastName": "Lovelace",
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<serde_json::Value>()["data"],
+        serde_json::json!({ "password": ["password is required"] })
+    );
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use uralium_lib::{
+    adapters::dto::otp::OtpKind,
+    services::otp_service::{OtpService, OtpServiceTrait},
+};
+
+const NEW_PASSWORD: &str = "Quiet-Meadow-Compass-87";
+
+fn reset_request(token: &str) -> serde_json::Value {
+    serde_json::json!({
+        "token": token,
+        "password": NEW_PASSWORD,
+        "confirmPassword": NEW_PASSWORD,
+    })
+}
+
+#[tokio::test]
+async fn test_a_reset_token_can_only_be_used_once() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    // what the forgotten password email links to
+    let token = OtpService::init(&pool)
+        .issue_token(&user, OtpKind::PasswordReset, Duration::from_secs(600))
+        .await
+        .unwrap();
+
+    server
+        .post("/reset-password")
+        .json(&reset_request(&token))
+        .await
+        .assert_status_ok();
+    server
+        .post("/login")
+        .json(&serde_json::json!({ "email": email, "password": NEW_PASSWORD }))
+        .await
+        .assert_status_ok();
+
+    let replayed = server
+        .post("/reset-password")
+        .json(&reset_request(&token))
+        .await;
+    replayed.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        replayed.json::<serde_json::Value>()["message"],
+        "the one-time code is invalid"
+    );
+}
+
+#[tokio::test]
+async fn test_an_expired_or_unknown_reset_token_is_refused() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let token = OtpService::init(&pool)
+        .issue_token(&user, OtpKind::PasswordReset, Duration::ZERO)
+        .await
+        .unwrap();
+
+    let expired = server
+        .post("/reset-password")
+        .json(&reset_request(&token))
+        .await;
+    expired.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        expired.json::<serde_json::Value>()["message"],
+        "the one-time code has expired, request a new one"
+    );
+
+    server
+        .post("/reset-password")
+        .json(&reset_request("not-a-reset-token"))
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    // the password is unchanged
+    common::login(&server, &email).await;
+}
@@ -0,0 +1,121 @@
+use std::time::Duration;
+
+use axum::{Router, http::StatusCode, routing::post};
+use axum_test::TestServer;
+use uralium_lib::{
+    adapters::rate_limit::{RateLimitBackend, RateLimitStore, memory::MemoryStore},
+    config::network::NetworkConfig,
+    middlewares::rate_limit::{RateLimitKey, RateLimitLayer, RateLimitPolicy},
+};
+
+fn server(policies: &[RateLimitPolicy]) -> TestServer {
+    let layer = RateLimitLayer::new(
+        RateLimitBackend::Memory(MemoryStore::new()),
+        NetworkConfig {
+            trust_forwarded_for: true,
+        },
+        policies,
+    );
+    let app = Router::new().route("/", post(|body: String| async move { body }).layer(layer));
+
+    TestServer::new(app).unwrap()
+}
+
+#[tokio::test]
+async fn test_requests_over_the_limit_are_refused() {
+    let server = server(&[RateLimitPolicy::new(
+        "test:email",
+        RateLimitKey::Email,
+        2,
+        Duration::from_secs(60),
+    )]);
+    let body = serde_json::json!({ "email": "Ada@Example.com" });
+
+    let response = server.post("/").json(&body).await;
+    response.assert_status_ok();
+    assert_eq!(response.header("ratelimit-remaining"), "1");
+    // the handler still sees the body the limiter read the key from
+    assert_eq!(response.text(), serde_json::to_string(&body).unwrap());
+    server
+        .post("/")
+        .json(&serde_json::json!({ "email": "ada@example.com " }))
+        .await
+        .assert_status_ok();
+
+    let response = server.post("/").json(&body).await;
+    response.assert_status(StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.header("ratelimit-limit"), "2");
+    assert_eq!(response.header("ratelimit-remaining"), "0");
+    assert_eq!(response.header("ratelimit-policy"), "2;w=60");
+    let retry_after: u64 = response
+        .header("retry-after")
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=60).contains(&retry_after));
+    let envelope = response.json::<serde_json::Value>();
+    assert_eq!(envelope["data"], serde_json::Value::Null);
+    assert_eq!(
+        envelope["message"],
+        format!("too many requests, try again in {retry_after} seconds")
+    );
+
+    server
+        .post("/")
+        .json(&serde_json::json!({ "email": "grace@example.com" }))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_policies_are_keyed_by_address_and_client() {
+    let server = server(&[
+        RateLimitPolicy::new("test:ip", RateLimitKey::Ip, 1, Duration::from_secs(60)),
+        RateLimitPolicy::new(
+            "test:client",
+            RateLimitKey::ClientId,
+            1,
+            Duration::from_secs(60),
+        ),
+    ]);
+
+    server
+        .post("/")
+        .add_header("x-forwarded-for", "203.0.113.7, 10.0.0.1")
+        .form(&[("client_id", "first")])
+        .await
+        .assert_status_ok();
+    server
+        .post("/")
+        .add_header("x-forwarded-for", "203.0.113.7")
+        .form(&[("client_id", "second")])
+        .await
+        .assert_status(StatusCode::TOO_MANY_REQUESTS);
+    // basic authentication names the client as well as the body does
+    server
+        .post("/")
+        .add_header("x-forwarded-for", "203.0.113.8")
+        .authorization("Basic Zmlyc3Q6c2VjcmV0")
+        .await
+        .assert_status(StatusCode::TOO_MANY_REQUESTS);
+    server
+        .post("/")
+        .add_header("x-forwarded-for", "203.0.113.9")
+        .form(&[("client_id", "third")])
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_memory_store_starts_a_new_window() {
+    let store = MemoryStore::new();
+    let window = Duration::from_millis(100);
+
+    assert_eq!(store.hit("key", window).await.unwrap().hits, 1);
+    assert_eq!(store.hit("key", window).await.unwrap().hits, 2);
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    let hit = store.hit("key", window).await.unwrap();
+    assert_eq!(hit.hits, 1);
+    assert!(hit.reset_after <= window);
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn refresh(server: &TestServer, refresh_token: &str) -> TestResponse {
+    server
+        .post("/refresh-token")
+        .json(&serde_json::json!({ "refreshToken": refresh_token }))
+        .await
+}
+
+/// expiry, rotated and revoked times of the user's refresh tokens, oldest first
+async fn stored_tokens(
+    pool: &PgPool,
+    user: &Uuid,
+) -> Vec<(DateTime<Utc>, Option<DateTime<Utc>>, Option<DateTime<Utc>>)> {
+    sqlx::query_as(
+        r#"SELECT expires_at, rotated_at, revoked_at FROM refresh_tokens
+        WHERE user_identifier = $1 ORDER BY created_at"#,
+    )
+    .bind(user)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_rotation_keeps_the_family_expiry() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (_, refresh_token) = common::login(&server, &email).await;
+
+    let response = refresh(&server, &refresh_token).await;
+    response.assert_status_ok();
+    let rotated = response.json::<serde_json::Value>()["data"]["refreshToken"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_ne!(rotated, refresh_token);
+    refresh(&server, &rotated).await.assert_status_ok();
+
+    let tokens = stored_tokens(&pool, &user).await;
+    assert_eq!(tokens.len(), 3);
+    assert!(
+        tokens
+            .iter()
+            .all(|(expires_at, ..)| *expires_at == tokens[0].0)
+    );
+    assert!(
+        tokens[..2]
+            .iter()
+            .all(|(_, rotated_at, _)| rotated_at.is_some())
+    );
+}
+
+#[tokio::test]
+async fn test_reusing_a_rotated_token_revokes_the_family() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (_, refresh_token) = common::login(&server, &email).await;
+
+    let response = refresh(&server, &refresh_token).await;
+    response.assert_status_ok();
+    let rotated = response.json::<serde_json::Value>()["data"]["refreshToken"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    refresh(&server, &refresh_token)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    // the legitimate holder is signed out as well, the family cannot tell who is who
+    refresh(&server, &rotated)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    assert!(
+        stored_tokens(&pool, &user)
+            .await
+            .iter()
+            .all(|(.., revoked_at)| revoked_at.is_some())
+    );
+}
+
+#[tokio::test]
+async fn test_an_expired_token_is_refused_without_being_rotated() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (_, refresh_token) = common::login(&server, &email).await;
+    sqlx::query(
+        "UPDATE refresh_tokens SET expires_at = NOW() - INTERVAL '1 second' WHERE user_identifier = $1",
+    )
+    .bind(user)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    refresh(&server, &refresh_token)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    let tokens = stored_tokens(&pool, &user).await;
+    assert_eq!(tokens.len(), 1);
+    assert_eq!(tokens[0].1, None);
+    assert_eq!(tokens[0].2, None);
+}
@@ -0,0 +1,504 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::{StatusCode, header};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use roxmltree::{Document, Node};
+use rsa::{
+    RsaPublicKey,
+    pkcs1v15::{Signature, VerifyingKey},
+    pkcs8::DecodePublicKey,
+    signature::Verifier,
+    traits::PublicKeyParts,
+};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use uralium_lib::{
+    adapters::dto::{
+        permission::Permission,
+        saml::{NameIdFormat, SamlAssertion, SamlAttribute, SamlBinding},
+    },
+    config::saml::SamlConfig,
+    errors::{app_error::AppError, saml_error::SamlError},
+    shared::{
+        saml::{
+            ASSERTION_NAMESPACE, BINDING_HTTP_POST, build_response, decode_request, encode_request,
+            identity_provider_metadata, parse_authn_request, parse_service_provider_metadata,
+        },
+        saml_signer::SamlSigner,
+        xml::canonicalize,
+    },
+};
+use uuid::Uuid;
+use x509_cert::{
+    Certificate,
+    der::{Decode, Encode},
+};
+
+const DSIG_NAMESPACE: &str = "http://www.w3.org/2000/09/xmldsig#";
+
+const AUTHN_REQUEST: &str = r#"<samlp:AuthnRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="_request-1" Version="2.0" IssueInstant="2025-07-19T09:00:00Z" Destination="http://localhost:5006/saml/sso" AssertionConsumerServiceURL="https://sp.example.com/acs" ProtocolBinding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST">
+  <saml:Issuer>https://sp.example.com/metadata</saml:Issuer>
+  <samlp:NameIDPolicy Format="urn:oasis:names:tc:SAML:2.0:nameid-format:persistent" AllowCreate="true"/>
+</samlp:AuthnRequest>"#;
+
+const SERVICE_PROVIDER_METADATA: &str = r#"<md:EntityDescriptor xmlns:md="urn:oasis:names:tc:SAML:2.0:metadata" entityID="https://sp.example.com/metadata">
+  <md:SPSSODescriptor protocolSupportEnumeration="urn:oasis:names:tc:SAML:2.0:protocol">
+    <md:NameIDFormat>urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress</md:NameIDFormat>
+    <md:AssertionConsumerService Binding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-Artifact" Location="https://sp.example.com/artifact" index="0"/>
+    <md:AssertionConsumerService Binding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST" Location="https://sp.example.com/acs/secondary" index="1"/>
+    <md:AssertionConsumerService Binding="urn:oasis:names:tc:SAML:2.0:bindings:HTTP-POST" Location="https://sp.example.com/acs" index="2" isDefault="true"/>
+  </md:SPSSODescriptor>
+</md:EntityDescriptor>"#;
+
+fn assertion() -> SamlAssertion {
+    SamlAssertion {
+        issuer: "http://localhost:5006/saml/metadata".into(),
+        audience: "https://sp.example.com/metadata".into(),
+        destination: "https://sp.example.com/acs".into(),
+        in_response_to: Some("_request-1".into()),
+        name_id: "ada@example.com".into(),
+        name_id_format: NameIdFormat::EmailAddress,
+        session_index: Some("9b2f4c1e-7d3a-4f6b-8e2d-1c5a9f0b3e7d".into()),
+        authn_instant: chrono::Utc::now(),
+        attributes: vec![
+            SamlAttribute {
+                name: "email".into(),
+                value: "ada@example.com".into(),
+            },
+            SamlAttribute {
+                name: "urn:oid:2.5.4.42".into(),
+                value: "Ada & co".into(),
+            },
+        ],
+        lifetime: Duration::from_secs(300),
+    }
+}
+
+fn find<'a, 'input>(node: Node<'a, 'input>, namespace: &str, name: &str) -> Node<'a, 'input> {
+    node.descendants()
+        .find(|node| node.has_tag_name((namespace, name)))
+        .unwrap_or_else(|| panic!("no {name} element"))
+}
+
+#[test]
+fn test_canonicalization_matches_the_exclusive_c14n_example() {
+    // section 2.2 of the Exclusive XML Canonicalization recommendation, n3 is declared where it is
+    // used and n0 is left out although both are in scope
+    let xml = r#"<n0:local xmlns:n0="foo:bar" xmlns:n3="ftp://example.org">
+  <n1:elem2 xmlns:n1="http://example.net" xml:lang="en">
+    <n3:stuff xmlns:n3="ftp://example.org"/>
+  </n1:elem2>
+</n0:local>"#;
+    let document = Document::parse(xml).unwrap();
+    let elem2 = document.root_element().first_element_child().unwrap();
+
+    assert_eq!(
+        canonicalize(elem2),
+        r#"<n1:elem2 xmlns:n1="http://example.net" xml:lang="en">
+    <n3:stuff xmlns:n3="ftp://example.org"></n3:stuff>
+  </n1:elem2>"#
+    );
+}
+
+#[test]
+fn test_canonicalization_matches_libxml2() {
+    // the expected output is `xmllint --exc-c14n` of the input
+    let vectors = [
+        (
+            r##"<samlp:Response xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" xmlns:unused="urn:example:unused" ID="_r1" Version="2.0"><saml:Assertion xmlns:xs="http://www.w3.org/2001/XMLSchema" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" Version="2.0" ID="_a1" IssueInstant="2025-07-19T09:00:00Z"><saml:Issuer>idp</saml:Issuer><saml:AttributeStatement><saml:Attribute Name="note" b:z="2" a:y="1" xmlns:b="urn:b" xmlns:a="urn:a"><saml:AttributeValue xsi:type="xs:string">Ada &amp; co &lt;admin&gt; "quoted" &#xD;</saml:AttributeValue><saml:AttributeValue attr="tab&#9;newline&#10;quote&quot;lt&lt;gt>" /></saml:Attribute></saml:AttributeStatement></saml:Assertion></samlp:Response>"##,
+            r##"<samlp:Response xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" ID="_r1" Version="2.0"><saml:Assertion xmlns:saml="urn:oasis:names:tc:SAML:2.0:assertion" ID="_a1" IssueInstant="2025-07-19T09:00:00Z" Version="2.0"><saml:Issuer>idp</saml:Issuer><saml:AttributeStatement><saml:Attribute xmlns:a="urn:a" xmlns:b="urn:b" Name="note" a:y="1" b:z="2"><saml:AttributeValue xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:type="xs:string">Ada &amp; co &lt;admin&gt; "quoted" &#xD;</saml:AttributeValue><saml:AttributeValue attr="tab&#x9;newline&#xA;quote&quot;lt&lt;gt>"></saml:AttributeValue></saml:Attribute></saml:AttributeStatement></saml:Assertion></samlp:Response>"##,
+        ),
+        (
+            r##"<doc xmlns="http://example.org/default" xmlns:p="urn:p"><child><p:leaf/></child><plain xmlns=""><inner p:attr="v"><![CDATA[<cdata> & text]]></inner></plain><?pi some data?><p:other xmlns:p="urn:p2" xml:lang="en"><again xmlns="http://example.org/default"/></p:other></doc>"##,
+            r##"<doc xmlns="http://example.org/default"><child><p:leaf xmlns:p="urn:p"></p:leaf></child><plain xmlns=""><inner xmlns:p="urn:p" p:attr="v">&lt;cdata&gt; &amp; text</inner></plain><?pi some data?><p:other xmlns:p="urn:p2" xml:lang="en"><again></again></p:other></doc>"##,
+        ),
+    ];
+    for (xml, expected) in vectors {
+        assert_eq!(canonicalize(Document::parse(xml).unwrap().root()), expected);
+    }
+
+    // the variant without comments is the one signatures use
+    let commented = Document::parse("<a><!-- note --><b>text</b><!-- note --></a>").unwrap();
+    assert_eq!(canonicalize(commented.root()), "<a><b>text</b></a>");
+}
+
+#[test]
+fn test_redirect_binding_round_trips_and_parses_the_request() {
+    let encoded = encode_request(AUTHN_REQUEST);
+    let xml = decode_request(&encoded, SamlBinding::Redirect).unwrap();
+    assert_eq!(xml, AUTHN_REQUEST);
+
+    let request = parse_authn_request(&xml).unwrap();
+    assert_eq!(request.id, "_request-1");
+    assert_eq!(request.issuer, "https://sp.example.com/metadata");
+    assert_eq!(
+        request.assertion_consumer_service_url.as_deref(),
+        Some("https://sp.example.com/acs")
+    );
+    assert_eq!(request.protocol_binding.as_deref(), Some(BINDING_HTTP_POST));
+    assert_eq!(
+        request.name_id_format.as_deref(),
+        Some(NameIdFormat::Persistent.as_str())
+    );
+
+    // the POST binding is plain base64, folded over lines
+    let folded = STANDARD
+        .encode(AUTHN_REQUEST)
+        .as_bytes()
+        .chunks(64)
+        .map(|line| std::str::from_utf8(line).unwrap())
+        .collect::<Vec<_>>()
+        .join("\r\n");
+    assert_eq!(
+        decode_request(&folded, SamlBinding::Post).unwrap(),
+        AUTHN_REQUEST
+    );
+}
+
+#[test]
+fn test_parse_authn_request_rejects_other_messages() {
+    let response = r#"<samlp:Response xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" ID="_1" Version="2.0"/>"#;
+    assert!(matches!(
+        parse_authn_request(response),
+        Err(SamlError::InvalidRequest(_))
+    ));
+
+    let without_issuer = r#"<samlp:AuthnRequest xmlns:samlp="urn:oasis:names:tc:SAML:2.0:protocol" ID="_1" Version="2.0"/>"#;
+    assert!(matches!(
+        parse_authn_request(without_issuer),
+        Err(SamlError::InvalidRequest(_))
+    ));
+}
+
+#[test]
+fn test_service_provider_metadata_puts_the_default_consumer_service_first() {
+    let metadata = parse_service_provider_metadata(SERVICE_PROVIDER_METADATA).unwrap();
+
+    assert_eq!(metadata.entity_id, "https://sp.example.com/metadata");
+    assert_eq!(
+        metadata.assertion_consumer_services,
+        vec![
+            "https://sp.example.com/acs".to_string(),
+            "https://sp.example.com/acs/secondary".to_string(),
+        ]
+    );
+    assert_eq!(
+        metadata.name_id_formats,
+        vec![NameIdFormat::EmailAddress.to_string()]
+    );
+}
+
+#[test]
+fn test_service_provider_metadata_requires_https_consumer_services() {
+    let insecure = SERVICE_PROVIDER_METADATA.replace(
+        "https://sp.example.com/acs/secondary",
+        "http://sp.example.com/acs/secondary",
+    );
+    assert!(matches!(
+        parse_service_provider_metadata(&insecure),
+        Err(SamlError::InvalidMetadata(_))
+    ));
+
+    let loopback = SERVICE_PROVIDER_METADATA.replace(
+        "https://sp.example.com/acs/secondary",
+        "http://localhost:8080/acs",
+    );
+    assert!(parse_service_provider_metadata(&loopback).is_ok());
+}
+
+#[test]
+fn test_signer_refuses_to_start_without_a_key_unless_told_to_make_one_up() {
+    let config = SamlConfig {
+        entity_id: "http://localhost:5006/saml/metadata".into(),
+        sso_url: "http://localhost:5006/saml/sso".into(),
+        login_url: "http://localhost:3000/saml".into(),
+        assertion_ttl: Duration::from_secs(300),
+        certificate_path: None,
+        private_key_path: None,
+        ephemeral_key: false,
+    };
+    assert!(matches!(
+        SamlSigner::from_config(&config),
+        Err(AppError::StartupError(_))
+    ));
+
+    // a certificate without its key is a mistake even in development
+    let config = SamlConfig {
+        certificate_path: Some("./keys/saml.crt".into()),
+        ephemeral_key: true,
+        ..config
+    };
+    assert!(matches!(
+        SamlSigner::from_config(&config),
+        Err(AppError::StartupError(_))
+    ));
+}
+
+#[test]
+fn test_identity_provider_metadata_publishes_the_certificate_and_endpoints() {
+    let signer = SamlSigner::generate_ephemeral().unwrap();
+    let xml = identity_provider_metadata(
+        "http://localhost:5006/saml/metadata",
+        "http://localhost:5006/saml/sso",
+        &signer.certificate(),
+    );
+    let document = Document::parse(&xml).unwrap();
+    let entity = document.root_element();
+    assert_eq!(
+        entity.attribute("entityID"),
+        Some("http://localhost:5006/saml/metadata")
+    );
+
+    let certificate = find(entity, DSIG_NAMESPACE, "X509Certificate");
+    assert_eq!(certificate.text(), Some(signer.certificate().as_str()));
+
+    let formats: Vec<_> = entity
+        .descendants()
+        .filter(|node| node.tag_name().name() == "NameIDFormat")
+        .filter_map(|node| node.text())
+        .collect();
+    assert_eq!(formats.len(), NameIdFormat::ALL.len());
+
+    let endpoints: Vec<_> = entity
+        .descendants()
+        .filter(|node| node.tag_name().name() == "SingleSignOnService")
+        .map(|node| node.attribute("Location").unwrap())
+        .collect();
+    assert_eq!(endpoints, vec!["http://localhost:5006/saml/sso"; 2]);
+}
+
+#[test]
+fn test_response_carries_an_assertion_signed_with_the_published_key() {
+    let signer = SamlSigner::generate_ephemeral().unwrap();
+    let xml = build_response(&assertion(), &signer).unwrap();
+    let document = Document::parse(&xml).unwrap();
+    let response = document.root_element();
+    assert_eq!(response.attribute("InResponseTo"), Some("_request-1"));
+
+    let assertion = find(response, ASSERTION_NAMESPACE, "Assertion");
+    assert_eq!(
+        find(assertion, ASSERTION_NAMESPACE, "NameID").text(),
+        Some("ada@example.com")
+    );
+    assert_eq!(
+        find(assertion, ASSERTION_NAMESPACE, "Audience").text(),
+        Some("https://sp.example.com/metadata")
+    );
+    let values: Vec<_> = assertion
+        .descendants()
+        .filter(|node| node.has_tag_name((ASSERTION_NAMESPACE, "AttributeValue")))
+        .filter_map(|node| node.text())
+        .collect();
+    assert_eq!(values, vec!["ada@example.com", "Ada & co"]);
+
+    // the reference digest is over the assertion with the signature taken out
+    let signature = find(assertion, DSIG_NAMESPACE, "Signature");
+    let unsigned = format!(
+        "{}{}",
+        &xml[assertion.range().start..signature.range().start],
+        &xml[signature.range().end..assertion.range().end]
+    );
+    let unsigned = Document::parse(&unsigned).unwrap();
+    let digest = STANDARD.encode(Sha256::digest(canonicalize(unsigned.root_element())));
+    let reference = find(signature, DSIG_NAMESPACE, "Reference");
+    assert_eq!(
+        reference.attribute("URI"),
+        Some(format!("#{}", assertion.attribute("ID").unwrap()).as_str())
+    );
+    assert_eq!(
+        find(reference, DSIG_NAMESPACE, "DigestValue").text(),
+        Some(digest.as_str())
+    );
+
+    let certificate = STANDARD
+        .decode(
+            find(signature, DSIG_NAMESPACE, "X509Certificate")
+                .text()
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(STANDARD.encode(&certificate), signer.certificate());
+    let certificate = Certificate::from_der(&certificate).unwrap();
+    // the ephemeral key is RSA-2048, the key every service provider accepts
+    let public_key = RsaPublicKey::from_public_key_der(
+        &certificate
+            .tbs_certificate
+            .subject_public_key_info
+            .to_der()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(public_key.size() * 8, 2048);
+    assert_eq!(
+        find(signature, DSIG_NAMESPACE, "SignatureMethod").attribute("Algorithm"),
+        Some("http://www.w3.org/2001/04/xmldsig-more#rsa-sha256")
+    );
+    let key = VerifyingKey::<Sha256>::new(public_key);
+    let signature_value = STANDARD
+        .decode(
+            find(signature, DSIG_NAMESPACE, "SignatureValue")
+                .text()
+                .unwrap(),
+        )
+        .unwrap();
+    let signed_info = canonicalize(find(signature, DSIG_NAMESPACE, "SignedInfo"));
+    key.verify(
+        signed_info.as_bytes(),
+        &Signature::try_from(signature_value.as_slice()).unwrap(),
+    )
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_identity_provider_metadata_route_serves_xml() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server.get("/saml/metadata").await;
+    response.assert_status(StatusCode::OK);
+    assert_eq!(
+        response.header(header::CONTENT_TYPE),
+        "application/samlmetadata+xml"
+    );
+    assert!(Document::parse(&response.text()).is_ok());
+}
+
+#[tokio::test]
+async fn test_service_provider_registry_requires_authentication() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server.get("/saml/service-providers").await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<serde_json::Value>()["message"],
+        "Missing authorization headers"
+    );
+}
+
+#[tokio::test]
+async fn test_authn_instant_is_when_the_user_signed_in() {
+    let pool = common::database().await;
+    let (admin, admin_email) = common::create_user(&pool).await;
+    common::grant_permissions(&pool, &admin, &[Permission::ManageServiceProviders]).await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (admin_token, _) = common::login(&server, &admin_email).await;
+    let entity_id = format!(
+        "https://{}.sp.example.com/metadata",
+        Uuid::new_v4().simple()
+    );
+    server
+        .post("/saml/service-providers")
+        .authorization_bearer(&admin_token)
+        .json(&json!({
+            "name": "Reports",
+            "metadata": SERVICE_PROVIDER_METADATA
+                .replace("https://sp.example.com/metadata", &entity_id),
+        }))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    // signed in an hour ago, the access token has been refreshed since
+    let (_, refresh_token) = common::login(&server, &email).await;
+    let signed_in_at: chrono::DateTime<chrono::Utc> = sqlx::query_scalar(
+        r#"UPDATE sessions SET created_at = date_trunc('second', NOW()) - INTERVAL '1 hour'
+        WHERE user_identifier = $1 RETURNING created_at"#,
+    )
+    .bind(user)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let response = server
+        .post("/refresh-token")
+        .json(&json!({ "refreshToken": refresh_token }))
+        .await;
+    response.assert_status_ok();
+    let token = response.json::<serde_json::Value>()["data"]["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let response = server
+        .post("/saml/sso/initiate")
+        .authorization_bearer(&token)
+        .json(&json!({ "entityId": entity_id }))
+        .await;
+    response.assert_status_ok();
+    let xml = String::from_utf8(
+        STANDARD
+            .decode(
+                response.json::<serde_json::Value>()["data"]["samlResponse"]
+                    .as_str()
+                    .unwrap(),
+            )
+            .unwrap(),
+    )
+    .unwrap();
+    let document = Document::parse(&xml).unwrap();
+    let authn_instant = find(
+        document.root_element(),
+        ASSERTION_NAMESPACE,
+        "AuthnStatement",
+    )
+    .attribute("AuthnInstant")
+    .unwrap();
+    assert_eq!(
+        chrono::DateTime::parse_from_rfc3339(authn_instant).unwrap(),
+        signed_in_at
+    );
+}
+
+#[tokio::test]
+async fn test_a_request_cannot_ask_for_another_name_id_format() {
+    let pool = common::database().await;
+    let (admin, admin_email) = common::create_user(&pool).await;
+    common::grant_permissions(&pool, &admin, &[Permission::ManageServiceProviders]).await;
+    let server = common::server(pool.clone());
+    let (admin_token, _) = common::login(&server, &admin_email).await;
+    let entity_id = format!(
+        "https://{}.sp.example.com/metadata",
+        Uuid::new_v4().simple()
+    );
+    // registered for email addresses
+    server
+        .post("/saml/service-providers")
+        .authorization_bearer(&admin_token)
+        .json(&json!({
+            "name": "Reports",
+            "metadata": SERVICE_PROVIDER_METADATA
+                .replace("https://sp.example.com/metadata", &entity_id),
+        }))
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let config = SamlConfig::from_env();
+
+    let start = |format: NameIdFormat| {
+        let request = AUTHN_REQUEST
+            .replace("http://localhost:5006/saml/sso", &config.sso_url)
+            .replace("https://sp.example.com/metadata", &entity_id)
+            .replace(NameIdFormat::Persistent.as_str(), format.as_str());
+        server
+            .get("/saml/sso")
+            .add_query_param("SAMLRequest", encode_request(&request))
+    };
+    let response = start(NameIdFormat::Persistent).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<serde_json::Value>()["message"],
+        "the authentication request is invalid: the service provider is registered for the urn:oasis:names:tc:SAML:1.1:nameid-format:emailAddress name id format"
+    );
+
+    for format in [NameIdFormat::EmailAddress, NameIdFormat::Unspecified] {
+        let response = start(format).await;
+        response.assert_status(StatusCode::SEE_OTHER);
+        assert!(
+            response
+                .header(header::LOCATION)
+                .to_str()
+                .unwrap()
+                .starts_with(&config.login_url)
+        );
+    }
+}
@@ -0,0 +1,211 @@
+mod common;
+
+use axum::http::{StatusCode, header};
+use axum_test::{TestResponse, TestServer};
+use serde_json::Value;
+use uralium_lib::{
+    adapters::dto::jwt::{JwtCredentials, TEN_MINUTES, TokenUse},
+    config::jwt::JwtConfig,
+    entities::session::SessionEntity,
+};
+use uuid::Uuid;
+
+#[test]
+fn test_access_tokens_carry_the_session_they_belong_to() {
+    let config = JwtConfig {
+        issuer: "http://localhost:5006".into(),
+        audience: "uranium".into(),
+    };
+    let signed_in_at = chrono::Utc::now() - chrono::Duration::hours(1);
+    let session = SessionEntity {
+        identifier: Uuid::new_v4(),
+        user_identifier: Uuid::new_v4(),
+        family_identifier: Uuid::new_v4(),
+        user_agent: None,
+        ip_address: None,
+        created_at: signed_in_at,
+        last_seen_at: chrono::Utc::now(),
+    };
+    let credentials = JwtCredentials::new("ada@example.com", &Uuid::new_v4(), 0, TokenUse::Access);
+
+    let claims = credentials
+        .with_session(&session)
+        .to_claims(&config, TEN_MINUTES);
+    assert_eq!(claims.sid, Some(session.identifier));
+    assert_eq!(claims.auth_time, Some(signed_in_at.timestamp()));
+    assert_eq!(
+        serde_json::to_value(&claims).unwrap()["sid"],
+        session.identifier.to_string()
+    );
+
+    let claims = JwtCredentials::new("ada@example.com", &Uuid::new_v4(), 0, TokenUse::Access)
+        .to_claims(&config, TEN_MINUTES);
+    let claims = serde_json::to_value(&claims).unwrap();
+    assert!(claims.get("sid").is_none());
+    assert!(claims.get("auth_time").is_none());
+}
+
+#[tokio::test]
+async fn test_session_routes_require_an_access_token() {
+    let server = common::server(common::lazy_pool());
+
+    for response in [
+        server.get("/users/sessions").await,
+        server.delete("/users/sessions/others").await,
+        server
+            .delete(&format!("/users/sessions/{}", Uuid::new_v4()))
+            .await,
+    ] {
+        response.assert_status(StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.json::<serde_json::Value>()["message"],
+            "Missing authorization headers"
+        );
+    }
+}
+
+/// signs in from a device with the given user agent, returning the access and refresh tokens
+async fn login_from(server: &TestServer, email: &str, user_agent: &str) -> (String, String) {
+    let response = server
+        .post("/login")
+        .add_header(header::USER_AGENT, user_agent)
+        .json(&serde_json::json!({ "email": email, "password": common::PASSWORD }))
+        .await;
+    response.assert_status_ok();
+
+    let data = &response.json::<Value>()["data"];
+    (
+        data["token"].as_str().unwrap().to_string(),
+        data["refreshToken"].as_str().unwrap().to_string(),
+    )
+}
+
+async fn sessions(server: &TestServer, token: &str) -> Vec<Value> {
+    let response = server
+        .get("/users/sessions")
+        .authorization_bearer(token)
+        .await;
+    response.assert_status_ok();
+
+    response.json::<Value>()["data"].as_array().unwrap().clone()
+}
+
+async fn refresh(server: &TestServer, refresh_token: &str) -> TestResponse {
+    server
+        .post("/refresh-token")
+        .json(&serde_json::json!({ "refreshToken": refresh_token }))
+        .await
+}
+
+#[tokio::test]
+async fn test_revoking_a_session_signs_that_device_out() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (laptop_token, laptop_refresh) = login_from(&server, &email, "Laptop").await;
+    let (phone_token, phone_refresh) = login_from(&server, &email, "Phone").await;
+
+    let listed = sessions(&server, &laptop_token).await;
+    assert_eq!(listed.len(), 2);
+    let phone = listed
+        .iter()
+        .find(|session| session["userAgent"] == "Phone")
+        .unwrap();
+    assert_eq!(phone["current"], false);
+    assert!(
+        listed
+            .iter()
+            .any(|session| session["userAgent"] == "Laptop" && session["current"] == true)
+    );
+
+    server
+        .delete(&format!(
+            "/users/sessions/{}",
+            phone["identifier"].as_str().unwrap()
+        ))
+        .authorization_bearer(&laptop_token)
+        .await
+        .assert_status_ok();
+
+    // both the access token and the refresh token of the phone stop working
+    server
+        .get("/users/profile")
+        .authorization_bearer(&phone_token)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    refresh(&server, &phone_refresh)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    server
+        .get("/users/profile")
+        .authorization_bearer(&laptop_token)
+        .await
+        .assert_status_ok();
+    refresh(&server, &laptop_refresh).await.assert_status_ok();
+    assert_eq!(sessions(&server, &laptop_token).await.len(), 1);
+
+    // a revoked session is gone
+    server
+        .delete(&format!(
+            "/users/sessions/{}",
+            phone["identifier"].as_str().unwrap()
+        ))
+        .authorization_bearer(&laptop_token)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_sessions_of_other_users_cannot_be_revoked() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let (_, other_email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, _) = login_from(&server, &email, "Laptop").await;
+    let (other_token, _) = login_from(&server, &other_email, "Laptop").await;
+    let other_session = sessions(&server, &other_token).await[0]["identifier"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    server
+        .delete(&format!("/users/sessions/{other_session}"))
+        .authorization_bearer(&token)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    server
+        .get("/users/profile")
+        .authorization_bearer(&other_token)
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_revoking_the_other_sessions_keeps_the_current_one() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, refresh_token) = login_from(&server, &email, "Laptop").await;
+    let (phone_token, _) = login_from(&server, &email, "Phone").await;
+    let (tablet_token, _) = login_from(&server, &email, "Tablet").await;
+
+    let response = server
+        .delete("/users/sessions/others")
+        .authorization_bearer(&token)
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Value>()["data"]["revoked"], 2);
+
+    for revoked in [&phone_token, &tablet_token] {
+        server
+            .get("/users/profile")
+            .authorization_bearer(revoked)
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
+    let listed = sessions(&server, &token).await;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["current"], true);
+    refresh(&server, &refresh_token).await.assert_status_ok();
+}
@@ -0,0 +1,326 @@
+mod common;
+
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use serde_json::Value;
+use sqlx::PgPool;
+use uralium_lib::{
+    adapters::dto::{
+        oauth::{GRANT_TOKEN_EXCHANGE, TOKEN_TYPE_ACCESS_TOKEN},
+        permission::Permission,
+    },
+    shared::crypto::sha256_base64url,
+};
+use url::Url;
+use uuid::Uuid;
+
+const CLIENT_SECRET: &str = "support-console-secret";
+const CODE_VERIFIER: &str = "a-code-verifier-long-enough-to-satisfy-the-pkce-rules-0123456789";
+
+/// a confidential client allowed to exchange tokens, its tokens would outlive any login
+async fn exchanging_client(pool: &PgPool) -> String {
+    let client_id = common::create_client(
+        pool,
+        Some(CLIENT_SECRET),
+        &[GRANT_TOKEN_EXCHANGE],
+        &["reports:read", "reports:write"],
+    )
+    .await;
+    sqlx::query("UPDATE oauth_clients SET access_token_ttl_seconds = 86400 WHERE client_id = $1")
+        .bind(&client_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    client_id
+}
+
+/// the access token the given client obtains for the signed-in user through the authorization
+/// code flow, a third-party token unlike the login token it started from
+async fn authorize(server: &TestServer, token: &str, client_id: &str, scope: &str) -> String {
+    let response = server
+        .post("/oauth/authorize")
+        .authorization_bearer(token)
+        .json(&serde_json::json!({
+            "response_type": "code",
+            "client_id": client_id,
+            "redirect_uri": common::REDIRECT_URI,
+            "scope": scope,
+            "code_challenge": sha256_base64url(CODE_VERIFIER),
+            "code_challenge_method": "S256",
+        }))
+        .await;
+    response.assert_status_ok();
+    let redirect_to = Url::parse(
+        response.json::<Value>()["data"]["redirectTo"]
+            .as_str()
+            .unwrap(),
+    )
+    .unwrap();
+    let code = redirect_to
+        .query_pairs()
+        .find(|(key, _)| key == "code")
+        .unwrap()
+        .1
+        .to_string();
+
+    let response = server
+        .post("/oauth/token")
+        .form(&[
+            ("grant_type", "authorization_code"),
+            ("code", &code),
+            ("redirect_uri", common::REDIRECT_URI),
+            ("client_id", client_id),
+            ("client_secret", CLIENT_SECRET),
+            ("code_verifier", CODE_VERIFIER),
+        ])
+        .await;
+    response.assert_status_ok();
+    response.json::<Value>()["access_token"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn exchange(
+    server: &TestServer,
+    client_id: &str,
+    subject_token: &str,
+    extra: &[(&str, &str)],
+) -> TestResponse {
+    let mut form = vec![
+        ("grant_type", GRANT_TOKEN_EXCHANGE),
+        ("client_id", client_id),
+        ("client_secret", CLIENT_SECRET),
+        ("subject_token", subject_token),
+        ("subject_token_type", TOKEN_TYPE_ACCESS_TOKEN),
+    ];
+    form.extend_from_slice(extra);
+
+    server.post("/oauth/token").form(&form).await
+}
+
+/// the audit rows written for exchanges of the given subject token, oldest first
+async fn audits(pool: &PgPool, subject_token: &str) -> Vec<Value> {
+    let jti = Uuid::parse_str(common::jwt_claims(subject_token)["jti"].as_str().unwrap()).unwrap();
+    sqlx::query_scalar::<_, Value>(
+        r#"SELECT json_build_object(
+            'subject_identifier', subject_identifier, 'actor', actor, 'impersonation', impersonation,
+            'scope', scope, 'granted', granted, 'reason', reason)
+        FROM token_exchange_audits WHERE subject_token_jti = $1 ORDER BY created_at"#,
+    )
+    .bind(jti)
+    .fetch_all(pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_exchanged_tokens_nest_their_actors() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let client_id = exchanging_client(&pool).await;
+    let other_client_id = exchanging_client(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+
+    let response = exchange(&server, &client_id, &token, &[("scope", "reports:read")]).await;
+    response.assert_status_ok();
+    let exchanged = response.json::<Value>()["access_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let claims = common::jwt_claims(&exchanged);
+    assert_eq!(claims["sub"], user.to_string());
+    assert_eq!(claims["scope"], "reports:read");
+    assert_eq!(claims["act"]["sub"], client_id.as_str());
+    assert!(claims["act"].get("act").is_none());
+    // capped at what was left of the login token
+    assert!(claims["exp"].as_i64() <= common::jwt_claims(&token)["exp"].as_i64());
+
+    let response = exchange(&server, &other_client_id, &exchanged, &[]).await;
+    response.assert_status_ok();
+    let claims = common::jwt_claims(response.json::<Value>()["access_token"].as_str().unwrap());
+    assert_eq!(claims["scope"], "reports:read");
+    assert_eq!(claims["act"]["sub"], other_client_id.as_str());
+    assert_eq!(claims["act"]["act"]["sub"], client_id.as_str());
+
+    // a token can only be narrowed, never widened
+    let response = exchange(
+        &server,
+        &other_client_id,
+        &exchanged,
+        &[("scope", "reports:write")],
+    )
+    .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["error"], "invalid_scope");
+    let audits = audits(&pool, &exchanged).await;
+    assert_eq!(audits.len(), 2);
+    assert_eq!(audits[0]["granted"], true);
+    assert_eq!(audits[1]["granted"], false);
+    assert_eq!(
+        audits[1]["reason"],
+        "the subject token was not granted the reports:write scope"
+    );
+}
+
+#[tokio::test]
+async fn test_impersonation_takes_the_permission() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let (target, _) = common::create_user(&pool).await;
+    let client_id = exchanging_client(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+
+    let response = exchange(
+        &server,
+        &client_id,
+        &token,
+        &[("requested_subject", &target.to_string())],
+    )
+    .await;
+    response.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(response.json::<Value>()["error"], "access_denied");
+
+    let audits = audits(&pool, &token).await;
+    assert_eq!(audits.len(), 1);
+    assert_eq!(audits[0]["subject_identifier"], target.to_string());
+    assert_eq!(audits[0]["impersonation"], true);
+    assert_eq!(audits[0]["granted"], false);
+    assert_eq!(
+        audits[0]["reason"],
+        "the actor lacks the users:impersonate permission"
+    );
+}
+
+#[tokio::test]
+async fn test_an_admin_can_impersonate_for_no_longer_than_their_own_token() {
+    let pool = common::database().await;
+    let (admin, email) = common::create_user(&pool).await;
+    let (target, _) = common::create_user(&pool).await;
+    common::grant_permissions(&pool, &admin, &[Permission::ImpersonateUsers]).await;
+    let client_id = exchanging_client(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+
+    let response = exchange(
+        &server,
+        &client_id,
+        &token,
+        &[("requested_subject", &target.to_string())],
+    )
+    .await;
+    response.assert_status_ok();
+    let tokens = response.json::<Value>();
+    assert!(tokens["expires_in"].as_u64().unwrap() <= 600);
+    let impersonating = tokens["access_token"].as_str().unwrap().to_string();
+    let claims = common::jwt_claims(&impersonating);
+    assert_eq!(claims["sub"], target.to_string());
+    assert_eq!(claims["act"]["sub"], admin.to_string());
+    assert_eq!(claims["act"]["sub_type"], "user");
+    assert!(claims["exp"].as_i64() <= common::jwt_claims(&token)["exp"].as_i64());
+
+    // an impersonating token cannot be used to impersonate again
+    let response = exchange(
+        &server,
+        &client_id,
+        &impersonating,
+        &[("requested_subject", &admin.to_string())],
+    )
+    .await;
+    response.assert_status(StatusCode::FORBIDDEN);
+
+    let audits = audits(&pool, &token).await;
+    assert_eq!(audits.len(), 1);
+    assert_eq!(audits[0]["actor"], admin.to_string());
+    assert_eq!(audits[0]["granted"], true);
+    assert!(audits[0]["reason"].is_null());
+    assert_eq!(
+        self::audits(&pool, &impersonating).await[0]["reason"],
+        "the subject token was itself obtained through a token exchange"
+    );
+}
+
+#[tokio::test]
+async fn test_impersonation_takes_a_token_of_this_client_and_keeps_to_its_scope() {
+    let pool = common::database().await;
+    let (admin, email) = common::create_user(&pool).await;
+    let (target, _) = common::create_user(&pool).await;
+    common::grant_permissions(&pool, &admin, &[Permission::ImpersonateUsers]).await;
+    let client_id = common::create_client(
+        &pool,
+        Some(CLIENT_SECRET),
+        &[GRANT_TOKEN_EXCHANGE, "authorization_code"],
+        &["reports:read", "reports:write"],
+    )
+    .await;
+    let other_client_id = common::create_client(
+        &pool,
+        Some(CLIENT_SECRET),
+        &["authorization_code"],
+        &["reports:read", "reports:write"],
+    )
+    .await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+    let target = target.to_string();
+    let impersonate = [("requested_subject", target.as_str())];
+
+    // a token the admin handed to some other application
+    let other_token = authorize(&server, &token, &other_client_id, "reports:read").await;
+    let response = exchange(&server, &client_id, &other_token, &impersonate).await;
+    response.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(response.json::<Value>()["error"], "access_denied");
+    assert_eq!(
+        audits(&pool, &other_token).await[0]["reason"],
+        "the subject token was issued to another third-party client"
+    );
+
+    // a token of the exchanging client itself, the impersonation gets no more than it was granted
+    let own_token = authorize(&server, &token, &client_id, "reports:read").await;
+    let response = exchange(&server, &client_id, &own_token, &impersonate).await;
+    response.assert_status_ok();
+    let tokens = response.json::<Value>();
+    assert_eq!(tokens["scope"], "reports:read");
+    let claims = common::jwt_claims(tokens["access_token"].as_str().unwrap());
+    assert_eq!(claims["sub"], target);
+    assert_eq!(claims["scope"], "reports:read");
+
+    let widened = [impersonate[0], ("scope", "reports:write")];
+    let response = exchange(&server, &client_id, &own_token, &widened).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["error"], "invalid_scope");
+}
+
+#[tokio::test]
+async fn test_every_refused_exchange_is_audited() {
+    let pool = common::database().await;
+    let (admin, email) = common::create_user(&pool).await;
+    common::grant_permissions(&pool, &admin, &[Permission::ImpersonateUsers]).await;
+    let client_id = exchanging_client(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+    let unknown_user = Uuid::new_v4().to_string();
+
+    let refusals: [&[(&str, &str)]; 4] = [
+        &[("audience", "https://unknown.example.com")],
+        &[("scope", "users:manage")],
+        &[("requested_subject", &unknown_user)],
+        &[("requested_subject", "not-a-user")],
+    ];
+    for extra in refusals {
+        exchange(&server, &client_id, &token, extra)
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    let audits = audits(&pool, &token).await;
+    assert_eq!(audits.len(), refusals.len());
+    assert!(audits.iter().all(|audit| audit["granted"] == false));
+    assert!(audits.iter().all(|audit| audit["reason"].is_string()));
+    assert_eq!(audits[1]["scope"], "users:manage");
+    assert_eq!(audits[2]["subject_identifier"], unknown_user);
+    assert_eq!(audits[3]["reason"], "requested_subject is not a known user");
+}
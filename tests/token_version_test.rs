@@ -0,0 +1,137 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum_test::TestServer;
+use uralium_lib::{
+    adapters::dto::{otp::OtpKind, permission::Permission},
+    services::otp_service::{OtpService, OtpServiceTrait},
+};
+
+async fn assert_signed_out(server: &TestServer, token: &str, refresh_token: &str) {
+    server
+        .get("/users/profile")
+        .authorization_bearer(token)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .post("/refresh-token")
+        .json(&serde_json::json!({ "refreshToken": refresh_token }))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_logging_out_everywhere_ends_every_session() {
+    let pool = common::database().await;
+    let (_, email) = common::create_user(&pool).await;
+    let server = common::server(pool);
+    let (token, _) = common::login(&server, &email).await;
+    let (other_token, other_refresh_token) = common::login(&server, &email).await;
+
+    server
+        .post("/logout/all")
+        .authorization_bearer(&token)
+        .await
+        .assert_status_ok();
+
+    assert_signed_out(&server, &other_token, &other_refresh_token).await;
+    server
+        .get("/users/profile")
+        .authorization_bearer(&token)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    // signing in again is not affected
+    let (token, _) = common::login(&server, &email).await;
+    server
+        .get("/users/profile")
+        .authorization_bearer(&token)
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_an_administrator_can_sign_a_user_out() {
+    let pool = common::database().await;
+    let (admin, admin_email) = common::create_user(&pool).await;
+    let (user, email) = common::create_user(&pool).await;
+    common::grant_permissions(&pool, &admin, &[Permission::ManageUsers]).await;
+    let server = common::server(pool);
+    let (admin_token, _) = common::login(&server, &admin_email).await;
+    let (token, refresh_token) = common::login(&server, &email).await;
+
+    // without the permission a user cannot do it to another
+    server
+        .post(&format!("/users/{admin}/sign-out"))
+        .authorization_bearer(&token)
+        .await
+        .assert_status(StatusCode::FORBIDDEN);
+
+    server
+        .post(&format!("/users/{user}/sign-out"))
+        .authorization_bearer(&admin_token)
+        .await
+        .assert_status_ok();
+
+    assert_signed_out(&server, &token, &refresh_token).await;
+    server
+        .get("/users/profile")
+        .authorization_bearer(&admin_token)
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_a_password_reset_ends_every_session() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, refresh_token) = common::login(&server, &email).await;
+    let reset_token = OtpService::init(&pool)
+        .issue_token(&user, OtpKind::PasswordReset, Duration::from_secs(600))
+        .await
+        .unwrap();
+
+    server
+        .post("/reset-password")
+        .json(&serde_json::json!({
+            "token": reset_token,
+            "password": "Quiet-Meadow-Compass-87",
+            "confirmPassword": "Quiet-Meadow-Compass-87",
+        }))
+        .await
+        .assert_status_ok();
+
+    assert_signed_out(&server, &token, &refresh_token).await;
+}
+
+#[tokio::test]
+async fn test_confirming_an_email_change_ends_every_session() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, refresh_token) = common::login(&server, &email).await;
+
+    server
+        .post("/change-email")
+        .authorization_bearer(&token)
+        .json(&serde_json::json!({
+            "email": common::unique_email(),
+            "password": common::PASSWORD,
+        }))
+        .await
+        .assert_status_ok();
+    let code = OtpService::init(&pool)
+        .issue(&user, OtpKind::EmailChange)
+        .await
+        .unwrap();
+    server
+        .post("/change-email/confirm")
+        .authorization_bearer(&token)
+        .json(&serde_json::json!({ "otp": code }))
+        .await
+        .assert_status_ok();
+
+    assert_signed_out(&server, &token, &refresh_token).await;
+}
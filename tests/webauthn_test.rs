@@ -0,0 +1,582 @@
+mod common;
+
+use std::time::Duration;
+
+use axum::http::StatusCode;
+use axum_test::{TestResponse, TestServer};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ciborium::Value;
+use p256::ecdsa::{DerSignature, SigningKey, signature::Signer};
+use serde_json::{Value as Json, json};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uralium_lib::{
+    config::webauthn::WebAuthnConfig,
+    errors::webauthn_service_error::WebAuthnServiceError,
+    shared::webauthn::{Assertion, CollectedClientData, verify_assertion, verify_registration},
+};
+use uuid::Uuid;
+
+const ORIGIN: &str = "https://app.uranium.test";
+/// the relying party the app runs as without WEBAUTHN_* settings
+const APP_RP_ID: &str = "localhost";
+const APP_ORIGIN: &str = "http://localhost:3000";
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL_DATA: u8 = 0x40;
+
+/// a P-256 platform authenticator in software, enough to run both ceremonies without hardware
+#[derive(Clone)]
+struct SoftwareAuthenticator {
+    key: SigningKey,
+    credential_id: Vec<u8>,
+    sign_count: u32,
+    rp_id: String,
+}
+
+impl SoftwareAuthenticator {
+    fn new(rp_id: &str) -> Self {
+        Self {
+            key: SigningKey::random(&mut rand::rngs::OsRng),
+            credential_id: rand::random::<[u8; 16]>().to_vec(),
+            sign_count: 0,
+            rp_id: rp_id.to_string(),
+        }
+    }
+
+    fn cose_public_key(&self) -> Vec<u8> {
+        let point = self.key.verifying_key().to_encoded_point(false);
+        let key = Value::Map(vec![
+            (Value::from(1), Value::from(2)),
+            (Value::from(3), Value::from(-7)),
+            (Value::from(-1), Value::from(1)),
+            (Value::from(-2), Value::Bytes(point.x().unwrap().to_vec())),
+            (Value::from(-3), Value::Bytes(point.y().unwrap().to_vec())),
+        ]);
+
+        let mut encoded = Vec::new();
+        ciborium::ser::into_writer(&key, &mut encoded).unwrap();
+        encoded
+    }
+
+    fn authenticator_data(&self, flags: u8) -> Vec<u8> {
+        let mut data = Sha256::digest(self.rp_id.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&self.sign_count.to_be_bytes());
+        if flags & FLAG_ATTESTED_CREDENTIAL_DATA != 0 {
+            data.extend_from_slice(&[0u8; 16]);
+            data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+            data.extend_from_slice(&self.credential_id);
+            data.extend_from_slice(&self.cose_public_key());
+        }
+        data
+    }
+
+    fn client_data(ceremony: &str, challenge: &str, origin: &str) -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "type": ceremony,
+            "challenge": challenge,
+            "origin": origin,
+            "crossOrigin": false,
+        }))
+        .unwrap()
+    }
+
+    /// `navigator.credentials.create()`, returns the client data and the attestation object
+    fn create(&self, challenge: &str, origin: &str) -> (Vec<u8>, Vec<u8>) {
+        let attestation_object = Value::Map(vec![
+            (Value::from("fmt"), Value::from("none")),
+            (Value::from("attStmt"), Value::Map(vec![])),
+            (
+                Value::from("authData"),
+                Value::Bytes(self.authenticator_data(
+                    FLAG_USER_PRESENT | FLAG_USER_VERIFIED | FLAG_ATTESTED_CREDENTIAL_DATA,
+                )),
+            ),
+        ]);
+        let mut encoded = Vec::new();
+        ciborium::ser::into_writer(&attestation_object, &mut encoded).unwrap();
+
+        (
+            Self::client_data("webauthn.create", challenge, origin),
+            encoded,
+        )
+    }
+
+    /// `navigator.credentials.get()`, every call moves the signature counter on
+    fn get(&mut self, challenge: &str, origin: &str, flags: u8) -> Assertion {
+        self.sign_count += 1;
+        let client_data_json = Self::client_data("webauthn.get", challenge, origin);
+        let authenticator_data = self.authenticator_data(flags);
+        let signed = [
+            authenticator_data.as_slice(),
+            Sha256::digest(&client_data_json).as_slice(),
+        ]
+        .concat();
+        let signature: DerSignature = self.key.sign(&signed);
+
+        Assertion {
+            client_data_json,
+            authenticator_data,
+            signature: signature.as_bytes().to_vec(),
+        }
+    }
+}
+
+fn config() -> WebAuthnConfig {
+    WebAuthnConfig {
+        rp_id: "uranium.test".into(),
+        rp_name: "Uranium".into(),
+        origins: vec![ORIGIN.into()],
+        challenge_ttl: Duration::from_secs(300),
+        reauthentication_window: Duration::from_secs(300),
+    }
+}
+
+fn challenge() -> String {
+    URL_SAFE_NO_PAD.encode(rand::random::<[u8; 32]>())
+}
+
+#[test]
+fn test_software_authenticator_registers_and_signs_in() {
+    let config = config();
+    let mut authenticator = SoftwareAuthenticator::new(&config.rp_id);
+
+    let (client_data_json, attestation_object) = authenticator.create(&challenge(), ORIGIN);
+    let registration = verify_registration(
+        &config,
+        &CollectedClientData::parse(&client_data_json).unwrap(),
+        &attestation_object,
+    )
+    .unwrap();
+    assert_eq!(registration.credential_id, authenticator.credential_id);
+    assert_eq!(registration.sign_count, 0);
+
+    let assertion = authenticator.get(&challenge(), ORIGIN, FLAG_USER_PRESENT | FLAG_USER_VERIFIED);
+    let client_data = CollectedClientData::parse(&assertion.client_data_json).unwrap();
+    let sign_count = verify_assertion(
+        &config,
+        &client_data,
+        &assertion,
+        &registration.public_key,
+        registration.sign_count,
+        true,
+    )
+    .unwrap();
+    assert_eq!(sign_count, 1);
+
+    // the same assertion again looks like a cloned authenticator
+    assert!(matches!(
+        verify_assertion(
+            &config,
+            &client_data,
+            &assertion,
+            &registration.public_key,
+            sign_count,
+            true
+        ),
+        Err(WebAuthnServiceError::VerificationFailed)
+    ));
+}
+
+#[test]
+fn test_assertions_are_checked_against_the_relying_party() {
+    let config = config();
+    let mut authenticator = SoftwareAuthenticator::new(&config.rp_id);
+    let (client_data_json, attestation_object) = authenticator.create(&challenge(), ORIGIN);
+    let registration = verify_registration(
+        &config,
+        &CollectedClientData::parse(&client_data_json).unwrap(),
+        &attestation_object,
+    )
+    .unwrap();
+    let verify = |assertion: &Assertion, require_user_verification: bool| {
+        verify_assertion(
+            &config,
+            &CollectedClientData::parse(&assertion.client_data_json).unwrap(),
+            assertion,
+            &registration.public_key,
+            0,
+            require_user_verification,
+        )
+    };
+
+    let phished = authenticator.get(&challenge(), "https://uranium.evil", FLAG_USER_PRESENT);
+    assert!(matches!(
+        verify(&phished, false),
+        Err(WebAuthnServiceError::InvalidResponse(_))
+    ));
+
+    let unverified = authenticator.get(&challenge(), ORIGIN, FLAG_USER_PRESENT);
+    assert!(verify(&unverified, false).is_ok());
+    assert!(matches!(
+        verify(&unverified, true),
+        Err(WebAuthnServiceError::InvalidResponse(_))
+    ));
+
+    let mut tampered = authenticator.get(&challenge(), ORIGIN, FLAG_USER_PRESENT);
+    tampered.authenticator_data[32] |= FLAG_USER_VERIFIED;
+    assert!(matches!(
+        verify(&tampered, true),
+        Err(WebAuthnServiceError::VerificationFailed)
+    ));
+
+    let other_rp = SoftwareAuthenticator::new("uranium.evil").create(&challenge(), ORIGIN);
+    assert!(
+        verify_registration(
+            &config,
+            &CollectedClientData::parse(&other_rp.0).unwrap(),
+            &other_rp.1
+        )
+        .is_err()
+    );
+}
+
+#[tokio::test]
+async fn test_passkey_login_rejects_malformed_responses() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server
+        .post("/login/webauthn")
+        .json(&serde_json::json!({
+            "credential": {
+                "id": "AAAA",
+                "response": {
+                    "clientDataJSON": "not base64url!",
+                    "authenticatorData": "",
+                    "signature": ""
+                }
+            }
+        }))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<serde_json::Value>()["message"],
+        "the authenticator response is invalid: clientDataJSON is not base64url"
+    );
+}
+
+/// runs the registration ceremony through the routes with the options issued to `token`, the
+/// response is returned for the caller to check
+async fn register(
+    server: &TestServer,
+    token: &str,
+    authenticator: &SoftwareAuthenticator,
+    challenge_token: &str,
+) -> TestResponse {
+    let options = server
+        .post("/users/webauthn/register/options")
+        .authorization_bearer(challenge_token)
+        .json(&json!({}))
+        .await;
+    options.assert_status_ok();
+    let challenge = options.json::<Json>()["data"]["publicKey"]["challenge"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let (client_data_json, attestation_object) = authenticator.create(&challenge, APP_ORIGIN);
+    server
+        .post("/users/webauthn/register")
+        .authorization_bearer(token)
+        .json(&json!({
+            "name": "Laptop",
+            "credential": {
+                "id": URL_SAFE_NO_PAD.encode(&authenticator.credential_id),
+                "response": {
+                    "clientDataJSON": URL_SAFE_NO_PAD.encode(client_data_json),
+                    "attestationObject": URL_SAFE_NO_PAD.encode(attestation_object),
+                    "transports": ["internal"]
+                }
+            }
+        }))
+        .await
+}
+
+/// the challenge out of a `navigator.credentials.get()` options response
+fn request_challenge(response: TestResponse) -> String {
+    response.assert_status_ok();
+    response.json::<Json>()["data"]["publicKey"]["challenge"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+/// the assertion as `PublicKeyCredential.toJSON()` serialises it, with the user handle of a passkey
+fn assertion_json(
+    authenticator: &SoftwareAuthenticator,
+    assertion: &Assertion,
+    user: &Uuid,
+) -> Json {
+    json!({
+        "id": URL_SAFE_NO_PAD.encode(&authenticator.credential_id),
+        "response": {
+            "clientDataJSON": URL_SAFE_NO_PAD.encode(&assertion.client_data_json),
+            "authenticatorData": URL_SAFE_NO_PAD.encode(&assertion.authenticator_data),
+            "signature": URL_SAFE_NO_PAD.encode(&assertion.signature),
+            "userHandle": URL_SAFE_NO_PAD.encode(user.as_bytes())
+        }
+    })
+}
+
+async fn passkey_login(
+    server: &TestServer,
+    authenticator: &mut SoftwareAuthenticator,
+    user: &Uuid,
+) -> (Json, TestResponse) {
+    let challenge = request_challenge(server.post("/login/webauthn/options").await);
+    let assertion = authenticator.get(
+        &challenge,
+        APP_ORIGIN,
+        FLAG_USER_PRESENT | FLAG_USER_VERIFIED,
+    );
+    let body = json!({ "credential": assertion_json(authenticator, &assertion, user) });
+    let response = server.post("/login/webauthn").json(&body).await;
+
+    (body, response)
+}
+
+/// the password half of a login for a user with a passkey, a challenge is good for one attempt
+async fn password_step(server: &TestServer, email: &str) -> String {
+    let response = server
+        .post("/login")
+        .json(&json!({ "email": email, "password": common::PASSWORD }))
+        .await;
+    response.assert_status_ok();
+
+    response.json::<Json>()["data"]["mfaToken"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn mfa_challenge(server: &TestServer, mfa_token: &str) -> TestResponse {
+    server
+        .post("/login/mfa/webauthn/options")
+        .json(&json!({ "mfaToken": mfa_token }))
+        .await
+}
+
+async fn second_factor(server: &TestServer, mfa_token: &str, credential: Json) -> TestResponse {
+    server
+        .post("/login/mfa")
+        .json(&json!({ "mfaToken": mfa_token, "credential": credential }))
+        .await
+}
+
+async fn stored_sign_count(pool: &PgPool, user: &Uuid) -> i64 {
+    sqlx::query_scalar("SELECT sign_count FROM webauthn_credentials WHERE user_identifier = $1")
+        .bind(user)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_a_registered_passkey_signs_in_once_per_challenge() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+    let mut authenticator = SoftwareAuthenticator::new(APP_RP_ID);
+
+    let response = register(&server, &token, &authenticator, &token).await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<Json>()["data"]["name"], "Laptop");
+    assert_eq!(stored_sign_count(&pool, &user).await, 0);
+
+    let (body, response) = passkey_login(&server, &mut authenticator, &user).await;
+    response.assert_status_ok();
+    let token = response.json::<Json>()["data"]["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(common::jwt_claims(&token)["sub"], user.to_string());
+    assert_eq!(stored_sign_count(&pool, &user).await, 1);
+    let last_used_at: Option<chrono::DateTime<chrono::Utc>> = sqlx::query_scalar(
+        "SELECT last_used_at FROM webauthn_credentials WHERE user_identifier = $1",
+    )
+    .bind(user)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert!(last_used_at.is_some());
+
+    // the challenge went with the first use
+    let response = server.post("/login/webauthn").json(&body).await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.json::<Json>()["message"],
+        "the challenge has expired or was already used, start over"
+    );
+
+    // the same authenticator cannot be registered twice
+    register(&server, &token, &authenticator, &token)
+        .await
+        .assert_status(StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_a_cloned_authenticator_is_refused() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+    let mut authenticator = SoftwareAuthenticator::new(APP_RP_ID);
+    register(&server, &token, &authenticator, &token)
+        .await
+        .assert_status_ok();
+    let mut clone = authenticator.clone();
+
+    passkey_login(&server, &mut authenticator, &user)
+        .await
+        .1
+        .assert_status_ok();
+    passkey_login(&server, &mut authenticator, &user)
+        .await
+        .1
+        .assert_status_ok();
+    assert_eq!(stored_sign_count(&pool, &user).await, 2);
+
+    // the copy's counter is behind the stored one
+    passkey_login(&server, &mut clone, &user)
+        .await
+        .1
+        .assert_status(StatusCode::UNAUTHORIZED);
+    assert_eq!(stored_sign_count(&pool, &user).await, 2);
+
+    passkey_login(&server, &mut authenticator, &user)
+        .await
+        .1
+        .assert_status_ok();
+    assert_eq!(stored_sign_count(&pool, &user).await, 3);
+}
+
+#[tokio::test]
+async fn test_challenges_are_bound_to_their_user() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let (other_user, other_email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (token, _) = common::login(&server, &email).await;
+    let (other_token, _) = common::login(&server, &other_email).await;
+    let mut authenticator = SoftwareAuthenticator::new(APP_RP_ID);
+    let mut other_authenticator = SoftwareAuthenticator::new(APP_RP_ID);
+
+    // a registration challenge issued to one user cannot register a passkey for another
+    register(&server, &other_token, &other_authenticator, &token)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+    register(&server, &token, &authenticator, &token)
+        .await
+        .assert_status_ok();
+    register(&server, &other_token, &other_authenticator, &other_token)
+        .await
+        .assert_status_ok();
+
+    // the options only allow the user's own passkey
+    let mfa_token = password_step(&server, &email).await;
+    let response = mfa_challenge(&server, &mfa_token).await;
+    response.assert_status_ok();
+    let allowed = &response.json::<Json>()["data"]["publicKey"]["allowCredentials"];
+    assert_eq!(allowed.as_array().unwrap().len(), 1);
+    assert_eq!(
+        allowed[0]["id"],
+        URL_SAFE_NO_PAD.encode(&authenticator.credential_id)
+    );
+
+    // another user's passkey does not complete the login, even against a challenge for it
+    let challenge = request_challenge(mfa_challenge(&server, &mfa_token).await);
+    let assertion = other_authenticator.get(&challenge, APP_ORIGIN, FLAG_USER_PRESENT);
+    let credential = assertion_json(&other_authenticator, &assertion, &other_user);
+    second_factor(&server, &mfa_token, credential)
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+
+    // nor does a challenge issued for a passkey login
+    let mfa_token = password_step(&server, &email).await;
+    let challenge = request_challenge(server.post("/login/webauthn/options").await);
+    let assertion = authenticator.get(&challenge, APP_ORIGIN, FLAG_USER_PRESENT);
+    let credential = assertion_json(&authenticator, &assertion, &user);
+    second_factor(&server, &mfa_token, credential)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let mfa_token = password_step(&server, &email).await;
+    let challenge = request_challenge(mfa_challenge(&server, &mfa_token).await);
+    let assertion = authenticator.get(&challenge, APP_ORIGIN, FLAG_USER_PRESENT);
+    let credential = assertion_json(&authenticator, &assertion, &user);
+    let response = second_factor(&server, &mfa_token, credential).await;
+    response.assert_status_ok();
+    let tokens = response.json::<Json>();
+    let claims = common::jwt_claims(tokens["data"]["token"].as_str().unwrap());
+    assert_eq!(claims["sub"], user.to_string());
+}
+
+#[tokio::test]
+async fn test_adding_a_passkey_long_after_signing_in_takes_the_password() {
+    let pool = common::database().await;
+    let (user, email) = common::create_user(&pool).await;
+    let server = common::server(pool.clone());
+    let (_, refresh_token) = common::login(&server, &email).await;
+    // the session was signed in to an hour ago, the access token itself is fresh
+    sqlx::query(
+        "UPDATE sessions SET created_at = now() - interval '1 hour' WHERE user_identifier = $1",
+    )
+    .bind(user)
+    .execute(&pool)
+    .await
+    .unwrap();
+    let response = server
+        .post("/refresh-token")
+        .json(&json!({ "refreshToken": refresh_token }))
+        .await;
+    response.assert_status_ok();
+    let token = response.json::<Json>()["data"]["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let authenticator = SoftwareAuthenticator::new(APP_RP_ID);
+
+    let options = |body: Json| {
+        server
+            .post("/users/webauthn/register/options")
+            .authorization_bearer(&token)
+            .json(&body)
+    };
+    let response = options(json!({})).await;
+    response.assert_status(StatusCode::FORBIDDEN);
+    assert_eq!(
+        response.json::<Json>()["message"],
+        "enter your password again to continue"
+    );
+    options(json!({ "password": "not-the-password" }))
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    let response = options(json!({ "password": common::PASSWORD })).await;
+    response.assert_status_ok();
+    let challenge = response.json::<Json>()["data"]["publicKey"]["challenge"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let (client_data_json, attestation_object) = authenticator.create(&challenge, APP_ORIGIN);
+    let register = |password: Option<&str>| {
+        server
+            .post("/users/webauthn/register")
+            .authorization_bearer(&token)
+            .json(&json!({
+                "password": password,
+                "credential": {
+                    "id": URL_SAFE_NO_PAD.encode(&authenticator.credential_id),
+                    "response": {
+                        "clientDataJSON": URL_SAFE_NO_PAD.encode(&client_data_json),
+                        "attestationObject": URL_SAFE_NO_PAD.encode(&attestation_object),
+                    }
+                }
+            }))
+    };
+    register(None).await.assert_status(StatusCode::FORBIDDEN);
+    register(Some(common::PASSWORD)).await.assert_status_ok();
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use axum::http::StatusCode;
+
+#[tokio::test]
+async fn test_jwks_publishes_signing_key() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server.get("/.well-known/jwks.json").await;
+    response.assert_status(StatusCode::OK);
+
+    let jwks = response.json::<serde_json::Value>();
+    let keys = jwks["keys"].as_array().unwrap();
+    assert!(!keys.is_empty());
+    assert!(
+        keys.iter()
+            .all(|key| key["kid"].is_string() && key["use"] == "sig")
+    );
+}
+
+#[tokio::test]
+async fn test_openid_configuration_advertises_endpoints() {
+    let server = common::server(common::lazy_pool());
+
+    let response = server.get("/.well-known/openid-configuration").await;
+    response.assert_status(StatusCode::OK);
+
+    let configuration = response.json::<serde_json::Value>();
+    let issuer = configuration["issuer"].as_str().unwrap();
+    assert_eq!(
+        configuration["token_endpoint"],
+        format!("{}/oauth/token", issuer.trim_end_matches('/'))
+    );
+    assert_eq!(configuration["code_challenge_methods_supported"][0], "S256");
+    assert!(
+        configuration["grant_types_supported"]
+            .as_array()
+            .unwrap()
+            .contains(&"urn:ietf:params:oauth:grant-type:token-exchange".into())
+    );
+}